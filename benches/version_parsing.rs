@@ -0,0 +1,61 @@
+//! Hot path of `bldr check`: parsing every release of a package and picking
+//! the newest one that satisfies a constraint.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use rust_buildout_releaser::version::python::{
+    parse_python_version_with_epoch, parse_version_constraint, ParsedReleases,
+};
+
+/// Release history shaped like a long-lived PyPI project (~450 versions)
+fn release_history() -> Vec<String> {
+    let mut versions = Vec::new();
+    for major in 1..=6 {
+        for minor in 0..=14 {
+            for patch in 0..=3 {
+                versions.push(format!("{}.{}.{}", major, minor, patch));
+            }
+            versions.push(format!("{}.{}.0a1", major, minor));
+            versions.push(format!("{}.{}.0rc1", major, minor));
+            versions.push(format!("{}.{}.0.post1", major, minor));
+            versions.push(format!("{}.{}.0.dev2", major, minor));
+        }
+    }
+    versions
+}
+
+fn bench_parse(c: &mut Criterion) {
+    let versions = release_history();
+
+    c.bench_function("parse release history", |b| {
+        b.iter(|| {
+            versions
+                .iter()
+                .filter_map(|v| parse_python_version_with_epoch(black_box(v)))
+                .count()
+        })
+    });
+}
+
+fn bench_constraint(c: &mut Criterion) {
+    let versions = release_history();
+    let parsed = ParsedReleases::parse(&versions);
+
+    c.bench_function("newest matching release", |b| {
+        b.iter(|| {
+            let (req, exclusions) = parse_version_constraint(black_box(">=2.0,<5")).unwrap();
+            parsed
+                .newest_first()
+                .filter(|(v, _)| v.pre.is_empty())
+                .find(|(v, _)| {
+                    req.matches(v)
+                        && exclusions
+                            .iter()
+                            .all(|(start, end)| !(*v >= start && *v < end))
+                })
+                .map(|(_, raw)| raw.to_string())
+        })
+    });
+}
+
+criterion_group!(benches, bench_parse, bench_constraint);
+criterion_main!(benches);
@@ -0,0 +1,253 @@
+use crate::buildout::PinnedPackage;
+use crate::config::{AuditConfig, HttpConfig};
+use crate::error::{ReleaserError, Result};
+use crate::http::{self, RetryPolicy};
+use crate::pypi::normalize_project_name;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+use std::time::Duration;
+
+const USER_AGENT: &str = concat!("bldr/", env!("CARGO_PKG_VERSION"));
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+/// OSV accepts at most this many queries per batch request
+const BATCH_SIZE: usize = 1000;
+
+/// A known vulnerability affecting a pinned package
+#[derive(Debug, Clone, Serialize)]
+pub struct Vulnerability {
+    pub id: String,
+    pub aliases: Vec<String>,
+    pub summary: Option<String>,
+    pub severity: Option<String>,
+    pub fixed_versions: Vec<String>,
+    /// When the advisory was published (RFC 3339)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub published: Option<String>,
+}
+
+/// Vulnerabilities found for one pin
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditFinding {
+    pub package: String,
+    pub version: String,
+    pub vulnerabilities: Vec<Vulnerability>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BatchResponse {
+    results: Vec<BatchResult>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BatchResult {
+    #[serde(default)]
+    vulns: Vec<VulnId>,
+}
+
+#[derive(Debug, Deserialize)]
+struct VulnId {
+    id: String,
+}
+
+/// Client for the OSV vulnerability database (https://osv.dev)
+pub struct OsvClient {
+    client: reqwest::Client,
+    base_url: String,
+    retry: RetryPolicy,
+}
+
+impl OsvClient {
+    pub fn with_config(config: &AuditConfig, http: &HttpConfig) -> Result<Self> {
+        let builder = reqwest::Client::builder()
+            .user_agent(USER_AGENT)
+            .timeout(REQUEST_TIMEOUT);
+        let client = http::configure_client(builder, http)?.build()?;
+
+        Ok(Self {
+            client,
+            base_url: config.osv_url.trim_end_matches('/').to_string(),
+            retry: RetryPolicy::from_config(http),
+        })
+    }
+
+    /// Look up every pin and return those with known vulnerabilities,
+    /// skipping advisory ids listed in `ignore`
+    pub async fn audit(
+        &self,
+        pins: &[PinnedPackage],
+        ignore: &[String],
+    ) -> Result<Vec<AuditFinding>> {
+        let mut details: HashMap<String, serde_json::Value> = HashMap::new();
+        let mut findings = Vec::new();
+
+        for chunk in pins.chunks(BATCH_SIZE) {
+            let ids = self.query_batch(chunk).await?;
+
+            for (pin, ids) in chunk.iter().zip(ids) {
+                let mut vulnerabilities = Vec::new();
+
+                for id in ids {
+                    if !details.contains_key(&id) {
+                        let vuln = self.get_vulnerability(&id).await?;
+                        details.insert(id.clone(), vuln);
+                    }
+
+                    let vulnerability = parse_vulnerability(&details[&id], &pin.name);
+                    let ignored = std::iter::once(&vulnerability.id)
+                        .chain(vulnerability.aliases.iter())
+                        .any(|id| ignore.iter().any(|i| i.eq_ignore_ascii_case(id)));
+                    if !ignored {
+                        vulnerabilities.push(vulnerability);
+                    }
+                }
+
+                if !vulnerabilities.is_empty() {
+                    findings.push(AuditFinding {
+                        package: pin.name.clone(),
+                        version: pin.version.clone(),
+                        vulnerabilities,
+                    });
+                }
+            }
+        }
+
+        Ok(findings)
+    }
+
+    /// Query OSV for a batch of pins; returns the advisory ids for each pin in order
+    async fn query_batch(&self, pins: &[PinnedPackage]) -> Result<Vec<Vec<String>>> {
+        let queries: Vec<_> = pins
+            .iter()
+            .map(|pin| {
+                json!({
+                    "package": {"name": pin.name, "ecosystem": "PyPI"},
+                    "version": pin.version,
+                })
+            })
+            .collect();
+
+        let url = format!("{}/querybatch", self.base_url);
+        let request = self.client.post(&url).json(&json!({ "queries": queries }));
+        let response = self.retry.send(request).await?;
+
+        if !response.status().is_success() {
+            return Err(ReleaserError::AuditError(format!(
+                "OSV batch query failed with status {}",
+                response.status()
+            )));
+        }
+
+        let batch: BatchResponse = response.json().await.map_err(|e| {
+            ReleaserError::AuditError(format!("Failed to parse OSV response: {}", e))
+        })?;
+
+        Ok(batch
+            .results
+            .into_iter()
+            .map(|result| result.vulns.into_iter().map(|v| v.id).collect())
+            .collect())
+    }
+
+    async fn get_vulnerability(&self, id: &str) -> Result<serde_json::Value> {
+        let url = format!("{}/vulns/{}", self.base_url, id);
+        let response = self.retry.send(self.client.get(&url)).await?;
+
+        if !response.status().is_success() {
+            return Err(ReleaserError::AuditError(format!(
+                "Failed to fetch advisory {}: status {}",
+                id,
+                response.status()
+            )));
+        }
+
+        response.json().await.map_err(|e| {
+            ReleaserError::AuditError(format!("Failed to parse advisory {}: {}", id, e))
+        })
+    }
+}
+
+/// Extract the fields we report from an OSV advisory record
+fn parse_vulnerability(record: &serde_json::Value, package: &str) -> Vulnerability {
+    let strings = |value: &serde_json::Value| -> Vec<String> {
+        value
+            .as_array()
+            .map(|items| {
+                items
+                    .iter()
+                    .filter_map(|item| item.as_str().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+
+    let package = normalize_project_name(package);
+    let mut fixed_versions: Vec<String> = record["affected"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|affected| {
+            affected["package"]["ecosystem"] == "PyPI"
+                && affected["package"]["name"]
+                    .as_str()
+                    .map(|name| normalize_project_name(name) == package)
+                    .unwrap_or(false)
+        })
+        .flat_map(|affected| affected["ranges"].as_array().into_iter().flatten())
+        .flat_map(|range| range["events"].as_array().into_iter().flatten())
+        .filter_map(|event| event["fixed"].as_str().map(str::to_string))
+        .collect();
+    fixed_versions.dedup();
+
+    // GitHub advisories carry a readable level; otherwise fall back to the CVSS vector
+    let severity = record["database_specific"]["severity"]
+        .as_str()
+        .or_else(|| record["severity"][0]["score"].as_str())
+        .map(str::to_string);
+
+    Vulnerability {
+        id: record["id"].as_str().unwrap_or_default().to_string(),
+        aliases: strings(&record["aliases"]),
+        summary: record["summary"].as_str().map(str::to_string),
+        severity,
+        fixed_versions,
+        published: record["published"].as_str().map(str::to_string),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_vulnerability() {
+        let record = json!({
+            "id": "GHSA-xxxx-yyyy-zzzz",
+            "aliases": ["CVE-2024-0001"],
+            "summary": "Open redirect in login form",
+            "published": "2024-05-02T12:00:00Z",
+            "database_specific": {"severity": "MODERATE"},
+            "affected": [
+                {
+                    "package": {"name": "Products.CMFPlone", "ecosystem": "PyPI"},
+                    "ranges": [{"type": "ECOSYSTEM", "events": [
+                        {"introduced": "0"}, {"fixed": "5.2.15"},
+                        {"introduced": "6.0"}, {"fixed": "6.0.11"}
+                    ]}]
+                },
+                {
+                    "package": {"name": "other-package", "ecosystem": "PyPI"},
+                    "ranges": [{"type": "ECOSYSTEM", "events": [{"fixed": "9.9"}]}]
+                }
+            ]
+        });
+
+        let vuln = parse_vulnerability(&record, "products-cmfplone");
+
+        assert_eq!(vuln.id, "GHSA-xxxx-yyyy-zzzz");
+        assert_eq!(vuln.aliases, vec!["CVE-2024-0001"]);
+        assert_eq!(vuln.severity.as_deref(), Some("MODERATE"));
+        assert_eq!(vuln.fixed_versions, vec!["5.2.15", "6.0.11"]);
+        assert_eq!(vuln.published.as_deref(), Some("2024-05-02T12:00:00Z"));
+    }
+}
@@ -1,55 +1,329 @@
 use crate::error::{ReleaserError, Result};
+use crate::pypi::normalize_project_name;
+use once_cell::sync::Lazy;
 use regex::Regex;
-use std::collections::HashMap;
-use std::path::Path;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 
-#[derive(Debug, Clone)]
+/// A trailing note left by an earlier annotated update
+static PIN_NOTE_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\s*#\s*was \S+, updated \S+ by bldr\s*$").unwrap());
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BuildoutVersions {
     /// Raw content of the file
     content: String,
-    /// Parsed versions: package_name -> (version, line_number)
-    versions: HashMap<String, (String, usize)>,
+    /// Content as loaded, to diff the pending changes against
+    #[serde(skip)]
+    original: String,
+    /// Parsed versions: normalized package name -> every pin of it, in file order
+    versions: HashMap<String, Vec<Pin>>,
     /// File path
     path: String,
+    /// Directory a relative `path` is read from and saved to; the current
+    /// directory when empty
+    #[serde(skip)]
+    root: PathBuf,
+    /// Files pulled in through `extends`, highest precedence first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    extended: Vec<BuildoutVersions>,
+    /// Whether `content` differs from what was loaded
+    #[serde(skip)]
+    modified: bool,
+    /// Downloaded upstream file (e.g. a Plone KGS); never written back
+    #[serde(skip)]
+    remote: bool,
+    /// Remote `extends` targets that were not available when loading
+    #[serde(skip)]
+    unresolved_extends: Vec<String>,
+    /// Append `# was <old>, updated <date> by bldr` to rewritten pins
+    #[serde(skip)]
+    annotate_updates: bool,
+    /// Keep the previous content as `<path>.bak` when saving
+    #[serde(skip)]
+    keep_backup: bool,
+    /// Packages of the mr.developer `[sources]` section
+    #[serde(skip)]
+    sources: Vec<String>,
+}
+
+/// One `name = version` line of a `[versions*]` section
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Pin {
+    /// Package name as written in the file
+    name: String,
+    version: String,
+    /// 0-based line in the file
+    line: usize,
+    /// Section name, e.g. `versions` or `versions:python312`
+    section: String,
+}
+
+/// State shared while walking an `extends` chain
+struct ExtendsWalk<'a> {
+    /// Already downloaded remote files, by URL
+    remote: &'a HashMap<String, String>,
+    /// Canonical local paths and URLs seen so far
+    visited: HashSet<String>,
+    unresolved: Vec<String>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct VersionUpdate {
     pub package_name: String,
     pub old_version: String,
     pub new_version: String,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PinnedPackage {
+    pub name: String,
+    pub version: String,
+}
+
+/// Pins that appear in only one of two versions snapshots
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PinDiff {
+    pub added: Vec<PinnedPackage>,
+    pub removed: Vec<PinnedPackage>,
+}
+
+impl PinDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+
+    /// Merge the diff of another versions file, skipping pins already listed
+    pub fn merge(&mut self, other: PinDiff) {
+        for pin in other.added {
+            if !self.added.iter().any(|p| p.name == pin.name) {
+                self.added.push(pin);
+            }
+        }
+        for pin in other.removed {
+            if !self.removed.iter().any(|p| p.name == pin.name) {
+                self.removed.push(pin);
+            }
+        }
+        self.added.sort_by_key(|pin| pin.name.to_lowercase());
+        self.removed.sort_by_key(|pin| pin.name.to_lowercase());
+    }
+
+    /// Keep only the pins accepted by the predicate
+    pub fn retain<F: Fn(&str) -> bool>(&mut self, keep: F) {
+        self.added.retain(|p| keep(&p.name));
+        self.removed.retain(|p| keep(&p.name));
+    }
+}
+
+/// A package pinned more than once in the same versions section
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicatePin {
+    pub name: String,
+    pub section: String,
+    /// 1-based line numbers of every occurrence
+    pub lines: Vec<usize>,
+}
+
+/// A package pinned to different versions in co-versioned files
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PinMismatch {
+    pub package: String,
+    /// (file, version) for every file that pins the package
+    pub pins: Vec<(String, String)>,
+}
+
+/// Packages pinned in more than one file whose versions disagree
+pub fn find_pin_mismatches(files: &[BuildoutVersions]) -> Vec<PinMismatch> {
+    // Normalized name -> name as first written
+    let mut names: Vec<(&str, &str)> = files
+        .iter()
+        .flat_map(|f| f.effective_pins())
+        .map(|(key, pin)| (key, pin.name.as_str()))
+        .collect();
+    names.sort_by_key(|(key, _)| *key);
+    names.dedup_by_key(|(key, _)| *key);
+
+    names
+        .into_iter()
+        .filter_map(|(key, name)| {
+            let pins: Vec<(String, String)> = files
+                .iter()
+                .filter_map(|f| f.get_version(key).map(|v| (f.path.clone(), v.to_string())))
+                .collect();
+            let disagree = pins.iter().any(|(_, v)| *v != pins[0].1);
+            disagree.then(|| PinMismatch {
+                package: name.to_string(),
+                pins,
+            })
+        })
+        .collect()
+}
+
 impl BuildoutVersions {
-    /// Load and parse a buildout versions file
-    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+    /// Load and parse the buildout versions file at `path` relative to `root`;
+    /// `path` is kept as given for display and staging
+    pub fn load_in<P: AsRef<Path>>(root: &Path, path: P) -> Result<Self> {
         let path_str = path.as_ref().to_string_lossy().to_string();
-        let content = std::fs::read_to_string(path.as_ref())?;
+        let content = std::fs::read_to_string(root.join(path.as_ref())).map_err(|source| {
+            ReleaserError::FileError {
+                path: path_str.clone(),
+                source,
+            }
+        })?;
 
         let versions = Self::parse_versions(&content)?;
+        let sources = parse_sources(&content);
 
         Ok(Self {
+            original: content.clone(),
             content,
             versions,
             path: path_str,
+            root: root.to_path_buf(),
+            extended: Vec::new(),
+            modified: false,
+            remote: false,
+            unresolved_extends: Vec::new(),
+            annotate_updates: false,
+            keep_backup: false,
+            sources,
         })
     }
 
+    /// Load a versions file along with the files it `extends`, recursively.
+    /// Pins in the file override those of its parents, and later parents override
+    /// earlier ones, as buildout does. Remote targets are taken from `remote`
+    /// (by URL); the ones missing there are listed by `unresolved_extends`.
+    /// `path` is relative to `dir`, like in [`Self::load_in`]
+    pub fn load_with_extends_in<P: AsRef<Path>>(
+        dir: &Path,
+        path: P,
+        remote: &HashMap<String, String>,
+    ) -> Result<Self> {
+        let mut root = Self::load_in(dir, path.as_ref())?;
+        let mut walk = ExtendsWalk {
+            remote,
+            visited: HashSet::from([canonical(&dir.join(path.as_ref()))]),
+            unresolved: Vec::new(),
+        };
+
+        let mut extended = Vec::new();
+        root.collect_extended(&mut walk, &mut extended)?;
+        root.extended = extended;
+        root.unresolved_extends = walk.unresolved;
+        Ok(root)
+    }
+
+    /// Load a downloaded versions file (e.g. a published known-good set) with
+    /// the files it `extends`; contents are taken from `remote` by URL, and the
+    /// ones missing there are listed by `unresolved_extends`
+    pub fn load_remote(url: &str, remote: &HashMap<String, String>) -> Result<Self> {
+        let content = remote.get(url).cloned().ok_or_else(|| {
+            ReleaserError::BuildoutParseError(format!("{} was not downloaded", url))
+        })?;
+        let mut root = Self::from_content(content, url)?;
+        root.remote = true;
+
+        let mut walk = ExtendsWalk {
+            remote,
+            visited: HashSet::from([url.to_string()]),
+            unresolved: Vec::new(),
+        };
+        let mut extended = Vec::new();
+        root.collect_extended(&mut walk, &mut extended)?;
+        root.extended = extended;
+        root.unresolved_extends = walk.unresolved;
+        Ok(root)
+    }
+
+    /// Append the `extends` chain of this file to `out`, highest precedence first
+    fn collect_extended(&self, walk: &mut ExtendsWalk, out: &mut Vec<Self>) -> Result<()> {
+        for target in parse_extends(&self.content).iter().rev() {
+            let parent = if self.remote || target.contains("://") {
+                let url = self.resolve_url(target)?;
+                if !walk.visited.insert(url.clone()) {
+                    continue;
+                }
+                let Some(content) = walk.remote.get(&url) else {
+                    walk.unresolved.push(url);
+                    continue;
+                };
+
+                let mut parent = Self::from_content(content.clone(), url)?;
+                parent.remote = true;
+                parent
+            } else {
+                let base = Path::new(&self.path).parent().unwrap_or(Path::new(""));
+                let path = base.join(target);
+                if !walk.visited.insert(canonical(&self.root.join(&path))) {
+                    continue;
+                }
+
+                Self::load_in(&self.root, &path).map_err(|e| {
+                    ReleaserError::BuildoutParseError(format!(
+                        "{} extends {}: {}",
+                        self.path,
+                        path.display(),
+                        e
+                    ))
+                })?
+            };
+
+            let mut ancestors = Vec::new();
+            parent.collect_extended(walk, &mut ancestors)?;
+            out.push(parent);
+            out.append(&mut ancestors);
+        }
+
+        Ok(())
+    }
+
+    /// Absolute URL of an `extends` target; relative ones in remote files
+    /// are resolved against that file's URL
+    fn resolve_url(&self, target: &str) -> Result<String> {
+        let url = if self.remote {
+            reqwest::Url::parse(&self.path).and_then(|base| base.join(target))
+        } else {
+            reqwest::Url::parse(target)
+        };
+
+        url.map(String::from).map_err(|e| {
+            ReleaserError::BuildoutParseError(format!("{} extends {}: {}", self.path, target, e))
+        })
+    }
+
+    /// Remote `extends` targets that still need to be downloaded
+    pub fn unresolved_extends(&self) -> &[String] {
+        &self.unresolved_extends
+    }
+
     /// Build a versions snapshot from raw content
     pub fn from_content<S: Into<String>>(content: String, path: S) -> Result<Self> {
         let versions = Self::parse_versions(&content)?;
+        let sources = parse_sources(&content);
 
         Ok(Self {
+            original: content.clone(),
             content,
             versions,
             path: path.into(),
+            root: PathBuf::new(),
+            extended: Vec::new(),
+            modified: false,
+            remote: false,
+            unresolved_extends: Vec::new(),
+            annotate_updates: false,
+            keep_backup: false,
+            sources,
         })
     }
 
     /// Parse version pins from buildout cfg content
-    fn parse_versions(content: &str) -> Result<HashMap<String, (String, usize)>> {
-        let mut versions = HashMap::new();
-        let mut in_versions_section = false;
+    fn parse_versions(content: &str) -> Result<HashMap<String, Vec<Pin>>> {
+        let mut versions: HashMap<String, Vec<Pin>> = HashMap::new();
+        let mut versions_section: Option<&str> = None;
 
         // Match section headers like [versions] or [versions:python3]
         let section_re = Regex::new(r"^\s*\[([^\]]+)\]\s*$").unwrap();
@@ -68,16 +342,23 @@ impl BuildoutVersions {
             // Check for section headers
             if let Some(caps) = section_re.captures(line) {
                 let section = caps.get(1).unwrap().as_str();
-                in_versions_section = section.starts_with("versions");
+                versions_section = section.starts_with("versions").then_some(section);
                 continue;
             }
 
             // Parse version pins in versions section
-            if in_versions_section {
+            if let Some(section) = versions_section {
                 if let Some(caps) = version_re.captures(line) {
-                    let package = caps.get(1).unwrap().as_str().to_string();
-                    let version = caps.get(2).unwrap().as_str().to_string();
-                    versions.insert(package, (version, line_num));
+                    let package = caps.get(1).unwrap().as_str();
+                    versions
+                        .entry(normalize_project_name(package))
+                        .or_default()
+                        .push(Pin {
+                            name: package.to_string(),
+                            version: caps.get(2).unwrap().as_str().to_string(),
+                            line: line_num,
+                            section: section.to_string(),
+                        });
                 }
             }
         }
@@ -87,65 +368,305 @@ impl BuildoutVersions {
 
     /// Get the current version of a package
     pub fn get_version(&self, package_name: &str) -> Option<&str> {
-        self.versions.get(package_name).map(|(v, _)| v.as_str())
+        self.get_version_in(package_name, None)
     }
 
-    /// Get all tracked packages and their versions
+    /// Current version of a package in one section (e.g. `versions:python312`),
+    /// or the pin that takes effect when `section` is `None`
+    pub fn get_version_in(&self, package_name: &str, section: Option<&str>) -> Option<&str> {
+        self.file_for(package_name, section)
+            .and_then(|file| file.pin(package_name, section))
+            .map(|pin| pin.version.as_str())
+    }
+
+    /// File and 1-based line of the pin that takes effect, following `extends`
+    pub fn pin_location(&self, package_name: &str) -> Option<(&str, usize)> {
+        let file = self.file_for(package_name, None)?;
+        let pin = file.pin(package_name, None)?;
+        Some((file.path.as_str(), pin.line + 1))
+    }
+
+    /// This file's pin of a package in `section`; without one, the last pin
+    /// wins as later sections override earlier ones. Names match regardless of
+    /// case and `-`/`_`/`.` separators, as pip does
+    fn pin(&self, package_name: &str, section: Option<&str>) -> Option<&Pin> {
+        let pins = self.versions.get(&normalize_project_name(package_name))?;
+        match section {
+            Some(section) => pins.iter().rev().find(|pin| pin.section == section),
+            None => pins.last(),
+        }
+    }
+
+    /// The file whose pin of a package takes effect (and is edited on update)
+    fn file_for(&self, package_name: &str, section: Option<&str>) -> Option<&BuildoutVersions> {
+        std::iter::once(self)
+            .chain(self.extended.iter())
+            .find(|file| file.pin(package_name, section).is_some())
+    }
+
+    /// URL of the upstream file the effective pin of a package is inherited from
+    pub fn upstream_source(&self, package_name: &str) -> Option<&str> {
+        self.file_for(package_name, None)
+            .filter(|file| file.remote)
+            .map(|file| file.path.as_str())
+    }
+
+    /// Packages checked out from source by mr.developer, with the file listing
+    /// them in `[sources]`; this file first, then the files it extends
+    pub fn source_checkouts(&self) -> impl Iterator<Item = (&str, &str)> {
+        std::iter::once(self)
+            .chain(self.extended.iter())
+            .flat_map(|file| {
+                file.sources
+                    .iter()
+                    .map(move |name| (name.as_str(), file.path.as_str()))
+            })
+    }
+
+    /// Paths of this file and every local file it extends
+    pub fn paths(&self) -> Vec<&str> {
+        std::iter::once(self)
+            .chain(self.extended.iter())
+            .filter(|file| !file.remote)
+            .map(|file| file.path.as_str())
+            .collect()
+    }
+
+    /// Packages pinned more than once within the same `[versions*]` section
+    pub fn duplicate_pins(&self) -> Vec<DuplicatePin> {
+        let section_re = Regex::new(r"^\s*\[([^\]]+)\]\s*$").unwrap();
+        let version_re = Regex::new(r"^\s*([a-zA-Z0-9._-]+)\s*=\s*([^\s#]+)").unwrap();
+
+        let mut seen: Vec<DuplicatePin> = Vec::new();
+        let mut section: Option<String> = None;
+
+        for (line_num, line) in self.content.lines().enumerate() {
+            let trimmed = line.trim();
+            if trimmed.starts_with('#') || trimmed.is_empty() {
+                continue;
+            }
+
+            if let Some(caps) = section_re.captures(line) {
+                let name = caps.get(1).unwrap().as_str();
+                section = name.starts_with("versions").then(|| name.to_string());
+                continue;
+            }
+
+            let (Some(current), Some(caps)) = (section.as_ref(), version_re.captures(line)) else {
+                continue;
+            };
+            let name = caps.get(1).unwrap().as_str();
+            match seen.iter_mut().find(|d| {
+                d.section == *current
+                    && normalize_project_name(&d.name) == normalize_project_name(name)
+            }) {
+                Some(entry) => entry.lines.push(line_num + 1),
+                None => seen.push(DuplicatePin {
+                    name: name.to_string(),
+                    section: current.clone(),
+                    lines: vec![line_num + 1],
+                }),
+            }
+        }
+
+        seen.retain(|d| d.lines.len() > 1);
+        seen
+    }
+
+    /// Get all tracked packages (as written where the pin takes effect) and
+    /// their effective versions
     pub fn get_all_versions(&self) -> impl Iterator<Item = (&str, &str)> {
-        self.versions
-            .iter()
-            .map(|(k, (v, _))| (k.as_str(), v.as_str()))
+        self.effective_pins()
+            .into_values()
+            .map(|pin| (pin.name.as_str(), pin.version.as_str()))
+    }
+
+    /// Pins merged across the `extends` chain, by normalized name
+    fn effective_pins(&self) -> HashMap<&str, &Pin> {
+        let mut merged = HashMap::new();
+        for file in std::iter::once(self).chain(self.extended.iter()) {
+            for (key, pins) in &file.versions {
+                if let Some(pin) = pins.last() {
+                    merged.entry(key.as_str()).or_insert(pin);
+                }
+            }
+        }
+        merged
     }
 
-    /// Update a package version and return the update info
-    pub fn update_version(
+    /// Pins present in both snapshots whose version changed, sorted by name
+    pub fn changed_pins(&self, previous: &BuildoutVersions) -> Vec<VersionUpdate> {
+        let previous = previous.effective_pins();
+        let mut changed: Vec<VersionUpdate> = self
+            .effective_pins()
+            .into_iter()
+            .filter_map(|(key, pin)| {
+                let old = previous.get(key).filter(|old| old.version != pin.version)?;
+                Some(VersionUpdate {
+                    package_name: pin.name.clone(),
+                    old_version: old.version.clone(),
+                    new_version: pin.version.clone(),
+                })
+            })
+            .collect();
+        changed.sort_by_key(|update| update.package_name.to_lowercase());
+        changed
+    }
+
+    /// Compare against a previous snapshot and list pins that were added or removed
+    pub fn diff_pins(&self, previous: &BuildoutVersions) -> PinDiff {
+        let collect = |from: &HashMap<&str, &Pin>, other: &HashMap<&str, &Pin>| {
+            let mut pins: Vec<PinnedPackage> = from
+                .iter()
+                .filter(|(key, _)| !other.contains_key(*key))
+                .map(|(_, pin)| PinnedPackage {
+                    name: pin.name.clone(),
+                    version: pin.version.clone(),
+                })
+                .collect();
+            pins.sort_by_key(|pin| pin.name.to_lowercase());
+            pins
+        };
+
+        let current = self.effective_pins();
+        let previous = previous.effective_pins();
+        PinDiff {
+            added: collect(&current, &previous),
+            removed: collect(&previous, &current),
+        }
+    }
+
+    /// Leave a note on every pin line rewritten from now on, here and in
+    /// extended files
+    pub fn set_annotate_updates(&mut self, enabled: bool) {
+        self.annotate_updates = enabled;
+        for file in &mut self.extended {
+            file.set_annotate_updates(enabled);
+        }
+    }
+
+    /// Keep the previous content of saved files as `<path>.bak`, here and in extended files
+    pub fn set_keep_backup(&mut self, enabled: bool) {
+        self.keep_backup = enabled;
+        for file in &mut self.extended {
+            file.set_keep_backup(enabled);
+        }
+    }
+
+    /// Update the pin of a package in `section` only (the effective pin when `None`)
+    pub fn update_version_in(
         &mut self,
         package_name: &str,
         new_version: &str,
+        section: Option<&str>,
     ) -> Result<Option<VersionUpdate>> {
-        let old_version = match self.versions.get(package_name) {
-            Some((v, _)) => v.clone(),
-            None => return Ok(None), // Package not in file
+        let Some(pin) = self.pin(package_name, section).cloned() else {
+            // Pinned by an extended file: edit that one instead
+            let Some(file) = self
+                .extended
+                .iter_mut()
+                .find(|file| file.pin(package_name, section).is_some())
+            else {
+                return Ok(None); // Package not in file
+            };
+            if !file.remote {
+                return file.update_version_in(package_name, new_version, section);
+            }
+
+            // Upstream files are read-only: override the inherited pin here
+            let old_version = file.pin(package_name, section).unwrap().version.clone();
+            if old_version == new_version {
+                return Ok(None);
+            }
+            self.add_version(package_name, new_version, section)?;
+            return Ok(Some(VersionUpdate {
+                package_name: package_name.to_string(),
+                old_version,
+                new_version: new_version.to_string(),
+            }));
         };
 
-        if old_version == new_version {
+        if pin.version == new_version {
             return Ok(None); // No change needed
         }
 
-        // Create regex to find and replace the version line
+        // Only rewrite the line of that pin, never a same-named pin in another section
         let pattern = format!(
-            r"(?m)^(\s*{}\s*=\s*){}(\s*(?:#.*)?)$",
-            regex::escape(package_name),
-            regex::escape(&old_version)
+            r"^(\s*{}\s*=\s*){}(\s*(?:#.*)?)$",
+            regex::escape(&pin.name),
+            regex::escape(&pin.version)
         );
         let re =
             Regex::new(&pattern).map_err(|e| ReleaserError::BuildoutParseError(e.to_string()))?;
-
-        self.content = re
-            .replace(&self.content, format!("${{1}}{}${{2}}", new_version))
-            .to_string();
-
-        // Update internal tracking
-        if let Some((v, line)) = self.versions.get_mut(package_name) {
-            *v = new_version.to_string();
-            let _ = line; // Keep line number (it doesn't change)
-        }
+        let note = self.annotate_updates.then(|| {
+            format!(
+                "was {}, updated {} by bldr",
+                pin.version,
+                chrono::Local::now().format("%Y-%m-%d")
+            )
+        });
+        self.edit_line(pin.line, |line| {
+            let line = re.replace(line, format!("${{1}}{}${{2}}", new_version));
+            match note {
+                Some(note) => annotate_pin_line(&line, &note),
+                None => line.into_owned(),
+            }
+        })?;
 
         Ok(Some(VersionUpdate {
             package_name: package_name.to_string(),
-            old_version,
+            old_version: pin.version,
             new_version: new_version.to_string(),
         }))
     }
 
-    /// Add a new package version (if not exists)
-    pub fn add_version(&mut self, package_name: &str, version: &str) -> Result<bool> {
-        if self.versions.contains_key(package_name) {
+    /// Replace the effective pin of `old_name` with `new_name = version` on the same line
+    pub fn rename_pin(&mut self, old_name: &str, new_name: &str, version: &str) -> Result<bool> {
+        let Some(pin) = self.pin(old_name, None).cloned() else {
+            return match self
+                .extended
+                .iter_mut()
+                .find(|file| file.pin(old_name, None).is_some())
+            {
+                Some(file) if !file.remote => file.rename_pin(old_name, new_name, version),
+                _ => Ok(false),
+            };
+        };
+
+        let pattern = format!(
+            r"^(\s*){}(\s*=\s*){}(\s*(?:#.*)?)$",
+            regex::escape(&pin.name),
+            regex::escape(&pin.version)
+        );
+        let re =
+            Regex::new(&pattern).map_err(|e| ReleaserError::BuildoutParseError(e.to_string()))?;
+        self.edit_line(pin.line, |line| {
+            re.replace(line, format!("${{1}}{}${{2}}{}${{3}}", new_name, version))
+                .into_owned()
+        })?;
+
+        Ok(true)
+    }
+
+    /// Add a new package version to `section` (the first `[versions*]` section
+    /// when `None`), unless the package is already pinned there
+    pub fn add_version(
+        &mut self,
+        package_name: &str,
+        version: &str,
+        section: Option<&str>,
+    ) -> Result<bool> {
+        if self.pin(package_name, section).is_some() {
             return Ok(false);
         }
 
-        // Find the [versions] section and add at the end of it
-        let section_re = Regex::new(r"(?m)^\s*\[versions[^\]]*\]\s*$").unwrap();
+        // Find the section and add at the end of it
+        let header = match section {
+            Some(section) => format!(r"(?m)^\s*\[{}\]\s*$", regex::escape(section)),
+            None => r"(?m)^\s*\[versions[^\]]*\]\s*$".to_string(),
+        };
+        let section_re =
+            Regex::new(&header).map_err(|e| ReleaserError::BuildoutParseError(e.to_string()))?;
 
         if let Some(mat) = section_re.find(&self.content) {
             // Find the next section or end of file
@@ -158,42 +679,330 @@ impl BuildoutVersions {
                 self.content.len()
             };
 
-            // Insert the new version line
-            let new_line = format!("{} = {}\n", package_name, version);
+            // Insert the new version line, ending the last one if the file doesn't
+            let mut new_line = format!("{} = {}\n", package_name, version);
+            if insert_pos > 0 && !self.content[..insert_pos].ends_with('\n') {
+                new_line.insert(0, '\n');
+            }
             self.content.insert_str(insert_pos, &new_line);
-
-            self.versions
-                .insert(package_name.to_string(), (version.to_string(), 0));
+            self.modified = true;
+            self.versions = Self::parse_versions(&self.content)?;
 
             Ok(true)
         } else {
-            Err(ReleaserError::BuildoutParseError(
-                "Could not find [versions] section".to_string(),
-            ))
+            Err(ReleaserError::BuildoutParseError(format!(
+                "Could not find [{}] section",
+                section.unwrap_or("versions")
+            )))
         }
     }
 
-    /// Save the modified content back to the file
-    pub fn save(&self) -> Result<()> {
-        std::fs::write(&self.path, &self.content)?;
+    /// Rewrite one line of the content, then re-parse the pins
+    fn edit_line<F: FnOnce(&str) -> String>(&mut self, line: usize, edit: F) -> Result<()> {
+        let mut lines: Vec<&str> = self.content.split_inclusive('\n').collect();
+        let Some(target) = lines.get(line).copied() else {
+            return Err(ReleaserError::BuildoutParseError(format!(
+                "{} has no line {}",
+                self.path,
+                line + 1
+            )));
+        };
+
+        let body = target.trim_end_matches(['\n', '\r']);
+        let edited = format!("{}{}", edit(body), &target[body.len()..]);
+        lines[line] = &edited;
+        self.content = lines.concat();
+
+        self.versions = Self::parse_versions(&self.content)?;
+        self.modified = true;
         Ok(())
     }
 
-    /// Save to a different path
-    pub fn save_to<P: AsRef<Path>>(&self, path: P) -> Result<()> {
-        std::fs::write(path.as_ref(), &self.content)?;
+    /// Rewrite the `[versions*]` sections of this file in canonical form
+    /// (see `format_versions_content`). Returns whether anything changed
+    pub fn format_versions(&mut self) -> Result<bool> {
+        let formatted = format_versions_content(&self.content);
+        if formatted == self.content {
+            return Ok(false);
+        }
+
+        self.content = formatted;
+        self.versions = Self::parse_versions(&self.content)?;
+        self.modified = true;
+        Ok(true)
+    }
+
+    /// Save the modified content back to the file, and any edited extended file
+    pub fn save(&self) -> Result<()> {
+        write_atomic(&self.root.join(&self.path), &self.content, self.keep_backup)?;
+        for file in self.extended.iter().filter(|file| file.modified) {
+            file.save()?;
+        }
         Ok(())
     }
 
-    /// Get the raw content
-    pub fn content(&self) -> &str {
-        &self.content
+    /// Unified diff of the unsaved changes to this file and the extended files
+    /// edited through it; empty when nothing changed
+    pub fn unified_diff(&self) -> String {
+        let mut out = String::new();
+        if self.content != self.original {
+            out.push_str(
+                &similar::TextDiff::from_lines(&self.original, &self.content)
+                    .unified_diff()
+                    .context_radius(3)
+                    .header(&format!("a/{}", self.path), &format!("b/{}", self.path))
+                    .to_string(),
+            );
+        }
+        for file in self.extended.iter().filter(|file| file.modified) {
+            out.push_str(&file.unified_diff());
+        }
+        out
+    }
+}
+
+/// Targets of the `extends` option in the `[buildout]` section
+fn parse_extends(content: &str) -> Vec<String> {
+    let section_re = Regex::new(r"^\s*\[([^\]]+)\]\s*$").unwrap();
+    let option_re = Regex::new(r"^extends\s*[:=](.*)$").unwrap();
+
+    let mut in_buildout = false;
+    let mut collecting = false;
+    let mut targets = Vec::new();
+
+    for line in content.lines() {
+        if let Some(caps) = section_re.captures(line) {
+            in_buildout = caps.get(1).unwrap().as_str().trim() == "buildout";
+            collecting = false;
+            continue;
+        }
+        if !in_buildout || line.trim_start().starts_with(['#', ';']) {
+            continue;
+        }
+
+        let continuation = line.starts_with([' ', '\t']);
+        if collecting && continuation {
+            targets.extend(line.split_whitespace().map(str::to_string));
+        } else if !continuation {
+            collecting = false;
+            if let Some(caps) = option_re.captures(line) {
+                collecting = true;
+                targets.extend(caps[1].split_whitespace().map(str::to_string));
+            }
+        }
+    }
+
+    targets
+}
+
+/// One pin of a `[versions*]` section with the comment lines right above it
+struct PinEntry<'a> {
+    key: String,
+    comments: Vec<&'a str>,
+    line: String,
+}
+
+/// Sort every `[versions*]` section by package name and write pins as
+/// `name = version`. Comment lines directly above a pin move with it; other
+/// comments stay at the top (or, after the first pin, the bottom) of the section.
+/// Other sections are left untouched.
+fn format_versions_content(content: &str) -> String {
+    let section_re = Regex::new(r"^\s*\[([^\]]+)\]\s*$").unwrap();
+    let pin_re = Regex::new(r"^\s*([a-zA-Z0-9._-]+)\s*=\s*([^\s#]+)(.*)$").unwrap();
+    let newline = if content.contains("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    };
+
+    let lines: Vec<&str> = content.lines().collect();
+    let mut out: Vec<String> = Vec::with_capacity(lines.len());
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i];
+        i += 1;
+        out.push(line.to_string());
+
+        let is_versions = section_re
+            .captures(line)
+            .is_some_and(|caps| caps[1].starts_with("versions"));
+        if !is_versions {
+            continue;
+        }
+
+        let end = lines[i..]
+            .iter()
+            .position(|line| section_re.is_match(line))
+            .map_or(lines.len(), |offset| i + offset);
+
+        let mut head: Vec<&str> = Vec::new();
+        let mut tail: Vec<&str> = Vec::new();
+        let mut entries: Vec<PinEntry> = Vec::new();
+        let mut pending: Vec<&str> = Vec::new();
+        for &line in &lines[i..end] {
+            if line.trim().is_empty() {
+                // A blank line detaches the comments above it from the next pin
+                let loose = std::mem::take(&mut pending);
+                if entries.is_empty() {
+                    head.extend(loose);
+                } else {
+                    tail.extend(loose);
+                }
+            } else if let Some(caps) = pin_re.captures(line) {
+                let rest = caps[3].trim();
+                let mut pin = format!("{} = {}", &caps[1], &caps[2]);
+                if rest.starts_with(['#', ';']) {
+                    pin = format!("{}  {}", pin, rest);
+                } else if !rest.is_empty() {
+                    pin = format!("{} {}", pin, rest);
+                }
+                entries.push(PinEntry {
+                    key: caps[1].to_lowercase().replace(['_', '.'], "-"),
+                    comments: std::mem::take(&mut pending),
+                    line: pin,
+                });
+            } else {
+                pending.push(line.trim_end());
+            }
+        }
+        if entries.is_empty() {
+            head.append(&mut pending);
+        } else {
+            tail.append(&mut pending);
+        }
+
+        // Stable, so repeated pins of one package keep their order
+        entries.sort_by(|a, b| a.key.cmp(&b.key));
+
+        let has_pins = !entries.is_empty();
+        out.extend(head.iter().map(|line| line.to_string()));
+        if !head.is_empty() && has_pins {
+            out.push(String::new());
+        }
+        for entry in entries {
+            out.extend(entry.comments.iter().map(|line| line.to_string()));
+            out.push(entry.line);
+        }
+        if !tail.is_empty() {
+            out.push(String::new());
+        }
+        out.extend(tail.iter().map(|line| line.to_string()));
+        if end < lines.len() {
+            out.push(String::new());
+        }
+        i = end;
+    }
+
+    let mut formatted = out.join(newline);
+    if content.ends_with('\n') {
+        formatted.push_str(newline);
+    }
+    formatted
+}
+
+/// Package names of the mr.developer `[sources]` section
+/// (`name = git https://... branch=main`)
+fn parse_sources(content: &str) -> Vec<String> {
+    let section_re = Regex::new(r"^\s*\[([^\]]+)\]\s*$").unwrap();
+    let source_re = Regex::new(r"^([a-zA-Z0-9._-]+)\s*=\s*\S").unwrap();
+
+    let mut in_sources = false;
+    let mut sources = Vec::new();
+    for line in content.lines() {
+        if let Some(caps) = section_re.captures(line) {
+            in_sources = caps[1].trim() == "sources";
+            continue;
+        }
+        if !in_sources {
+            continue;
+        }
+        if let Some(caps) = source_re.captures(line) {
+            sources.push(caps[1].to_string());
+        }
     }
+
+    sources
+}
+
+/// Append `# <note>` to a pin line, replacing a note from an earlier update.
+/// Any other comment on the line is kept as is.
+fn annotate_pin_line(line: &str, note: &str) -> String {
+    let line = PIN_NOTE_RE.replace(line, "");
+    format!("{}  # {}", line.trim_end(), note)
+}
+
+/// `<path>.bak`, where `write_atomic` keeps the previous content of `path`
+pub fn backup_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".bak");
+    path.with_file_name(name)
+}
+
+/// Put back the content `write_atomic` kept in `<path>.bak`, consuming the
+/// backup; `false` when there is none
+pub fn restore_backup(path: &Path) -> Result<bool> {
+    // Restore through symlinks like they were written
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let backup = backup_path(&path);
+    if !backup.is_file() {
+        return Ok(false);
+    }
+    std::fs::rename(&backup, &path).map_err(|source| ReleaserError::FileError {
+        path: path.display().to_string(),
+        source,
+    })?;
+    Ok(true)
+}
+
+/// Replace a file through a temporary file in the same directory, so a crash never
+/// leaves it half-written. With `backup`, the old content is kept as `<path>.bak`
+fn write_atomic(path: &Path, content: &str, backup: bool) -> Result<()> {
+    use std::io::Write;
+
+    // Write through symlinks instead of replacing them
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .ok_or_else(|| {
+            ReleaserError::BuildoutParseError(format!("{} is not a file", path.display()))
+        })?;
+    let temp = path.with_file_name(format!(".{}.bldr-tmp", name));
+
+    let write = || -> std::io::Result<()> {
+        let mut file = std::fs::File::create(&temp)?;
+        file.write_all(content.as_bytes())?;
+        file.sync_all()?;
+        if let Ok(metadata) = std::fs::metadata(&path) {
+            std::fs::set_permissions(&temp, metadata.permissions())?;
+            if backup {
+                std::fs::copy(&path, backup_path(&path))?;
+            }
+        }
+        std::fs::rename(&temp, &path)
+    };
+
+    write().map_err(|source| {
+        let _ = std::fs::remove_file(&temp);
+        ReleaserError::FileError {
+            path: path.display().to_string(),
+            source,
+        }
+    })
+}
+
+/// Path identity used to detect `extends` cycles
+fn canonical(path: &Path) -> String {
+    path.canonicalize()
+        .unwrap_or_else(|_| path.to_path_buf())
+        .to_string_lossy()
+        .into_owned()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testing::temp_dir;
 
     #[test]
     fn test_parse_versions() {
@@ -211,16 +1020,406 @@ plone.api = 2.0.0
 six = 1.16.0
 "#;
 
-        let versions = BuildoutVersions::parse_versions(content).unwrap();
+        let versions = BuildoutVersions::from_content(content.to_string(), "versions.cfg").unwrap();
+
+        assert_eq!(versions.get_version("zope.interface"), Some("5.4.0"));
+        assert_eq!(versions.get_version("plone.api"), Some("2.0.0"));
+        assert_eq!(versions.get_version("six"), Some("1.16.0"));
+        assert_eq!(
+            versions.get_version_in("six", Some("versions:python3")),
+            Some("1.16.0")
+        );
+        assert_eq!(versions.get_version_in("six", Some("versions")), None);
+    }
+
+    #[test]
+    fn test_update_version_in_section() {
+        let content = "[versions]\nsix = 1.15.0\nplone.api = 2.0.0\n\n[versions:python312]\nsix = 1.15.0  # py312\n";
+        let mut versions =
+            BuildoutVersions::from_content(content.to_string(), "versions.cfg").unwrap();
 
+        let update = versions
+            .update_version_in("six", "1.16.0", Some("versions"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(update.old_version, "1.15.0");
         assert_eq!(
-            versions.get("zope.interface").map(|(v, _)| v.as_str()),
-            Some("5.4.0")
+            versions.content,
+            "[versions]\nsix = 1.16.0\nplone.api = 2.0.0\n\n[versions:python312]\nsix = 1.15.0  # py312\n"
         );
+
+        // Without a section the pin that takes effect (the last one) is updated
+        versions.update_version_in("six", "1.17.0", None).unwrap();
         assert_eq!(
-            versions.get("plone.api").map(|(v, _)| v.as_str()),
-            Some("2.0.0")
+            versions.get_version_in("six", Some("versions")),
+            Some("1.16.0")
         );
-        assert_eq!(versions.get("six").map(|(v, _)| v.as_str()), Some("1.16.0"));
+        assert_eq!(
+            versions.get_version_in("six", Some("versions:python312")),
+            Some("1.17.0")
+        );
+        assert!(versions.content.ends_with("six = 1.17.0  # py312\n"));
+
+        assert!(versions
+            .update_version_in("plone.api", "2.1.0", Some("versions:python312"))
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_annotated_updates() {
+        let content = "[versions]\nsix = 1.15.0  # keep for py2\nplone.api = 2.0.0\n";
+        let mut versions =
+            BuildoutVersions::from_content(content.to_string(), "versions.cfg").unwrap();
+        versions.set_annotate_updates(true);
+
+        versions.update_version_in("six", "1.16.0", None).unwrap();
+        versions
+            .update_version_in("plone.api", "2.1.0", None)
+            .unwrap();
+        versions
+            .update_version_in("plone.api", "2.2.0", None)
+            .unwrap();
+
+        let lines: Vec<&str> = versions.content.lines().collect();
+        assert!(lines[1].starts_with("six = 1.16.0  # keep for py2  # was 1.15.0, updated "));
+        assert!(lines[1].ends_with(" by bldr"));
+        // A second update refreshes the note instead of stacking another one
+        assert!(lines[2].starts_with("plone.api = 2.2.0  # was 2.1.0, updated "));
+        assert_eq!(lines[2].matches('#').count(), 1);
+        assert_eq!(versions.get_version("plone.api"), Some("2.2.0"));
+    }
+
+    #[test]
+    fn test_add_version_without_trailing_newline() {
+        let mut versions =
+            BuildoutVersions::from_content("[versions]\nfoo = 1.0".to_string(), "versions.cfg")
+                .unwrap();
+
+        assert!(versions.add_version("bar", "2.0", None).unwrap());
+        assert_eq!(versions.content, "[versions]\nfoo = 1.0\nbar = 2.0\n");
+        assert_eq!(versions.get_version("foo"), Some("1.0"));
+        assert_eq!(versions.get_version("bar"), Some("2.0"));
+    }
+
+    #[test]
+    fn test_pin_names_are_normalized() {
+        let mut versions = BuildoutVersions::from_content(
+            "[versions]\nProducts.CMFPlone = 6.0.9\nzope_interface = 6.0\n".to_string(),
+            "versions.cfg",
+        )
+        .unwrap();
+
+        assert_eq!(versions.get_version("products.cmfplone"), Some("6.0.9"));
+        assert_eq!(versions.get_version("zope.interface"), Some("6.0"));
+        assert!(!versions.add_version("Zope-Interface", "6.1", None).unwrap());
+
+        // The pin keeps its spelling when rewritten
+        versions
+            .update_version_in("products-cmfplone", "6.0.10", None)
+            .unwrap();
+        versions
+            .rename_pin("zope.interface", "zope.interface", "6.1")
+            .unwrap();
+        assert_eq!(
+            versions.content,
+            "[versions]\nProducts.CMFPlone = 6.0.10\nzope.interface = 6.1\n"
+        );
+
+        let previous = BuildoutVersions::from_content(
+            "[versions]\nproducts.cmfplone = 6.0.9\nzope.interface = 6.1\n".to_string(),
+            "versions.cfg@1.0.0",
+        )
+        .unwrap();
+        assert!(versions.diff_pins(&previous).is_empty());
+        assert_eq!(
+            versions.changed_pins(&previous)[0].package_name,
+            "Products.CMFPlone"
+        );
+    }
+
+    #[test]
+    fn test_duplicate_pins() {
+        let versions = BuildoutVersions::from_content(
+            "[versions]\nsix = 1.15.0\nplone.api = 2.0.0\nsix = 1.16.0\n\n[versions:python3]\nsix = 1.16.0\n"
+                .to_string(),
+            "versions.cfg",
+        )
+        .unwrap();
+
+        assert_eq!(
+            versions.duplicate_pins(),
+            vec![DuplicatePin {
+                name: "six".to_string(),
+                section: "versions".to_string(),
+                lines: vec![2, 4],
+            }]
+        );
+        assert_eq!(
+            versions.pin_location("plone.api"),
+            Some(("versions.cfg", 3))
+        );
+    }
+
+    #[test]
+    fn test_load_with_extends() {
+        let tmp = temp_dir("extends");
+        let dir = tmp.path();
+        std::fs::create_dir_all(dir.join("base")).unwrap();
+        std::fs::write(
+            dir.join("base/plone.cfg"),
+            "[versions]\nplone.api = 2.0.0\nsix = 1.15.0\nzope.interface = 5.4.0\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("overrides.cfg"),
+            "[buildout]\nextends = versions.cfg\n\n[versions]\nsix = 1.16.0\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("versions.cfg"),
+            "[buildout]\nextends =\n    base/plone.cfg\n    overrides.cfg\n\n[versions]\nplone.api = 2.1.0\n",
+        )
+        .unwrap();
+
+        let path = dir.join("versions.cfg");
+        let mut versions =
+            BuildoutVersions::load_with_extends_in(dir, "versions.cfg", &HashMap::new()).unwrap();
+
+        assert_eq!(
+            versions.paths(),
+            vec!["versions.cfg", "overrides.cfg", "base/plone.cfg"]
+        );
+        assert_eq!(versions.get_version("plone.api"), Some("2.1.0"));
+        assert_eq!(versions.get_version("six"), Some("1.16.0"));
+        assert_eq!(versions.get_version("zope.interface"), Some("5.4.0"));
+        assert_eq!(versions.get_all_versions().count(), 3);
+
+        versions
+            .update_version_in("zope.interface", "6.0", None)
+            .unwrap();
+        versions.save().unwrap();
+
+        let base = std::fs::read_to_string(dir.join("base/plone.cfg")).unwrap();
+        assert!(base.contains("zope.interface = 6.0"));
+        assert!(!std::fs::read_to_string(&path)
+            .unwrap()
+            .contains("zope.interface"));
+    }
+
+    #[test]
+    fn test_remote_extends() {
+        let tmp = temp_dir("remote-extends");
+        let dir = tmp.path();
+        let path = dir.join("versions.cfg");
+        std::fs::write(
+            &path,
+            "[buildout]\nextends = https://dist.plone.org/release/6.0.10/versions.cfg\n\n[versions]\nplone.api = 2.1.0\n",
+        )
+        .unwrap();
+
+        let kgs = "https://dist.plone.org/release/6.0.10/versions.cfg";
+        let extra = "https://dist.plone.org/release/6.0.10/versions-extra.cfg";
+        let mut remote = HashMap::new();
+
+        let versions =
+            BuildoutVersions::load_with_extends_in(Path::new(""), &path, &remote).unwrap();
+        assert_eq!(versions.unresolved_extends(), [kgs]);
+
+        remote.insert(
+            kgs.to_string(),
+            "[buildout]\nextends = versions-extra.cfg\n\n[versions]\nplone.api = 2.0.0\nsix = 1.16.0\n"
+                .to_string(),
+        );
+        let versions =
+            BuildoutVersions::load_with_extends_in(Path::new(""), &path, &remote).unwrap();
+        assert_eq!(versions.unresolved_extends(), [extra]);
+
+        remote.insert(
+            extra.to_string(),
+            "[versions]\nzope.interface = 6.0\n".to_string(),
+        );
+        let mut versions =
+            BuildoutVersions::load_with_extends_in(Path::new(""), &path, &remote).unwrap();
+        assert!(versions.unresolved_extends().is_empty());
+        assert_eq!(versions.get_version("plone.api"), Some("2.1.0"));
+        assert_eq!(versions.upstream_source("plone.api"), None);
+        assert_eq!(versions.upstream_source("six"), Some(kgs));
+        assert_eq!(versions.upstream_source("zope.interface"), Some(extra));
+        assert_eq!(versions.paths().len(), 1);
+
+        // Inherited pins are overridden locally instead of editing the upstream file
+        let update = versions
+            .update_version_in("six", "1.17.0", None)
+            .unwrap()
+            .unwrap();
+        assert_eq!(update.old_version, "1.16.0");
+        assert_eq!(versions.upstream_source("six"), None);
+        assert!(versions.content.contains("six = 1.17.0"));
+
+        // A known-good set loaded on its own, with what it extends
+        let kgs_versions = BuildoutVersions::load_remote(kgs, &remote).unwrap();
+        assert!(kgs_versions.unresolved_extends().is_empty());
+        assert_eq!(kgs_versions.get_version("plone.api"), Some("2.0.0"));
+        assert_eq!(kgs_versions.get_version("zope.interface"), Some("6.0"));
+    }
+
+    #[test]
+    fn test_rename_pin() {
+        let mut versions = BuildoutVersions::from_content(
+            "[versions]\nProducts.X = 1.4  # legacy\nsix = 1.16.0\n".to_string(),
+            "versions.cfg",
+        )
+        .unwrap();
+
+        assert!(versions
+            .rename_pin("Products.X", "collective.x", "2.0.1")
+            .unwrap());
+        assert!(!versions
+            .rename_pin("Products.Y", "collective.y", "1.0")
+            .unwrap());
+
+        assert_eq!(versions.get_version("Products.X"), None);
+        assert_eq!(versions.get_version("collective.x"), Some("2.0.1"));
+        assert_eq!(
+            versions.content,
+            "[versions]\ncollective.x = 2.0.1  # legacy\nsix = 1.16.0\n"
+        );
+    }
+
+    #[test]
+    fn test_find_pin_mismatches() {
+        let prod = BuildoutVersions::from_content(
+            "[versions]\nplone.api = 2.0.0\nsix = 1.16.0\nprod.only = 1.0\n".to_string(),
+            "prod.cfg",
+        )
+        .unwrap();
+        let worker = BuildoutVersions::from_content(
+            "[versions]\nplone.api = 2.0.1\nsix = 1.16.0\ncelery = 5.3.0\n".to_string(),
+            "worker.cfg",
+        )
+        .unwrap();
+
+        assert_eq!(
+            find_pin_mismatches(&[prod, worker]),
+            vec![PinMismatch {
+                package: "plone.api".to_string(),
+                pins: vec![
+                    ("prod.cfg".to_string(), "2.0.0".to_string()),
+                    ("worker.cfg".to_string(), "2.0.1".to_string()),
+                ],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_diff_pins() {
+        let previous = BuildoutVersions::from_content(
+            "[versions]\nplone.api = 2.0.0\nplone.bar = 1.0.0\n".to_string(),
+            "versions.cfg@1.0.0",
+        )
+        .unwrap();
+        let current = BuildoutVersions::from_content(
+            "[versions]\nplone.api = 2.1.0\nplone.foo = 1.0.0\n".to_string(),
+            "versions.cfg",
+        )
+        .unwrap();
+
+        let diff = current.diff_pins(&previous);
+
+        assert_eq!(
+            diff.added,
+            vec![PinnedPackage {
+                name: "plone.foo".to_string(),
+                version: "1.0.0".to_string(),
+            }]
+        );
+        assert_eq!(diff.removed.len(), 1);
+        assert_eq!(diff.removed[0].name, "plone.bar");
+
+        assert_eq!(
+            current.changed_pins(&previous),
+            vec![VersionUpdate {
+                package_name: "plone.api".to_string(),
+                old_version: "2.0.0".to_string(),
+                new_version: "2.1.0".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_format_versions() {
+        let content = "[buildout]\nparts = app\n\n[versions]\n# Pinned by the KGS\n\nzope.interface=6.0\n# needed until plone/issues#1\nPlone.API =  2.1.0   # keep\nsix = 1.16.0\n\n# zc.recipe.egg = 2.0.7\n[versions:python312]\nsix = 1.16.0\nattrs = 23.1.0\n";
+        let mut versions =
+            BuildoutVersions::from_content(content.to_string(), "versions.cfg").unwrap();
+
+        assert!(versions.format_versions().unwrap());
+        assert_eq!(
+            versions.content,
+            "[buildout]\nparts = app\n\n[versions]\n# Pinned by the KGS\n\n# needed until plone/issues#1\nPlone.API = 2.1.0  # keep\nsix = 1.16.0\nzope.interface = 6.0\n\n# zc.recipe.egg = 2.0.7\n\n[versions:python312]\nattrs = 23.1.0\nsix = 1.16.0\n"
+        );
+        assert_eq!(versions.get_version("Plone.API"), Some("2.1.0"));
+
+        // Formatting is idempotent
+        assert!(!versions.format_versions().unwrap());
+    }
+
+    #[test]
+    fn test_unified_diff() {
+        let mut versions = BuildoutVersions::from_content(
+            "[versions]\nplone.api = 2.0.0\nsix = 1.15.0\n".to_string(),
+            "versions.cfg",
+        )
+        .unwrap();
+        assert_eq!(versions.unified_diff(), "");
+
+        versions.update_version_in("six", "1.16.0", None).unwrap();
+        assert_eq!(
+            versions.unified_diff(),
+            "--- a/versions.cfg\n+++ b/versions.cfg\n@@ -1,3 +1,3 @@\n [versions]\n plone.api = 2.0.0\n-six = 1.15.0\n+six = 1.16.0\n"
+        );
+    }
+
+    #[test]
+    fn test_source_checkouts() {
+        let content = "[buildout]\nextensions = mr.developer\nauto-checkout = *\n\n[sources]\n# our add-ons\ncollective.foo = git https://github.com/collective/collective.foo.git branch=main\n    pushurl=git@github.com:collective/collective.foo.git\nmy.theme = fs my.theme\n\n[versions]\ncollective.foo = 1.0\n";
+        let versions = BuildoutVersions::from_content(content.to_string(), "versions.cfg").unwrap();
+
+        let sources: Vec<(&str, &str)> = versions.source_checkouts().collect();
+        assert_eq!(
+            sources,
+            [
+                ("collective.foo", "versions.cfg"),
+                ("my.theme", "versions.cfg")
+            ]
+        );
+        assert_eq!(versions.get_version("collective.foo"), Some("1.0"));
+    }
+
+    #[test]
+    fn test_save_atomic_with_backup() {
+        let tmp = temp_dir("atomic");
+        let dir = tmp.path();
+        let path = dir.join("versions.cfg");
+        std::fs::write(&path, "[versions]\nsix = 1.15.0\n").unwrap();
+
+        let mut versions = BuildoutVersions::load_in(dir, "versions.cfg").unwrap();
+        versions.set_keep_backup(true);
+        versions.update_version_in("six", "1.16.0", None).unwrap();
+        versions.save().unwrap();
+
+        let saved = std::fs::read_to_string(&path).unwrap();
+        let backup = std::fs::read_to_string(dir.join("versions.cfg.bak")).unwrap();
+        let leftovers = std::fs::read_dir(dir).unwrap().count();
+        let restored = restore_backup(&path).unwrap();
+        let content = std::fs::read_to_string(&path).unwrap();
+        let restored_again = restore_backup(&path).unwrap();
+
+        assert_eq!(saved, "[versions]\nsix = 1.16.0\n");
+        assert_eq!(backup, "[versions]\nsix = 1.15.0\n");
+        assert_eq!(leftovers, 2);
+        assert!(restored);
+        assert_eq!(content, "[versions]\nsix = 1.15.0\n");
+        assert!(!restored_again);
     }
 }
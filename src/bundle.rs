@@ -0,0 +1,371 @@
+use crate::buildout::BuildoutVersions;
+use crate::error::{ReleaserError, Result};
+use crate::pypi::{PyPiClient, ReleaseInfo};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// Description of an offline bundle, written next to the downloaded files
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BundleManifest {
+    /// Release tag the bundle was built for, if any
+    pub release: Option<String>,
+    pub created: String,
+    pub files: Vec<BundleFile>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleFile {
+    pub package: String,
+    /// Name of the pin in the versions file, when it differs from the package name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub buildout_name: Option<String>,
+    pub version: String,
+    pub filename: String,
+    pub sha256: String,
+    pub url: String,
+}
+
+/// Download every non-yanked file of a pinned release into `dir`, verifying
+/// the sha256 digest PyPI advertises. Files already present and matching are kept.
+pub async fn download_release(
+    pypi: &PyPiClient,
+    package: &str,
+    buildout_name: &str,
+    version: &str,
+    dir: &Path,
+) -> Result<Vec<BundleFile>> {
+    let files: Vec<ReleaseInfo> = pypi
+        .release_files(package, version)
+        .await?
+        .into_iter()
+        .filter(|f| !f.yanked)
+        .collect();
+
+    if files.is_empty() {
+        return Err(ReleaserError::PackageNotFound(format!(
+            "{}=={} has no downloadable files",
+            package, version
+        )));
+    }
+
+    let mut bundled = Vec::with_capacity(files.len());
+
+    for file in files {
+        let target = dir.join(safe_filename(&file.filename)?);
+        let expected = file.digests.get("sha256").map(|d| d.to_lowercase());
+
+        let existing = std::fs::read(&target).ok().map(|bytes| sha256_hex(&bytes));
+        let sha256 = match existing {
+            Some(digest) if expected.as_deref().is_some_and(|e| e == digest) => digest,
+            _ => {
+                let bytes = pypi.download(&file.url).await?;
+                let digest = sha256_hex(&bytes);
+                if let Some(ref expected) = expected {
+                    if *expected != digest {
+                        return Err(ReleaserError::PyPiError(format!(
+                            "Checksum mismatch for {}: expected {}, got {}",
+                            file.filename, expected, digest
+                        )));
+                    }
+                }
+                std::fs::write(&target, &bytes)?;
+                digest
+            }
+        };
+
+        bundled.push(BundleFile {
+            package: package.to_string(),
+            buildout_name: (buildout_name != package).then(|| buildout_name.to_string()),
+            version: version.to_string(),
+            filename: file.filename,
+            sha256,
+            url: file.url,
+        });
+    }
+
+    Ok(bundled)
+}
+
+/// Write the manifest into the bundle directory
+pub fn write_manifest(dir: &Path, manifest: &BundleManifest) -> Result<PathBuf> {
+    let path = dir.join(MANIFEST_FILE);
+    let content = serde_json::to_string_pretty(manifest)
+        .map_err(|e| ReleaserError::IoError(std::io::Error::other(e)))?;
+    std::fs::write(&path, content)?;
+    Ok(path)
+}
+
+/// Read the manifest of an existing bundle directory
+pub fn load_manifest(dir: &Path) -> Result<BundleManifest> {
+    let path = dir.join(MANIFEST_FILE);
+    let content = std::fs::read_to_string(&path).map_err(|e| {
+        ReleaserError::BundleError(format!("cannot read {}: {}", path.display(), e))
+    })?;
+    serde_json::from_str(&content)
+        .map_err(|e| ReleaserError::BundleError(format!("invalid {}: {}", path.display(), e)))
+}
+
+/// A difference between a bundle, its manifest and the current versions file
+#[derive(Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Drift {
+    MissingFile {
+        filename: String,
+    },
+    ChecksumMismatch {
+        filename: String,
+        expected: String,
+        actual: String,
+    },
+    UnexpectedFile {
+        filename: String,
+    },
+    PinChanged {
+        package: String,
+        bundled: String,
+        pinned: String,
+    },
+    NotPinned {
+        package: String,
+        bundled: String,
+    },
+}
+
+impl fmt::Display for Drift {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Drift::MissingFile { filename } => write!(f, "{}: missing from bundle", filename),
+            Drift::ChecksumMismatch {
+                filename,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "{}: sha256 {} does not match manifest ({})",
+                filename, actual, expected
+            ),
+            Drift::UnexpectedFile { filename } => write!(f, "{}: not listed in manifest", filename),
+            Drift::PinChanged {
+                package,
+                bundled,
+                pinned,
+            } => write!(
+                f,
+                "{}: bundled {} but versions file pins {}",
+                package, bundled, pinned
+            ),
+            Drift::NotPinned { package, bundled } => write!(
+                f,
+                "{}: bundled {} but no longer pinned in versions file",
+                package, bundled
+            ),
+        }
+    }
+}
+
+/// Re-hash every bundled file and compare the manifest against the pins in `versions`
+pub fn verify(
+    dir: &Path,
+    manifest: &BundleManifest,
+    versions: &BuildoutVersions,
+) -> Result<Vec<Drift>> {
+    let mut drift = Vec::new();
+
+    for file in &manifest.files {
+        let path = dir.join(safe_filename(&file.filename)?);
+        match std::fs::read(&path) {
+            Ok(bytes) => {
+                let actual = sha256_hex(&bytes);
+                if !actual.eq_ignore_ascii_case(&file.sha256) {
+                    drift.push(Drift::ChecksumMismatch {
+                        filename: file.filename.clone(),
+                        expected: file.sha256.clone(),
+                        actual,
+                    });
+                }
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                drift.push(Drift::MissingFile {
+                    filename: file.filename.clone(),
+                });
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+
+    let listed: HashSet<&str> = manifest.files.iter().map(|f| f.filename.as_str()).collect();
+    let mut unexpected: Vec<String> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().map(|t| t.is_file()).unwrap_or(false))
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .filter(|name| name != MANIFEST_FILE && !listed.contains(name.as_str()))
+        .collect();
+    unexpected.sort();
+    drift.extend(
+        unexpected
+            .into_iter()
+            .map(|filename| Drift::UnexpectedFile { filename }),
+    );
+
+    // One entry per package, even when it has several files
+    let bundled: BTreeMap<&str, &BundleFile> = manifest
+        .files
+        .iter()
+        .map(|f| (f.package.as_str(), f))
+        .collect();
+    for (package, file) in bundled {
+        let pin_name = file.buildout_name.as_deref().unwrap_or(package);
+        match versions.get_version(pin_name) {
+            Some(pinned) if pinned == file.version => {}
+            Some(pinned) => drift.push(Drift::PinChanged {
+                package: package.to_string(),
+                bundled: file.version.clone(),
+                pinned: pinned.to_string(),
+            }),
+            None => drift.push(Drift::NotPinned {
+                package: package.to_string(),
+                bundled: file.version.clone(),
+            }),
+        }
+    }
+
+    Ok(drift)
+}
+
+/// Pack the bundle directory into `<dir>.tar.gz` using the system tar
+pub fn create_tarball(dir: &Path) -> Result<PathBuf> {
+    let name = dir
+        .file_name()
+        .ok_or_else(|| ReleaserError::ConfigError("Invalid bundle directory".to_string()))?;
+    let parent = dir
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let archive = parent.join(format!("{}.tar.gz", name.to_string_lossy()));
+
+    let output = Command::new("tar")
+        .arg("-czf")
+        .arg(&archive)
+        .arg("-C")
+        .arg(parent)
+        .arg(name)
+        .output()?;
+
+    if !output.status.success() {
+        return Err(ReleaserError::IoError(std::io::Error::other(format!(
+            "tar failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))));
+    }
+
+    Ok(archive)
+}
+
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Reject filenames that would escape the bundle directory
+fn safe_filename(filename: &str) -> Result<&str> {
+    if filename.is_empty()
+        || filename.contains('/')
+        || filename.contains('\\')
+        || filename.starts_with('.')
+    {
+        return Err(ReleaserError::PyPiError(format!(
+            "Refusing to write unexpected filename {:?}",
+            filename
+        )));
+    }
+    Ok(filename)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::temp_dir;
+
+    #[test]
+    fn test_sha256_hex() {
+        assert_eq!(
+            sha256_hex(b"bldr"),
+            "7a026f37f3eec847f6d58f593a25f7737d2bf5efb69cf6e2233f0caa3baa6ba9"
+        );
+    }
+
+    #[test]
+    fn test_verify_reports_drift() {
+        let tmp = temp_dir("bundle-verify");
+        let dir = tmp.path();
+        std::fs::write(dir.join("good-1.0.tar.gz"), b"good").unwrap();
+        std::fs::write(dir.join("bad-2.0.tar.gz"), b"tampered").unwrap();
+        std::fs::write(dir.join("stray.whl"), b"stray").unwrap();
+
+        let file = |package: &str, version: &str, filename: &str, content: &[u8]| BundleFile {
+            package: package.to_string(),
+            buildout_name: None,
+            version: version.to_string(),
+            filename: filename.to_string(),
+            sha256: sha256_hex(content),
+            url: String::new(),
+        };
+        let manifest = BundleManifest {
+            release: Some("v1.0".to_string()),
+            created: String::new(),
+            files: vec![
+                file("good", "1.0", "good-1.0.tar.gz", b"good"),
+                file("bad", "2.0", "bad-2.0.tar.gz", b"bad"),
+                file("gone", "3.0", "gone-3.0.tar.gz", b"gone"),
+            ],
+        };
+        let versions_path = dir.join("versions.cfg");
+        std::fs::write(&versions_path, "[versions]\ngood = 1.0\nbad = 2.1\n").unwrap();
+        let versions = BuildoutVersions::load_in(Path::new(""), &versions_path).unwrap();
+
+        let drift = verify(dir, &manifest, &versions).unwrap();
+
+        assert!(
+            matches!(&drift[0], Drift::ChecksumMismatch { filename, .. } if filename == "bad-2.0.tar.gz")
+        );
+        assert_eq!(
+            drift[1..],
+            [
+                Drift::MissingFile {
+                    filename: "gone-3.0.tar.gz".to_string()
+                },
+                Drift::UnexpectedFile {
+                    filename: "stray.whl".to_string()
+                },
+                Drift::UnexpectedFile {
+                    filename: "versions.cfg".to_string()
+                },
+                Drift::PinChanged {
+                    package: "bad".to_string(),
+                    bundled: "2.0".to_string(),
+                    pinned: "2.1".to_string()
+                },
+                Drift::NotPinned {
+                    package: "gone".to_string(),
+                    bundled: "3.0".to_string()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_safe_filename() {
+        assert!(safe_filename("plone.api-2.0.0-py3-none-any.whl").is_ok());
+        assert!(safe_filename("../etc/passwd").is_err());
+        assert!(safe_filename("dir/file.tar.gz").is_err());
+        assert!(safe_filename("").is_err());
+    }
+}
@@ -0,0 +1,179 @@
+use crate::buildout::BuildoutVersions;
+use crate::error::Result;
+use crate::pypi::normalize_project_name;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+
+/// Cache root inside the checkout
+pub const DEFAULT_CACHE_DIR: &str = ".bldr/cache";
+
+/// On-disk cache of parsed versions snapshots, keyed by commit SHA and file path
+pub struct SnapshotCache {
+    dir: PathBuf,
+}
+
+impl SnapshotCache {
+    pub fn new<P: AsRef<Path>>(root: P) -> Self {
+        Self {
+            dir: root.as_ref().join("snapshots"),
+        }
+    }
+
+    fn entry_path(&self, commit: &str, versions_file: &str) -> PathBuf {
+        let file_key: String = versions_file
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        self.dir.join(format!("{}-{}.json", commit, file_key))
+    }
+
+    /// Load a cached snapshot, ignoring missing or unreadable entries
+    pub fn get(&self, commit: &str, versions_file: &str) -> Option<BuildoutVersions> {
+        let content = std::fs::read_to_string(self.entry_path(commit, versions_file)).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    /// Store a parsed snapshot
+    pub fn put(
+        &self,
+        commit: &str,
+        versions_file: &str,
+        snapshot: &BuildoutVersions,
+    ) -> Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        let content = serde_json::to_string(snapshot)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        std::fs::write(self.entry_path(commit, versions_file), content)?;
+        Ok(())
+    }
+}
+
+/// A changelog file as last downloaded, with the ETag to revalidate it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedChangelog {
+    pub url: String,
+    pub etag: String,
+    pub content: String,
+}
+
+/// On-disk cache of raw changelog files, keyed by package and URL, shared by
+/// every project of the user (`~/.cache/bldr/changelogs`)
+#[derive(Debug, Clone)]
+pub struct ChangelogCache {
+    dir: PathBuf,
+    refresh: bool,
+}
+
+impl ChangelogCache {
+    pub fn new<P: AsRef<Path>>(root: P) -> Self {
+        Self {
+            dir: root.as_ref().join("changelogs"),
+            refresh: false,
+        }
+    }
+
+    /// `$XDG_CACHE_HOME/bldr`, or `~/.cache/bldr`
+    pub fn user() -> Option<Self> {
+        let root = std::env::var_os("XDG_CACHE_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".cache")))?;
+        Some(Self::new(root.join("bldr")))
+    }
+
+    /// Ignore cached entries (they are still rewritten)
+    pub fn refresh(mut self, refresh: bool) -> Self {
+        self.refresh = refresh;
+        self
+    }
+
+    fn entry_path(&self, package: &str, url: &str) -> PathBuf {
+        let digest = Sha256::digest(url.as_bytes());
+        let url_key: String = digest[..8].iter().map(|b| format!("{:02x}", b)).collect();
+        self.dir
+            .join(normalize_project_name(package))
+            .join(format!("{}.json", url_key))
+    }
+
+    /// Load a cached changelog, ignoring missing or unreadable entries
+    pub fn get(&self, package: &str, url: &str) -> Option<CachedChangelog> {
+        if self.refresh {
+            return None;
+        }
+        let content = std::fs::read_to_string(self.entry_path(package, url)).ok()?;
+        serde_json::from_str::<CachedChangelog>(&content)
+            .ok()
+            .filter(|entry| entry.url == url)
+    }
+
+    /// Store a downloaded changelog
+    pub fn put(&self, package: &str, entry: &CachedChangelog) -> Result<()> {
+        let path = self.entry_path(package, &entry.url);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let content = serde_json::to_string(entry)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        std::fs::write(path, content)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::temp_dir;
+
+    #[test]
+    fn test_snapshot_round_trip() {
+        let tmp = temp_dir("cache");
+        let root = tmp.path().join("cache");
+        let cache = SnapshotCache::new(&root);
+
+        assert!(cache.get("abc123", "versions.cfg").is_none());
+
+        let snapshot = BuildoutVersions::from_content(
+            "[versions]\nplone.api = 2.0.0\n".to_string(),
+            "versions.cfg@1.0.0",
+        )
+        .unwrap();
+        cache.put("abc123", "versions.cfg", &snapshot).unwrap();
+
+        let cached = cache
+            .get("abc123", "versions.cfg")
+            .expect("cached snapshot");
+
+        assert_eq!(cached.get_version("plone.api"), Some("2.0.0"));
+        assert!(cache.get("abc123", "other/versions.cfg").is_none());
+    }
+
+    #[test]
+    fn test_changelog_cache_round_trip() {
+        let tmp = temp_dir("changelogs");
+        let root = tmp.path().join("changelogs");
+        let cache = ChangelogCache::new(&root);
+        let url = "https://raw.githubusercontent.com/plone/plone.api/main/CHANGES.rst";
+
+        assert!(cache.get("plone.api", url).is_none());
+        cache
+            .put(
+                "plone.api",
+                &CachedChangelog {
+                    url: url.to_string(),
+                    etag: "\"abc\"".to_string(),
+                    content: "2.1.0 (2025-06-01)\n".to_string(),
+                },
+            )
+            .unwrap();
+
+        let cached = cache.get("Plone_API", url).expect("cached changelog");
+        let refreshing = cache.clone().refresh(true).get("plone.api", url);
+        let other_url = cache.get("plone.api", "https://example.com/CHANGES.rst");
+
+        assert_eq!(cached.etag, "\"abc\"");
+        assert_eq!(cached.content, "2.1.0 (2025-06-01)\n");
+        assert!(refreshing.is_none());
+        assert!(other_url.is_none());
+    }
+}
@@ -1,26 +1,52 @@
-use crate::buildout::VersionUpdate;
-use crate::config::{ChangelogConfig, ChangelogFormat, PackageConfig};
+use crate::buildout::{PinDiff, PinnedPackage, VersionUpdate};
+use crate::cache::{CachedChangelog, ChangelogCache};
+use crate::config::{ChangelogConfig, ChangelogFormat, ChangelogSource, HttpConfig, PackageConfig};
 use crate::error::{ReleaserError, Result};
+use crate::http::{self, RetryPolicy};
+use crate::markup;
+use crate::pypi::PackageMetadataStore;
+use crate::version::python::compare_versions_lenient;
+use crate::warnings::{self, WarningKind};
 use regex::Regex;
-use reqwest::Client;
+use reqwest::header::{ETAG, IF_NONE_MATCH};
+use reqwest::{Client, StatusCode};
+use serde::Serialize;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
+use std::sync::{Arc, Mutex};
 
 const USER_AGENT: &str = concat!("bldr/", env!("CARGO_PKG_VERSION"));
 
+/// A changelog document and the markup it declares, when known
+type Document = (String, Option<ChangelogFormat>);
+
 #[derive(Debug, Clone)]
 pub struct PackageChangelog {
     pub package_name: String,
     pub old_version: String,
     pub new_version: String,
     pub entries: Vec<ChangelogEntry>,
-    pub raw_content: Option<String>,
+    /// Web page of the upstream diff between the two release tags
+    pub compare_url: Option<String>,
+    /// A changelog file was cut at `changelog.max_download_bytes`
+    pub truncated: bool,
 }
 
-#[derive(Debug, Clone)]
+impl PackageChangelog {
+    /// The pin went back to an older version; the entries are the reverted ones
+    pub fn is_downgrade(&self) -> bool {
+        compare_versions_lenient(&self.new_version, &self.old_version) == Ordering::Less
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ChangelogEntry {
     pub version: String,
     pub date: Option<String>,
     pub content: String,
+    /// Markup the upstream document declared, when known
+    pub markup: Option<ChangelogFormat>,
 }
 
 #[derive(Debug, Clone)]
@@ -28,33 +54,96 @@ pub struct ConsolidatedChangelog {
     pub release_version: String,
     pub date: String,
     pub package_changelogs: Vec<PackageChangelog>,
+    /// Pins listed in an initial release (no previous release to diff against)
+    pub initial_pins: Vec<PinnedPackage>,
+    /// Pins that appeared since the previous release
+    pub added_packages: Vec<PinnedPackage>,
+    /// Pins that disappeared since the previous release
+    pub removed_packages: Vec<PinnedPackage>,
     pub header_template: String,
     pub package_template: String,
+    /// Merge the entries of all updated packages by Keep a Changelog category
+    pub group_by_category: bool,
+    /// Markdown templates of packages overriding the global ones, by package name
+    package_overrides: HashMap<String, PackageTemplates>,
+    /// Words that put an entry line in the attention summary
+    pub attention_keywords: Vec<String>,
+    /// Rewrite entries in the markup of the output format
+    pub convert_markup: bool,
+    /// Render the "Thanks to" section
+    pub show_contributors: bool,
+    /// Authors of our own commits in this release
+    pub contributors: Vec<String>,
+}
+
+/// An entry line that mentions an attention keyword (breaking, CVE, ...)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AttentionItem {
+    pub package: String,
+    pub version: String,
+    pub line: String,
+}
+
+/// A package's own `package_template` and `entry_template`
+#[derive(Debug, Clone, Default)]
+struct PackageTemplates {
+    package: Option<String>,
+    entry: Option<String>,
 }
 
 pub struct ChangelogCollector {
     client: Client,
+    retry: RetryPolicy,
     changelog_files: Vec<String>,
     github_branches: Vec<String>,
+    gitlab_hosts: Vec<String>,
+    commit_fallback: bool,
+    compare_links: bool,
+    max_download_bytes: usize,
+    /// Packages whose changelog file was cut while downloading
+    truncated: Mutex<HashSet<String>>,
+    store: Option<PackageMetadataStore>,
+    cache: Option<ChangelogCache>,
 }
 
 impl ChangelogCollector {
     pub fn new() -> Self {
-        Self::with_config(&ChangelogConfig::default())
+        Self::with_config(&ChangelogConfig::default(), &HttpConfig::default())
+            .expect("Failed to create HTTP client")
     }
 
-    pub fn with_config(config: &ChangelogConfig) -> Self {
+    pub fn with_config(config: &ChangelogConfig, http: &HttpConfig) -> Result<Self> {
         let mut github_branches = vec!["main".to_string(), "master".to_string()];
         github_branches.extend(config.github_branches.clone());
 
-        Self {
-            client: Client::builder()
-                .user_agent(USER_AGENT)
-                .build()
-                .expect("Failed to create HTTP client"),
+        let builder = Client::builder().user_agent(USER_AGENT);
+
+        Ok(Self {
+            client: http::configure_client(builder, http)?.build()?,
+            retry: RetryPolicy::from_config(http),
             changelog_files: config.changelog_files.clone(),
             github_branches,
-        }
+            gitlab_hosts: config.gitlab_hosts.clone(),
+            commit_fallback: config.commit_fallback,
+            compare_links: config.compare_links,
+            max_download_bytes: config.max_download_bytes,
+            truncated: Mutex::new(HashSet::new()),
+            store: None,
+            cache: None,
+        })
+    }
+
+    /// Reuse project metadata already downloaded by the update phase
+    pub fn with_store(mut self, store: &PackageMetadataStore) -> Self {
+        self.store = Some(store.clone());
+        self
+    }
+
+    /// Revalidate changelog files downloaded by earlier runs instead of
+    /// downloading them again
+    pub fn with_cache(mut self, cache: ChangelogCache) -> Self {
+        self.cache = Some(cache);
+        self
     }
 
     /// Fetch changelog for a package from various sources
@@ -63,50 +152,394 @@ impl ChangelogCollector {
         package_name: &str,
         old_version: &str,
         new_version: &str,
-        custom_url: Option<&str>,
+        custom_urls: &[&str],
+        sources: &[ChangelogSource],
     ) -> Result<PackageChangelog> {
-        // Try custom URL first if provided
-        let raw_content = if let Some(url) = custom_url {
-            self.fetch_url_content(url).await.ok().flatten()
-        } else {
-            self.try_fetch_from_pypi(package_name)
+        let (lower, higher) = version_bounds(old_version, new_version);
+        let compare_url = if self.compare_links {
+            self.compare_url(package_name, lower, higher, custom_urls)
                 .await
-                .ok()
-                .flatten()
+        } else {
+            None
+        };
+
+        if !sources.is_empty() {
+            let mut changelog = self
+                .fetch_from_sources(package_name, old_version, new_version, custom_urls, sources)
+                .await;
+            changelog.compare_url = compare_url;
+            changelog.truncated = self.take_truncated(package_name);
+            return Ok(changelog);
+        }
+
+        // Custom URLs take precedence; split history files are concatenated
+        let (mut raw_content, markup) = if custom_urls.is_empty() {
+            match self.try_fetch_from_pypi(package_name).await.ok().flatten() {
+                Some((content, markup)) => (Some(content), markup),
+                None => (None, None),
+            }
+        } else {
+            (
+                self.fetch_concatenated(package_name, custom_urls).await,
+                custom_urls.first().and_then(|url| markup_of_url(url)),
+            )
         };
 
+        if let Some(ref content) = raw_content {
+            if !covers_version(content, lower) {
+                if let Some(older) = self
+                    .follow_older_entries_link(package_name, content, custom_urls.last().copied())
+                    .await
+                {
+                    raw_content = Some(format!("{}\n\n{}", content, older));
+                }
+            }
+        }
+
         let mut entries = if let Some(ref content) = raw_content {
-            self.parse_changelog(content, old_version, new_version)
+            self.parse_changelog_as(content, old_version, new_version, markup)
         } else {
             Vec::new()
         };
 
-        if entries.is_empty() && custom_url.is_none() {
-            if let Ok(Some(content)) = self
-                .try_fetch_from_pypi_release(package_name, new_version)
-                .await
+        if entries.is_empty() && custom_urls.is_empty() {
+            if let Ok(Some((content, markup))) =
+                self.try_fetch_from_pypi_release(package_name, higher).await
             {
-                let fallback_entries = self.parse_changelog(&content, old_version, new_version);
+                let fallback_entries =
+                    self.parse_changelog_as(&content, old_version, new_version, markup);
                 if !fallback_entries.is_empty() {
                     entries = fallback_entries;
                 }
             }
         }
 
+        if entries.is_empty() && self.commit_fallback {
+            entries.extend(
+                self.commit_log_entry(package_name, lower, higher, custom_urls)
+                    .await,
+            );
+        }
+
         Ok(PackageChangelog {
             package_name: package_name.to_string(),
             old_version: old_version.to_string(),
             new_version: new_version.to_string(),
             entries,
-            raw_content,
+            compare_url,
+            truncated: self.take_truncated(package_name),
         })
     }
 
+    /// Try the configured sources in order, keeping the first one that has
+    /// entries for the update
+    async fn fetch_from_sources(
+        &self,
+        package_name: &str,
+        old_version: &str,
+        new_version: &str,
+        custom_urls: &[&str],
+        sources: &[ChangelogSource],
+    ) -> PackageChangelog {
+        let (lower, higher) = version_bounds(old_version, new_version);
+        let mut entries = Vec::new();
+
+        for source in sources {
+            let content = match source {
+                ChangelogSource::CustomUrl if custom_urls.is_empty() => None,
+                ChangelogSource::CustomUrl => {
+                    let content = self.fetch_concatenated(package_name, custom_urls).await;
+                    match content {
+                        Some(content) if !covers_version(&content, lower) => {
+                            let content = match self
+                                .follow_older_entries_link(
+                                    package_name,
+                                    &content,
+                                    custom_urls.last().copied(),
+                                )
+                                .await
+                            {
+                                Some(older) => format!("{}\n\n{}", content, older),
+                                None => content,
+                            };
+                            Some(content)
+                        }
+                        content => content,
+                    }
+                    .map(|content| {
+                        (
+                            content,
+                            custom_urls.first().and_then(|url| markup_of_url(url)),
+                        )
+                    })
+                }
+                ChangelogSource::GithubReleases => self
+                    .try_release_notes(package_name, custom_urls)
+                    .await
+                    .map(|content| (content, Some(ChangelogFormat::Markdown))),
+                ChangelogSource::PypiDescription => {
+                    self.try_pypi_description(package_name, higher).await
+                }
+                ChangelogSource::GitLog => {
+                    match self
+                        .commit_log_entry(package_name, lower, higher, custom_urls)
+                        .await
+                    {
+                        Some(entry) => {
+                            entries.push(entry);
+                            break;
+                        }
+                        None => continue,
+                    }
+                }
+            };
+
+            let Some((content, markup)) = content else {
+                continue;
+            };
+            let parsed = self.parse_changelog_as(&content, old_version, new_version, markup);
+            if !parsed.is_empty() {
+                entries = parsed;
+                break;
+            }
+        }
+
+        PackageChangelog {
+            package_name: package_name.to_string(),
+            old_version: old_version.to_string(),
+            new_version: new_version.to_string(),
+            entries,
+            compare_url: None,
+            truncated: false,
+        }
+    }
+
+    /// Release notes published on the package's GitHub or GitLab repository
+    async fn try_release_notes(&self, package_name: &str, custom_urls: &[&str]) -> Option<String> {
+        let repository = self.repository_url(package_name, custom_urls).await?;
+        let url = match GitLabProject::from_url(&repository, &self.gitlab_hosts) {
+            Some(project) => project.api_url("releases"),
+            None => {
+                let (owner, repo) = github_repo(&repository)?;
+                format!(
+                    "https://api.github.com/repos/{}/{}/releases?per_page=100",
+                    owner, repo
+                )
+            }
+        };
+
+        let body = self
+            .fetch_url_content(package_name, &url)
+            .await
+            .ok()
+            .flatten()?;
+        let notes = serde_json::from_str::<serde_json::Value>(&body).ok()?;
+        release_notes_as_changelog(&notes)
+    }
+
+    /// Long description of a release on PyPI, whether or not it looks like a
+    /// changelog
+    async fn try_pypi_description(&self, package_name: &str, version: &str) -> Option<Document> {
+        let data = self
+            .fetch_release_json(package_name, version)
+            .await
+            .ok()
+            .flatten()?;
+        data["info"]["description"]
+            .as_str()
+            .filter(|description| !description.trim().is_empty())
+            .map(|description| (description.to_string(), description_markup(&data["info"])))
+    }
+
+    /// Fetch several changelog files and join them in order
+    async fn fetch_concatenated(&self, package_name: &str, urls: &[&str]) -> Option<String> {
+        let mut parts = Vec::new();
+        for url in urls {
+            // A GitLab project page stands for its changelog file or release notes
+            let content = match GitLabProject::from_url(url, &self.gitlab_hosts) {
+                Some(project) if !url.contains("/-/") => {
+                    self.try_gitlab_changelog(package_name, &project).await
+                }
+                Some(_) => {
+                    let raw_url = url.replacen("/-/blob/", "/-/raw/", 1);
+                    self.fetch_url_content(package_name, &raw_url).await
+                }
+                None => self.fetch_url_content(package_name, url).await,
+            };
+            if let Ok(Some(content)) = content {
+                parts.push(content);
+            }
+        }
+
+        if parts.is_empty() {
+            None
+        } else {
+            Some(parts.join("\n\n"))
+        }
+    }
+
+    /// Follow an "older entries can be found in X" pointer one level deep
+    async fn follow_older_entries_link(
+        &self,
+        package_name: &str,
+        content: &str,
+        base_url: Option<&str>,
+    ) -> Option<String> {
+        let target = older_entries_link(content)?;
+        let url = resolve_link(&target, base_url)?;
+        self.fetch_url_content(package_name, &url)
+            .await
+            .ok()
+            .flatten()
+    }
+
+    /// Commit subjects between the release tags of `lower` and `higher` in the
+    /// upstream repository, as one entry for `higher`
+    async fn commit_log_entry(
+        &self,
+        package_name: &str,
+        lower: &str,
+        higher: &str,
+        custom_urls: &[&str],
+    ) -> Option<ChangelogEntry> {
+        let repository = self.repository_url(package_name, custom_urls).await?;
+
+        // Release tags are spelled either `1.2.0` or `v1.2.0`
+        for prefix in ["", "v"] {
+            let from = format!("{}{}", prefix, lower);
+            let to = format!("{}{}", prefix, higher);
+            let url = match GitLabProject::from_url(&repository, &self.gitlab_hosts) {
+                Some(project) => project.compare_url(&from, &to),
+                None => {
+                    let (owner, repo) = github_repo(&repository)?;
+                    format!(
+                        "https://api.github.com/repos/{}/{}/compare/{}...{}",
+                        owner, repo, from, to
+                    )
+                }
+            };
+
+            let Ok(Some(body)) = self.fetch_url_content(package_name, &url).await else {
+                continue;
+            };
+            let Ok(comparison) = serde_json::from_str::<serde_json::Value>(&body) else {
+                continue;
+            };
+            let subjects = compare_subjects(&comparison);
+            if !subjects.is_empty() {
+                return Some(ChangelogEntry {
+                    version: higher.to_string(),
+                    date: None,
+                    content: subjects
+                        .iter()
+                        .map(|subject| format!("- {}", subject))
+                        .collect::<Vec<_>>()
+                        .join("\n"),
+                    markup: None,
+                });
+            }
+        }
+
+        None
+    }
+
+    /// Web page of the diff between the release tags of `lower` and `higher`,
+    /// once it is known how the upstream repository names its tags
+    async fn compare_url(
+        &self,
+        package_name: &str,
+        lower: &str,
+        higher: &str,
+        custom_urls: &[&str],
+    ) -> Option<String> {
+        let repository = self.repository_url(package_name, custom_urls).await?;
+        let (tag_api, compare_page) = match GitLabProject::from_url(&repository, &self.gitlab_hosts)
+        {
+            Some(project) => (
+                project.api_url("repository/tags/{tag}"),
+                format!(
+                    "{}/{}/-/compare/{{from}}...{{to}}",
+                    project.base, project.path
+                ),
+            ),
+            None => {
+                let (owner, repo) = github_repo(&repository)?;
+                (
+                    format!(
+                        "https://api.github.com/repos/{}/{}/git/ref/tags/{{tag}}",
+                        owner, repo
+                    ),
+                    format!(
+                        "https://github.com/{}/{}/compare/{{from}}...{{to}}",
+                        owner, repo
+                    ),
+                )
+            }
+        };
+
+        // Release tags are spelled either `1.2.0` or `v1.2.0`
+        'prefixes: for prefix in ["", "v"] {
+            let from = format!("{}{}", prefix, lower);
+            let to = format!("{}{}", prefix, higher);
+            for tag in [&from, &to] {
+                let url = tag_api.replace("{tag}", tag);
+                if !matches!(
+                    self.fetch_url_content(package_name, &url).await,
+                    Ok(Some(_))
+                ) {
+                    continue 'prefixes;
+                }
+            }
+            return Some(compare_page.replace("{from}", &from).replace("{to}", &to));
+        }
+
+        None
+    }
+
+    /// GitHub or GitLab repository of a package: a configured changelog URL
+    /// pointing into one, else its PyPI project URLs
+    async fn repository_url(&self, package_name: &str, custom_urls: &[&str]) -> Option<String> {
+        let is_repository = |url: &str| {
+            github_repo(url).is_some() || GitLabProject::from_url(url, &self.gitlab_hosts).is_some()
+        };
+        if let Some(url) = custom_urls.iter().find(|url| is_repository(url)) {
+            return Some(url.to_string());
+        }
+
+        let data = self.project_data(package_name).await.ok().flatten()?;
+        let info = &data["info"];
+        ["Source", "Repository", "GitHub", "GitLab", "Homepage"]
+            .iter()
+            .filter_map(|key| info["project_urls"][key].as_str())
+            .chain(info["home_page"].as_str())
+            .find(|url| is_repository(url))
+            .map(str::to_string)
+    }
+
+    /// PyPI project JSON, shared with the update phase when a store is attached
+    async fn project_data(&self, package_name: &str) -> Result<Option<Arc<serde_json::Value>>> {
+        match self.store {
+            Some(ref store) => {
+                store
+                    .get_or_fetch(package_name, || self.fetch_project_json(package_name))
+                    .await
+            }
+            None => Ok(self.fetch_project_json(package_name).await?.map(Arc::new)),
+        }
+    }
+
     /// Try to fetch changelog from PyPI package description or project URLs
-    async fn try_fetch_from_pypi(&self, package_name: &str) -> Result<Option<String>> {
+    async fn try_fetch_from_pypi(&self, package_name: &str) -> Result<Option<Document>> {
+        match self.project_data(package_name).await? {
+            Some(data) => self.parse_pypi_payload(&data).await,
+            None => Ok(None),
+        }
+    }
+
+    async fn fetch_project_json(&self, package_name: &str) -> Result<Option<serde_json::Value>> {
         let url = format!("https://pypi.org/pypi/{}/json", package_name);
 
-        let response = self.client.get(&url).send().await?;
+        let response = self.retry.send(self.client.get(&url)).await?;
 
         if !response.status().is_success() {
             return Ok(None);
@@ -116,17 +549,28 @@ impl ChangelogCollector {
             ReleaserError::PyPiError(format!("Failed to parse PyPI response: {}", e))
         })?;
 
-        self.parse_pypi_payload(&data).await
+        Ok(Some(data))
     }
 
     async fn try_fetch_from_pypi_release(
         &self,
         package_name: &str,
         version: &str,
-    ) -> Result<Option<String>> {
+    ) -> Result<Option<Document>> {
+        match self.fetch_release_json(package_name, version).await? {
+            Some(data) => self.parse_pypi_payload(&data).await,
+            None => Ok(None),
+        }
+    }
+
+    async fn fetch_release_json(
+        &self,
+        package_name: &str,
+        version: &str,
+    ) -> Result<Option<serde_json::Value>> {
         let url = format!("https://pypi.org/pypi/{}/{}/json", package_name, version);
 
-        let response = self.client.get(&url).send().await?;
+        let response = self.retry.send(self.client.get(&url)).await?;
 
         if !response.status().is_success() {
             return Ok(None);
@@ -136,17 +580,19 @@ impl ChangelogCollector {
             ReleaserError::PyPiError(format!("Failed to parse PyPI response: {}", e))
         })?;
 
-        self.parse_pypi_payload(&data).await
+        Ok(Some(data))
     }
 
-    async fn parse_pypi_payload(
-        &self,
-        data: &serde_json::Value,
-    ) -> Result<Option<String>> {
+    async fn parse_pypi_payload(&self, data: &serde_json::Value) -> Result<Option<Document>> {
+        let package_name = data["info"]["name"].as_str().unwrap_or_default();
+
         // Try to get changelog from description
         if let Some(description) = data["info"]["description"].as_str() {
             if Self::looks_like_changelog(description) {
-                return Ok(Some(description.to_string()));
+                return Ok(Some((
+                    description.to_string(),
+                    description_markup(&data["info"]),
+                )));
             }
         }
 
@@ -154,21 +600,23 @@ impl ChangelogCollector {
         if let Some(urls) = data["info"]["project_urls"].as_object() {
             for key in ["Changelog", "Changes", "History", "Release Notes"] {
                 if let Some(changelog_url) = urls.get(key).and_then(|v| v.as_str()) {
-                    if let Ok(Some(content)) = self.fetch_url_content(changelog_url).await {
-                        return Ok(Some(content));
+                    if let Ok(Some(content)) =
+                        self.fetch_url_content(package_name, changelog_url).await
+                    {
+                        return Ok(Some((content, markup_of_url(changelog_url))));
                     }
                 }
             }
         }
 
-        // Try common GitHub raw URLs if we have a GitHub project URL
+        // Try common GitHub or GitLab raw URLs if we have a repository URL
         if let Some(urls) = data["info"]["project_urls"].as_object() {
-            for key in ["Homepage", "Source", "Repository", "GitHub"] {
+            for key in ["Homepage", "Source", "Repository", "GitHub", "GitLab"] {
                 if let Some(url) = urls.get(key).and_then(|v| v.as_str()) {
-                    if url.contains("github.com") {
-                        if let Ok(Some(content)) = self.try_github_changelog(url).await {
-                            return Ok(Some(content));
-                        }
+                    if let Ok(Some(content)) =
+                        self.try_repository_changelog(package_name, url).await
+                    {
+                        return Ok(Some((content, None)));
                     }
                 }
             }
@@ -176,10 +624,9 @@ impl ChangelogCollector {
 
         // Also check home_page
         if let Some(home_page) = data["info"]["home_page"].as_str() {
-            if home_page.contains("github.com") {
-                if let Ok(Some(content)) = self.try_github_changelog(home_page).await {
-                    return Ok(Some(content));
-                }
+            if let Ok(Some(content)) = self.try_repository_changelog(package_name, home_page).await
+            {
+                return Ok(Some((content, None)));
             }
         }
 
@@ -198,41 +645,169 @@ impl ChangelogCollector {
                 .is_match(content)
     }
 
-    /// Fetch content from a URL
-    async fn fetch_url_content(&self, url: &str) -> Result<Option<String>> {
-        let response = self.client.get(url).send().await?;
+    /// Fetch content from a URL, answering from the cache when the server
+    /// reports the cached copy is still current
+    async fn fetch_url_content(&self, package_name: &str, url: &str) -> Result<Option<String>> {
+        let cached = self
+            .cache
+            .as_ref()
+            .and_then(|cache| cache.get(package_name, url));
+
+        let mut request = self.client.get(url);
+        if let Some(entry) = &cached {
+            request = request.header(IF_NONE_MATCH, entry.etag.as_str());
+        }
+        let response = self.retry.send(request).await?;
 
+        if response.status() == StatusCode::NOT_MODIFIED {
+            return Ok(cached.map(|entry| entry.content));
+        }
         if !response.status().is_success() {
             return Ok(None);
         }
 
-        let content = response.text().await?;
+        let etag = response
+            .headers()
+            .get(ETAG)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+        let (content, truncated) = self.read_limited(response).await?;
+        if truncated {
+            self.truncated
+                .lock()
+                .unwrap()
+                .insert(package_name.to_string());
+            warnings::warn(
+                WarningKind::Changelog,
+                Some(package_name),
+                format!(
+                    "{} is larger than {} bytes; only its beginning was read",
+                    url, self.max_download_bytes
+                ),
+            );
+            // A cut copy is not cached, so a larger limit takes effect next run
+            return Ok(Some(content));
+        }
+
+        // Best effort: a cache that can't be written only costs a download
+        if let (Some(cache), Some(etag)) = (&self.cache, etag) {
+            let entry = CachedChangelog {
+                url: url.to_string(),
+                etag,
+                content,
+            };
+            let _ = cache.put(package_name, &entry);
+            return Ok(Some(entry.content));
+        }
         Ok(Some(content))
     }
 
+    /// Body of a response, streamed up to `max_download_bytes`; the flag tells
+    /// whether it was cut
+    async fn read_limited(&self, mut response: reqwest::Response) -> Result<(String, bool)> {
+        let mut body: Vec<u8> = Vec::new();
+        while let Some(chunk) = response.chunk().await? {
+            body.extend_from_slice(&chunk);
+            if body.len() > self.max_download_bytes {
+                cut_after_last_line(&mut body, self.max_download_bytes);
+                return Ok((String::from_utf8_lossy(&body).into_owned(), true));
+            }
+        }
+        Ok((String::from_utf8_lossy(&body).into_owned(), false))
+    }
+
+    /// Whether a changelog file of the package was cut, forgetting it
+    fn take_truncated(&self, package_name: &str) -> bool {
+        self.truncated.lock().unwrap().remove(package_name)
+    }
+
+    /// Changelog of the GitHub or GitLab repository `url` points into
+    async fn try_repository_changelog(
+        &self,
+        package_name: &str,
+        url: &str,
+    ) -> Result<Option<String>> {
+        if url.contains("github.com") {
+            return self.try_github_changelog(package_name, url).await;
+        }
+        match GitLabProject::from_url(url, &self.gitlab_hosts) {
+            Some(project) => self.try_gitlab_changelog(package_name, &project).await,
+            None => Ok(None),
+        }
+    }
+
+    /// Branches to look for changelog files on: the repository's default
+    /// branch as its API reports it, then `main`, `master` and `github_branches`
+    async fn repository_branches(&self, package_name: &str, api_url: &str) -> Vec<String> {
+        let default_branch = self
+            .fetch_url_content(package_name, api_url)
+            .await
+            .ok()
+            .flatten()
+            .and_then(|body| serde_json::from_str::<serde_json::Value>(&body).ok())
+            .and_then(|repository| repository["default_branch"].as_str().map(str::to_string));
+        branch_order(default_branch.as_deref(), &self.github_branches)
+    }
+
+    /// Try a changelog file on the default branches of a GitLab project, then
+    /// its release notes, then the messages of its annotated tags
+    async fn try_gitlab_changelog(
+        &self,
+        package_name: &str,
+        project: &GitLabProject,
+    ) -> Result<Option<String>> {
+        let branches = self
+            .repository_branches(package_name, &project.project_url())
+            .await;
+        for branch in &branches {
+            for file in &self.changelog_files {
+                let raw_url = project.raw_url(branch, file);
+                if let Ok(Some(content)) = self.fetch_url_content(package_name, &raw_url).await {
+                    return Ok(Some(content));
+                }
+            }
+        }
+
+        for endpoint in ["releases", "repository/tags"] {
+            let Some(body) = self
+                .fetch_url_content(package_name, &project.api_url(endpoint))
+                .await?
+            else {
+                continue;
+            };
+            let Ok(notes) = serde_json::from_str::<serde_json::Value>(&body) else {
+                continue;
+            };
+            if let Some(content) = release_notes_as_changelog(&notes) {
+                return Ok(Some(content));
+            }
+        }
+
+        Ok(None)
+    }
+
     /// Try to fetch changelog from GitHub repository
-    async fn try_github_changelog(&self, github_url: &str) -> Result<Option<String>> {
+    async fn try_github_changelog(
+        &self,
+        package_name: &str,
+        github_url: &str,
+    ) -> Result<Option<String>> {
         // Convert GitHub URL to raw content URL
-        let repo_pattern = Regex::new(r"github\.com/([^/]+)/([^/]+)").unwrap();
-
-        let (owner, repo) = if let Some(caps) = repo_pattern.captures(github_url) {
-            (
-                caps.get(1).unwrap().as_str(),
-                caps.get(2).unwrap().as_str().trim_end_matches(".git"),
-            )
-        } else {
+        let Some((owner, repo)) = github_repo(github_url) else {
             return Ok(None);
         };
 
         // Try configured changelog files and branches
-        for branch in &self.github_branches {
+        let api_url = format!("https://api.github.com/repos/{}/{}", owner, repo);
+        let branches = self.repository_branches(package_name, &api_url).await;
+        for branch in &branches {
             for file in &self.changelog_files {
                 let raw_url = format!(
                     "https://raw.githubusercontent.com/{}/{}/{}/{}",
                     owner, repo, branch, file
                 );
 
-                if let Ok(Some(content)) = self.fetch_url_content(&raw_url).await {
+                if let Ok(Some(content)) = self.fetch_url_content(package_name, &raw_url).await {
                     return Ok(Some(content));
                 }
             }
@@ -253,6 +828,10 @@ impl ChangelogCollector {
             return parsed;
         }
 
+        if let Some(parsed) = self.try_parse_zest_changelog(content, old_version, new_version) {
+            return parsed;
+        }
+
         if let Some(parsed) = self.try_parse_rst_changelog(content, old_version, new_version) {
             return parsed;
         }
@@ -264,6 +843,33 @@ impl ChangelogCollector {
         Vec::new()
     }
 
+    /// Parse with the parser of the markup a document declares first, then
+    /// try every format as `parse_changelog` does
+    fn parse_changelog_as(
+        &self,
+        content: &str,
+        old_version: &str,
+        new_version: &str,
+        markup: Option<ChangelogFormat>,
+    ) -> Vec<ChangelogEntry> {
+        let declared = match markup {
+            // Versions are often nested under a `## Changelog` heading of a README
+            Some(ChangelogFormat::Markdown) => {
+                self.parse_markdown_headings(content, old_version, new_version, "#{2,4}")
+            }
+            Some(ChangelogFormat::Rst) => self
+                .try_parse_zest_changelog(content, old_version, new_version)
+                .or_else(|| self.try_parse_rst_changelog(content, old_version, new_version)),
+            Some(ChangelogFormat::Text) | None => None,
+        };
+        let mut entries =
+            declared.unwrap_or_else(|| self.parse_changelog(content, old_version, new_version));
+        for entry in &mut entries {
+            entry.markup = markup;
+        }
+        entries
+    }
+
     /// Parse Markdown-style changelog (## [version] or ## version)
     fn try_parse_markdown_changelog(
         &self,
@@ -271,9 +877,21 @@ impl ChangelogCollector {
         old_version: &str,
         new_version: &str,
     ) -> Option<Vec<ChangelogEntry>> {
-        let header_pattern = Regex::new(
-            r"(?m)^##\s+\[?v?(\d+\.\d+(?:\.\d+)?(?:[._-]?\w+)?)\]?(?:\s*[-–—]\s*(.+))?$",
-        )
+        self.parse_markdown_headings(content, old_version, new_version, "##")
+    }
+
+    /// Markdown changelog whose version headings match the `level` pattern
+    fn parse_markdown_headings(
+        &self,
+        content: &str,
+        old_version: &str,
+        new_version: &str,
+        level: &str,
+    ) -> Option<Vec<ChangelogEntry>> {
+        let header_pattern = Regex::new(&format!(
+            r"(?m)^{}\s+\[?v?(\d+\.\d+(?:\.\d+)?(?:[._-]?\w+)?)\]?(?:\s*[-–—]\s*(.+))?$",
+            level
+        ))
         .ok()?;
 
         let mut entries = Vec::new();
@@ -281,8 +899,7 @@ impl ChangelogCollector {
         let mut current_entry: Option<ChangelogEntry> = None;
         let mut content_buffer = String::new();
 
-        let old_ver_normalized = normalize_version(old_version);
-        let new_ver_normalized = normalize_version(new_version);
+        let window = EntryWindow::new(old_version, new_version);
 
         for line in content.lines() {
             if let Some(caps) = header_pattern.captures(line) {
@@ -296,18 +913,16 @@ impl ChangelogCollector {
 
                 let version = caps.get(1).unwrap().as_str();
                 let date = caps.get(2).map(|m| m.as_str().trim().to_string());
-                let ver_normalized = normalize_version(version);
 
-                if compare_versions(&ver_normalized, &old_ver_normalized) > 0
-                    && compare_versions(&ver_normalized, &new_ver_normalized) <= 0
-                {
+                if window.contains(version) {
                     capture_content = true;
                     current_entry = Some(ChangelogEntry {
                         version: version.to_string(),
                         date,
                         content: String::new(),
+                        markup: None,
                     });
-                } else if compare_versions(&ver_normalized, &old_ver_normalized) <= 0 {
+                } else if window.is_at_or_before_start(version) {
                     capture_content = false;
                 }
             } else if capture_content {
@@ -347,8 +962,7 @@ impl ChangelogCollector {
         let mut current_entry: Option<ChangelogEntry> = None;
         let mut content_buffer = String::new();
 
-        let old_ver_normalized = normalize_version(old_version);
-        let new_ver_normalized = normalize_version(new_version);
+        let window = EntryWindow::new(old_version, new_version);
 
         let mut i = 0;
         while i < lines.len() {
@@ -368,18 +982,16 @@ impl ChangelogCollector {
 
                     let version = caps.get(1).unwrap().as_str();
                     let date = caps.get(2).map(|m| m.as_str().trim().to_string());
-                    let ver_normalized = normalize_version(version);
 
-                    if compare_versions(&ver_normalized, &old_ver_normalized) > 0
-                        && compare_versions(&ver_normalized, &new_ver_normalized) <= 0
-                    {
+                    if window.contains(version) {
                         capture_content = true;
                         current_entry = Some(ChangelogEntry {
                             version: version.to_string(),
                             date,
                             content: String::new(),
+                            markup: None,
                         });
-                    } else if compare_versions(&ver_normalized, &old_ver_normalized) <= 0 {
+                    } else if window.is_at_or_before_start(version) {
                         capture_content = false;
                     }
 
@@ -410,6 +1022,90 @@ impl ChangelogCollector {
         }
     }
 
+    /// Parse zest.releaser / Plone style `CHANGES.rst`: `2.1.3 (2024-03-12)` or
+    /// `2.2.0 (unreleased)` headers underlined with dashes, towncrier category
+    /// lines (`Bug fixes:`), bullets with `[author]` credits, and RST comments
+    /// such as `.. towncrier release notes start`, which are dropped
+    fn try_parse_zest_changelog(
+        &self,
+        content: &str,
+        old_version: &str,
+        new_version: &str,
+    ) -> Option<Vec<ChangelogEntry>> {
+        let header_pattern =
+            Regex::new(r"^v?(\d+(?:\.\d+)+(?:[._+-]?[A-Za-z]+\d*)*)\s+\(([^)]+)\)\s*$").ok()?;
+        let underline_pattern = Regex::new(r"^(?:-{3,}|={3,}|~{3,})\s*$").ok()?;
+
+        let lines: Vec<&str> = content.lines().collect();
+        let window = EntryWindow::new(old_version, new_version);
+        let mut entries = Vec::new();
+        let mut current: Option<ChangelogEntry> = None;
+        let mut body: Vec<&str> = Vec::new();
+        let mut in_comment = false;
+
+        let mut finish = |entry: Option<ChangelogEntry>, body: &mut Vec<&str>| {
+            if let Some(mut entry) = entry {
+                entry.content = body.join("\n").trim().to_string();
+                if !entry.content.is_empty() {
+                    entries.push(entry);
+                }
+            }
+            body.clear();
+        };
+
+        let mut i = 0;
+        while i < lines.len() {
+            let line = lines[i].trim_end();
+            let underlined = lines
+                .get(i + 1)
+                .is_some_and(|next| underline_pattern.is_match(next));
+
+            if let Some(caps) = header_pattern.captures(line).filter(|_| underlined) {
+                finish(current.take(), &mut body);
+                in_comment = false;
+
+                let version = &caps[1];
+                let date = &caps[2];
+                if window.contains(version) {
+                    current = Some(ChangelogEntry {
+                        version: version.to_string(),
+                        // Not released yet: there is no date to show
+                        date: (!date.eq_ignore_ascii_case("unreleased"))
+                            .then(|| date.trim().to_string()),
+                        content: String::new(),
+                        markup: None,
+                    });
+                }
+
+                i += 2;
+                continue;
+            }
+
+            if current.is_some() {
+                // An RST comment runs on over its indented lines
+                if line.starts_with("..") && !line.starts_with("...") {
+                    in_comment = true;
+                } else {
+                    in_comment = in_comment && line.starts_with([' ', '\t']);
+                    // towncrier leaves runs of blank lines between categories
+                    let repeated_blank =
+                        line.is_empty() && body.last().is_some_and(|last| last.is_empty());
+                    if !in_comment && !repeated_blank {
+                        body.push(line);
+                    }
+                }
+            }
+            i += 1;
+        }
+        finish(current.take(), &mut body);
+
+        if entries.is_empty() {
+            None
+        } else {
+            Some(entries)
+        }
+    }
+
     /// Generic changelog parser for other formats
     fn try_parse_generic_changelog(
         &self,
@@ -427,8 +1123,7 @@ impl ChangelogCollector {
         let mut current_entry: Option<ChangelogEntry> = None;
         let mut content_buffer = String::new();
 
-        let old_ver_normalized = normalize_version(old_version);
-        let new_ver_normalized = normalize_version(new_version);
+        let window = EntryWindow::new(old_version, new_version);
 
         for line in content.lines() {
             if let Some(caps) = header_pattern.captures(line) {
@@ -451,18 +1146,16 @@ impl ChangelogCollector {
                 }
 
                 let date = caps.get(2).map(|m| m.as_str().trim().to_string());
-                let ver_normalized = normalize_version(version);
 
-                if compare_versions(&ver_normalized, &old_ver_normalized) > 0
-                    && compare_versions(&ver_normalized, &new_ver_normalized) <= 0
-                {
+                if window.contains(version) {
                     capture_content = true;
                     current_entry = Some(ChangelogEntry {
                         version: version.to_string(),
                         date,
                         content: String::new(),
+                        markup: None,
                     });
-                } else if compare_versions(&ver_normalized, &old_ver_normalized) <= 0 {
+                } else if window.is_at_or_before_start(version) {
                     capture_content = false;
                 }
             } else if capture_content {
@@ -497,33 +1190,41 @@ impl ChangelogCollector {
             // Find the package config to get custom changelog URL
             let package_config = package_configs
                 .iter()
-                .find(|p| p.name == update.package_name || p.buildout_name() == update.package_name);
+                .find(|p| p.is_named(&update.package_name));
             if matches!(package_config, Some(config) if !config.include_in_changelog) {
                 continue;
             }
-            let custom_url = package_config.and_then(|p| p.changelog_url.as_deref());
+            let custom_urls = package_config
+                .map(|p| p.changelog_sources())
+                .unwrap_or_default();
+            let sources = package_config
+                .map(|p| p.changelog_from.as_slice())
+                .unwrap_or_default();
 
             match self
                 .fetch_changelog(
                     &update.package_name,
                     &update.old_version,
                     &update.new_version,
-                    custom_url,
+                    &custom_urls,
+                    sources,
                 )
                 .await
             {
                 Ok(changelog) => changelogs.push(changelog),
                 Err(e) => {
-                    eprintln!(
-                        "Warning: Could not fetch changelog for {}: {}",
-                        update.package_name, e
+                    warnings::warn(
+                        WarningKind::Changelog,
+                        Some(&update.package_name),
+                        format!("could not fetch changelog: {}", e),
                     );
                     changelogs.push(PackageChangelog {
                         package_name: update.package_name.clone(),
                         old_version: update.old_version.clone(),
                         new_version: update.new_version.clone(),
                         entries: Vec::new(),
-                        raw_content: None,
+                        compare_url: None,
+                        truncated: false,
                     });
                 }
             }
@@ -539,122 +1240,680 @@ impl Default for ChangelogCollector {
     }
 }
 
-impl ConsolidatedChangelog {
-    /// Create a consolidated changelog from multiple package changelogs
-    pub fn new(
-        release_version: &str,
-        date: &str,
-        package_changelogs: Vec<PackageChangelog>,
-    ) -> Self {
-        Self::with_templates(
-            release_version,
-            date,
-            package_changelogs,
-            &ChangelogConfig::default(),
-        )
+/// Cut a package's entries to `max_entries` (the newest, as changelogs list
+/// them first) and `max_lines` lines, pointing to the full changelog on PyPI
+fn limit_entries(
+    changelog: &mut PackageChangelog,
+    max_entries: Option<usize>,
+    max_lines: Option<usize>,
+) {
+    let mut truncated = false;
+    if let Some(max) = max_entries.filter(|max| changelog.entries.len() > *max) {
+        changelog.entries.truncate(max);
+        truncated = true;
+    }
+
+    if let Some(max) = max_lines {
+        let mut remaining = max;
+        let mut keep = 0;
+        for entry in &mut changelog.entries {
+            let lines = entry.content.lines().count();
+            if lines > remaining {
+                entry.content = entry
+                    .content
+                    .lines()
+                    .take(remaining)
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                truncated = true;
+                if remaining > 0 {
+                    keep += 1;
+                }
+                break;
+            }
+            remaining -= lines;
+            keep += 1;
+        }
+        if keep < changelog.entries.len() {
+            changelog.entries.truncate(keep);
+            truncated = true;
+        }
+    }
+
+    if let (true, Some(last)) = (truncated, changelog.entries.last_mut()) {
+        last.content.push_str(&format!(
+            "\n\n… see the full changelog at https://pypi.org/project/{}/",
+            changelog.package_name
+        ));
+    }
+}
+
+/// Cut release notes to `max_chars` at a line boundary, ending them with a link
+/// to the full changelog when there is one
+pub fn truncate_release_notes(
+    notes: &str,
+    max_chars: usize,
+    full_changelog: Option<&str>,
+) -> String {
+    if notes.chars().count() <= max_chars {
+        return notes.to_string();
+    }
+
+    let note = match full_changelog {
+        Some(url) => format!("\n\n… see the full changelog at {}\n", url),
+        None => "\n\n… release notes truncated\n".to_string(),
+    };
+    let budget = max_chars.saturating_sub(note.chars().count());
+    let mut kept = String::new();
+    let mut used = 0;
+    for line in notes.lines() {
+        used += line.chars().count() + 1;
+        if used > budget {
+            break;
+        }
+        kept.push_str(line);
+        kept.push('\n');
+    }
+
+    format!("{}{}", kept.trim_end(), note)
+}
+
+/// Keep a Changelog change types, in the order they are rendered
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum ChangeCategory {
+    Added,
+    Changed,
+    Deprecated,
+    Removed,
+    Fixed,
+    Security,
+}
+
+impl ChangeCategory {
+    /// Category of a heading line (`### Fixed`, `Bug fixes:`), if it is one
+    fn from_heading(line: &str) -> Option<Self> {
+        let title = line
+            .trim()
+            .trim_start_matches('#')
+            .trim()
+            .trim_end_matches(':');
+        match title.trim().to_lowercase().as_str() {
+            "added" | "new features" | "features" => Some(Self::Added),
+            "changed" | "breaking changes" => Some(Self::Changed),
+            "deprecated" | "deprecations" => Some(Self::Deprecated),
+            "removed" => Some(Self::Removed),
+            "fixed" | "bug fixes" | "bugfixes" => Some(Self::Fixed),
+            "security" => Some(Self::Security),
+            _ => None,
+        }
+    }
+
+    fn title(self) -> &'static str {
+        match self {
+            Self::Added => "Added",
+            Self::Changed => "Changed",
+            Self::Deprecated => "Deprecated",
+            Self::Removed => "Removed",
+            Self::Fixed => "Fixed",
+            Self::Security => "Security",
+        }
+    }
+}
+
+/// Split an entry into the parts under each category heading; text before the
+/// first heading, or in an entry without headings, has no category
+fn split_categories(content: &str) -> Vec<(Option<ChangeCategory>, String)> {
+    let mut parts: Vec<(Option<ChangeCategory>, String)> = Vec::new();
+    let mut current = None;
+    let mut buffer = String::new();
+
+    let mut flush = |category, buffer: &mut String| {
+        let text = buffer.trim();
+        if !text.is_empty() {
+            parts.push((category, text.to_string()));
+        }
+        buffer.clear();
+    };
+
+    for line in content.lines() {
+        if let Some(category) = ChangeCategory::from_heading(line) {
+            flush(current, &mut buffer);
+            current = Some(category);
+            continue;
+        }
+        buffer.push_str(line);
+        buffer.push('\n');
+    }
+    flush(current, &mut buffer);
+    parts
+}
+
+/// Text of each package under one category heading
+type CategoryItems<'a> = Vec<(&'a PackageChangelog, String)>;
+
+/// Entries of `packages` grouped by category, in category order, with
+/// uncategorized entries last under "Other changes"
+fn category_groups<'a>(
+    packages: &[&'a PackageChangelog],
+) -> Vec<(&'static str, CategoryItems<'a>)> {
+    let mut groups: Vec<(Option<ChangeCategory>, CategoryItems)> = Vec::new();
+    for &pkg in packages {
+        for entry in &pkg.entries {
+            for (category, text) in split_categories(&entry.content) {
+                let index = match groups.iter().position(|(c, _)| *c == category) {
+                    Some(index) => index,
+                    None => {
+                        groups.push((category, Vec::new()));
+                        groups.len() - 1
+                    }
+                };
+                let items = &mut groups[index].1;
+                match items.iter_mut().find(|(p, _)| std::ptr::eq(*p, pkg)) {
+                    Some((_, existing)) => {
+                        existing.push('\n');
+                        existing.push_str(&text);
+                    }
+                    None => items.push((pkg, text)),
+                }
+            }
+        }
+    }
+
+    // `None` sorts first; uncategorized entries go last
+    groups.sort_by_key(|(category, _)| (category.is_none(), *category));
+    groups
+        .into_iter()
+        .map(|(category, items)| {
+            (
+                category.map_or("Other changes", ChangeCategory::title),
+                items,
+            )
+        })
+        .collect()
+}
+
+/// Names of the packages without entries, comma-separated
+fn packages_without_entries(packages: &[&PackageChangelog]) -> Option<String> {
+    let names: Vec<&str> = packages
+        .iter()
+        .filter(|pkg| pkg.entries.is_empty())
+        .map(|pkg| pkg.package_name.as_str())
+        .collect();
+    (!names.is_empty()).then(|| names.join(", "))
+}
+
+fn push_indented(output: &mut String, content: &str, indent: usize) {
+    for line in content.lines() {
+        if line.trim().is_empty() {
+            output.push('\n');
+        } else {
+            output.push_str(&format!("{}{}\n", " ".repeat(indent), line));
+        }
+    }
+}
+
+fn push_rst_package(output: &mut String, pkg: &PackageChangelog) {
+    let pkg_title = format!(
+        "{} ({} → {})",
+        pkg.package_name, pkg.old_version, pkg.new_version
+    );
+    output.push_str(&pkg_title);
+    output.push('\n');
+    output.push_str(&"~".repeat(pkg_title.len()));
+    output.push_str("\n\n");
+
+    if pkg.entries.is_empty() {
+        output.push_str("*No changelog entries found.*\n\n");
+    } else {
+        for entry in &pkg.entries {
+            let date_str = entry
+                .date
+                .as_ref()
+                .map(|d| format!(" ({})", d))
+                .unwrap_or_default();
+
+            let ver_title = format!("Version {}{}", entry.version, date_str);
+            output.push_str(&ver_title);
+            output.push('\n');
+            output.push_str(&"^".repeat(ver_title.len()));
+            output.push_str("\n\n");
+            output.push_str(&entry.content);
+            output.push_str("\n\n");
+        }
+    }
+    if let Some(url) = &pkg.compare_url {
+        output.push_str(&format!("Full diff: {}\n\n", url));
+    }
+}
+
+fn push_text_package(output: &mut String, pkg: &PackageChangelog) {
+    output.push_str(&format!(
+        "{}: {} → {}\n",
+        pkg.package_name, pkg.old_version, pkg.new_version
+    ));
+    output.push_str(&"-".repeat(40));
+    output.push('\n');
+
+    if pkg.entries.is_empty() {
+        output.push_str("  No changelog entries found.\n");
+    } else {
+        for entry in &pkg.entries {
+            let date_str = entry
+                .date
+                .as_ref()
+                .map(|d| format!(" ({})", d))
+                .unwrap_or_default();
+
+            output.push_str(&format!("\n  Version {}{}:\n", entry.version, date_str));
+            for line in entry.content.lines() {
+                output.push_str(&format!("    {}\n", line));
+            }
+        }
+    }
+    if let Some(url) = &pkg.compare_url {
+        output.push_str(&format!("\n  Full diff: {}\n", url));
+    }
+    output.push('\n');
+}
+
+/// Packages developed in one repository often share its changelog: identical
+/// entries for the same update are kept once, under all their names
+fn merge_shared_changelogs(changelogs: &[PackageChangelog]) -> Vec<PackageChangelog> {
+    let mut merged: Vec<PackageChangelog> = Vec::new();
+    for changelog in changelogs {
+        let shared = merged.iter_mut().find(|other| {
+            !changelog.entries.is_empty()
+                && other.old_version == changelog.old_version
+                && other.new_version == changelog.new_version
+                && other.entries == changelog.entries
+        });
+        match shared {
+            Some(other) => {
+                other.package_name = format!("{}, {}", other.package_name, changelog.package_name)
+            }
+            None => merged.push(changelog.clone()),
+        }
+    }
+    merged
+}
+
+/// Compile `exclude_patterns`; invalid ones are reported and skipped
+fn compile_exclude_patterns(patterns: &[String]) -> Vec<Regex> {
+    patterns
+        .iter()
+        .filter_map(|pattern| match Regex::new(pattern) {
+            Ok(re) => Some(re),
+            Err(e) => {
+                warnings::warn(
+                    WarningKind::Changelog,
+                    None,
+                    format!("ignoring invalid exclude pattern '{}': {}", pattern, e),
+                );
+                None
+            }
+        })
+        .collect()
+}
+
+/// Drop entry lines matching any pattern (compared without leading indentation),
+/// along with the indented continuation lines of a dropped bullet
+fn filter_noise(content: &str, patterns: &[Regex]) -> String {
+    let mut kept = Vec::new();
+    let mut dropping: Option<usize> = None;
+
+    for line in content.lines() {
+        let indent = line.len() - line.trim_start().len();
+        if let Some(bullet_indent) = dropping {
+            if !line.trim().is_empty() && indent > bullet_indent {
+                continue;
+            }
+            dropping = None;
+        }
+
+        if patterns.iter().any(|re| re.is_match(line.trim_start())) {
+            dropping = Some(indent);
+            continue;
+        }
+        kept.push(line);
     }
 
+    kept.join("\n")
+}
+
+impl ConsolidatedChangelog {
+    /// Create a consolidated changelog from multiple package changelogs
     pub fn with_templates(
         release_version: &str,
         date: &str,
-        package_changelogs: Vec<PackageChangelog>,
+        mut package_changelogs: Vec<PackageChangelog>,
         config: &ChangelogConfig,
     ) -> Self {
+        let patterns = compile_exclude_patterns(&config.exclude_patterns);
+        if !patterns.is_empty() {
+            for changelog in &mut package_changelogs {
+                for entry in &mut changelog.entries {
+                    entry.content = filter_noise(&entry.content, &patterns);
+                }
+                changelog
+                    .entries
+                    .retain(|entry| !entry.content.trim().is_empty());
+            }
+        }
+        for changelog in &mut package_changelogs {
+            limit_entries(
+                changelog,
+                config.max_entries_per_package,
+                config.max_lines_per_package,
+            );
+        }
+
         Self {
             release_version: release_version.to_string(),
-            date: date.to_string(),
+            date: config.format_date(date),
             package_changelogs,
+            initial_pins: Vec::new(),
+            added_packages: Vec::new(),
+            removed_packages: Vec::new(),
             header_template: config.header_template.clone(),
             package_template: config.package_template.clone(),
+            group_by_category: config.groups_by_category(),
+            package_overrides: HashMap::new(),
+            attention_keywords: config.attention_keywords.clone(),
+            convert_markup: config.convert_markup,
+            show_contributors: config.contributors,
+            contributors: Vec::new(),
+        }
+    }
+
+    /// Create an initial release changelog listing every current pin
+    pub fn initial_release(
+        release_version: &str,
+        date: &str,
+        mut pins: Vec<PinnedPackage>,
+        config: &ChangelogConfig,
+    ) -> Self {
+        pins.sort_by_key(|pin| pin.name.to_lowercase());
+
+        let mut changelog = Self::with_templates(release_version, date, Vec::new(), config);
+        changelog.initial_pins = pins;
+        changelog
+    }
+
+    /// Apply the changelog options of `[[packages]]`: their own templates and
+    /// `max_entries`, which keeps the newest entries only
+    pub fn with_package_overrides(mut self, packages: &[PackageConfig]) -> Self {
+        for changelog in &mut self.package_changelogs {
+            let Some(package) = packages
+                .iter()
+                .find(|p| p.is_named(&changelog.package_name))
+            else {
+                continue;
+            };
+
+            limit_entries(changelog, package.max_entries, None);
+            if package.package_template.is_some() || package.entry_template.is_some() {
+                self.package_overrides.insert(
+                    changelog.package_name.clone(),
+                    PackageTemplates {
+                        package: package.package_template.clone(),
+                        entry: package.entry_template.clone(),
+                    },
+                );
+            }
+        }
+        self
+    }
+
+    /// Credit the authors of our own commits next to the upstream ones
+    pub fn with_contributors(mut self, authors: Vec<String>) -> Self {
+        self.contributors = authors;
+        self
+    }
+
+    /// People credited in the "Thanks to" section: `[name]` suffixes of
+    /// upstream entries (zest.releaser style) and our commit authors, sorted
+    pub fn thanks_to(&self) -> Vec<String> {
+        if !self.show_contributors {
+            return Vec::new();
+        }
+
+        let credit = Regex::new(r"\[([^\[\]()]+)\]\s*$").unwrap();
+        let upstream = self
+            .package_changelogs
+            .iter()
+            .flat_map(|changelog| &changelog.entries)
+            .flat_map(|entry| entry.content.lines())
+            .filter_map(|line| credit.captures(line))
+            .flat_map(|caps| {
+                caps[1]
+                    .split([',', ';'])
+                    .flat_map(|names| names.split(" and "))
+                    .map(|name| name.trim().trim_start_matches('@').to_string())
+                    .collect::<Vec<_>>()
+            });
+
+        let mut names: Vec<String> = Vec::new();
+        for name in upstream.chain(self.contributors.iter().cloned()) {
+            if !name.is_empty() && !names.iter().any(|known| known.eq_ignore_ascii_case(&name)) {
+                names.push(name);
+            }
         }
+        names.sort_by_key(|name| name.to_lowercase());
+        names
+    }
+
+    /// Attach pins added or removed since the previous release
+    pub fn with_pin_changes(mut self, diff: PinDiff) -> Self {
+        self.added_packages = diff.added;
+        self.removed_packages = diff.removed;
+        self
     }
 
     /// Render as Markdown
     pub fn to_markdown(&self) -> String {
+        self.heading(ChangelogFormat::Markdown) + &self.render_body(ChangelogFormat::Markdown)
+    }
+
+    /// Title of the release (with its date), as `render` starts with it
+    pub fn heading(&self, format: ChangelogFormat) -> String {
+        match format {
+            ChangelogFormat::Markdown => {
+                // Apply header template
+                let header = self
+                    .header_template
+                    .replace("{version}", &self.release_version)
+                    .replace("{date}", &self.date);
+                format!("{}\n\n", header)
+            }
+            ChangelogFormat::Rst => {
+                let title = format!("Release {}", self.release_version);
+                format!(
+                    "{}\n{}\n\n**Date:** {}\n\n",
+                    title,
+                    "=".repeat(title.len()),
+                    self.date
+                )
+            }
+            ChangelogFormat::Text => format!(
+                "RELEASE {} ({})\n{}\n\n",
+                self.release_version,
+                self.date,
+                "=".repeat(60)
+            ),
+        }
+    }
+
+    /// Everything `render` writes after the heading
+    pub fn render_body(&self, format: ChangelogFormat) -> String {
+        let mut prepared = self.clone();
+        prepared.package_changelogs = merge_shared_changelogs(&self.package_changelogs);
+        if self.convert_markup {
+            for changelog in &mut prepared.package_changelogs {
+                for entry in &mut changelog.entries {
+                    entry.content = markup::convert(&entry.content, entry.markup, format);
+                }
+            }
+        }
+
+        match format {
+            ChangelogFormat::Markdown => prepared.markdown_body(),
+            ChangelogFormat::Rst => prepared.rst_body(),
+            ChangelogFormat::Text => prepared.text_body(),
+        }
+    }
+
+    fn markdown_body(&self) -> String {
         let mut output = String::new();
 
-        // Apply header template
-        let header = self
-            .header_template
-            .replace("{version}", &self.release_version)
-            .replace("{date}", &self.date);
-        output.push_str(&header);
-        output.push_str("\n\n");
+        let attention = self.attention();
+        if !attention.is_empty() {
+            output.push_str("## ⚠ Attention\n\n");
+            for item in &attention {
+                output.push_str(&format!(
+                    "- **{}** {}: {}\n",
+                    item.package, item.version, item.line
+                ));
+            }
+            output.push('\n');
+        }
 
-        for pkg in &self.package_changelogs {
-            // Apply package template
-            let pkg_header = self
-                .package_template
-                .replace("{package}", &pkg.package_name)
-                .replace("{old_version}", &pkg.old_version)
-                .replace("{new_version}", &pkg.new_version);
-            output.push_str(&pkg_header);
-            output.push_str("\n\n");
+        if !self.initial_pins.is_empty() {
+            output.push_str("Initial release.\n\n");
+            for pin in &self.initial_pins {
+                output.push_str(&format!("- **{}** {}\n", pin.name, pin.version));
+            }
+            output.push('\n');
+        }
+
+        if !self.added_packages.is_empty() || !self.removed_packages.is_empty() {
+            for pin in &self.added_packages {
+                output.push_str(&format!("- Added **{}** {}\n", pin.name, pin.version));
+            }
+            for pin in &self.removed_packages {
+                output.push_str(&format!("- Removed **{}**\n", pin.name));
+            }
+            output.push('\n');
+        }
 
-            if pkg.entries.is_empty() {
-                output.push_str("*No changelog entries found.*\n\n");
-            } else {
-                for entry in &pkg.entries {
-                    let date_str = entry
-                        .date
-                        .as_ref()
-                        .map(|d| format!(" ({})", d))
-                        .unwrap_or_default();
-
-                    output.push_str(&format!("#### Version {}{}\n\n", entry.version, date_str));
-                    output.push_str(&entry.content);
-                    output.push_str("\n\n");
+        let (updated, reverted) = self.split_downgrades();
+        if self.group_by_category {
+            for (category, items) in category_groups(&updated) {
+                output.push_str(&format!("### {}\n\n", category));
+                for (pkg, content) in items {
+                    output.push_str(&format!(
+                        "- **{}** ({} → {})\n",
+                        pkg.package_name, pkg.old_version, pkg.new_version
+                    ));
+                    push_indented(&mut output, &content, 2);
                 }
+                output.push('\n');
+            }
+            if let Some(names) = packages_without_entries(&updated) {
+                output.push_str(&format!("*No changelog entries found for {}.*\n\n", names));
+            }
+        } else {
+            for pkg in updated {
+                self.push_markdown_package(&mut output, pkg);
+            }
+        }
+        if !reverted.is_empty() {
+            output.push_str("## Reverted changes\n\n");
+            for pkg in reverted {
+                self.push_markdown_package(&mut output, pkg);
             }
         }
 
+        let thanks = self.thanks_to();
+        if !thanks.is_empty() {
+            output.push_str(&format!("## Thanks to\n\n{}\n\n", thanks.join(", ")));
+        }
+
         output
     }
 
     /// Render as RST (reStructuredText)
     pub fn to_rst(&self) -> String {
+        self.heading(ChangelogFormat::Rst) + &self.render_body(ChangelogFormat::Rst)
+    }
+
+    fn rst_body(&self) -> String {
         let mut output = String::new();
 
-        let title = format!("Release {}", self.release_version);
-        output.push_str(&title);
-        output.push('\n');
-        output.push_str(&"=".repeat(title.len()));
-        output.push_str("\n\n");
+        let attention = self.attention();
+        if !attention.is_empty() {
+            output.push_str("⚠ Attention\n");
+            output.push_str("------------\n\n");
+            for item in &attention {
+                output.push_str(&format!(
+                    "- **{}** {}: {}\n",
+                    item.package, item.version, item.line
+                ));
+            }
+            output.push('\n');
+        }
+
+        if !self.initial_pins.is_empty() {
+            output.push_str("Initial Release\n");
+            output.push_str("---------------\n\n");
 
-        output.push_str(&format!("**Date:** {}\n\n", self.date));
+            for pin in &self.initial_pins {
+                output.push_str(&format!("- **{}** {}\n", pin.name, pin.version));
+            }
+            output.push('\n');
+        }
 
-        output.push_str("Package Updates\n");
-        output.push_str("---------------\n\n");
+        if self.initial_pins.is_empty() || !self.package_changelogs.is_empty() {
+            output.push_str("Package Updates\n");
+            output.push_str("---------------\n\n");
+        }
 
-        for pkg in &self.package_changelogs {
-            let pkg_title = format!(
-                "{} ({} → {})",
-                pkg.package_name, pkg.old_version, pkg.new_version
-            );
-            output.push_str(&pkg_title);
+        if !self.added_packages.is_empty() || !self.removed_packages.is_empty() {
+            for pin in &self.added_packages {
+                output.push_str(&format!("- Added **{}** {}\n", pin.name, pin.version));
+            }
+            for pin in &self.removed_packages {
+                output.push_str(&format!("- Removed **{}**\n", pin.name));
+            }
             output.push('\n');
-            output.push_str(&"~".repeat(pkg_title.len()));
-            output.push_str("\n\n");
+        }
 
-            if pkg.entries.is_empty() {
-                output.push_str("*No changelog entries found.*\n\n");
-            } else {
-                for entry in &pkg.entries {
-                    let date_str = entry
-                        .date
-                        .as_ref()
-                        .map(|d| format!(" ({})", d))
-                        .unwrap_or_default();
-
-                    let ver_title = format!("Version {}{}", entry.version, date_str);
-                    output.push_str(&ver_title);
+        let (updated, reverted) = self.split_downgrades();
+        if self.group_by_category {
+            for (category, items) in category_groups(&updated) {
+                output.push_str(category);
+                output.push('\n');
+                output.push_str(&"~".repeat(category.len()));
+                output.push_str("\n\n");
+                for (pkg, content) in items {
+                    output.push_str(&format!(
+                        "- **{}** ({} → {})\n\n",
+                        pkg.package_name, pkg.old_version, pkg.new_version
+                    ));
+                    push_indented(&mut output, &content, 2);
                     output.push('\n');
-                    output.push_str(&"^".repeat(ver_title.len()));
-                    output.push_str("\n\n");
-                    output.push_str(&entry.content);
-                    output.push_str("\n\n");
                 }
             }
+            if let Some(names) = packages_without_entries(&updated) {
+                output.push_str(&format!("*No changelog entries found for {}.*\n\n", names));
+            }
+        } else {
+            for pkg in updated {
+                push_rst_package(&mut output, pkg);
+            }
+        }
+        if !reverted.is_empty() {
+            output.push_str("Reverted Changes\n");
+            output.push_str("----------------\n\n");
+            for pkg in reverted {
+                push_rst_package(&mut output, pkg);
+            }
+        }
+
+        let thanks = self.thanks_to();
+        if !thanks.is_empty() {
+            output.push_str("Thanks to\n");
+            output.push_str("---------\n\n");
+            output.push_str(&format!("{}\n\n", thanks.join(", ")));
         }
 
         output
@@ -662,45 +1921,213 @@ impl ConsolidatedChangelog {
 
     /// Render as plain text
     pub fn to_text(&self) -> String {
+        self.heading(ChangelogFormat::Text) + &self.render_body(ChangelogFormat::Text)
+    }
+
+    fn text_body(&self) -> String {
         let mut output = String::new();
 
-        output.push_str(&format!(
-            "RELEASE {} ({})\n",
-            self.release_version, self.date
-        ));
-        output.push_str(&"=".repeat(60));
-        output.push_str("\n\n");
+        let attention = self.attention();
+        if !attention.is_empty() {
+            output.push_str("⚠ ATTENTION:\n");
+            for item in &attention {
+                output.push_str(&format!(
+                    "  {} {}: {}\n",
+                    item.package, item.version, item.line
+                ));
+            }
+            output.push('\n');
+        }
 
-        for pkg in &self.package_changelogs {
-            output.push_str(&format!(
-                "{}: {} → {}\n",
-                pkg.package_name, pkg.old_version, pkg.new_version
-            ));
-            output.push_str(&"-".repeat(40));
+        if !self.initial_pins.is_empty() {
+            output.push_str("Initial release:\n");
+            for pin in &self.initial_pins {
+                output.push_str(&format!("  {} {}\n", pin.name, pin.version));
+            }
             output.push('\n');
+        }
 
-            if pkg.entries.is_empty() {
-                output.push_str("  No changelog entries found.\n");
-            } else {
-                for entry in &pkg.entries {
-                    let date_str = entry
-                        .date
-                        .as_ref()
-                        .map(|d| format!(" ({})", d))
-                        .unwrap_or_default();
-
-                    output.push_str(&format!("\n  Version {}{}:\n", entry.version, date_str));
-                    for line in entry.content.lines() {
-                        output.push_str(&format!("    {}\n", line));
-                    }
-                }
+        if !self.added_packages.is_empty() || !self.removed_packages.is_empty() {
+            for pin in &self.added_packages {
+                output.push_str(&format!("Added {} {}\n", pin.name, pin.version));
+            }
+            for pin in &self.removed_packages {
+                output.push_str(&format!("Removed {}\n", pin.name));
             }
             output.push('\n');
         }
 
+        let (updated, reverted) = self.split_downgrades();
+        if self.group_by_category {
+            for (category, items) in category_groups(&updated) {
+                output.push_str(&format!("{}:\n", category));
+                for (pkg, content) in items {
+                    output.push_str(&format!(
+                        "  {}: {} → {}\n",
+                        pkg.package_name, pkg.old_version, pkg.new_version
+                    ));
+                    push_indented(&mut output, &content, 4);
+                }
+                output.push('\n');
+            }
+            if let Some(names) = packages_without_entries(&updated) {
+                output.push_str(&format!("No changelog entries found for {}.\n\n", names));
+            }
+        } else {
+            for pkg in updated {
+                push_text_package(&mut output, pkg);
+            }
+        }
+        if !reverted.is_empty() {
+            output.push_str("Reverted changes:\n\n");
+            for pkg in reverted {
+                push_text_package(&mut output, pkg);
+            }
+        }
+
+        let thanks = self.thanks_to();
+        if !thanks.is_empty() {
+            output.push_str(&format!("Thanks to:\n  {}\n\n", thanks.join(", ")));
+        }
+
         output
     }
 
+    /// Entry lines that mention an attention keyword, or bullets under a heading
+    /// that does (`Breaking changes:`), in package order
+    pub fn attention(&self) -> Vec<AttentionItem> {
+        let keywords: Vec<String> = self
+            .attention_keywords
+            .iter()
+            .map(|keyword| keyword.to_lowercase())
+            .collect();
+        let mentions = |text: &str| {
+            let text = text.to_lowercase();
+            keywords
+                .iter()
+                .any(|keyword| text.contains(keyword.as_str()))
+        };
+
+        let mut items = Vec::new();
+        if keywords.is_empty() {
+            return items;
+        }
+        for pkg in &self.package_changelogs {
+            for entry in &pkg.entries {
+                let mut flagged_section = false;
+                for line in entry.content.lines().map(str::trim) {
+                    let is_heading = line.starts_with('#')
+                        || ChangeCategory::from_heading(line).is_some()
+                        || (line.ends_with(':') && !line.starts_with(['-', '*']));
+                    if is_heading {
+                        flagged_section = mentions(line);
+                        continue;
+                    }
+
+                    let bullet = line.strip_prefix("- ").or(line.strip_prefix("* "));
+                    let text = bullet.unwrap_or(line).trim();
+                    if !text.is_empty() && (mentions(text) || (flagged_section && bullet.is_some()))
+                    {
+                        items.push(AttentionItem {
+                            package: pkg.package_name.clone(),
+                            version: entry.version.clone(),
+                            line: text.to_string(),
+                        });
+                    }
+                }
+            }
+        }
+        items
+    }
+
+    /// The changelog as JSON, with each package flagged when it needs attention
+    pub fn to_json(&self) -> serde_json::Value {
+        let attention = self.attention();
+        let packages: Vec<serde_json::Value> = self
+            .package_changelogs
+            .iter()
+            .map(|pkg| {
+                let entries: Vec<serde_json::Value> = pkg
+                    .entries
+                    .iter()
+                    .map(|entry| {
+                        serde_json::json!({
+                            "version": entry.version,
+                            "date": entry.date,
+                            "content": entry.content,
+                        })
+                    })
+                    .collect();
+                serde_json::json!({
+                    "package": pkg.package_name,
+                    "old_version": pkg.old_version,
+                    "new_version": pkg.new_version,
+                    "attention": attention.iter().any(|item| item.package == pkg.package_name),
+                    "truncated": pkg.truncated,
+                    "entries": entries,
+                })
+            })
+            .collect();
+
+        serde_json::json!({
+            "version": self.release_version,
+            "date": self.date,
+            "attention": attention,
+            "packages": packages,
+            "added": self.added_packages,
+            "removed": self.removed_packages,
+        })
+    }
+
+    /// Package changelogs split into updates and downgrades, in their original order
+    fn split_downgrades(&self) -> (Vec<&PackageChangelog>, Vec<&PackageChangelog>) {
+        self.package_changelogs
+            .iter()
+            .partition(|pkg| !pkg.is_downgrade())
+    }
+
+    fn push_markdown_package(&self, output: &mut String, pkg: &PackageChangelog) {
+        let overrides = self.package_overrides.get(&pkg.package_name);
+
+        // Apply package template
+        let pkg_header = overrides
+            .and_then(|o| o.package.as_deref())
+            .unwrap_or(&self.package_template)
+            .replace("{package}", &pkg.package_name)
+            .replace("{old_version}", &pkg.old_version)
+            .replace("{new_version}", &pkg.new_version);
+        output.push_str(&pkg_header);
+        output.push_str("\n\n");
+
+        if pkg.entries.is_empty() {
+            output.push_str("*No changelog entries found.*\n\n");
+        } else {
+            for entry in &pkg.entries {
+                let heading = match overrides.and_then(|o| o.entry.as_deref()) {
+                    Some(template) => template
+                        .replace("{version}", &entry.version)
+                        .replace("{date}", entry.date.as_deref().unwrap_or_default()),
+                    None => {
+                        let date_str = entry
+                            .date
+                            .as_ref()
+                            .map(|d| format!(" ({})", d))
+                            .unwrap_or_default();
+                        format!("#### Version {}{}", entry.version, date_str)
+                    }
+                };
+
+                output.push_str(&heading);
+                output.push_str("\n\n");
+                output.push_str(&entry.content);
+                output.push_str("\n\n");
+            }
+        }
+        if let Some(url) = &pkg.compare_url {
+            output.push_str(&format!("Full diff: {}\n\n", url));
+        }
+    }
+
     /// Render in specified format
     pub fn render(&self, format: ChangelogFormat) -> String {
         match format {
@@ -755,8 +2182,11 @@ impl ConsolidatedChangelog {
                         continue;
                     }
 
-                    // Found main title (# Changelog)
-                    if trimmed.starts_with("# ") && !trimmed.starts_with("## ") {
+                    // Found main title (# Changelog); a later `# Release` is an entry
+                    if !found_main_title
+                        && trimmed.starts_with("# ")
+                        && !trimmed.starts_with("# Release")
+                    {
                         found_main_title = true;
                         insert_position = i + 1;
                         continue;
@@ -894,68 +2324,385 @@ impl ConsolidatedChangelog {
     }
 }
 
-/// Normalize version string for comparison
-fn normalize_version(version: &str) -> Vec<u32> {
-    let mut result = Vec::new();
-
-    let v = version.trim_start_matches('v');
+/// The "Unreleased" section at the top of a changelog file, which `update`
+/// adds entries to and `release` turns into the entry of the new release
+pub struct UnreleasedSection {
+    format: ChangelogFormat,
+    /// First line of a release's heading, where the section ends
+    release_heading: Regex,
+}
 
-    // Split on dots first
-    let parts: Vec<&str> = v.split('.').collect();
+impl UnreleasedSection {
+    pub fn new(config: &ChangelogConfig, format: ChangelogFormat) -> Self {
+        let version = r"\S*\d\S*";
+        let pattern = match format {
+            ChangelogFormat::Markdown => {
+                let first_line = config.header_template.lines().next().unwrap_or_default();
+                regex::escape(first_line.trim())
+                    .replace(r"\{version\}", version)
+                    .replace(r"\{date\}", ".*")
+            }
+            ChangelogFormat::Rst => format!("Release {}", version),
+            ChangelogFormat::Text => format!(r"RELEASE {} \(.*\)", version),
+        };
 
-    for (i, part) in parts.iter().enumerate() {
-        // For major, minor, patch we take leading digits
-        let digits: String = part.chars().take_while(|c| c.is_ascii_digit()).collect();
-        if digits.is_empty() {
-            break;
+        Self {
+            format,
+            release_heading: Regex::new(&format!("^{}$", pattern)).unwrap(),
         }
-        let n: u32 = match digits.parse() {
-            Ok(n) => n,
-            Err(_) => break,
-        };
-        result.push(n);
-
-        // If this is the patch component and it has a prerelease suffix (like "3a1"),
-        // extract the trailing number as an extra component.
-        if i == 2 {
-            // Remainder after the leading digits
-            let suffix: String = part.chars().skip(digits.len()).collect();
-            if !suffix.is_empty() {
-                // Try to get trailing digits from suffix, e.g. "a1" -> 1
-                let trailing_digits: String = suffix
-                    .chars()
-                    .rev()
-                    .take_while(|c| c.is_ascii_digit())
-                    .collect::<String>()
-                    .chars()
-                    .rev()
-                    .collect();
-                if let Ok(m) = trailing_digits.parse::<u32>() {
-                    result.push(m);
+    }
+
+    fn heading(&self) -> String {
+        match self.format {
+            ChangelogFormat::Markdown => "## Unreleased".to_string(),
+            ChangelogFormat::Rst => "Unreleased\n==========".to_string(),
+            ChangelogFormat::Text => format!("UNRELEASED\n{}", "=".repeat(60)),
+        }
+    }
+
+    /// Lines of the section: where its heading starts, where its entries
+    /// start, and where the next release begins
+    fn find(&self, lines: &[&str]) -> Option<(usize, usize, usize)> {
+        // Only above the first release, not in the entries of older ones
+        let first_release = lines
+            .iter()
+            .position(|line| self.release_heading.is_match(line.trim()))
+            .unwrap_or(lines.len());
+        let start = lines[..first_release].iter().position(|line| {
+            let title = line.trim().trim_start_matches('#').trim();
+            let title = title.trim_start_matches('[').trim_end_matches(']');
+            title.eq_ignore_ascii_case("unreleased")
+        })?;
+
+        let mut body = start + 1;
+        if self.format != ChangelogFormat::Markdown
+            && lines
+                .get(body)
+                .is_some_and(|line| !line.is_empty() && line.chars().all(|c| c == '='))
+        {
+            body += 1;
+        }
+
+        let end = lines[body..]
+            .iter()
+            .position(|line| self.release_heading.is_match(line.trim()))
+            .map_or(lines.len(), |offset| body + offset);
+        Some((start, body, end))
+    }
+
+    /// Add rendered entries at the end of the section, creating it (and the
+    /// file) when needed
+    pub fn append<P: AsRef<Path>>(&self, path: P, entries: &str) -> Result<()> {
+        let path = path.as_ref();
+        let entries = entries.trim();
+        let section = format!("{}\n\n{}", self.heading(), entries);
+
+        if !path.exists() {
+            let content = ConsolidatedChangelog::add_file_header(&section, self.format);
+            std::fs::write(path, format!("{}\n", content))?;
+            return Ok(());
+        }
+
+        let existing = std::fs::read_to_string(path)?;
+        let lines: Vec<&str> = existing.lines().collect();
+        let combined = match self.find(&lines) {
+            Some((_, _, end)) => {
+                let before = lines[..end].join("\n");
+                let mut combined = format!("{}\n\n{}\n", before.trim_end(), entries);
+                if end < lines.len() {
+                    combined.push('\n');
+                    combined.push_str(&lines[end..].join("\n"));
+                    combined.push('\n');
                 }
+                combined
             }
+            None => ConsolidatedChangelog::prepend_to_changelog(&section, &existing, self.format),
+        };
+        std::fs::write(path, combined)?;
+        Ok(())
+    }
+
+    /// Replace the section's heading by `release_heading` and return its
+    /// entries; `None` when the file has no section or it is empty
+    pub fn promote<P: AsRef<Path>>(
+        &self,
+        path: P,
+        release_heading: &str,
+    ) -> Result<Option<String>> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let existing = std::fs::read_to_string(path)?;
+        let lines: Vec<&str> = existing.lines().collect();
+        let Some((start, body, end)) = self.find(&lines) else {
+            return Ok(None);
+        };
+        let entries = lines[body..end].join("\n").trim().to_string();
+        if entries.is_empty() {
+            return Ok(None);
         }
+
+        let mut promoted: Vec<&str> = lines[..start].to_vec();
+        promoted.extend(release_heading.trim_end().lines());
+        promoted.push("");
+        promoted.push(&entries);
+        if end < lines.len() {
+            promoted.push("");
+            promoted.extend(&lines[end..]);
+        }
+        std::fs::write(path, format!("{}\n", promoted.join("\n")))?;
+        Ok(Some(entries))
     }
+}
 
-    result
+/// Markup declared by a PyPI `description_content_type` (`text/markdown`,
+/// `text/x-rst`); `None` when missing or plain text
+fn description_markup(info: &serde_json::Value) -> Option<ChangelogFormat> {
+    let content_type = info["description_content_type"].as_str()?;
+    let mime = content_type.split(';').next()?.trim().to_lowercase();
+    match mime.as_str() {
+        "text/markdown" => Some(ChangelogFormat::Markdown),
+        "text/x-rst" => Some(ChangelogFormat::Rst),
+        _ => None,
+    }
 }
 
-/// Compare two normalized versions
-fn compare_versions(a: &[u32], b: &[u32]) -> i32 {
-    let max_len = a.len().max(b.len());
+/// Markup of a changelog file, by its extension
+fn markup_of_url(url: &str) -> Option<ChangelogFormat> {
+    let path = url.split(['?', '#']).next()?.to_lowercase();
+    if path.ends_with(".md") || path.ends_with(".markdown") {
+        Some(ChangelogFormat::Markdown)
+    } else if path.ends_with(".rst") {
+        Some(ChangelogFormat::Rst)
+    } else {
+        None
+    }
+}
+
+/// Keep at most `max` bytes, ending after the last whole line among them
+fn cut_after_last_line(body: &mut Vec<u8>, max: usize) {
+    body.truncate(max);
+    if let Some(end) = body.iter().rposition(|&byte| byte == b'\n') {
+        body.truncate(end + 1);
+    }
+}
+
+/// Whether the changelog has a version header at or below `old_version`
+fn covers_version(content: &str, old_version: &str) -> bool {
+    let header_pattern =
+        Regex::new(r"(?m)^(?:#+\s+|\*\s+|Version\s+)?\[?v?(\d+\.\d+(?:\.\d+)?(?:[._-]?\w+)?)\]?")
+            .unwrap();
+
+    let covered = header_pattern.captures_iter(content).any(|caps| {
+        compare_versions_lenient(caps.get(1).unwrap().as_str(), old_version) != Ordering::Greater
+    });
+    covered
+}
+
+/// A project on gitlab.com or a self-hosted GitLab
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct GitLabProject {
+    /// Scheme and host, e.g. `https://gitlab.com`
+    base: String,
+    /// Namespace and project, e.g. `group/subgroup/project`
+    path: String,
+}
+
+impl GitLabProject {
+    /// The project a URL points into, when its host is a GitLab (the name
+    /// contains "gitlab", or it is one of `extra_hosts`)
+    fn from_url(url: &str, extra_hosts: &[String]) -> Option<Self> {
+        let (scheme, rest) = url.split_once("://")?;
+        let (host, path) = rest.split_once('/')?;
+        let host_name = host.split(':').next()?;
+        if !host_name.contains("gitlab") && !extra_hosts.iter().any(|h| h == host_name) {
+            return None;
+        }
+
+        let path = path.split(['?', '#']).next()?;
+        let path = path.split("/-/").next()?.trim_end_matches('/');
+        let path = path.trim_end_matches(".git");
+        if !path.contains('/') {
+            return None;
+        }
+
+        Some(Self {
+            base: format!("{}://{}", scheme, host),
+            path: path.to_string(),
+        })
+    }
+
+    fn raw_url(&self, branch: &str, file: &str) -> String {
+        format!("{}/{}/-/raw/{}/{}", self.base, self.path, branch, file)
+    }
 
-    for i in 0..max_len {
-        let av = a.get(i).copied().unwrap_or(0);
-        let bv = b.get(i).copied().unwrap_or(0);
+    fn compare_url(&self, from: &str, to: &str) -> String {
+        format!(
+            "{}/api/v4/projects/{}/repository/compare?from={}&to={}",
+            self.base,
+            self.path.replace('/', "%2F"),
+            from,
+            to
+        )
+    }
+
+    /// Project API endpoint, which reports the default branch
+    fn project_url(&self) -> String {
+        format!(
+            "{}/api/v4/projects/{}",
+            self.base,
+            self.path.replace('/', "%2F")
+        )
+    }
+
+    fn api_url(&self, endpoint: &str) -> String {
+        format!(
+            "{}/api/v4/projects/{}/{}?per_page=100",
+            self.base,
+            self.path.replace('/', "%2F"),
+            endpoint
+        )
+    }
+}
 
-        if av < bv {
-            return -1;
-        } else if av > bv {
-            return 1;
+/// `default` (when known) first, then the fallbacks in order, each once
+fn branch_order(default: Option<&str>, fallbacks: &[String]) -> Vec<String> {
+    let mut branches: Vec<String> = default.map(str::to_string).into_iter().collect();
+    for branch in fallbacks {
+        if !branches.contains(branch) {
+            branches.push(branch.clone());
         }
     }
+    branches
+}
 
-    0
+/// Owner and name of the GitHub repository a URL points into
+fn github_repo(url: &str) -> Option<(String, String)> {
+    let repo_pattern =
+        Regex::new(r"(?:github\.com|raw\.githubusercontent\.com)/([^/]+)/([^/#?]+)").unwrap();
+    let caps = repo_pattern.captures(url)?;
+    Some((
+        caps[1].to_string(),
+        caps[2].trim_end_matches(".git").to_string(),
+    ))
+}
+
+/// First lines of the commits in a GitHub or GitLab compare response,
+/// without merge commits
+fn compare_subjects(comparison: &serde_json::Value) -> Vec<String> {
+    let Some(commits) = comparison["commits"].as_array() else {
+        return Vec::new();
+    };
+    commits
+        .iter()
+        .filter_map(|commit| {
+            commit["title"]
+                .as_str()
+                .or(commit["commit"]["message"].as_str())
+        })
+        .filter_map(|message| message.lines().next())
+        .map(str::trim)
+        .filter(|subject| !subject.is_empty() && !subject.starts_with("Merge "))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Markdown changelog built from GitHub releases (`body`), GitLab releases
+/// (`description`) or GitLab tags (`release.description`, else the annotated
+/// tag `message`), newest first
+fn release_notes_as_changelog(notes: &serde_json::Value) -> Option<String> {
+    let mut out = String::new();
+    for note in notes.as_array()? {
+        let Some(name) = note["tag_name"].as_str().or(note["name"].as_str()) else {
+            continue;
+        };
+        let body = note["description"]
+            .as_str()
+            .or(note["body"].as_str())
+            .or(note["release"]["description"].as_str())
+            .or(note["message"].as_str())
+            .unwrap_or_default()
+            .trim();
+        if body.is_empty() {
+            continue;
+        }
+
+        let date = note["released_at"]
+            .as_str()
+            .or(note["published_at"].as_str())
+            .or(note["commit"]["created_at"].as_str())
+            .and_then(|date| date.get(..10));
+        match date {
+            Some(date) => out.push_str(&format!("## {} - {}\n\n", name, date)),
+            None => out.push_str(&format!("## {}\n\n", name)),
+        }
+        out.push_str(body);
+        out.push_str("\n\n");
+    }
+
+    (!out.is_empty()).then_some(out)
+}
+
+/// Find the target of an "older entries can be found in X" style note
+fn older_entries_link(content: &str) -> Option<String> {
+    let note_pattern = Regex::new(
+        r"(?i)(?:older|earlier|previous)\s+(?:entries|changes|releases|versions|history)[^\n]*",
+    )
+    .unwrap();
+    let target_pattern = Regex::new(r"(https?://[^\s<>`)\]]+|[\w./-]+\.(?:rst|md|txt))").unwrap();
+
+    let target = note_pattern.find_iter(content).find_map(|note| {
+        target_pattern
+            .find(note.as_str())
+            .map(|m| m.as_str().trim_end_matches('.').to_string())
+    });
+    target
+}
+
+/// Resolve a changelog link relative to the URL of the file it appeared in
+fn resolve_link(target: &str, base_url: Option<&str>) -> Option<String> {
+    if target.starts_with("http://") || target.starts_with("https://") {
+        return Some(target.to_string());
+    }
+
+    let base = base_url?;
+    let dir = &base[..base.rfind('/')? + 1];
+    Some(format!("{}{}", dir, target.trim_start_matches("./")))
+}
+
+/// Version range `(old, new]` whose changelog entries should be collected
+/// `(lower, higher)` of two versions
+fn version_bounds<'a>(a: &'a str, b: &'a str) -> (&'a str, &'a str) {
+    if compare_versions_lenient(b, a) == Ordering::Less {
+        (b, a)
+    } else {
+        (a, b)
+    }
+}
+
+struct EntryWindow<'a> {
+    old: &'a str,
+    new: &'a str,
+}
+
+impl<'a> EntryWindow<'a> {
+    /// Entries after `old` up to `new`; for a downgrade, the entries being
+    /// reverted (after `new` up to `old`)
+    fn new(old: &'a str, new: &'a str) -> Self {
+        let (old, new) = version_bounds(old, new);
+        Self { old, new }
+    }
+
+    fn contains(&self, version: &str) -> bool {
+        compare_versions_lenient(version, self.old) == Ordering::Greater
+            && compare_versions_lenient(version, self.new) != Ordering::Greater
+    }
+
+    fn is_at_or_before_start(&self, version: &str) -> bool {
+        compare_versions_lenient(version, self.old) != Ordering::Greater
+    }
 }
 
 #[cfg(test)]
@@ -964,22 +2711,386 @@ mod tests {
     use serde_json::json;
     use crate::buildout::VersionUpdate;
     use crate::config::PackageConfig;
+    use crate::testing::temp_dir;
+
+    /// A consolidated changelog rendered with the default templates
+    fn consolidated(
+        release_version: &str,
+        date: &str,
+        package_changelogs: Vec<PackageChangelog>,
+    ) -> ConsolidatedChangelog {
+        ConsolidatedChangelog::with_templates(
+            release_version,
+            date,
+            package_changelogs,
+            &ChangelogConfig::default(),
+        )
+    }
+
+    #[test]
+    fn test_exclude_patterns_drop_noise_lines() {
+        let config = ChangelogConfig {
+            exclude_patterns: vec![
+                "^- Update dependabot".to_string(),
+                "^- Bump pre-commit".to_string(),
+            ],
+            ..ChangelogConfig::default()
+        };
+        let changelog = PackageChangelog {
+            package_name: "plone.api".to_string(),
+            old_version: "2.0.0".to_string(),
+            new_version: "2.1.0".to_string(),
+            entries: vec![
+                ChangelogEntry {
+                    version: "2.1.0".to_string(),
+                    date: None,
+                    content: "- Fix login redirect.\n- Bump pre-commit hooks\n  to latest versions.\n- Add api.user.search."
+                        .to_string(),
+                    markup: None,
+                },
+                ChangelogEntry {
+                    version: "2.0.1".to_string(),
+                    date: None,
+                    content: "- Update dependabot config.".to_string(),
+                    markup: None,
+                },
+            ],
+            compare_url: None,
+            truncated: false,
+        };
+
+        let consolidated =
+            ConsolidatedChangelog::with_templates("1.0.0", "2024-01-01", vec![changelog], &config);
+        let entries = &consolidated.package_changelogs[0].entries;
+
+        assert_eq!(entries.len(), 1);
+        assert_eq!(
+            entries[0].content,
+            "- Fix login redirect.\n- Add api.user.search."
+        );
+    }
+
+    #[test]
+    fn test_compare_entry_versions_orders_prereleases() {
+        assert_eq!(compare_versions_lenient("1.0rc1", "1.0"), Ordering::Less);
+        assert_eq!(compare_versions_lenient("1.0", "1.0.0"), Ordering::Equal);
+        assert_eq!(
+            compare_versions_lenient("1.0.post1", "1.0"),
+            Ordering::Greater
+        );
+        assert_eq!(compare_versions_lenient("2.0b2", "2.0b10"), Ordering::Less);
+    }
+
+    #[test]
+    fn test_parse_changelog_selects_entries_around_prereleases() {
+        let collector = ChangelogCollector::new();
+        let content = "## 2.0 - 2024-03-01\n\n- Final\n\n## 2.0rc1 - 2024-02-01\n\n- Candidate\n\n## 1.9 - 2024-01-01\n\n- Old\n";
+
+        let entries = collector.parse_changelog(content, "2.0rc1", "2.0");
+        let versions: Vec<&str> = entries.iter().map(|e| e.version.as_str()).collect();
+        assert_eq!(versions, vec!["2.0"]);
+
+        let entries = collector.parse_changelog(content, "1.9", "2.0rc1");
+        let versions: Vec<&str> = entries.iter().map(|e| e.version.as_str()).collect();
+        assert_eq!(versions, vec!["2.0rc1"]);
+    }
+
+    #[test]
+    fn test_downgrade_collects_reverted_entries() {
+        let collector = ChangelogCollector::new();
+        let content =
+            "## 2.1.0 - 2024-03-01\n\n- Add search\n\n## 2.0.0 - 2024-01-01\n\n- Initial\n";
+
+        let entries = collector.parse_changelog(content, "2.1.0", "2.0.0");
+        let versions: Vec<&str> = entries.iter().map(|e| e.version.as_str()).collect();
+        assert_eq!(versions, vec!["2.1.0"]);
+
+        let reverted = PackageChangelog {
+            package_name: "plone.api".to_string(),
+            old_version: "2.1.0".to_string(),
+            new_version: "2.0.0".to_string(),
+            entries,
+            compare_url: None,
+            truncated: false,
+        };
+        let updated = PackageChangelog {
+            package_name: "plone.foo".to_string(),
+            old_version: "1.0".to_string(),
+            new_version: "1.1".to_string(),
+            entries: Vec::new(),
+            compare_url: None,
+            truncated: false,
+        };
+        assert!(reverted.is_downgrade());
+        assert!(!updated.is_downgrade());
+
+        let changelog = consolidated("1.1.0", "2024-04-01", vec![reverted, updated]);
+        let markdown = changelog.to_markdown();
+        let foo = markdown.find("### plone.foo (1.0 → 1.1)").unwrap();
+        let heading = markdown.find("## Reverted changes").unwrap();
+        let api = markdown.find("### plone.api (2.1.0 → 2.0.0)").unwrap();
+        assert!(foo < heading && heading < api);
+        assert!(markdown[api..].contains("- Add search"));
+
+        assert!(changelog
+            .to_rst()
+            .contains("Reverted Changes\n----------------"));
+        assert!(changelog.to_text().contains("Reverted changes:"));
+    }
 
     #[test]
-    fn test_normalize_version() {
-        assert_eq!(normalize_version("1.2.3"), vec![1, 2, 3]);
-        assert_eq!(normalize_version("v1.2.3"), vec![1, 2, 3]);
-        assert_eq!(normalize_version("1.2.3a1"), vec![1, 2, 3, 1]);
-        assert_eq!(normalize_version("1.2"), vec![1, 2]);
+    fn test_older_entries_link_detection() {
+        let content = "2.0 (2024-01-01)\n----------------\n\n- New\n\nOlder entries can be found in HISTORY_OLD.rst.\n";
+
+        assert!(!covers_version(content, "1.5"));
+        assert!(covers_version(content, "2.0"));
+
+        let target = older_entries_link(content).expect("link");
+        assert_eq!(target, "HISTORY_OLD.rst");
+        assert_eq!(
+            resolve_link(&target, Some("https://example.com/pkg/CHANGES.rst")).as_deref(),
+            Some("https://example.com/pkg/HISTORY_OLD.rst")
+        );
+        assert!(resolve_link(&target, None).is_none());
+        assert_eq!(
+            resolve_link("https://example.com/old.md", None).as_deref(),
+            Some("https://example.com/old.md")
+        );
     }
 
     #[test]
-    fn test_compare_versions() {
-        assert_eq!(compare_versions(&vec![1, 2, 3], &vec![1, 2, 3]), 0);
-        assert_eq!(compare_versions(&vec![1, 2, 3], &vec![1, 2, 4]), -1);
-        assert_eq!(compare_versions(&vec![1, 2, 4], &vec![1, 2, 3]), 1);
-        assert_eq!(compare_versions(&vec![1, 2], &vec![1, 2, 0]), 0);
-        assert_eq!(compare_versions(&vec![2, 0, 0], &vec![1, 9, 9]), 1);
+    fn test_gitlab_project_and_release_notes() {
+        let hosts = vec!["git.example.org".to_string()];
+        let project =
+            GitLabProject::from_url("https://gitlab.com/group/sub/project.git", &hosts).unwrap();
+        assert_eq!(project.path, "group/sub/project");
+        assert_eq!(
+            project.api_url("releases"),
+            "https://gitlab.com/api/v4/projects/group%2Fsub%2Fproject/releases?per_page=100"
+        );
+        assert_eq!(
+            project.project_url(),
+            "https://gitlab.com/api/v4/projects/group%2Fsub%2Fproject"
+        );
+        assert_eq!(
+            GitLabProject::from_url("https://git.example.org/team/lib/-/tree/main", &hosts)
+                .unwrap()
+                .raw_url("main", "CHANGES.md"),
+            "https://git.example.org/team/lib/-/raw/main/CHANGES.md"
+        );
+        assert!(GitLabProject::from_url("https://github.com/plone/plone.api", &hosts).is_none());
+        assert!(GitLabProject::from_url("https://gitlab.com/group", &hosts).is_none());
+
+        let fallbacks = vec!["main".to_string(), "master".to_string()];
+        assert_eq!(
+            branch_order(Some("develop"), &fallbacks),
+            vec!["develop", "main", "master"]
+        );
+        assert_eq!(
+            branch_order(Some("master"), &fallbacks),
+            vec!["master", "main"]
+        );
+        assert_eq!(branch_order(None, &fallbacks), fallbacks);
+
+        let releases = serde_json::json!([
+            {"tag_name": "v1.2.0", "released_at": "2025-06-01T10:00:00Z", "description": "- Add feature"},
+            {"tag_name": "v1.1.1", "released_at": "2025-05-01T10:00:00Z", "description": ""},
+            {"tag_name": "v1.1.0", "released_at": "2025-04-01T10:00:00Z", "description": "- Fix bug"}
+        ]);
+        let content = release_notes_as_changelog(&releases).unwrap();
+        let entries = ChangelogCollector::new().parse_changelog(&content, "1.0.0", "1.2.0");
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].version, "1.2.0");
+        assert_eq!(entries[0].date.as_deref(), Some("2025-06-01"));
+        assert_eq!(entries[1].content, "- Fix bug");
+
+        let tags = serde_json::json!([
+            {"name": "2.0", "message": "Release 2.0", "commit": {"created_at": "2025-07-01T00:00:00+02:00"}}
+        ]);
+        assert_eq!(
+            release_notes_as_changelog(&tags).as_deref(),
+            Some("## 2.0 - 2025-07-01\n\nRelease 2.0\n\n")
+        );
+        assert!(release_notes_as_changelog(&serde_json::json!([])).is_none());
+    }
+
+    #[test]
+    fn test_commit_subjects_from_compare_responses() {
+        assert_eq!(
+            github_repo("https://github.com/plone/plone.api.git"),
+            Some(("plone".to_string(), "plone.api".to_string()))
+        );
+        assert_eq!(
+            github_repo("https://raw.githubusercontent.com/zopefoundation/Zope/master/CHANGES.rst"),
+            Some(("zopefoundation".to_string(), "Zope".to_string()))
+        );
+
+        let github = serde_json::json!({"commits": [
+            {"commit": {"message": "Fix login redirect\n\nLonger description"}},
+            {"commit": {"message": "Merge pull request #12 from plone/fix"}},
+            {"commit": {"message": "Release 2.1.0"}}
+        ]});
+        assert_eq!(
+            compare_subjects(&github),
+            vec!["Fix login redirect", "Release 2.1.0"]
+        );
+
+        let gitlab = serde_json::json!({"commits": [{"title": "Drop Python 3.8", "message": "Drop Python 3.8\n"}]});
+        assert_eq!(compare_subjects(&gitlab), vec!["Drop Python 3.8"]);
+        assert!(compare_subjects(&serde_json::json!({"message": "404 Not Found"})).is_empty());
+    }
+
+    #[test]
+    fn test_github_releases_source() {
+        let package: PackageConfig = toml::from_str(
+            "name = \"plone.restapi\"\nchangelog_from = [\"github_releases\", \"git_log\"]\n",
+        )
+        .unwrap();
+        assert_eq!(
+            package.changelog_from,
+            vec![ChangelogSource::GithubReleases, ChangelogSource::GitLog]
+        );
+        assert!(toml::from_str::<PackageConfig>(
+            "name = \"plone.restapi\"\nchangelog_from = [\"readme\"]\n"
+        )
+        .is_err());
+
+        let releases = serde_json::json!([
+            {"tag_name": "9.1.0", "published_at": "2025-05-02T10:00:00Z", "body": "- Add @site endpoint"},
+            {"tag_name": "9.0.0", "published_at": "2025-03-01T08:00:00Z", "body": ""},
+            {"tag_name": "8.9.0", "published_at": "2025-01-10T08:00:00Z", "body": "- Fix batching"}
+        ]);
+        let content = release_notes_as_changelog(&releases).unwrap();
+        assert_eq!(
+            content,
+            "## 9.1.0 - 2025-05-02\n\n- Add @site endpoint\n\n## 8.9.0 - 2025-01-10\n\n- Fix batching\n\n"
+        );
+
+        let entries = ChangelogCollector::new().parse_changelog(&content, "8.9.0", "9.1.0");
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].version, "9.1.0");
+    }
+
+    #[test]
+    fn test_group_entries_by_category() {
+        let collector = ChangelogCollector::new();
+        let upstream = "# Changelog\n\n## [2.1.0] - 2025-06-01\n\n### Added\n\n- Batch API\n\n### Fixed\n\n- Login redirect\n\n## [2.0.0] - 2025-01-01\n\n### Security\n\n- Escape titles\n";
+        let entries = collector.parse_changelog(upstream, "1.0.0", "2.1.0");
+        let towncrier = "3.1 (2025-05-20)\n----------------\n\nBug fixes:\n\n- Fix cache key\n";
+
+        let mut config = ChangelogConfig {
+            group_by: Some("category".to_string()),
+            ..ChangelogConfig::default()
+        };
+        let changelog = ConsolidatedChangelog::with_templates(
+            "1.8.0",
+            "2025-06-08",
+            vec![
+                PackageChangelog {
+                    package_name: "plone.api".to_string(),
+                    old_version: "1.0.0".to_string(),
+                    new_version: "2.1.0".to_string(),
+                    entries,
+                    compare_url: None,
+                    truncated: false,
+                },
+                PackageChangelog {
+                    package_name: "plone.memoize".to_string(),
+                    old_version: "3.0".to_string(),
+                    new_version: "3.1".to_string(),
+                    entries: collector.parse_changelog(towncrier, "3.0", "3.1"),
+                    compare_url: None,
+                    truncated: false,
+                },
+                PackageChangelog {
+                    package_name: "six".to_string(),
+                    old_version: "1.15".to_string(),
+                    new_version: "1.16".to_string(),
+                    entries: Vec::new(),
+                    compare_url: None,
+                    truncated: false,
+                },
+            ],
+            &config,
+        );
+
+        let markdown = changelog.to_markdown();
+        assert!(markdown.contains(
+            "### Added\n\n- **plone.api** (1.0.0 → 2.1.0)\n  - Batch API\n\n### Fixed\n\n- **plone.api** (1.0.0 → 2.1.0)\n  - Login redirect\n- **plone.memoize** (3.0 → 3.1)\n  - Fix cache key\n\n### Security\n"
+        ));
+        assert!(markdown.contains("*No changelog entries found for six.*"));
+        assert!(!markdown.contains("#### Version"));
+
+        config.group_by = None;
+        let per_package = ConsolidatedChangelog::with_templates(
+            "1.8.0",
+            "2025-06-08",
+            changelog.package_changelogs,
+            &config,
+        );
+        assert!(per_package.to_markdown().contains("#### Version 2.1.0"));
+    }
+
+    #[test]
+    fn test_parse_zest_releaser_changelog() {
+        let content = "Changelog
+=========
+
+.. You should *NOT* be adding new change log entries to this file.
+   You should create a file in the news directory instead.
+
+.. towncrier release notes start
+
+2.2.0 (unreleased)
+------------------
+
+- Nothing changed yet.
+
+
+2.1.3 (2024-03-12)
+------------------
+
+Bug fixes:
+
+
+- Fix the search for titles with
+  non-ASCII characters.
+  [ale-rt] (#123)
+
+
+Internal:
+
+
+- Update configuration files.
+  [plone devs] (6e36bcc4)
+
+
+2.1.2 (2024-01-26)
+------------------
+
+- Add ``plone.api.content.find`` sorting. [mauritsvanrees]
+
+2.1.1 (2023-11-02)
+------------------
+
+- Old change.
+";
+        let collector = ChangelogCollector::new();
+        let entries = collector.parse_changelog(content, "2.1.1", "2.1.3");
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].version, "2.1.3");
+        assert_eq!(entries[0].date.as_deref(), Some("2024-03-12"));
+        assert_eq!(
+            entries[0].content,
+            "Bug fixes:\n\n- Fix the search for titles with\n  non-ASCII characters.\n  [ale-rt] (#123)\n\nInternal:\n\n- Update configuration files.\n  [plone devs] (6e36bcc4)"
+        );
+        assert_eq!(entries[1].version, "2.1.2");
+        assert_eq!(entries[1].date.as_deref(), Some("2024-01-26"));
+
+        let unreleased = collector.parse_changelog(content, "2.1.3", "2.2.0");
+        assert_eq!(unreleased.len(), 1);
+        assert_eq!(unreleased[0].date, None);
+        assert_eq!(unreleased[0].content, "- Nothing changed yet.");
     }
 
     #[test]
@@ -1022,6 +3133,359 @@ mod tests {
         assert!(result.contains("## Release 1.0.0"));
     }
 
+    #[test]
+    fn test_initial_release_lists_all_pins() {
+        let pins = vec![
+            PinnedPackage {
+                name: "zope.interface".to_string(),
+                version: "5.4.0".to_string(),
+            },
+            PinnedPackage {
+                name: "plone.api".to_string(),
+                version: "2.0.0".to_string(),
+            },
+        ];
+
+        let changelog = ConsolidatedChangelog::initial_release(
+            "1.0.0",
+            "2024-01-01",
+            pins,
+            &ChangelogConfig::default(),
+        );
+        let output = changelog.to_markdown();
+
+        assert!(output.contains("# Release 1.0.0"));
+        assert!(output.contains("Initial release."));
+        assert!(output.contains("- **plone.api** 2.0.0"));
+        assert!(
+            output.find("plone.api").unwrap() < output.find("zope.interface").unwrap(),
+            "pins should be sorted by name"
+        );
+        assert!(!output.contains("→"));
+    }
+
+    #[test]
+    fn test_render_added_and_removed_packages() {
+        let diff = PinDiff {
+            added: vec![PinnedPackage {
+                name: "plone.foo".to_string(),
+                version: "1.0.0".to_string(),
+            }],
+            removed: vec![PinnedPackage {
+                name: "plone.bar".to_string(),
+                version: "2.0.0".to_string(),
+            }],
+        };
+
+        let changelog = consolidated("1.1.0", "2024-02-01", Vec::new()).with_pin_changes(diff);
+
+        let markdown = changelog.to_markdown();
+        assert!(markdown.contains("- Added **plone.foo** 1.0.0"));
+        assert!(markdown.contains("- Removed **plone.bar**"));
+
+        let text = changelog.to_text();
+        assert!(text.contains("Added plone.foo 1.0.0"));
+        assert!(text.contains("Removed plone.bar"));
+    }
+
+    #[test]
+    fn test_render_compare_links() {
+        let package = PackageChangelog {
+            package_name: "plone.api".to_string(),
+            old_version: "2.0.0".to_string(),
+            new_version: "2.1.0".to_string(),
+            entries: vec![ChangelogEntry {
+                version: "2.1.0".to_string(),
+                date: None,
+                content: "- Add batch API".to_string(),
+                markup: None,
+            }],
+            compare_url: Some(
+                "https://github.com/plone/plone.api/compare/2.0.0...2.1.0".to_string(),
+            ),
+            truncated: false,
+        };
+        let changelog = consolidated("1.1.0", "2024-02-01", vec![package]);
+
+        let link = "Full diff: https://github.com/plone/plone.api/compare/2.0.0...2.1.0\n";
+        assert!(changelog
+            .to_markdown()
+            .ends_with(&format!("- Add batch API\n\n{}\n", link)));
+        assert!(changelog
+            .to_rst()
+            .ends_with(&format!("- Add batch API\n\n{}\n", link)));
+        assert!(changelog
+            .to_text()
+            .ends_with(&format!("    - Add batch API\n\n  {}\n", link)));
+    }
+
+    #[test]
+    fn test_unreleased_section_accumulates_and_promotes() {
+        let tmp = temp_dir("unreleased");
+        let path = tmp.path().join("CHANGELOG.md");
+        std::fs::write(
+            &path,
+            "# Changelog\n\n# Release 1.0.0\n\n**Date:** 2025-01-01\n\n## Package Updates\n\n### Unreleased fix in plone.app\n",
+        )
+        .unwrap();
+
+        let config = ChangelogConfig::default();
+        let section = UnreleasedSection::new(&config, ChangelogFormat::Markdown);
+        section
+            .append(
+                &path,
+                "### plone.api (2.0.0 → 2.1.0)\n\n- Add batch API\n\n",
+            )
+            .unwrap();
+        section
+            .append(&path, "### plone.rest (3.0.0 → 3.0.1)\n\n- Fix CORS\n")
+            .unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "# Changelog\n\n## Unreleased\n\n### plone.api (2.0.0 → 2.1.0)\n\n- Add batch API\n\n### plone.rest (3.0.0 → 3.0.1)\n\n- Fix CORS\n\n# Release 1.0.0\n\n**Date:** 2025-01-01\n\n## Package Updates\n\n### Unreleased fix in plone.app\n"
+        );
+
+        let heading =
+            ConsolidatedChangelog::with_templates("1.1.0", "2025-02-01", Vec::new(), &config)
+                .heading(ChangelogFormat::Markdown);
+        let entries = section.promote(&path, &heading).unwrap().unwrap();
+        assert!(entries.starts_with("### plone.api") && entries.ends_with("- Fix CORS"));
+        let promoted = std::fs::read_to_string(&path).unwrap();
+        assert!(promoted.starts_with(
+            "# Changelog\n\n# Release 1.1.0\n\n**Date:** 2025-02-01\n\n## Package Updates\n\n### plone.api"
+        ));
+        assert!(promoted.contains("- Fix CORS\n\n# Release 1.0.0\n"));
+        assert!(section.promote(&path, &heading).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_shared_changelogs_render_once() {
+        let changelog = |name: &str, content: &str| PackageChangelog {
+            package_name: name.to_string(),
+            old_version: "3.0.0".to_string(),
+            new_version: "3.0.1".to_string(),
+            entries: vec![ChangelogEntry {
+                version: "3.0.1".to_string(),
+                date: Some("2025-03-01".to_string()),
+                content: content.to_string(),
+                markup: None,
+            }],
+            compare_url: None,
+            truncated: false,
+        };
+        let consolidated = consolidated(
+            "1.2.0",
+            "2025-03-02",
+            vec![
+                changelog("plone.app.event", "- Fix recurrence (shared)"),
+                changelog("plone.app.layout", "- Fix viewlets"),
+                changelog("plone.app.dexterity", "- Fix recurrence (shared)"),
+            ],
+        );
+
+        let markdown = consolidated.to_markdown();
+        assert!(markdown.contains("### plone.app.event, plone.app.dexterity (3.0.0 → 3.0.1)\n"));
+        assert!(markdown.contains("### plone.app.layout (3.0.0 → 3.0.1)\n"));
+        assert_eq!(markdown.matches("Fix recurrence").count(), 1);
+        // The packages are still reported on their own
+        assert_eq!(consolidated.package_changelogs.len(), 3);
+    }
+
+    #[test]
+    fn test_thanks_to_section() {
+        let package = PackageChangelog {
+            package_name: "plonemeeting.portal.core".to_string(),
+            old_version: "2.2.5".to_string(),
+            new_version: "2.2.6".to_string(),
+            entries: vec![ChangelogEntry {
+                version: "2.2.6".to_string(),
+                date: None,
+                content: "- Sort publications.\n  [aduchene]\n- Fix [the docs](https://x.org)\n- Drop Python 3.8 [ale-rt, @mauritsvanrees]".to_string(),
+                markup: None,
+            }],
+            compare_url: None,
+            truncated: false,
+        };
+        let config = ChangelogConfig {
+            contributors: true,
+            ..ChangelogConfig::default()
+        };
+        let changelog =
+            ConsolidatedChangelog::with_templates("1.1.0", "2025-06-01", vec![package], &config)
+                .with_contributors(vec!["Aduchene".to_string(), "Jane Doe".to_string()]);
+
+        assert_eq!(
+            changelog.thanks_to(),
+            vec!["aduchene", "ale-rt", "Jane Doe", "mauritsvanrees"]
+        );
+        assert!(changelog
+            .to_markdown()
+            .ends_with("## Thanks to\n\naduchene, ale-rt, Jane Doe, mauritsvanrees\n\n"));
+        assert!(changelog
+            .to_rst()
+            .contains("Thanks to\n---------\n\naduchene, "));
+
+        let disabled = consolidated("1.1.0", "2025-06-01", changelog.package_changelogs);
+        assert!(!disabled.to_markdown().contains("Thanks to"));
+    }
+
+    #[test]
+    fn test_cut_download_after_last_line() {
+        let mut body = "## 2.0\n\n- New\n\n## 1.0\n\n- Old\n".as_bytes().to_vec();
+        cut_after_last_line(&mut body, 16);
+        assert_eq!(body, b"## 2.0\n\n- New\n\n");
+
+        // A single long line is cut at the limit
+        let mut body = b"abcdef".to_vec();
+        cut_after_last_line(&mut body, 4);
+        assert_eq!(body, b"abcd");
+    }
+
+    #[test]
+    fn test_package_overrides() {
+        let entry = |version: &str, date: Option<&str>| ChangelogEntry {
+            version: version.to_string(),
+            date: date.map(str::to_string),
+            content: format!("- Change in {}", version),
+            markup: None,
+        };
+        let changelogs = vec![
+            PackageChangelog {
+                package_name: "plone.api".to_string(),
+                old_version: "2.0.0".to_string(),
+                new_version: "2.3.0".to_string(),
+                entries: vec![
+                    entry("2.3.0", Some("2025-06-01")),
+                    entry("2.2.0", None),
+                    entry("2.1.0", None),
+                ],
+                compare_url: None,
+                truncated: false,
+            },
+            PackageChangelog {
+                package_name: "six".to_string(),
+                old_version: "1.15".to_string(),
+                new_version: "1.16".to_string(),
+                entries: vec![entry("1.16", None)],
+                compare_url: None,
+                truncated: false,
+            },
+        ];
+        let packages: Vec<PackageConfig> = vec![toml::from_str(
+            r#"
+            name = "plone.api"
+            package_template = '### plone.api, now {new_version}'
+            entry_template = "- **{version}** {date}"
+            max_entries = 2
+            "#,
+        )
+        .unwrap()];
+
+        let markdown = consolidated("1.1.0", "2025-06-08", changelogs)
+            .with_package_overrides(&packages)
+            .to_markdown();
+
+        assert!(markdown.contains(
+            "### plone.api, now 2.3.0\n\n- **2.3.0** 2025-06-01\n\n- Change in 2.3.0\n\n- **2.2.0** \n\n- Change in 2.2.0\n\n"
+        ));
+        assert!(!markdown.contains("2.1.0"));
+        assert!(markdown.contains("### six (1.15 → 1.16)\n\n#### Version 1.16\n\n"));
+    }
+
+    #[test]
+    fn test_attention_summary() {
+        let changelog = |name: &str, content: &str| PackageChangelog {
+            package_name: name.to_string(),
+            old_version: "1.0".to_string(),
+            new_version: "2.0".to_string(),
+            entries: vec![ChangelogEntry {
+                version: "2.0".to_string(),
+                date: None,
+                content: content.to_string(),
+                markup: None,
+            }],
+            compare_url: None,
+            truncated: false,
+        };
+        let consolidated = consolidated(
+            "1.1.0",
+            "2025-06-08",
+            vec![
+                changelog(
+                    "plone.api",
+                    "Breaking changes:\n\n- Drop Python 3.8\n\nBug fixes:\n\n- Fix XSS, see CVE-2025-1234\n- Fix typo",
+                ),
+                changelog("six", "- Deprecate six.moves.urllib"),
+                changelog("zope.interface", "- Faster lookups"),
+            ],
+        );
+
+        let attention = consolidated.attention();
+        let lines: Vec<&str> = attention.iter().map(|item| item.line.as_str()).collect();
+        assert_eq!(
+            lines,
+            vec![
+                "Drop Python 3.8",
+                "Fix XSS, see CVE-2025-1234",
+                "Deprecate six.moves.urllib"
+            ]
+        );
+
+        let markdown = consolidated.to_markdown();
+        assert!(markdown.contains(
+            "## ⚠ Attention\n\n- **plone.api** 2.0: Drop Python 3.8\n- **plone.api** 2.0: Fix XSS, see CVE-2025-1234\n- **six** 2.0: Deprecate six.moves.urllib\n"
+        ));
+        assert!(markdown.find("⚠ Attention") < markdown.find("### plone.api"));
+
+        let json = consolidated.to_json();
+        assert_eq!(json["packages"][0]["attention"], true);
+        assert_eq!(json["packages"][2]["attention"], false);
+        assert_eq!(json["attention"][2]["package"], "six");
+
+        let mut quiet = consolidated;
+        quiet.attention_keywords.clear();
+        assert!(!quiet.to_markdown().contains("Attention"));
+    }
+
+    #[test]
+    fn test_truncation_limits() {
+        let entry = |version: &str, lines: usize| ChangelogEntry {
+            version: version.to_string(),
+            date: None,
+            content: vec!["- change"; lines].join("\n"),
+            markup: None,
+        };
+        let mut changelog = PackageChangelog {
+            package_name: "Django".to_string(),
+            old_version: "4.0".to_string(),
+            new_version: "5.0".to_string(),
+            entries: vec![entry("5.0", 3), entry("4.2", 3), entry("4.1", 3)],
+            compare_url: None,
+            truncated: false,
+        };
+
+        limit_entries(&mut changelog, Some(2), Some(4));
+        assert_eq!(changelog.entries.len(), 2);
+        assert_eq!(
+            changelog.entries[1].content,
+            "- change\n\n… see the full changelog at https://pypi.org/project/Django/"
+        );
+
+        let mut short = changelog.clone();
+        short.entries.truncate(1);
+        let before = short.entries[0].content.clone();
+        limit_entries(&mut short, Some(2), Some(10));
+        assert_eq!(short.entries[0].content, before);
+
+        let notes = format!("# Release 1.0\n\n{}", "- change\n".repeat(30));
+        assert_eq!(truncate_release_notes(&notes, 1000, None), notes);
+        let url = "https://github.com/o/r/blob/1.0/CHANGES.md";
+        let cut = truncate_release_notes(&notes, 120, Some(url));
+        assert!(cut.chars().count() <= 120);
+        assert!(cut.starts_with("# Release 1.0\n\n- change\n- change\n- change\n\n…"));
+        assert!(cut.ends_with(&format!("see the full changelog at {}\n", url)));
+    }
+
     #[tokio::test]
     async fn test_parse_pypi_payload_uses_description_changelog() {
         let collector = ChangelogCollector::new();
@@ -1064,7 +3528,8 @@ Changelog
 
         let result = collector.parse_pypi_payload(&payload).await.unwrap();
 
-        let content = result.expect("expected changelog content from description");
+        let (content, markup) = result.expect("expected changelog content from description");
+        assert_eq!(markup, None);
         assert!(content.contains("Changelog"));
         assert!(content.contains("2.2.6 (2025-12-11)"));
     }
@@ -1116,6 +3581,46 @@ Changelog
         assert!(result.is_none());
     }
 
+    #[tokio::test]
+    async fn test_parse_pypi_payload_respects_description_content_type() {
+        let collector = ChangelogCollector::new();
+        let payload = json!({
+            "info": {
+                "description": "# collective.foo\n\nA Plone add-on.\n\n## Changelog\n\n### 1.1.0 - 2025-01-02\n\n- Fix login\n\n### 1.0.0 - 2024-06-01\n\n- Initial release\n",
+                "description_content_type": "text/markdown; charset=UTF-8; variant=GFM",
+                "project_urls": {},
+                "home_page": null
+            }
+        });
+
+        let (content, markup) = collector
+            .parse_pypi_payload(&payload)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(markup, Some(ChangelogFormat::Markdown));
+
+        let entries = collector.parse_changelog_as(&content, "1.0.0", "1.1.0", markup);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].version, "1.1.0");
+        assert_eq!(entries[0].date.as_deref(), Some("2025-01-02"));
+        assert_eq!(entries[0].content, "- Fix login");
+
+        assert_eq!(
+            description_markup(&json!({"description_content_type": "text/x-rst"})),
+            Some(ChangelogFormat::Rst)
+        );
+        assert_eq!(
+            description_markup(&json!({"description_content_type": null})),
+            None
+        );
+        assert_eq!(
+            markup_of_url("https://github.com/plone/plone.api/blob/main/CHANGES.rst"),
+            Some(ChangelogFormat::Rst)
+        );
+        assert_eq!(markup_of_url("https://example.org/changes"), None);
+    }
+
     #[tokio::test]
     async fn test_collect_changelogs_skips_excluded_packages() {
         let collector = ChangelogCollector::new();
@@ -1130,7 +3635,16 @@ Changelog
             buildout_name: None,
             allow_prerelease: false,
             changelog_url: None,
+            changelog_urls: Vec::new(),
+            min_release_age_days: None,
             include_in_changelog: false,
+            changelog_from: Vec::new(),
+            package_template: None,
+            entry_template: None,
+            max_entries: None,
+            renamed_from: None,
+            section: None,
+            discovered: false,
         }];
 
         let changelogs = collector
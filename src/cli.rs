@@ -1,9 +1,14 @@
-use clap::{Parser, Subcommand, ValueEnum};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 
-#[derive(Parser)]
+#[derive(Parser, Clone)]
 #[command(name = "bldr")]
 #[command(author, version, about = "A zc.buildout package releaser tool", long_about = None)]
 pub struct Cli {
+    /// Run as if started in <PATH>: the config, versions files and git
+    /// repository are found relative to it
+    #[arg(short = 'C', long = "repo", global = true, value_name = "PATH")]
+    pub repo: Option<std::path::PathBuf>,
+
     /// Path to config file
     #[arg(short, long, default_value = "bldr.toml")]
     pub config: String,
@@ -16,10 +21,44 @@ pub struct Cli {
     #[arg(short, long)]
     pub verbose: bool,
 
+    /// Retries for failed HTTP requests (overrides config)
+    #[arg(long, global = true)]
+    pub max_retries: Option<usize>,
+
+    /// Save HTTP responses and git/gh output of this run to a fixtures directory
+    #[arg(long, global = true, value_name = "DIR", conflicts_with = "replay")]
+    pub record: Option<String>,
+
+    /// Answer HTTP requests and git/gh commands from a recorded fixtures directory
+    #[arg(long, global = true, value_name = "DIR")]
+    pub replay: Option<String>,
+
+    /// How to report a fatal error on stderr
+    #[arg(long, global = true, value_enum, default_value_t = ErrorFormat::Human)]
+    pub error_format: ErrorFormat,
+
     #[command(subcommand)]
     pub command: Commands,
 }
 
+#[derive(Clone, Copy, Debug, ValueEnum, PartialEq, Eq)]
+pub enum RollbackCommit {
+    /// Add a commit undoing the release commit (pushed unless --local-only)
+    Revert,
+    /// Drop the release commit from the branch; refused once it is pushed
+    Reset,
+    /// Leave the release commit in place
+    Keep,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum, PartialEq, Eq)]
+pub enum ErrorFormat {
+    /// Message and a hint, when there is one
+    Human,
+    /// One JSON object with kind, message, hint and context
+    Json,
+}
+
 #[derive(Clone, Copy, Debug, ValueEnum, PartialEq, Eq)]
 pub enum CliChangelogFormat {
     Markdown,
@@ -37,7 +76,110 @@ impl From<CliChangelogFormat> for crate::config::ChangelogFormat {
     }
 }
 
-#[derive(Subcommand)]
+#[derive(Clone, Copy, Debug, ValueEnum, PartialEq, Eq)]
+pub enum CliConfigTemplate {
+    /// Versions file only, nothing tracked
+    Minimal,
+    /// Every feature switched on, with a publiccode.yml
+    Full,
+    /// Plone 6 site extending the Plone known-good set
+    Plone6,
+    /// Zope application extending the Zope known-good set
+    Zope,
+}
+
+impl From<CliConfigTemplate> for crate::config::ConfigTemplate {
+    fn from(t: CliConfigTemplate) -> Self {
+        match t {
+            CliConfigTemplate::Minimal => crate::config::ConfigTemplate::Minimal,
+            CliConfigTemplate::Full => crate::config::ConfigTemplate::Full,
+            CliConfigTemplate::Plone6 => crate::config::ConfigTemplate::Plone6,
+            CliConfigTemplate::Zope => crate::config::ConfigTemplate::Zope,
+        }
+    }
+}
+
+/// Projects of a workspace file to run the command for, each from its own directory
+#[derive(Args, Debug, Clone, Default)]
+pub struct ProjectArgs {
+    /// Run for these projects of the workspace file (comma-separated)
+    #[arg(long = "project", value_name = "NAME", value_delimiter = ',')]
+    pub projects: Vec<String>,
+
+    /// Run for every project of the workspace file
+    #[arg(long, conflicts_with = "projects")]
+    pub all: bool,
+
+    /// Workspace file listing the projects (default: bldr-workspace.toml)
+    #[arg(long, value_name = "PATH")]
+    pub workspace: Option<String>,
+}
+
+impl ProjectArgs {
+    pub fn is_set(&self) -> bool {
+        self.all || !self.projects.is_empty()
+    }
+}
+
+/// Changelog options shared by `release` and `update-release`
+#[derive(Args, Debug, Clone, Default)]
+pub struct ChangelogArgs {
+    /// Collect changelogs (overrides config; `release` only collects with this flag)
+    #[arg(long, visible_alias = "with-changelog")]
+    pub changelog: bool,
+
+    /// Disable changelog collection (overrides config)
+    #[arg(long, conflicts_with = "changelog")]
+    pub no_changelog: bool,
+
+    /// Changelog output format (overrides config)
+    #[arg(long, value_enum)]
+    pub changelog_format: Option<CliChangelogFormat>,
+
+    /// Save changelog to file (overrides config)
+    #[arg(long)]
+    pub changelog_file: Option<String>,
+
+    /// Also print the changelog
+    #[arg(long)]
+    pub changelog_stdout: bool,
+
+    /// Regenerate the changelog file from the existing tags before adding the release
+    #[arg(long)]
+    pub rebuild_changelog: bool,
+
+    /// Fail when too many updated packages have no changelog entries (overrides config)
+    #[arg(long)]
+    pub require_changelogs: bool,
+
+    /// Only warn about missing changelog entries in required mode
+    #[arg(long)]
+    pub allow_missing: bool,
+
+    /// Download changelogs again instead of reusing cached copies (~/.cache/bldr)
+    #[arg(long)]
+    pub refresh: bool,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum, PartialEq, Eq)]
+pub enum CheckSort {
+    /// Group by update kind: security, major, minor, patch, up to date
+    Severity,
+    /// Flat table sorted by package name
+    Name,
+    /// Flat table, longest-available updates first
+    Age,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// pip constraints file (`name==version`, for `pip install -c`)
+    Constraints,
+    /// pip requirements file (`name==version`, for `pip install -r`)
+    Requirements,
+}
+
+#[derive(Subcommand, Clone)]
 pub enum Commands {
     /// Generate shell completion scripts
     Completions {
@@ -51,6 +193,19 @@ pub enum Commands {
         /// Force overwrite existing config
         #[arg(short, long)]
         force: bool,
+
+        /// Preset to start from (default: minimal)
+        #[arg(short, long, value_enum)]
+        template: Option<CliConfigTemplate>,
+
+        /// Track every package pinned in this versions file (also used as versions_file)
+        #[arg(long, value_name = "VERSIONS_CFG")]
+        from: Option<String>,
+
+        /// Ask about the versions file, tag prefix, publiccode.yml and changelog,
+        /// starting from what is detected in the working directory
+        #[arg(short, long)]
+        interactive: bool,
     },
 
     /// Check for available updates
@@ -62,6 +217,17 @@ pub enum Commands {
         /// Output as JSON
         #[arg(long)]
         json: bool,
+
+        /// Verify the latest versions ship a wheel for the configured target Python/platform
+        #[arg(long)]
+        wheels: bool,
+
+        /// How to order the results
+        #[arg(long, value_enum, default_value = "severity")]
+        sort: CheckSort,
+
+        #[command(flatten)]
+        projects: ProjectArgs,
     },
 
     /// Update package versions in buildout file
@@ -78,17 +244,49 @@ pub enum Commands {
         #[arg(short = 'n', long)]
         dry_run: bool,
 
-        /// Create a commit after updating
+        /// Create a commit after updating (default: git.auto_commit)
         #[arg(short = 'c', long)]
         commit: bool,
 
-        /// Push the commit to the remote
-        #[arg(short = 'p', long)]
+        /// Push the commit to the remote (default: git.auto_push)
+        #[arg(long)]
         push: bool,
+
+        /// Don't commit, even with git.auto_commit
+        #[arg(long, conflicts_with_all = ["commit", "push"])]
+        no_commit: bool,
+
+        /// Don't push, even with git.auto_push
+        #[arg(long, conflicts_with = "push")]
+        no_push: bool,
+
+        /// Branch to push HEAD to (default: git.branch; needed when HEAD is detached, e.g. on CI)
+        #[arg(long)]
+        push_to: Option<String>,
+
+        /// Print a unified diff of the versions file changes (always shown when prompting)
+        #[arg(long)]
+        show_diff: bool,
+
+        /// Commit trailer as `Key: value` (repeatable; replaces git.trailers)
+        #[arg(long = "trailer", value_name = "TRAILER")]
+        trailers: Vec<String>,
+
+        /// Commit to a new branch, push it and open a pull request with the
+        /// changelog, instead of committing to the release branch
+        #[arg(long, conflicts_with_all = ["no_commit", "no_push", "push_to"])]
+        pr: bool,
+
+        #[command(flatten)]
+        projects: ProjectArgs,
     },
 
     /// Create a release (commit, tag, and optionally push)
+    #[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
     Release {
+        #[command(subcommand)]
+        action: Option<ReleaseAction>,
+
         /// Version tag for the release (or use --bump)
         #[arg(short, long, required_unless_present = "bump")]
         tag: Option<String>,
@@ -116,6 +314,32 @@ pub enum Commands {
         /// Don't update metadata files (publiccode.yml, etc.)
         #[arg(long)]
         no_metadata: bool,
+
+        /// First release: seed the changelog with every current pin (requires no existing tags)
+        #[arg(long)]
+        first: bool,
+
+        #[command(flatten)]
+        changelog: ChangelogArgs,
+
+        /// Branch to push HEAD to (needed when HEAD is detached, e.g. on CI)
+        #[arg(long, conflicts_with = "no_push")]
+        push_to: Option<String>,
+
+        /// Commit trailer as `Key: value` (repeatable; replaces git.trailers)
+        #[arg(long = "trailer", value_name = "TRAILER")]
+        trailers: Vec<String>,
+
+        /// Move the release tag when it already exists, locally or on the remote
+        #[arg(long)]
+        force_retag: bool,
+
+        /// Attach a file to the GitHub release (repeatable; adds to github.assets)
+        #[arg(long = "asset", value_name = "PATH")]
+        assets: Vec<String>,
+
+        #[command(flatten)]
+        projects: ProjectArgs,
     },
 
     /// Update packages and create a release in one step
@@ -156,25 +380,72 @@ pub enum Commands {
         #[arg(short = 'n', long)]
         dry_run: bool,
 
-        /// Collect changelogs (overrides config)
+        #[command(flatten)]
+        changelog: ChangelogArgs,
+
+        /// Don't update metadata files (publiccode.yml, etc.)
+        #[arg(long)]
+        no_metadata: bool,
+
+        /// Branch to push HEAD to (needed when HEAD is detached, e.g. on CI)
+        #[arg(long, conflicts_with = "no_push")]
+        push_to: Option<String>,
+
+        /// Commit trailer as `Key: value` (repeatable; replaces git.trailers)
+        #[arg(long = "trailer", value_name = "TRAILER")]
+        trailers: Vec<String>,
+
+        /// Move the release tag when it already exists, locally or on the remote
         #[arg(long)]
-        changelog: bool,
+        force_retag: bool,
+
+        /// Attach a file to the GitHub release (repeatable; adds to github.assets)
+        #[arg(long = "asset", value_name = "PATH")]
+        assets: Vec<String>,
+
+        /// Commit to a new branch, push it and open a pull request with the
+        /// changelog instead of tagging; tag the merge commit once merged
+        #[arg(long, conflicts_with_all = ["no_push", "no_github", "push_to", "force_retag", "assets"])]
+        pr: bool,
+
+        #[command(flatten)]
+        projects: ProjectArgs,
+    },
 
-        /// Disable changelog collection (overrides config)
-        #[arg(long, conflicts_with = "changelog")]
-        no_changelog: bool,
+    /// Undo the latest release: GitHub release, remote and local tag, release commit
+    ///
+    /// Only what git recorded is undone. Versions files rewritten without a
+    /// commit (`--no-commit`) stay as they are unless `--restore-backup` puts
+    /// back the `<file>.bak` copies kept with `backup_pins = true`.
+    Rollback {
+        /// Release tag to roll back (default: the latest version tag)
+        #[arg(short, long)]
+        tag: Option<String>,
 
-        /// Changelog output format (overrides config)
+        /// What to do with the release commit (default: reset it when it is HEAD
+        /// and was never pushed, revert it when it is HEAD and pushed or the first
+        /// commit of the repository, else keep it; a tagged commit changing other
+        /// files than bldr's is always kept)
         #[arg(long, value_enum)]
-        changelog_format: Option<CliChangelogFormat>,
+        commit: Option<RollbackCommit>,
 
-        /// Save changelog to file (overrides config)
+        /// Only delete the local tag and undo the commit locally; leave the remote
+        /// and GitHub alone
         #[arg(long)]
-        changelog_file: Option<String>,
+        local_only: bool,
 
-        /// Don't update metadata files (publiccode.yml, etc.)
+        /// Don't ask for confirmation
+        #[arg(short = 'y', long)]
+        yes: bool,
+
+        /// Also put back the versions files saved as `<file>.bak` (`backup_pins`),
+        /// once the git steps are done
         #[arg(long)]
-        no_metadata: bool,
+        restore_backup: bool,
+
+        /// Show what would be undone without changing anything
+        #[arg(short = 'n', long)]
+        dry_run: bool,
     },
 
     /// Collect changelogs for package updates
@@ -191,10 +462,14 @@ pub enum Commands {
         #[arg(short, long)]
         output: Option<String>,
 
-        /// Force output to stdout, even if configured to write to a file
-        #[arg(long, conflicts_with = "output")]
+        /// Print to stdout; alone, skips the configured file, with --output writes both
+        #[arg(long)]
         stdout: bool,
 
+        /// Print the changelog as JSON, flagging packages that need attention
+        #[arg(long, conflicts_with_all = ["format", "output", "stdout", "rebuild"])]
+        json: bool,
+
         /// Release version for the changelog header
         #[arg(long)]
         release_version: Option<String>,
@@ -202,6 +477,30 @@ pub enum Commands {
         /// Rebuild the changelog from the first tag to the latest
         #[arg(long)]
         rebuild: bool,
+
+        /// Start the rebuild at this tag (used as the baseline snapshot)
+        #[arg(long, requires = "rebuild", conflicts_with = "last")]
+        from_tag: Option<String>,
+
+        /// Stop the rebuild at this tag (inclusive)
+        #[arg(long, requires = "rebuild")]
+        to_tag: Option<String>,
+
+        /// Only rebuild the last N releases
+        #[arg(long, requires = "rebuild")]
+        last: Option<usize>,
+
+        /// Don't read or write cached tag snapshots (.bldr/cache)
+        #[arg(long, requires = "rebuild")]
+        no_cache: bool,
+
+        /// Download changelogs again instead of reusing cached copies (~/.cache/bldr)
+        #[arg(long)]
+        refresh: bool,
+
+        /// Number of tags to process in parallel during a rebuild
+        #[arg(short, long, default_value_t = 1, requires = "rebuild")]
+        jobs: usize,
     },
 
     /// Show or bump version
@@ -231,6 +530,14 @@ pub enum Commands {
         /// Custom changelog URL
         #[arg(long)]
         changelog_url: Option<String>,
+
+        /// Also pin this version in the versions file
+        #[arg(long, value_name = "VERSION", conflicts_with = "pin_latest")]
+        pin: Option<String>,
+
+        /// Also pin the newest version allowed by the constraint
+        #[arg(long)]
+        pin_latest: bool,
     },
 
     /// Remove a package from tracking
@@ -249,10 +556,270 @@ pub enum Commands {
     /// Show package info from PyPI
     Info {
         /// Package name
-        package: String,
+        #[arg(required_unless_present = "all")]
+        package: Option<String>,
 
         /// Show all available versions
         #[arg(long)]
         versions: bool,
+
+        /// Show weekly download counts and trend from pypistats.org
+        #[arg(long)]
+        stats: bool,
+
+        /// Report on every tracked package (summary, homepage, latest version, license)
+        #[arg(long, conflicts_with_all = ["package", "versions", "stats"])]
+        all: bool,
+
+        /// With --all, output as JSON
+        #[arg(long, requires = "all", conflicts_with = "package")]
+        json: bool,
+    },
+
+    /// Report licenses of tracked packages from PyPI
+    Licenses {
+        /// Only report specific packages (comma-separated)
+        #[arg(short, long)]
+        packages: Option<String>,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+
+        /// Fail when a package's license is not in the configured allowlist
+        #[arg(long)]
+        check: bool,
+    },
+
+    /// Download pinned release files of tracked packages for offline installs
+    #[command(args_conflicts_with_subcommands = true)]
+    Bundle {
+        #[command(subcommand)]
+        action: Option<BundleAction>,
+
+        /// Only bundle specific packages (comma-separated)
+        #[arg(short, long)]
+        packages: Option<String>,
+
+        /// Target directory (default: bundle-<latest tag>)
+        #[arg(short, long)]
+        output: Option<String>,
+
+        /// Also pack the directory into <output>.tar.gz
+        #[arg(long)]
+        tar: bool,
+    },
+
+    /// Write editor diagnostics (outdated, duplicate, yanked, vulnerable pins) as JSON
+    Diagnostics {
+        /// Output file (default: stdout)
+        #[arg(short, long)]
+        output: Option<String>,
+
+        /// Only run checks that need no network access (duplicates)
+        #[arg(long)]
+        offline: bool,
+    },
+
+    /// Export the pins of the versions file for other tools
+    Export {
+        /// Output format
+        #[arg(short, long, value_enum, default_value = "constraints")]
+        format: ExportFormat,
+
+        /// Add --hash=sha256 entries from PyPI for pip's hash-checking mode
+        #[arg(long)]
+        hashes: bool,
+
+        /// Only export specific packages (comma-separated)
+        #[arg(short, long)]
+        packages: Option<String>,
+
+        /// Output file (default: stdout)
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+
+    /// Check pinned versions for known vulnerabilities (OSV)
+    Audit {
+        /// Only audit specific packages (comma-separated)
+        #[arg(short, long)]
+        packages: Option<String>,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+
+        /// Advisory ids to ignore, in addition to the config (comma-separated)
+        #[arg(long, value_delimiter = ',')]
+        ignore: Vec<String>,
+    },
+
+    /// Swap pins of renamed or forked packages (`renamed_from`) to their new names
+    Migrate {
+        /// Skip confirmation prompt (required with --non-interactive)
+        #[arg(short, long)]
+        yes: bool,
+
+        /// Show what would change without modifying files
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Pin a package to a specific version (e.g. an emergency downgrade)
+    Set {
+        /// Package name (PyPI or buildout spelling)
+        package: String,
+
+        /// Version to pin
+        version: String,
+
+        /// Versions section to write (default: the package's `section`, else the effective pin)
+        #[arg(long)]
+        section: Option<String>,
+
+        /// Create a commit after writing the pin (default: git.auto_commit)
+        #[arg(short, long)]
+        commit: bool,
+
+        /// Don't commit, even with git.auto_commit
+        #[arg(long, conflicts_with = "commit")]
+        no_commit: bool,
+
+        /// Don't push the commit, even with git.auto_push
+        #[arg(long)]
+        no_push: bool,
+
+        /// Commit trailer as `Key: value` (repeatable; replaces git.trailers)
+        #[arg(long = "trailer", value_name = "TRAILER")]
+        trailers: Vec<String>,
+    },
+
+    /// Sort and normalize the [versions] sections of the versions files
+    Fmt {
+        /// Only report files that need formatting, and fail if any does
+        #[arg(long)]
+        check: bool,
+    },
+
+    /// Work with a published known-good set (KGS) of versions
+    Kgs {
+        #[command(subcommand)]
+        action: KgsAction,
+    },
+
+    /// Summarize releases, applied and pending updates, and new advisories over a time window
+    Report {
+        /// Start of the window: a duration (7d, 2w, 48h) or a date (YYYY-MM-DD)
+        #[arg(long, default_value = "7d")]
+        since: String,
+
+        /// Output file (default: stdout)
+        #[arg(short, long)]
+        output: Option<String>,
+
+        /// Skip the PyPI and OSV lookups (pending updates and advisories)
+        #[arg(long)]
+        offline: bool,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Show the pins changed between two refs (tags, branches, commits) of the versions files
+    Diff {
+        /// Ref to compare from, e.g. v1.0.0
+        from: String,
+
+        /// Ref to compare to (default: the working tree)
+        to: Option<String>,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// List past releases with their date, changed packages and tag message
+    History {
+        /// Only the most recent N releases
+        #[arg(short = 'n', long)]
+        limit: Option<usize>,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Subcommand, Clone)]
+pub enum KgsAction {
+    /// Compare our pins against a KGS and adopt its versions for selected packages
+    Sync {
+        /// KGS versions file URL or path (default: kgs_url from the config)
+        #[arg(long)]
+        url: Option<String>,
+
+        /// Adopt the KGS version for these packages (comma-separated)
+        #[arg(short, long)]
+        packages: Option<String>,
+
+        /// Adopt the KGS version for every mismatch
+        #[arg(long, conflicts_with = "packages")]
+        all: bool,
+
+        /// Only show the mismatches
+        #[arg(short = 'n', long)]
+        dry_run: bool,
+
+        /// Output the mismatches as JSON (implies --dry-run)
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Subcommand, Clone)]
+pub enum ReleaseAction {
+    /// Change the notes or title of an existing GitHub release
+    Edit {
+        /// Release tag (or its version)
+        #[arg(short, long)]
+        tag: String,
+
+        /// File with the new release notes
+        #[arg(long, conflicts_with = "notes")]
+        notes_file: Option<String>,
+
+        /// New release notes
+        #[arg(long)]
+        notes: Option<String>,
+
+        /// New release title
+        #[arg(long)]
+        title: Option<String>,
+    },
+
+    /// Publish a draft GitHub release
+    Publish {
+        /// Release tag (or its version)
+        #[arg(short, long)]
+        tag: String,
+    },
+}
+
+#[derive(Subcommand, Clone)]
+pub enum BundleAction {
+    /// Check an existing bundle against its manifest and the versions file
+    Verify {
+        /// Bundle directory (containing manifest.json)
+        dir: String,
+
+        /// Versions file to compare pins against (default: from config)
+        #[arg(long)]
+        versions_file: Option<String>,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
     },
 }
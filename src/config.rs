@@ -1,14 +1,58 @@
 use crate::error::{ReleaserError, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Config {
     /// Path to the buildout versions file (e.g., versions.cfg)
     pub versions_file: String,
 
+    /// Other versions files released with the same tag (e.g., worker.cfg);
+    /// updates are applied to all of them and shared pins must stay identical
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub linked_versions_files: Vec<String>,
+
+    /// Other buildout files with an mr.developer `[sources]` section (e.g. sources.cfg);
+    /// the versions files and the files they extend are always read
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sources_files: Vec<String>,
+
+    /// Follow `extends =` in versions files so pins inherited from parent files
+    /// are seen and updated where they are defined
+    #[serde(default)]
+    pub follow_extends: bool,
+
+    /// With `follow_extends`, also download `extends` URLs (read-only), such as
+    /// an upstream known-good set, instead of skipping them
+    #[serde(default)]
+    pub remote_extends: bool,
+
+    /// Published known-good set compared against by `bldr kgs sync`
+    /// (e.g. https://dist.plone.org/release/6.0-latest/versions.cfg)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kgs_url: Option<String>,
+
+    /// Append `# was <old>, updated <date> by bldr` to pins rewritten by
+    /// `update`; the note is refreshed on later updates, other comments are kept
+    #[serde(default)]
+    pub annotate_pins: bool,
+
+    /// Keep the previous content of each rewritten versions file as `<file>.bak`,
+    /// a safety copy put back by `rollback --restore-backup` (or by hand)
+    #[serde(default)]
+    pub backup_pins: bool,
+
+    /// Track every pin of the versions files, not only the listed `packages`
+    #[serde(default)]
+    pub track_all: bool,
+
+    /// With `track_all`, pins (buildout names) to leave untracked
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude: Vec<String>,
+
     /// List of packages to track and update
+    #[serde(default)]
     pub packages: Vec<PackageConfig>,
 
     /// Git configuration
@@ -30,6 +74,35 @@ pub struct Config {
     /// Metadata files to update (like publiccode.yml)
     #[serde(default)]
     pub metadata_files: Vec<MetadataFileConfig>,
+
+    /// PyPI client configuration
+    #[serde(default)]
+    pub pypi: PyPiConfig,
+
+    /// History (changelog rebuild) configuration
+    #[serde(default)]
+    pub history: HistoryConfig,
+
+    /// HTTP client configuration shared by PyPI and changelog fetching
+    #[serde(default)]
+    pub http: HttpConfig,
+
+    /// Vulnerability audit configuration
+    #[serde(default)]
+    pub audit: AuditConfig,
+
+    /// License reporting configuration
+    #[serde(default)]
+    pub licenses: LicensesConfig,
+
+    /// Shell commands run around release stages
+    #[serde(default)]
+    pub hooks: HooksConfig,
+
+    /// Directory the relative paths above resolve against (`-C`, or the
+    /// project's directory); the current directory when empty
+    #[serde(skip)]
+    pub work_dir: PathBuf,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -53,43 +126,193 @@ pub struct PackageConfig {
     #[serde(default)]
     pub changelog_url: Option<String>,
 
+    /// Optional: further changelog files (e.g. `HISTORY_OLD.rst`) appended
+    /// after `changelog_url` for packages that split their history
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub changelog_urls: Vec<String>,
+
+    /// Optional: per-package override of `pypi.min_release_age_days`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_release_age_days: Option<u32>,
+
     /// Whether to include this package in consolidated changelog output
     #[serde(default = "default_true")]
     pub include_in_changelog: bool,
+
+    /// Optional: changelog sources to try, in order, instead of auto-detection
+    /// (e.g. `["github_releases", "git_log"]`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub changelog_from: Vec<ChangelogSource>,
+
+    /// Optional: Markdown header of this package, overriding
+    /// `changelog.package_template`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub package_template: Option<String>,
+
+    /// Optional: Markdown heading of each entry, with `{version}` and `{date}`
+    /// (default: `#### Version {version} ({date})`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub entry_template: Option<String>,
+
+    /// Optional: keep only the newest N changelog entries of this package
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_entries: Option<usize>,
+
+    /// Optional: previous name (or forked original) this package replaces,
+    /// e.g. `Products.X` for `collective.x`
+    #[serde(default, alias = "replaces", skip_serializing_if = "Option::is_none")]
+    pub renamed_from: Option<String>,
+
+    /// Optional: versions section to read and update (e.g. `versions:python312`);
+    /// by default the pin that takes effect is used
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub section: Option<String>,
+
+    /// Added by `track_all` rather than listed in the config; never saved
+    #[serde(skip)]
+    pub discovered: bool,
 }
 
 impl PackageConfig {
     pub fn buildout_name(&self) -> &str {
         self.buildout_name.as_deref().unwrap_or(&self.name)
     }
+
+    /// Whether a pin or project name refers to this package, regardless of
+    /// case and `-`/`_`/`.` separators
+    pub fn is_named(&self, name: &str) -> bool {
+        let name = crate::pypi::normalize_project_name(name);
+        crate::pypi::normalize_project_name(&self.name) == name
+            || crate::pypi::normalize_project_name(self.buildout_name()) == name
+    }
+
+    /// All custom changelog sources, in the order they should be concatenated
+    pub fn changelog_sources(&self) -> Vec<&str> {
+        self.changelog_url
+            .iter()
+            .chain(self.changelog_urls.iter())
+            .map(String::as_str)
+            .collect()
+    }
+}
+
+/// Shell commands (run with `sh -c`) per release stage. They see `BLDR_HOOK`,
+/// `BLDR_VERSION` and `BLDR_TAG` of the pending release, `BLDR_PACKAGES`
+/// (`name==version` of updated pins) and `BLDR_UPDATES` (JSON); a failing
+/// command aborts the run
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct HooksConfig {
+    /// Before pins are updated (`update`, `set`, `update-release`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pre_update: Vec<String>,
+
+    /// After the versions files are written (e.g. `bin/buildout -N`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub post_update: Vec<String>,
+
+    /// Before bldr commits
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pre_commit: Vec<String>,
+
+    /// Before the release tag is created (e.g. the test suite)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pre_tag: Vec<String>,
+
+    /// After the release is tagged, pushed and published
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub post_release: Vec<String>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct GitConfig {
-    /// Branch to commit to (default: current branch)
+    /// Release branch: commits are refused on other branches, and HEAD is
+    /// pushed to it (default: current branch)
     #[serde(default)]
     pub branch: Option<String>,
 
-    /// Whether to automatically push after commit
+    /// Check out `branch` instead of refusing to commit from another branch
+    #[serde(default)]
+    pub switch_branch: bool,
+
+    /// Commit after `update` and `set` without `--commit` (`--no-commit` overrides)
+    #[serde(default)]
+    pub auto_commit: bool,
+
+    /// Push after `update` and `set` commit, without `--push` (`--no-push` overrides)
     #[serde(default)]
     pub auto_push: bool,
 
     /// Commit message template
     #[serde(default = "default_commit_template")]
     pub commit_template: String,
+
+    /// Commit message body template, after a blank line ({packages} and {date}
+    /// as in `commit_template`)
+    #[serde(default)]
+    pub commit_body: String,
+
+    /// Trailers closing commit messages, as `Key: value` (e.g. `Refs: PLONE-123`);
+    /// `--trailer` replaces them
+    #[serde(default)]
+    pub trailers: Vec<String>,
+
+    /// Environment variable with a token used for pushes to HTTPS remotes
+    #[serde(default)]
+    pub token_env: Option<String>,
+
+    /// Remote commits and tags are pushed to
+    #[serde(default = "default_remote")]
+    pub remote: String,
+
+    /// Before committing a release that is pushed, fetch the target branch and
+    /// refuse to go on if the checkout is behind it
+    #[serde(default = "default_true")]
+    pub check_remote: bool,
+
+    /// Rebase onto the fetched branch (`git pull --rebase`) instead of refusing
+    #[serde(default)]
+    pub auto_rebase: bool,
+
+    /// Prefix of the branches `--pr` commits to (`bldr/update-<timestamp>`,
+    /// `bldr/release-<version>`)
+    #[serde(default = "default_pr_branch_prefix")]
+    pub pr_branch_prefix: String,
 }
 
 impl Default for GitConfig {
     fn default() -> Self {
         Self {
             branch: None,
+            switch_branch: false,
+            auto_commit: false,
             auto_push: false,
             commit_template: default_commit_template(),
+            commit_body: String::new(),
+            trailers: Vec::new(),
+            token_env: None,
+            remote: default_remote(),
+            check_remote: true,
+            auto_rebase: false,
+            pr_branch_prefix: default_pr_branch_prefix(),
         }
     }
 }
 
 impl GitConfig {
+    /// Whether to commit and push, from the command flags and `auto_commit`/`auto_push`.
+    /// Pushing implies committing; `auto_push` only applies to runs that commit
+    pub fn commit_and_push(
+        &self,
+        commit: bool,
+        push: bool,
+        no_commit: bool,
+        no_push: bool,
+    ) -> (bool, bool) {
+        let commit = !no_commit && (commit || push || self.auto_commit);
+        let push = commit && !no_push && (push || self.auto_push);
+        (commit, push)
+    }
+
     pub fn effective_commit_template(&self) -> &str {
         if self.commit_template.trim().is_empty() {
             "Use {packages}"
@@ -97,6 +320,58 @@ impl GitConfig {
             &self.commit_template
         }
     }
+
+    /// Replace the configured trailers by `overrides` (`--trailer`), if any, and
+    /// normalize them to `Key: value`; `Key=value` is accepted as with `git commit --trailer`
+    pub fn resolve_trailers(&mut self, overrides: &[String]) -> Result<()> {
+        if !overrides.is_empty() {
+            self.trailers = overrides.to_vec();
+        }
+
+        for trailer in self.trailers.iter_mut() {
+            let parsed = trailer
+                .split_once([':', '='])
+                .map(|(k, v)| (k.trim(), v.trim()))
+                .filter(|(k, v)| {
+                    !k.is_empty()
+                        && !v.is_empty()
+                        && k.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+                });
+            let Some((k, v)) = parsed else {
+                return Err(ReleaserError::ConfigError(format!(
+                    "Invalid commit trailer '{}'; expected 'Key: value'",
+                    trailer
+                )));
+            };
+            *trailer = format!("{}: {}", k, v);
+        }
+        Ok(())
+    }
+
+    /// `message` closed by the trailers, separated by a blank line
+    pub fn append_trailers(&self, message: &str) -> String {
+        if self.trailers.is_empty() {
+            return message.to_string();
+        }
+        format!("{}\n\n{}", message.trim_end(), self.trailers.join("\n"))
+    }
+}
+
+/// `[A-Za-z_][A-Za-z0-9_]*`, the names a POSIX shell expands
+fn is_env_var_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn default_remote() -> String {
+    "origin".to_string()
+}
+
+fn default_pr_branch_prefix() -> String {
+    "bldr/".to_string()
 }
 
 fn default_commit_template() -> String {
@@ -116,6 +391,64 @@ pub struct GitHubConfig {
     /// Tag prefix (e.g., "v" for v1.0.0)
     #[serde(default)]
     pub tag_prefix: String,
+
+    /// Tag schemes used before the current one, mapped into versions
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub legacy_tags: Vec<LegacyTagScheme>,
+
+    /// Create lightweight tags instead of annotated ones (no message, no metadata)
+    #[serde(default)]
+    pub lightweight_tags: bool,
+
+    /// Append a `Bldr-Metadata:` JSON trailer with the pin changes to annotated tag messages
+    #[serde(default)]
+    pub tag_metadata: bool,
+
+    /// Message of annotated tags (default: the release notes); placeholders:
+    /// {changelog} (the release notes), {version}, {date}
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag_message_template: Option<String>,
+
+    /// Title of GitHub releases; placeholders: {version}, {tag}, {date}, {project}, {updates}
+    #[serde(default = "default_release_title_template")]
+    pub release_title_template: String,
+
+    /// strftime-style format for `{date}` in release titles (default: %Y-%m-%d)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub release_date_format: Option<String>,
+
+    /// `{project}` in release titles (default: the repository name)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project_name: Option<String>,
+
+    /// Environment variable with a token for the GitHub API (default:
+    /// GITHUB_TOKEN, then GH_TOKEN); without one, releases go through `gh`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_env: Option<String>,
+
+    /// REST API root, for GitHub Enterprise (e.g. https://github.example.com/api/v3)
+    #[serde(default = "default_github_api_url")]
+    pub api_url: String,
+
+    /// Files attached to GitHub releases (e.g. "CHANGES.md", "versions.cfg")
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub assets: Vec<String>,
+
+    /// Also attach the pins of the versions file as `constraints.txt`
+    #[serde(default)]
+    pub asset_constraints: bool,
+
+    /// Also attach `SHA256SUMS` with the checksum of every other asset
+    #[serde(default)]
+    pub asset_checksums: bool,
+}
+
+fn default_release_title_template() -> String {
+    "Release {version}".to_string()
+}
+
+fn default_github_api_url() -> String {
+    "https://api.github.com".to_string()
 }
 
 impl Default for GitHubConfig {
@@ -124,10 +457,51 @@ impl Default for GitHubConfig {
             repository: None,
             create_release: true,
             tag_prefix: String::new(),
+            legacy_tags: Vec::new(),
+            lightweight_tags: false,
+            tag_metadata: false,
+            tag_message_template: None,
+            release_title_template: default_release_title_template(),
+            release_date_format: None,
+            project_name: None,
+            token_env: None,
+            api_url: default_github_api_url(),
+            assets: Vec::new(),
+            asset_constraints: false,
+            asset_checksums: false,
+        }
+    }
+}
+
+impl GitHubConfig {
+    /// `project_name`, else the name part of `repository`, else the name of
+    /// `work_dir` (relative to the current directory)
+    pub fn project_name(&self, work_dir: &Path) -> String {
+        if let Some(name) = &self.project_name {
+            return name.clone();
         }
+        if let Some((_, name)) = self.repository.as_deref().and_then(|r| r.rsplit_once('/')) {
+            return name.to_string();
+        }
+        std::env::current_dir()
+            .ok()
+            .and_then(|dir| dir.join(work_dir).canonicalize().ok())
+            .and_then(|dir| Some(dir.file_name()?.to_string_lossy().into_owned()))
+            .unwrap_or_default()
     }
 }
 
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct LegacyTagScheme {
+    /// Regex matching the whole tag (e.g. `prod-(\d{4})-(\d{2})-(\d+)`)
+    pub pattern: String,
+
+    /// Version built from the captures (e.g. `$1.$2.$3`); defaults to the
+    /// captured groups joined with dots
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+}
+
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct ChangelogConfig {
     /// Whether to collect changelogs by default
@@ -150,6 +524,11 @@ pub struct ChangelogConfig {
     #[serde(default = "default_true")]
     pub use_as_release_notes: bool,
 
+    /// Add the entries collected by `update` under an "Unreleased" heading of
+    /// `output_file`; `release` turns that section into the release's entry
+    #[serde(default)]
+    pub unreleased_section: bool,
+
     /// Custom header template
     #[serde(default = "default_changelog_header")]
     pub header_template: String,
@@ -162,9 +541,94 @@ pub struct ChangelogConfig {
     #[serde(default = "default_changelog_files")]
     pub changelog_files: Vec<String>,
 
-    /// Additional GitHub branches to try
+    /// Additional GitHub and GitLab branches to try
     #[serde(default)]
     pub github_branches: Vec<String>,
+
+    /// Self-hosted GitLab hosts without "gitlab" in their name (e.g. "git.example.org")
+    #[serde(default)]
+    pub gitlab_hosts: Vec<String>,
+
+    /// Fail update-release when too many updated packages have no entries
+    #[serde(default)]
+    pub required: bool,
+
+    /// Fraction (0.0-1.0) of updated packages allowed to lack entries in required mode
+    #[serde(default)]
+    pub max_missing_fraction: f64,
+
+    /// strftime-style format for `{date}` in changelog output (default: %Y-%m-%d)
+    #[serde(default)]
+    pub date_format: Option<String>,
+
+    /// Locale for month and weekday names in `date_format` (e.g. "nl_NL")
+    #[serde(default)]
+    pub date_locale: Option<String>,
+
+    /// Regexes for entry lines to drop before rendering (e.g. "^- Bump pre-commit")
+    #[serde(default)]
+    pub exclude_patterns: Vec<String>,
+
+    /// Without changelog entries, list the upstream commits between the two
+    /// release tags (GitHub or GitLab compare API)
+    #[serde(default)]
+    pub commit_fallback: bool,
+
+    /// Link each package to the diff between its two release tags on GitHub
+    /// or GitLab, when both tags exist upstream
+    #[serde(default = "default_true")]
+    pub compare_links: bool,
+
+    /// "package" (default): one block per package; "category": entries of all
+    /// packages merged under Keep a Changelog headings (Added, Fixed, ...)
+    #[serde(default)]
+    pub group_by: Option<String>,
+
+    /// Keep only the newest N entries of each package
+    #[serde(default)]
+    pub max_entries_per_package: Option<usize>,
+
+    /// Cut the entries of each package after N lines
+    #[serde(default)]
+    pub max_lines_per_package: Option<usize>,
+
+    /// Characters of GitHub release notes before they are cut (GitHub refuses
+    /// bodies over 125000)
+    #[serde(default = "default_max_release_notes_chars")]
+    pub max_release_notes_chars: usize,
+
+    /// Bytes of a changelog file downloaded before the rest is dropped
+    #[serde(default = "default_max_download_bytes")]
+    pub max_download_bytes: usize,
+
+    /// Case-insensitive words that put an entry line in the "⚠ Attention"
+    /// summary; empty disables it
+    #[serde(default = "default_attention_keywords")]
+    pub attention_keywords: Vec<String>,
+
+    /// Rewrite Markdown entries as reStructuredText (and the other way round)
+    /// to match `format`
+    #[serde(default = "default_true")]
+    pub convert_markup: bool,
+
+    /// Credit upstream authors (zest.releaser `[username]` suffixes) and the
+    /// authors of our commits since the last release in a "Thanks to" section
+    #[serde(default)]
+    pub contributors: bool,
+}
+
+fn default_attention_keywords() -> Vec<String> {
+    ["breaking", "deprecat", "security", "CVE"]
+        .map(String::from)
+        .to_vec()
+}
+
+fn default_max_release_notes_chars() -> usize {
+    125_000
+}
+
+fn default_max_download_bytes() -> usize {
+    5 * 1024 * 1024
 }
 
 fn default_changelog_format() -> String {
@@ -210,15 +674,48 @@ impl Default for ChangelogConfig {
             output_file: Some("CHANGELOG.md".to_string()), // Now has a default
             include_in_commit: true,
             use_as_release_notes: true,
+            unreleased_section: false,
             header_template: default_changelog_header(),
             package_template: default_package_template(),
             changelog_files: default_changelog_files(),
             github_branches: Vec::new(),
+            gitlab_hosts: Vec::new(),
+            required: false,
+            max_missing_fraction: 0.0,
+            date_format: None,
+            date_locale: None,
+            exclude_patterns: Vec::new(),
+            commit_fallback: false,
+            compare_links: true,
+            group_by: None,
+            max_entries_per_package: None,
+            max_lines_per_package: None,
+            max_release_notes_chars: default_max_release_notes_chars(),
+            max_download_bytes: default_max_download_bytes(),
+            attention_keywords: default_attention_keywords(),
+            convert_markup: true,
+            contributors: false,
         }
     }
 }
 
 impl ChangelogConfig {
+    /// Format an ISO release date for changelog output
+    pub fn format_date(&self, iso_date: &str) -> String {
+        format_date(
+            iso_date,
+            self.date_format.as_deref(),
+            self.date_locale.as_deref(),
+        )
+    }
+
+    /// Whether entries are merged by Keep a Changelog category across packages
+    pub fn groups_by_category(&self) -> bool {
+        self.group_by
+            .as_deref()
+            .is_some_and(|mode| mode.eq_ignore_ascii_case("category"))
+    }
+
     pub fn format_enum(&self) -> ChangelogFormat {
         match self.format.to_lowercase().as_str() {
             "rst" | "restructuredtext" => ChangelogFormat::Rst,
@@ -235,6 +732,240 @@ pub enum ChangelogFormat {
     Text,
 }
 
+/// Where a package's changelog can be read from (`packages.changelog_from`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangelogSource {
+    /// `changelog_url` and `changelog_urls`
+    CustomUrl,
+    /// Release notes of the GitHub (or GitLab) repository
+    GithubReleases,
+    /// The long description published on PyPI
+    PypiDescription,
+    /// Commit subjects between the two release tags
+    GitLog,
+}
+
+// ============================================================================
+// PyPI Configuration
+// ============================================================================
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct PyPiConfig {
+    /// Maximum number of concurrent PyPI requests (default: based on CPU count)
+    #[serde(default)]
+    pub concurrency: Option<usize>,
+
+    /// Base URL of the JSON API (`<index_url>/<name>/json`)
+    #[serde(default = "default_pypi_index_url")]
+    pub index_url: String,
+
+    /// Base URL of the PEP 691 Simple API, used when the JSON API has no entry
+    #[serde(default = "default_pypi_simple_url")]
+    pub simple_url: String,
+
+    /// Ignore releases published less than this many days ago
+    #[serde(default)]
+    pub min_release_age_days: u32,
+
+    /// Base URL of the pypistats.org API, used by `info --stats`
+    #[serde(default = "default_pypi_stats_url")]
+    pub stats_url: String,
+
+    /// Python version deployments run on (e.g. "3.11"), used by `check --wheels`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_python: Option<String>,
+
+    /// Platform tags deployments accept (e.g. "manylinux_2_28_x86_64"); pure wheels always match
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub target_platforms: Vec<String>,
+}
+
+fn default_pypi_index_url() -> String {
+    "https://pypi.org/pypi".to_string()
+}
+
+fn default_pypi_simple_url() -> String {
+    "https://pypi.org/simple".to_string()
+}
+
+fn default_pypi_stats_url() -> String {
+    "https://pypistats.org/api".to_string()
+}
+
+impl Default for PyPiConfig {
+    fn default() -> Self {
+        Self {
+            concurrency: None,
+            index_url: default_pypi_index_url(),
+            simple_url: default_pypi_simple_url(),
+            min_release_age_days: 0,
+            stats_url: default_pypi_stats_url(),
+            target_python: None,
+            target_platforms: Vec::new(),
+        }
+    }
+}
+
+impl PyPiConfig {
+    pub fn effective_concurrency(&self) -> usize {
+        self.concurrency
+            .filter(|n| *n > 0)
+            .unwrap_or_else(crate::pypi::default_concurrency)
+    }
+}
+
+// ============================================================================
+// HTTP Configuration
+// ============================================================================
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct HttpConfig {
+    /// How many times a failed request is retried (network errors, 5xx, 429)
+    #[serde(default = "default_max_retries")]
+    pub max_retries: usize,
+
+    /// Delay before the first retry in milliseconds, doubled on each retry
+    #[serde(default = "default_retry_backoff_ms")]
+    pub retry_backoff_ms: u64,
+
+    /// Proxy for all requests, e.g. http://proxy.corp:3128 (env: BLDR_PROXY).
+    /// When unset, the standard HTTP_PROXY/HTTPS_PROXY variables apply.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<String>,
+
+    /// Comma-separated hosts that bypass `proxy` (env: BLDR_NO_PROXY)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub no_proxy: Option<String>,
+
+    /// PEM bundle of extra root certificates to trust (env: BLDR_CA_BUNDLE)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ca_bundle: Option<String>,
+}
+
+fn default_max_retries() -> usize {
+    2
+}
+
+fn default_retry_backoff_ms() -> u64 {
+    300
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: default_max_retries(),
+            retry_backoff_ms: default_retry_backoff_ms(),
+            proxy: None,
+            no_proxy: None,
+            ca_bundle: None,
+        }
+    }
+}
+
+// ============================================================================
+// Audit Configuration
+// ============================================================================
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct AuditConfig {
+    /// Base URL of the OSV API
+    #[serde(default = "default_osv_url")]
+    pub osv_url: String,
+
+    /// Advisory ids (or aliases such as CVE ids) to leave out of audit reports
+    #[serde(default)]
+    pub ignore: Vec<String>,
+}
+
+fn default_osv_url() -> String {
+    "https://api.osv.dev/v1".to_string()
+}
+
+impl Default for AuditConfig {
+    fn default() -> Self {
+        Self {
+            osv_url: default_osv_url(),
+            ignore: Vec::new(),
+        }
+    }
+}
+
+// ============================================================================
+// Licenses Configuration
+// ============================================================================
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct LicensesConfig {
+    /// License names or SPDX ids permitted by `bldr licenses --check`
+    #[serde(default)]
+    pub allowed: Vec<String>,
+
+    /// Packages exempt from the allowlist (e.g. after a manual review)
+    #[serde(default)]
+    pub exceptions: Vec<String>,
+}
+
+// ============================================================================
+// History Configuration
+// ============================================================================
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct HistoryConfig {
+    /// Versions file locations for older tag ranges
+    #[serde(default)]
+    pub paths: Vec<HistoryPathConfig>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct HistoryPathConfig {
+    /// Versions file path used by tags in this range
+    pub path: String,
+
+    /// First tag (inclusive) using this path
+    #[serde(default)]
+    pub from_tag: Option<String>,
+
+    /// Last tag (inclusive) using this path
+    #[serde(default)]
+    pub to_tag: Option<String>,
+}
+
+impl HistoryPathConfig {
+    fn contains(&self, version: &crate::version::Version, tag_prefix: &str) -> bool {
+        let bound = |tag: &str| {
+            crate::version::Version::parse(tag.strip_prefix(tag_prefix).unwrap_or(tag)).ok()
+        };
+
+        let after_start = match self.from_tag.as_deref() {
+            Some(tag) => bound(tag).is_some_and(|from| *version >= from),
+            None => true,
+        };
+        let before_end = match self.to_tag.as_deref() {
+            Some(tag) => bound(tag).is_some_and(|to| *version <= to),
+            None => true,
+        };
+
+        after_start && before_end
+    }
+}
+
+impl HistoryConfig {
+    /// Versions file path for a tagged version, falling back to `default`
+    pub fn versions_file_for<'a>(
+        &'a self,
+        version: &crate::version::Version,
+        tag_prefix: &str,
+        default: &'a str,
+    ) -> &'a str {
+        self.paths
+            .iter()
+            .find(|entry| entry.contains(version, tag_prefix))
+            .map(|entry| entry.path.as_str())
+            .unwrap_or(default)
+    }
+}
+
 // ============================================================================
 // Version Configuration
 // ============================================================================
@@ -246,10 +977,6 @@ pub struct VersionConfig {
     pub levels: HashMap<String, VersionBumpType>,
 }
 
-fn default_version_pattern() -> String {
-    r#"(?m)^version\s*=\s*["']?(\d+\.\d+\.\d+)["']?"#.to_string()
-}
-
 fn default_version_levels() -> HashMap<String, VersionBumpType> {
     let mut levels = HashMap::new();
     levels.insert("major".to_string(), VersionBumpType::Major);
@@ -259,6 +986,9 @@ fn default_version_levels() -> HashMap<String, VersionBumpType> {
     levels.insert("hotfix".to_string(), VersionBumpType::Patch);
     levels.insert("feature".to_string(), VersionBumpType::Minor);
     levels.insert("breaking".to_string(), VersionBumpType::Major);
+    levels.insert("rc".to_string(), VersionBumpType::Prerelease);
+    levels.insert("rebuild".to_string(), VersionBumpType::Build);
+    levels.insert("retag".to_string(), VersionBumpType::None);
     levels
 }
 
@@ -276,6 +1006,13 @@ pub enum VersionBumpType {
     Major,
     Minor,
     Patch,
+    /// Next pre-release of the same version (`1.3.0-rc.1` → `1.3.0-rc.2`), or
+    /// the first of the next patch (`1.2.0` → `1.2.1-rc.1`)
+    Prerelease,
+    /// Same version with a build stamp of the current time (`1.2.0+202506031530`)
+    Build,
+    /// Same version, to re-tag a release whose changes are metadata only
+    None,
 }
 
 // ============================================================================
@@ -299,6 +1036,10 @@ pub struct MetadataFileConfig {
     #[serde(default = "default_date_fields")]
     pub date_fields: Vec<String>,
 
+    /// strftime-style format for date fields (default: ISO %Y-%m-%d)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub date_format: Option<String>,
+
     /// Whether to include this file in the commit
     #[serde(default = "default_true")]
     pub include_in_commit: bool,
@@ -316,17 +1057,172 @@ fn default_date_fields() -> Vec<String> {
     vec!["releaseDate".to_string()]
 }
 
+impl MetadataFileConfig {
+    /// `softwareVersion` and `releaseDate` of a publiccode.yml
+    pub fn publiccode() -> Self {
+        Self {
+            path: "publiccode.yml".to_string(),
+            format: "yaml".to_string(),
+            version_fields: vec!["softwareVersion".to_string()],
+            date_fields: vec!["releaseDate".to_string()],
+            date_format: None,
+            include_in_commit: true,
+        }
+    }
+
+    /// Format an ISO release date for this file's date fields
+    pub fn format_date(&self, iso_date: &str) -> String {
+        format_date(iso_date, self.date_format.as_deref(), None)
+    }
+}
+
+/// Format a `YYYY-MM-DD` date with an optional strftime format and locale.
+/// Dates that don't parse are returned unchanged; unknown locales fall back to English.
+pub fn format_date(iso_date: &str, format: Option<&str>, locale: Option<&str>) -> String {
+    let (Some(format), Ok(date)) = (
+        format,
+        chrono::NaiveDate::parse_from_str(iso_date, "%Y-%m-%d"),
+    ) else {
+        return iso_date.to_string();
+    };
+
+    let locale = locale
+        .and_then(|l| chrono::Locale::try_from(l.replace('-', "_").as_str()).ok())
+        .unwrap_or(chrono::Locale::POSIX);
+
+    date.format_localized(format, locale).to_string()
+}
+
 impl Config {
-    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
-        let content = std::fs::read_to_string(path.as_ref())
-            .map_err(|e| ReleaserError::ConfigError(format!("Failed to read config: {}", e)))?;
+    /// Load the config at `path` relative to `work_dir`, whose relative paths
+    /// then resolve against `work_dir` too
+    pub fn load_in<P: AsRef<Path>>(work_dir: &Path, path: P) -> Result<Self> {
+        let path = work_dir.join(path);
+        let content =
+            std::fs::read_to_string(&path).map_err(|source| ReleaserError::FileError {
+                path: path.display().to_string(),
+                source,
+            })?;
+
+        let mut config: Self = toml::from_str(&content)
+            .map_err(|e| ReleaserError::ConfigError(format!("Failed to parse config: {}", e)))?;
+        config.work_dir = work_dir.to_path_buf();
+        config.validate()?;
+        Ok(config)
+    }
 
-        toml::from_str(&content)
-            .map_err(|e| ReleaserError::ConfigError(format!("Failed to parse config: {}", e)))
+    /// Reject settings that would be unsafe to use as given
+    pub fn validate(&self) -> Result<()> {
+        // git.token_env ends up in the shell snippet of a credential helper
+        for (key, name) in [
+            ("git.token_env", &self.git.token_env),
+            ("github.token_env", &self.github.token_env),
+        ] {
+            if let Some(name) = name.as_deref().filter(|name| !is_env_var_name(name)) {
+                return Err(ReleaserError::ConfigError(format!(
+                    "Invalid {} '{}'; expected an environment variable name ([A-Za-z_][A-Za-z0-9_]*)",
+                    key, name
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Where a path of the config (or given next to it) is on disk
+    pub fn resolve<P: AsRef<Path>>(&self, path: P) -> PathBuf {
+        self.work_dir.join(path)
+    }
+
+    /// Title of the GitHub release of `version`, from `github.release_title_template`.
+    /// Month names in `{date}` follow `changelog.date_locale`.
+    pub fn release_title(&self, version: &str, iso_date: &str, updates: usize) -> String {
+        let date = format_date(
+            iso_date,
+            self.github.release_date_format.as_deref(),
+            self.changelog.date_locale.as_deref(),
+        );
+        self.github
+            .release_title_template
+            .replace("{version}", version)
+            .replace("{tag}", &format!("{}{}", self.github.tag_prefix, version))
+            .replace("{date}", &date)
+            .replace("{project}", &self.github.project_name(&self.work_dir))
+            .replace("{updates}", &updates.to_string())
+    }
+
+    /// Annotated tag message of `version`, from `github.tag_message_template`
+    /// (`notes` as is without one). `{date}` is formatted like in release titles.
+    pub fn tag_message(&self, version: &str, iso_date: &str, notes: &str) -> String {
+        let Some(template) = self.github.tag_message_template.as_deref() else {
+            return notes.to_string();
+        };
+        let date = format_date(
+            iso_date,
+            self.github.release_date_format.as_deref(),
+            self.changelog.date_locale.as_deref(),
+        );
+        template
+            .replace("{version}", version)
+            .replace("{date}", &date)
+            .replace("{changelog}", notes.trim_end())
+    }
+
+    /// The main versions file followed by every linked one
+    pub fn versions_files(&self) -> Vec<&str> {
+        std::iter::once(self.versions_file.as_str())
+            .chain(self.linked_versions_files.iter().map(String::as_str))
+            .collect()
+    }
+
+    /// With `track_all`, track every pinned package that isn't listed or excluded
+    pub fn track_pins<'a, I>(&mut self, pinned: I)
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        if !self.track_all {
+            return;
+        }
+
+        let normalize = crate::pypi::normalize_project_name;
+        let mut known: std::collections::HashSet<String> = self
+            .packages
+            .iter()
+            .flat_map(|p| [normalize(&p.name), normalize(p.buildout_name())])
+            .chain(self.exclude.iter().map(|name| normalize(name)))
+            .collect();
+
+        let mut discovered: Vec<&str> = pinned
+            .into_iter()
+            .filter(|name| known.insert(normalize(name)))
+            .collect();
+        discovered.sort_unstable();
+
+        self.packages
+            .extend(discovered.into_iter().map(|name| PackageConfig {
+                name: name.to_string(),
+                version_constraint: None,
+                buildout_name: None,
+                allow_prerelease: false,
+                changelog_url: None,
+                changelog_urls: Vec::new(),
+                min_release_age_days: None,
+                include_in_changelog: true,
+                changelog_from: Vec::new(),
+                package_template: None,
+                entry_template: None,
+                max_entries: None,
+                renamed_from: None,
+                section: None,
+                discovered: true,
+            }));
     }
 
     pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
-        let content = toml::to_string_pretty(self).map_err(|e| {
+        // Discovered packages come from the versions files, not from the config
+        let mut listed = self.clone();
+        listed.packages.retain(|p| !p.discovered);
+
+        let content = toml::to_string_pretty(&listed).map_err(|e| {
             ReleaserError::ConfigError(format!("Failed to serialize config: {}", e))
         })?;
 
@@ -334,40 +1230,229 @@ impl Config {
         Ok(())
     }
 
-    pub fn create_default<P: AsRef<Path>>(path: P) -> Result<Self> {
-        let config = Config {
+    /// Starting configuration for `bldr init`
+    pub fn from_template(template: ConfigTemplate) -> Self {
+        let mut config = Config {
             versions_file: "versions.cfg".to_string(),
-            packages: vec![PackageConfig {
-                name: "example-package".to_string(),
-                version_constraint: None,
-                buildout_name: None,
-                allow_prerelease: false,
-                changelog_url: None,
-                include_in_changelog: true,
-            }],
+            linked_versions_files: Vec::new(),
+            sources_files: Vec::new(),
+            follow_extends: false,
+            remote_extends: false,
+            kgs_url: template.kgs_url().map(str::to_string),
+            annotate_pins: false,
+            backup_pins: false,
+            track_all: false,
+            exclude: Vec::new(),
+            packages: Vec::new(),
             git: GitConfig::default(),
             github: GitHubConfig::default(),
             changelog: ChangelogConfig::default(),
             version: VersionConfig::default(),
-            metadata_files: vec![MetadataFileConfig {
-                path: "publiccode.yml".to_string(),
-                format: "yaml".to_string(),
-                version_fields: vec!["softwareVersion".to_string()],
-                date_fields: vec!["releaseDate".to_string()],
-                include_in_commit: true,
-            }],
+            metadata_files: Vec::new(),
+            pypi: PyPiConfig::default(),
+            history: HistoryConfig::default(),
+            http: HttpConfig::default(),
+            audit: AuditConfig::default(),
+            licenses: LicensesConfig::default(),
+            hooks: HooksConfig::default(),
+            work_dir: PathBuf::new(),
         };
 
-        config.save(path)?;
-        Ok(config)
+        match template {
+            ConfigTemplate::Minimal => {}
+            ConfigTemplate::Full => {
+                config.follow_extends = true;
+                config.annotate_pins = true;
+                config.changelog.enabled = true;
+                config.metadata_files.push(MetadataFileConfig::publiccode());
+            }
+            ConfigTemplate::Plone6 | ConfigTemplate::Zope => {
+                // Most pins come from the known-good set the versions file extends
+                config.follow_extends = true;
+                config.remote_extends = true;
+                config.changelog.enabled = true;
+                config.metadata_files.push(MetadataFileConfig::publiccode());
+            }
+        }
+
+        config.seed_packages(
+            template
+                .packages()
+                .iter()
+                .map(|(name, constraint)| (*name, *constraint)),
+        );
+        config
+    }
+
+    /// Track packages that aren't listed yet, in the given order
+    pub fn seed_packages<'a, I>(&mut self, packages: I) -> usize
+    where
+        I: IntoIterator<Item = (&'a str, Option<&'a str>)>,
+    {
+        let normalize = crate::pypi::normalize_project_name;
+        let mut known: std::collections::HashSet<String> = self
+            .packages
+            .iter()
+            .flat_map(|p| [normalize(&p.name), normalize(p.buildout_name())])
+            .collect();
+
+        let before = self.packages.len();
+        for (name, constraint) in packages {
+            if !known.insert(normalize(name)) {
+                continue;
+            }
+            self.packages.push(PackageConfig {
+                name: name.to_string(),
+                version_constraint: constraint.map(str::to_string),
+                buildout_name: None,
+                allow_prerelease: false,
+                changelog_url: None,
+                changelog_urls: Vec::new(),
+                min_release_age_days: None,
+                include_in_changelog: true,
+                changelog_from: Vec::new(),
+                package_template: None,
+                entry_template: None,
+                max_entries: None,
+                renamed_from: None,
+                section: None,
+                discovered: false,
+            });
+        }
+        self.packages.len() - before
+    }
+}
+
+/// Presets for `bldr init`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigTemplate {
+    /// Versions file only, nothing tracked
+    Minimal,
+    /// Every feature switched on, with a publiccode.yml
+    Full,
+    /// Plone 6 site extending the Plone known-good set
+    Plone6,
+    /// Plain Zope application extending the Zope known-good set
+    Zope,
+}
+
+impl ConfigTemplate {
+    /// Known-good set the versions file is expected to extend
+    pub fn kgs_url(self) -> Option<&'static str> {
+        match self {
+            ConfigTemplate::Plone6 => {
+                Some("https://dist.plone.org/release/6.0-latest/versions.cfg")
+            }
+            ConfigTemplate::Zope => {
+                Some("https://zopefoundation.github.io/Zope/releases/5.x/versions-prod.cfg")
+            }
+            ConfigTemplate::Minimal | ConfigTemplate::Full => None,
+        }
+    }
+
+    /// Packages tracked by default, with the constraint keeping them on the major line
+    fn packages(self) -> &'static [(&'static str, Option<&'static str>)] {
+        match self {
+            ConfigTemplate::Minimal | ConfigTemplate::Full => &[],
+            ConfigTemplate::Plone6 => &[
+                ("Products.CMFPlone", Some(">=6.0,<7")),
+                ("plone.restapi", None),
+                ("plone.volto", None),
+                ("plone.api", None),
+                ("plone.app.upgrade", None),
+            ],
+            ConfigTemplate::Zope => &[
+                ("Zope", Some(">=5.0,<6")),
+                ("ZODB", None),
+                ("waitress", None),
+            ],
+        }
+    }
+}
+
+/// Several buildouts of one repository, each with its own config and tags
+/// (`--project`, `--all`)
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct WorkspaceConfig {
+    #[serde(default)]
+    pub projects: Vec<ProjectConfig>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ProjectConfig {
+    pub name: String,
+
+    /// Directory of the project, relative to the workspace file; commands run
+    /// from it, so its config and versions files are relative to it
+    pub path: String,
+
+    /// Config file of the project
+    #[serde(default = "default_project_config")]
+    pub config: String,
+
+    /// Prefix of the project's release tags, overriding its `github.tag_prefix`
+    /// (default: that one, else `<name>-`, so projects never share tags)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag_prefix: Option<String>,
+}
+
+fn default_project_config() -> String {
+    "bldr.toml".to_string()
+}
+
+impl WorkspaceConfig {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let content =
+            std::fs::read_to_string(path.as_ref()).map_err(|source| ReleaserError::FileError {
+                path: path.as_ref().display().to_string(),
+                source,
+            })?;
+
+        toml::from_str(&content)
+            .map_err(|e| ReleaserError::ConfigError(format!("Failed to parse workspace: {}", e)))
+    }
+
+    /// Projects named in `names` (in workspace order), or all of them
+    pub fn select(&self, names: &[String], all: bool) -> Result<Vec<&ProjectConfig>> {
+        if let Some(unknown) = names
+            .iter()
+            .find(|name| !self.projects.iter().any(|p| &p.name == *name))
+        {
+            return Err(ReleaserError::ConfigError(format!(
+                "No project '{}' in the workspace (known: {})",
+                unknown,
+                self.projects
+                    .iter()
+                    .map(|p| p.name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )));
+        }
+
+        Ok(self
+            .projects
+            .iter()
+            .filter(|p| all || names.contains(&p.name))
+            .collect())
+    }
+}
+
+impl ProjectConfig {
+    /// Tag prefix of the project given the one of its config
+    pub fn effective_tag_prefix(&self, configured: &str) -> String {
+        match &self.tag_prefix {
+            Some(prefix) => prefix.clone(),
+            None if configured.is_empty() => format!("{}-", self.name),
+            None => configured.to_string(),
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testing::temp_dir;
     use std::fs;
-    use std::time::{SystemTime, UNIX_EPOCH};
 
     #[test]
     fn test_load_config_include_in_changelog() {
@@ -388,19 +1473,328 @@ include_in_changelog = false
 name = "collective.timestamp"
 allow_prerelease = false
 "#;
-        let timestamp = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .expect("time")
-            .as_nanos();
-        let path = std::env::temp_dir().join(format!("bldr-config-{}.toml", timestamp));
+        let tmp = temp_dir("config");
+        let path = tmp.path().join("bldr.toml");
 
         fs::write(&path, toml_content).expect("write temp config");
-        let config = Config::load(&path).expect("load config");
-        fs::remove_file(&path).ok();
+        let config = Config::load_in(Path::new(""), &path).expect("load config");
 
         assert_eq!(config.packages.len(), 3);
         assert!(config.packages[0].include_in_changelog);
         assert!(!config.packages[1].include_in_changelog);
         assert!(config.packages[2].include_in_changelog);
     }
+
+    #[test]
+    fn test_pypi_concurrency_setting() {
+        let config: Config = toml::from_str(
+            r#"
+versions_file = "versions.cfg"
+packages = []
+
+[pypi]
+concurrency = 3
+"#,
+        )
+        .expect("parse config");
+        assert_eq!(config.pypi.effective_concurrency(), 3);
+
+        let config: Config =
+            toml::from_str("versions_file = \"versions.cfg\"\npackages = []\n").expect("parse");
+        assert!(config.pypi.concurrency.is_none());
+        assert!(config.pypi.effective_concurrency() >= 1);
+    }
+
+    #[test]
+    fn test_http_retry_settings() {
+        let config: Config = toml::from_str(
+            r#"
+versions_file = "versions.cfg"
+packages = []
+
+[http]
+max_retries = 5
+"#,
+        )
+        .expect("parse config");
+        assert_eq!(config.http.max_retries, 5);
+        assert_eq!(config.http.retry_backoff_ms, 300);
+    }
+
+    #[test]
+    fn test_commit_trailers() {
+        let mut git = GitConfig {
+            trailers: vec!["Refs: PLONE-123".to_string()],
+            ..GitConfig::default()
+        };
+        git.resolve_trailers(&[]).unwrap();
+        assert_eq!(
+            git.append_trailers("Use plone.api = 2.1.0\n"),
+            "Use plone.api = 2.1.0\n\nRefs: PLONE-123"
+        );
+
+        // --trailer replaces the configured trailers
+        git.resolve_trailers(&[
+            "Refs=PLONE-456".to_string(),
+            "Co-authored-by: Jane <jane@example.com>".to_string(),
+        ])
+        .unwrap();
+        assert_eq!(
+            git.trailers,
+            vec!["Refs: PLONE-456", "Co-authored-by: Jane <jane@example.com>"]
+        );
+
+        assert!(git.resolve_trailers(&["no separator".to_string()]).is_err());
+        assert!(git.resolve_trailers(&["Bad key: x".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_token_env_must_be_a_variable_name() {
+        let parse = |git: &str, github: &str| -> Config {
+            toml::from_str(&format!(
+                "versions_file = \"versions.cfg\"\npackages = []\n\n[git]\n{}\n\n[github]\n{}\n",
+                git, github
+            ))
+            .expect("parse config")
+        };
+
+        assert!(parse("token_env = \"BLDR_TOKEN_2\"", "token_env = \"_GH\"")
+            .validate()
+            .is_ok());
+        assert!(parse("", "").validate().is_ok());
+
+        for name in ["X; rm -rf ~", "A}$(id)", "2TOKEN", "", "TOKEN-NAME"] {
+            let git = format!("token_env = {:?}", name);
+            assert!(parse(&git, "").validate().is_err(), "{}", name);
+            let github = format!("token_env = {:?}", name);
+            assert!(parse("", &github).validate().is_err(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_commit_and_push() {
+        let mut git = GitConfig::default();
+        assert_eq!(
+            git.commit_and_push(false, false, false, false),
+            (false, false)
+        );
+        assert_eq!(git.commit_and_push(false, true, false, false), (true, true));
+
+        git.auto_commit = true;
+        git.auto_push = true;
+        assert_eq!(
+            git.commit_and_push(false, false, false, false),
+            (true, true)
+        );
+        assert_eq!(
+            git.commit_and_push(false, false, false, true),
+            (true, false)
+        );
+        assert_eq!(
+            git.commit_and_push(false, false, true, false),
+            (false, false)
+        );
+
+        // auto_push alone doesn't make a run commit
+        git.auto_commit = false;
+        assert_eq!(
+            git.commit_and_push(false, false, false, false),
+            (false, false)
+        );
+        assert_eq!(git.commit_and_push(true, false, false, false), (true, true));
+    }
+
+    #[test]
+    fn test_init_templates() {
+        let minimal = Config::from_template(ConfigTemplate::Minimal);
+        assert!(minimal.packages.is_empty());
+        assert!(minimal.metadata_files.is_empty());
+
+        let mut plone = Config::from_template(ConfigTemplate::Plone6);
+        assert!(plone.follow_extends && plone.remote_extends);
+        assert_eq!(plone.metadata_files[0].path, "publiccode.yml");
+        assert_eq!(
+            plone.packages[0].version_constraint.as_deref(),
+            Some(">=6.0,<7")
+        );
+
+        // Pins already listed by the template are not added twice
+        let added = plone.seed_packages([
+            ("plone.restapi", None),
+            ("collective.easyform", None),
+            ("Plone.API", None),
+        ]);
+        assert_eq!(added, 1);
+        assert_eq!(plone.packages.last().unwrap().name, "collective.easyform");
+
+        let toml = toml::to_string_pretty(&plone).unwrap();
+        let reloaded: Config = toml::from_str(&toml).unwrap();
+        assert_eq!(reloaded.packages.len(), plone.packages.len());
+    }
+
+    #[test]
+    fn test_track_all_pins() {
+        let mut config: Config = toml::from_str(
+            r#"
+versions_file = "versions.cfg"
+track_all = true
+exclude = ["setuptools"]
+
+[[packages]]
+name = "plone.api"
+version_constraint = "<3"
+"#,
+        )
+        .expect("parse config");
+
+        config.track_pins([
+            "Plone.API",
+            "zope.interface",
+            "setuptools",
+            "Products.CMFCore",
+        ]);
+        let names: Vec<&str> = config.packages.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["plone.api", "Products.CMFCore", "zope.interface"]);
+        assert!(config.packages[1].discovered);
+
+        // Only the listed package is written back
+        let tmp = temp_dir("track-all");
+        let dir = tmp.path();
+        let path = dir.join("bldr.toml");
+        config.save(&path).unwrap();
+        let saved = Config::load_in(Path::new(""), &path).unwrap();
+        assert_eq!(saved.packages.len(), 1);
+        assert!(saved.track_all);
+    }
+
+    #[test]
+    fn test_format_date_with_locale() {
+        assert_eq!(
+            format_date("2025-06-01", Some("%-d %B %Y"), Some("nl_NL")),
+            "1 juni 2025"
+        );
+        assert_eq!(
+            format_date("2025-06-01", Some("%B %-d, %Y"), None),
+            "June 1, 2025"
+        );
+        assert_eq!(format_date("2025-06-01", None, Some("nl_NL")), "2025-06-01");
+        assert_eq!(format_date("unreleased", Some("%Y"), None), "unreleased");
+    }
+
+    #[test]
+    fn test_workspace_projects() {
+        let workspace: WorkspaceConfig = toml::from_str(
+            r#"
+[[projects]]
+name = "customer-a"
+path = "customers/a"
+
+[[projects]]
+name = "customer-b"
+path = "customers/b"
+config = "releaser.toml"
+tag_prefix = "b/v"
+"#,
+        )
+        .expect("parse workspace");
+
+        let names = |projects: Vec<&ProjectConfig>| {
+            projects.iter().map(|p| p.name.clone()).collect::<Vec<_>>()
+        };
+        assert_eq!(
+            names(workspace.select(&[], true).unwrap()),
+            vec!["customer-a", "customer-b"]
+        );
+        assert_eq!(
+            names(
+                workspace
+                    .select(&["customer-b".to_string()], false)
+                    .unwrap()
+            ),
+            vec!["customer-b"]
+        );
+        assert!(workspace
+            .select(&["customer-c".to_string()], false)
+            .is_err());
+
+        let (a, b) = (&workspace.projects[0], &workspace.projects[1]);
+        assert_eq!(a.config, "bldr.toml");
+        assert_eq!(a.effective_tag_prefix(""), "customer-a-");
+        assert_eq!(a.effective_tag_prefix("a/"), "a/");
+        assert_eq!(b.effective_tag_prefix("a/"), "b/v");
+    }
+
+    #[test]
+    fn test_tag_message() {
+        let mut config = Config::from_template(ConfigTemplate::Minimal);
+        assert_eq!(
+            config.tag_message("1.8.0", "2025-06-03", "Release 1.8.0"),
+            "Release 1.8.0"
+        );
+
+        config.github.tag_message_template =
+            Some("Release {version} ({date})\n\n{changelog}".to_string());
+        assert_eq!(
+            config.tag_message("1.8.0", "2025-06-03", "- plone.api 2.0.0 → 2.1.0\n"),
+            "Release 1.8.0 (2025-06-03)\n\n- plone.api 2.0.0 → 2.1.0"
+        );
+    }
+
+    #[test]
+    fn test_release_title() {
+        let mut config = Config::from_template(ConfigTemplate::Minimal);
+        config.github.tag_prefix = "v".to_string();
+        config.github.repository = Some("imio/deliberations".to_string());
+        assert_eq!(
+            config.release_title("1.8.0", "2025-06-03", 4),
+            "Release 1.8.0"
+        );
+
+        config.github.release_title_template =
+            "{project} {version} — {date} ({updates} updates, {tag})".to_string();
+        config.github.release_date_format = Some("%B %Y".to_string());
+        assert_eq!(
+            config.release_title("1.8.0", "2025-06-03", 4),
+            "deliberations 1.8.0 — June 2025 (4 updates, v1.8.0)"
+        );
+
+        config.github.project_name = Some("Deliberations".to_string());
+        config.changelog.date_locale = Some("fr_FR".to_string());
+        assert_eq!(
+            config.release_title("1.8.0", "2025-06-03", 0),
+            "Deliberations 1.8.0 — juin 2025 (0 updates, v1.8.0)"
+        );
+    }
+
+    #[test]
+    fn test_history_paths_by_tag_range() {
+        let config: Config = toml::from_str(
+            r#"
+versions_file = "versions.cfg"
+packages = []
+
+[[history.paths]]
+path = "buildout/versions.cfg"
+to_tag = "v1.9.0"
+
+[[history.paths]]
+path = "old-versions.cfg"
+from_tag = "v2.0.0"
+to_tag = "v2.1.0"
+"#,
+        )
+        .expect("parse config");
+
+        let resolve = |tag: &str| {
+            let version = crate::version::Version::parse(tag).unwrap();
+            config
+                .history
+                .versions_file_for(&version, "v", &config.versions_file)
+                .to_string()
+        };
+
+        assert_eq!(resolve("1.2.0"), "buildout/versions.cfg");
+        assert_eq!(resolve("2.0.5"), "old-versions.cfg");
+        assert_eq!(resolve("3.0.0"), "versions.cfg");
+    }
 }
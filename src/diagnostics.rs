@@ -0,0 +1,111 @@
+use crate::buildout::BuildoutVersions;
+use serde::Serialize;
+
+/// Bump when the shape of the report changes, so editor plugins can adapt
+pub const FORMAT_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+    Information,
+}
+
+/// One problem attached to a line of a versions file
+#[derive(Debug, Clone, Serialize)]
+pub struct Diagnostic {
+    pub file: String,
+    /// 1-based line number
+    pub line: usize,
+    pub severity: Severity,
+    /// Stable identifier: `outdated`, `duplicate`, `yanked` or `vulnerable`
+    pub code: &'static str,
+    pub package: String,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DiagnosticsReport {
+    pub version: u32,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl DiagnosticsReport {
+    /// Build a report with diagnostics ordered by file and line
+    pub fn new(mut diagnostics: Vec<Diagnostic>) -> Self {
+        diagnostics.sort_by(|a, b| (&a.file, a.line).cmp(&(&b.file, b.line)));
+        Self {
+            version: FORMAT_VERSION,
+            diagnostics,
+        }
+    }
+}
+
+/// Diagnostic for the effective pin of `package`, in whichever file defines it
+pub fn at_pin(
+    buildout: &BuildoutVersions,
+    package: &str,
+    severity: Severity,
+    code: &'static str,
+    message: String,
+) -> Option<Diagnostic> {
+    let (file, line) = buildout.pin_location(package)?;
+    Some(Diagnostic {
+        file: file.to_string(),
+        line,
+        severity,
+        code,
+        package: package.to_string(),
+        message,
+    })
+}
+
+/// Every repeated pin is reported; earlier occurrences are overridden by the last one
+pub fn duplicates(file: &str, buildout: &BuildoutVersions) -> Vec<Diagnostic> {
+    buildout
+        .duplicate_pins()
+        .into_iter()
+        .flat_map(|dup| {
+            let last = *dup.lines.last().unwrap_or(&0);
+            dup.lines.clone().into_iter().map(move |line| Diagnostic {
+                file: file.to_string(),
+                line,
+                severity: Severity::Warning,
+                code: "duplicate",
+                package: dup.name.clone(),
+                message: if line == last {
+                    format!("{} is pinned more than once in [{}]", dup.name, dup.section)
+                } else {
+                    format!("{} is pinned again on line {}", dup.name, last)
+                },
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_duplicates_point_at_every_occurrence() {
+        let buildout = BuildoutVersions::from_content(
+            "[versions]\nsix = 1.15.0\nsix = 1.16.0\n".to_string(),
+            "versions.cfg",
+        )
+        .unwrap();
+
+        let report = DiagnosticsReport::new(duplicates("versions.cfg", &buildout));
+        let lines: Vec<usize> = report.diagnostics.iter().map(|d| d.line).collect();
+        assert_eq!(lines, vec![2, 3]);
+        assert_eq!(
+            report.diagnostics[0].message,
+            "six is pinned again on line 3"
+        );
+        assert_eq!(
+            serde_json::to_value(&report.diagnostics[1]).unwrap()["severity"],
+            "warning"
+        );
+    }
+}
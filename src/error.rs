@@ -17,14 +17,234 @@ pub enum ReleaserError {
     #[error("Git operation failed: {0}")]
     GitError(String),
 
+    #[error("{command} failed: {message}")]
+    CommandFailed { command: String, message: String },
+
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
 
+    #[error("Cannot read {path}: {source}")]
+    FileError {
+        path: String,
+        #[source]
+        source: std::io::Error,
+    },
+
     #[error("HTTP request failed: {0}")]
     HttpError(#[from] reqwest::Error),
 
+    #[error("HTTP {status} from {url}")]
+    HttpStatus { url: String, status: u16 },
+
+    #[error("GitHub API returned {status} for {endpoint}: {message}")]
+    GitHubApi {
+        endpoint: String,
+        status: u16,
+        message: String,
+    },
+
+    #[error("Release {url} was created without {missing}: {source}")]
+    PartialRelease {
+        url: String,
+        missing: String,
+        #[source]
+        source: Box<ReleaserError>,
+    },
+
+    #[error("Linked versions files are out of sync: {0}")]
+    PinsOutOfSync(String),
+
+    #[error("Changelog incomplete: {0}")]
+    ChangelogIncomplete(String),
+
+    #[error("Vulnerability audit failed: {0}")]
+    AuditError(String),
+
+    #[error("Bundle verification failed: {0}")]
+    BundleError(String),
+
+    #[error("License check failed: {0}")]
+    LicenseError(String),
+
     #[error("Version parse error: {0}")]
     VersionError(String),
+
+    #[error("Fixture error: {0}")]
+    FixtureError(String),
+
+    #[error("Versions files need formatting: {0}")]
+    NotFormatted(String),
+}
+
+impl ReleaserError {
+    /// Stable identifier used by `--error-format json`
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ReleaserError::PyPiError(_) => "pypi",
+            ReleaserError::PackageNotFound(_) => "package_not_found",
+            ReleaserError::BuildoutParseError(_) => "buildout_parse",
+            ReleaserError::ConfigError(_) => "config",
+            ReleaserError::GitError(_) => "git",
+            ReleaserError::CommandFailed { .. } => "command_failed",
+            ReleaserError::IoError(_) => "io",
+            ReleaserError::FileError { .. } => "file",
+            ReleaserError::HttpError(_) => "http",
+            ReleaserError::HttpStatus { .. } => "http_status",
+            ReleaserError::GitHubApi { .. } => "github_api",
+            ReleaserError::PartialRelease { .. } => "partial_release",
+            ReleaserError::PinsOutOfSync(_) => "pins_out_of_sync",
+            ReleaserError::ChangelogIncomplete(_) => "changelog_incomplete",
+            ReleaserError::AuditError(_) => "audit",
+            ReleaserError::BundleError(_) => "bundle",
+            ReleaserError::LicenseError(_) => "license",
+            ReleaserError::VersionError(_) => "version",
+            ReleaserError::FixtureError(_) => "fixture",
+            ReleaserError::NotFormatted(_) => "not_formatted",
+        }
+    }
+
+    /// What to try next, when there is something better than reading the message
+    pub fn hint(&self) -> Option<&'static str> {
+        match self {
+            ReleaserError::PackageNotFound(_) => Some(
+                "check the name on the index (pypi.index_url); if the pin uses another spelling, set buildout_name on the package",
+            ),
+            ReleaserError::HttpStatus { status: 401 | 403, .. } => {
+                Some("the server rejected the request; check credentials and pypi.index_url")
+            }
+            ReleaserError::HttpStatus { status: 404, .. } => {
+                Some("check the URL, or pypi.index_url and pypi.simple_url for a private index")
+            }
+            ReleaserError::HttpStatus { status, .. } if *status == 429 || *status >= 500 => {
+                Some("the server is busy or failing; retry later or raise --max-retries")
+            }
+            ReleaserError::HttpError(e) if e.is_connect() || e.is_timeout() => Some(
+                "check network access and proxy settings (http.proxy, BLDR_PROXY, BLDR_CA_BUNDLE)",
+            ),
+            ReleaserError::FileError { source, .. }
+                if source.kind() == std::io::ErrorKind::NotFound =>
+            {
+                Some("paths are relative to the working directory; run `bldr init` to create a config, or pass --config")
+            }
+            ReleaserError::CommandFailed { command, message } => {
+                if message.contains("not a git repository") {
+                    Some("run bldr from inside the buildout repository")
+                } else if command.starts_with("gh ") {
+                    Some("check `gh auth status` and github.repository")
+                } else if message.contains("Authentication failed")
+                    || message.contains("could not read Username")
+                    || message.contains("Permission denied (publickey)")
+                {
+                    Some("set git.token_env to a token variable, or configure a credential helper or SSH key")
+                } else {
+                    None
+                }
+            }
+            ReleaserError::GitHubApi { status: 401 | 403, .. } => Some(
+                "check the token in GITHUB_TOKEN (or github.token_env); it needs write access to the repository's contents",
+            ),
+            ReleaserError::GitHubApi { status: 404, .. } => Some(
+                "check github.repository, that the tag was pushed, and that the token can see the repository",
+            ),
+            ReleaserError::PartialRelease { .. } => Some(
+                "upload the missing assets on the release page (or with `gh release upload`), or remove the release with `bldr rollback`",
+            ),
+            ReleaserError::FixtureError(message) if message.starts_with("no recorded") => {
+                Some("the run differs from the recorded one; record it again with --record <dir>")
+            }
+            ReleaserError::NotFormatted(_) => Some("run `bldr fmt` to rewrite them"),
+            ReleaserError::PinsOutOfSync(_) => Some(
+                "run `bldr update` to apply the same pins to every linked file, or align them by hand",
+            ),
+            _ => None,
+        }
+    }
+
+    /// Machine-readable form: kind, message, hint and the context fields of the variant
+    pub fn to_json(&self) -> serde_json::Value {
+        let mut value = serde_json::json!({
+            "kind": self.kind(),
+            "message": self.to_string(),
+        });
+
+        let context = match self {
+            ReleaserError::PackageNotFound(package) => serde_json::json!({ "package": package }),
+            ReleaserError::CommandFailed { command, .. } => {
+                serde_json::json!({ "command": command })
+            }
+            ReleaserError::FileError { path, .. } => serde_json::json!({ "path": path }),
+            ReleaserError::HttpStatus { url, status } => {
+                serde_json::json!({ "url": url, "status": status })
+            }
+            ReleaserError::GitHubApi {
+                endpoint, status, ..
+            } => serde_json::json!({ "endpoint": endpoint, "status": status }),
+            ReleaserError::PartialRelease { url, missing, .. } => {
+                serde_json::json!({ "url": url, "missing": missing })
+            }
+            ReleaserError::HttpError(e) => serde_json::json!({
+                "url": e.url().map(|url| url.to_string()),
+                "status": e.status().map(|status| status.as_u16()),
+            }),
+            _ => serde_json::Value::Null,
+        };
+        if !context.is_null() {
+            value["context"] = context;
+        }
+        if let Some(hint) = self.hint() {
+            value["hint"] = hint.into();
+        }
+
+        value
+    }
 }
 
 pub type Result<T> = std::result::Result<T, ReleaserError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hints_and_json() {
+        let err = ReleaserError::HttpStatus {
+            url: "https://pypi.org/pypi/plone.api/json".to_string(),
+            status: 503,
+        };
+        let json = err.to_json();
+        assert_eq!(json["kind"], "http_status");
+        assert_eq!(json["context"]["status"], 503);
+        assert!(json["hint"].as_str().unwrap().contains("--max-retries"));
+
+        let err = ReleaserError::CommandFailed {
+            command: "git status --porcelain".to_string(),
+            message: "fatal: not a git repository".to_string(),
+        };
+        assert_eq!(
+            err.to_string(),
+            "git status --porcelain failed: fatal: not a git repository"
+        );
+        assert!(err
+            .hint()
+            .unwrap()
+            .contains("inside the buildout repository"));
+
+        assert!(ReleaserError::ConfigError("bad".to_string())
+            .hint()
+            .is_none());
+        assert!(ReleaserError::ConfigError("bad".to_string())
+            .to_json()
+            .get("context")
+            .is_none());
+
+        let err = ReleaserError::GitHubApi {
+            endpoint: "repos/plone/site/releases".to_string(),
+            status: 403,
+            message: "Resource not accessible by integration".to_string(),
+        };
+        let json = err.to_json();
+        assert_eq!(json["kind"], "github_api");
+        assert_eq!(json["context"]["endpoint"], "repos/plone/site/releases");
+        assert!(json["hint"].as_str().unwrap().contains("GITHUB_TOKEN"));
+    }
+}
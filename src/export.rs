@@ -0,0 +1,95 @@
+use crate::buildout::PinnedPackage;
+use crate::error::{ReleaserError, Result};
+use crate::pypi::PyPiClient;
+
+/// A pin ready to be written out, with the sha256 digests of its release files
+#[derive(Debug, Clone)]
+pub struct ExportedPin {
+    pub name: String,
+    pub version: String,
+    pub hashes: Vec<String>,
+}
+
+/// Look up the sha256 digests of every non-yanked file of a pinned release
+pub async fn fetch_hashes(pypi: &PyPiClient, pin: &PinnedPackage) -> Result<Vec<String>> {
+    let mut hashes: Vec<String> = pypi
+        .release_files(&pin.name, &pin.version)
+        .await?
+        .into_iter()
+        .filter(|file| !file.yanked)
+        .filter_map(|file| file.digests.get("sha256").map(|d| d.to_lowercase()))
+        .collect();
+    hashes.sort();
+    hashes.dedup();
+
+    // pip refuses the whole file in hash-checking mode if one entry has no hash
+    if hashes.is_empty() {
+        return Err(ReleaserError::PyPiError(format!(
+            "No sha256 digests published for {}=={}",
+            pin.name, pin.version
+        )));
+    }
+
+    Ok(hashes)
+}
+
+/// Render a pip constraints file (`pip install -c`); pins with hashes get
+/// `--hash=sha256:` continuation lines
+pub fn render_constraints(pins: &[ExportedPin], source: &str) -> String {
+    render_pins(pins, &format!("# Generated by bldr from {}\n", source))
+}
+
+/// Render a pip requirements file (`pip install -r`) that installs every pin
+pub fn render_requirements(pins: &[ExportedPin], source: &str) -> String {
+    render_pins(
+        pins,
+        &format!(
+            "# Generated by bldr from {}\n# Install with: pip install -r <this file>\n",
+            source
+        ),
+    )
+}
+
+fn render_pins(pins: &[ExportedPin], header: &str) -> String {
+    let mut out = header.to_string();
+
+    for pin in pins {
+        out.push_str(&format!("{}=={}", pin.name, pin.version));
+        for hash in &pin.hashes {
+            out.push_str(&format!(" \\\n    --hash=sha256:{}", hash));
+        }
+        out.push('\n');
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_constraints() {
+        let pins = vec![
+            ExportedPin {
+                name: "plone.api".to_string(),
+                version: "2.0.0".to_string(),
+                hashes: vec!["aa".to_string(), "bb".to_string()],
+            },
+            ExportedPin {
+                name: "six".to_string(),
+                version: "1.16.0".to_string(),
+                hashes: Vec::new(),
+            },
+        ];
+
+        assert_eq!(
+            render_constraints(&pins, "versions.cfg"),
+            "# Generated by bldr from versions.cfg\n\
+             plone.api==2.0.0 \\\n    --hash=sha256:aa \\\n    --hash=sha256:bb\n\
+             six==1.16.0\n"
+        );
+        assert!(render_requirements(&pins[1..], "versions.cfg")
+            .ends_with("# Install with: pip install -r <this file>\nsix==1.16.0\n"));
+    }
+}
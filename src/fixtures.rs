@@ -0,0 +1,410 @@
+//! Record HTTP responses and git/gh transcripts to a fixtures directory
+//! (`--record`), or answer from them instead of the network and the
+//! repository (`--replay`), for reproducible runs and bug reports.
+
+use crate::error::{ReleaserError, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{Mutex, OnceLock};
+
+static SESSION: OnceLock<Session> = OnceLock::new();
+
+const HTTP_FILE: &str = "http.json";
+const COMMANDS_FILE: &str = "commands.json";
+
+/// Whether this run records or replays fixtures
+pub fn active() -> bool {
+    SESSION.get().is_some()
+}
+
+/// Capture every exchange of this run into `dir`
+pub fn start_recording(dir: &str) -> Result<()> {
+    std::fs::create_dir_all(dir)?;
+    install(Session::new(Mode::Record, dir))
+}
+
+/// Answer HTTP requests and git/gh commands from the fixtures in `dir`
+pub fn start_replay(dir: &str) -> Result<()> {
+    install(Session::load(dir)?)
+}
+
+fn install(session: Session) -> Result<()> {
+    SESSION
+        .set(session)
+        .map_err(|_| ReleaserError::FixtureError("a fixture session is already active".into()))
+}
+
+/// Exit status and output of an external command
+#[derive(Debug, Clone)]
+pub struct CommandOutput {
+    pub success: bool,
+    pub stdout: Vec<u8>,
+    pub stderr: Vec<u8>,
+}
+
+/// Run `command` (already set up with `args`), going through the active session
+pub fn run_command(
+    program: &str,
+    args: &[&str],
+    command: &mut Command,
+) -> std::io::Result<CommandOutput> {
+    match SESSION.get() {
+        Some(session) => session.run_command(program, args, command),
+        None => execute(command),
+    }
+}
+
+/// A recorded response for `request` when replaying
+pub fn replayed_response(request: &reqwest::RequestBuilder) -> Result<Option<reqwest::Response>> {
+    match SESSION.get() {
+        Some(session) if session.mode == Mode::Replay => {
+            let key = RequestKey::of(request)?;
+            session
+                .replay_http(&key)
+                .map(|exchange| Some(exchange.into_response()))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Record the final response to `request` when recording; the body is read
+/// and handed back in an equivalent response
+pub async fn recorded_response(
+    request: &reqwest::RequestBuilder,
+    response: reqwest::Response,
+) -> Result<reqwest::Response> {
+    let Some(session) = SESSION.get().filter(|s| s.mode == Mode::Record) else {
+        return Ok(response);
+    };
+
+    let key = RequestKey::of(request)?;
+    let status = response.status().as_u16();
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+    let body = response.bytes().await?;
+
+    let exchange = HttpExchange::new(key, status, content_type, &body);
+    session.record_http(exchange.clone())?;
+    Ok(exchange.into_response())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Record,
+    Replay,
+}
+
+struct Session {
+    mode: Mode,
+    dir: PathBuf,
+    http: Mutex<Transcript<HttpExchange>>,
+    commands: Mutex<Transcript<CommandRun>>,
+}
+
+impl Session {
+    fn new(mode: Mode, dir: &str) -> Self {
+        Self {
+            mode,
+            dir: PathBuf::from(dir),
+            http: Mutex::new(Transcript::default()),
+            commands: Mutex::new(Transcript::default()),
+        }
+    }
+
+    fn load(dir: &str) -> Result<Self> {
+        let session = Self::new(Mode::Replay, dir);
+        *session.http.lock().unwrap() =
+            Transcript::new(read_entries(&session.dir.join(HTTP_FILE))?);
+        *session.commands.lock().unwrap() =
+            Transcript::new(read_entries(&session.dir.join(COMMANDS_FILE))?);
+        Ok(session)
+    }
+
+    fn run_command(
+        &self,
+        program: &str,
+        args: &[&str],
+        command: &mut Command,
+    ) -> std::io::Result<CommandOutput> {
+        let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+
+        if self.mode == Mode::Replay {
+            let mut commands = self.commands.lock().unwrap();
+            return commands
+                .take(|run| run.program == program && run.args == args)
+                .map(CommandRun::output)
+                .ok_or_else(|| {
+                    std::io::Error::new(
+                        std::io::ErrorKind::NotFound,
+                        format!("no recorded output for `{} {}`", program, args.join(" ")),
+                    )
+                });
+        }
+
+        let output = execute(command)?;
+        let run = CommandRun {
+            program: program.to_string(),
+            args,
+            success: output.success,
+            stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+        };
+        let mut commands = self.commands.lock().unwrap();
+        commands.entries.push(run);
+        write_entries(&self.dir.join(COMMANDS_FILE), &commands.entries)
+            .map_err(|e| std::io::Error::other(e.to_string()))?;
+        Ok(output)
+    }
+
+    fn replay_http(&self, key: &RequestKey) -> Result<HttpExchange> {
+        self.http
+            .lock()
+            .unwrap()
+            .take(|exchange| exchange.request == *key)
+            .ok_or_else(|| {
+                ReleaserError::FixtureError(format!(
+                    "no recorded response for {} {}",
+                    key.method, key.url
+                ))
+            })
+    }
+
+    fn record_http(&self, exchange: HttpExchange) -> Result<()> {
+        let mut http = self.http.lock().unwrap();
+        http.entries.push(exchange);
+        write_entries(&self.dir.join(HTTP_FILE), &http.entries)
+    }
+}
+
+/// Recorded entries in order, with the ones already replayed marked
+struct Transcript<T> {
+    entries: Vec<T>,
+    used: Vec<bool>,
+}
+
+impl<T> Default for Transcript<T> {
+    fn default() -> Self {
+        Self::new(Vec::new())
+    }
+}
+
+impl<T> Transcript<T> {
+    fn new(entries: Vec<T>) -> Self {
+        let used = vec![false; entries.len()];
+        Self { entries, used }
+    }
+}
+
+impl<T: Clone> Transcript<T> {
+    /// The first unused matching entry; once all are used, the last one
+    /// matching is repeated (requests may be retried or reordered)
+    fn take<F: Fn(&T) -> bool>(&mut self, matches: F) -> Option<T> {
+        let unused = (0..self.entries.len()).find(|&i| !self.used[i] && matches(&self.entries[i]));
+        if let Some(i) = unused {
+            self.used[i] = true;
+            return Some(self.entries[i].clone());
+        }
+        self.entries
+            .iter()
+            .rev()
+            .find(|entry| matches(entry))
+            .cloned()
+    }
+}
+
+/// What identifies a request in the fixtures; headers (and tokens) are not recorded
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct RequestKey {
+    method: String,
+    url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    body: Option<String>,
+}
+
+impl RequestKey {
+    fn of(request: &reqwest::RequestBuilder) -> Result<Self> {
+        let request = request
+            .try_clone()
+            .ok_or_else(|| {
+                ReleaserError::FixtureError("streaming request bodies can't be recorded".into())
+            })?
+            .build()?;
+
+        Ok(Self {
+            method: request.method().to_string(),
+            url: request.url().to_string(),
+            body: request
+                .body()
+                .and_then(|body| body.as_bytes())
+                .map(|bytes| String::from_utf8_lossy(bytes).into_owned()),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct HttpExchange {
+    #[serde(flatten)]
+    request: RequestKey,
+    status: u16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    content_type: Option<String>,
+    /// Response body; hex-encoded when `binary`
+    body: String,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    binary: bool,
+}
+
+impl HttpExchange {
+    fn new(request: RequestKey, status: u16, content_type: Option<String>, body: &[u8]) -> Self {
+        let (body, binary) = match std::str::from_utf8(body) {
+            Ok(text) => (text.to_string(), false),
+            Err(_) => (body.iter().map(|b| format!("{:02x}", b)).collect(), true),
+        };
+
+        Self {
+            request,
+            status,
+            content_type,
+            body,
+            binary,
+        }
+    }
+
+    fn body_bytes(&self) -> Vec<u8> {
+        if !self.binary {
+            return self.body.clone().into_bytes();
+        }
+        (0..self.body.len())
+            .step_by(2)
+            .filter_map(|i| u8::from_str_radix(self.body.get(i..i + 2)?, 16).ok())
+            .collect()
+    }
+
+    fn into_response(self) -> reqwest::Response {
+        use reqwest::ResponseBuilderExt;
+
+        let mut builder = ::http::Response::builder().status(self.status);
+        if let Ok(url) = reqwest::Url::parse(&self.request.url) {
+            builder = builder.url(url);
+        }
+        if let Some(ref content_type) = self.content_type {
+            builder = builder.header(reqwest::header::CONTENT_TYPE, content_type.as_str());
+        }
+
+        builder
+            .body(self.body_bytes())
+            .map(reqwest::Response::from)
+            .expect("recorded status and headers are valid")
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CommandRun {
+    program: String,
+    args: Vec<String>,
+    success: bool,
+    #[serde(default)]
+    stdout: String,
+    #[serde(default)]
+    stderr: String,
+}
+
+impl CommandRun {
+    fn output(self) -> CommandOutput {
+        CommandOutput {
+            success: self.success,
+            stdout: self.stdout.into_bytes(),
+            stderr: self.stderr.into_bytes(),
+        }
+    }
+}
+
+fn execute(command: &mut Command) -> std::io::Result<CommandOutput> {
+    let output = command.output()?;
+    Ok(CommandOutput {
+        success: output.status.success(),
+        stdout: output.stdout,
+        stderr: output.stderr,
+    })
+}
+
+/// Entries of one fixture file; a missing file has none
+fn read_entries<T: for<'de> Deserialize<'de>>(path: &Path) -> Result<Vec<T>> {
+    match std::fs::read_to_string(path) {
+        Ok(content) => serde_json::from_str(&content)
+            .map_err(|e| ReleaserError::FixtureError(format!("{}: {}", path.display(), e))),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(source) => Err(ReleaserError::FileError {
+            path: path.display().to_string(),
+            source,
+        }),
+    }
+}
+
+fn write_entries<T: Serialize>(path: &Path, entries: &[T]) -> Result<()> {
+    let content = serde_json::to_string_pretty(entries)
+        .map_err(|e| ReleaserError::FixtureError(e.to_string()))?;
+    std::fs::write(path, content)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::temp_dir;
+
+    #[tokio::test]
+    async fn test_record_and_replay() {
+        let tmp = temp_dir("fixtures");
+        let dir = tmp.path();
+        let dir_str = dir.to_str().unwrap();
+
+        let recorder = Session::new(Mode::Record, dir_str);
+        let output = recorder
+            .run_command("git", &["--version"], Command::new("git").arg("--version"))
+            .unwrap();
+        let key = RequestKey {
+            method: "GET".to_string(),
+            url: "https://pypi.org/pypi/plone.api/json".to_string(),
+            body: None,
+        };
+        recorder
+            .record_http(HttpExchange::new(
+                key.clone(),
+                200,
+                Some("application/json".to_string()),
+                br#"{"info": {"name": "plone.api"}}"#,
+            ))
+            .unwrap();
+
+        let replay = Session::load(dir_str).unwrap();
+        let replayed = replay
+            .run_command("git", &["--version"], &mut Command::new("false"))
+            .unwrap();
+        assert_eq!(replayed.stdout, output.stdout);
+        assert!(replay
+            .run_command("git", &["status"], &mut Command::new("false"))
+            .is_err());
+
+        let response = replay.replay_http(&key).unwrap().into_response();
+        assert_eq!(response.url().as_str(), key.url);
+        let body: serde_json::Value = response.json().await.unwrap();
+        assert_eq!(body["info"]["name"], "plone.api");
+    }
+
+    #[test]
+    fn test_binary_bodies_round_trip() {
+        let key = RequestKey {
+            method: "GET".to_string(),
+            url: "https://files.example/pkg.whl".to_string(),
+            body: None,
+        };
+        let exchange = HttpExchange::new(key, 200, None, &[0x50, 0x4b, 0xff, 0x00]);
+        assert!(exchange.binary);
+        assert_eq!(exchange.body_bytes(), vec![0x50, 0x4b, 0xff, 0x00]);
+    }
+}
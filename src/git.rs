@@ -1,63 +1,275 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
 use std::process::Command;
 
-use chrono::Local;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
 
-use crate::buildout::VersionUpdate;
+use crate::buildout::{PinnedPackage, VersionUpdate};
+use crate::config::LegacyTagScheme;
 use crate::error::{ReleaserError, Result};
+use crate::fixtures;
+use crate::version::Version;
+use crate::warnings::{self, WarningKind};
 
+#[cfg(feature = "libgit2")]
+mod library;
+
+/// Repository access. With the `libgit2` feature, read-only operations (tags,
+/// files at a ref, status, authors) go through libgit2; everything that writes
+/// (staging, commits, tags, stashes, resets, reverts, pushes) runs `git`
 pub struct GitOps {
     /// Working directory
     work_dir: Option<String>,
+    /// Environment variable holding a token for HTTPS pushes
+    token_env: Option<String>,
+    /// Remote pushed to
+    remote: String,
+    /// Pre-migration tag schemes recognized alongside prefixed version tags
+    legacy_tags: Vec<LegacyTag>,
+    /// Fail instead of prompting for credentials
+    non_interactive: bool,
+}
+
+/// A file with uncommitted changes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirtyFile {
+    /// Path relative to the repository root
+    pub path: String,
+    /// Staged, so the next commit includes it whatever else is added
+    pub staged: bool,
+}
+
+/// A compiled legacy tag scheme
+struct LegacyTag {
+    pattern: Regex,
+    version: Option<String>,
+}
+
+impl LegacyTag {
+    fn compile(scheme: &LegacyTagScheme) -> Option<Self> {
+        match Regex::new(&format!("^(?:{})$", scheme.pattern)) {
+            Ok(pattern) => Some(Self {
+                pattern,
+                version: scheme.version.clone(),
+            }),
+            Err(e) => {
+                warnings::warn(
+                    WarningKind::Git,
+                    None,
+                    format!(
+                        "ignoring invalid legacy tag pattern '{}': {}",
+                        scheme.pattern, e
+                    ),
+                );
+                None
+            }
+        }
+    }
+
+    /// Version a legacy tag stands for, if the tag follows this scheme
+    fn version_of(&self, tag: &str) -> Option<Version> {
+        let captures = self.pattern.captures(tag)?;
+
+        let version = match self.version {
+            Some(ref template) => {
+                let mut expanded = String::new();
+                captures.expand(template, &mut expanded);
+                expanded
+            }
+            None => captures
+                .iter()
+                .skip(1)
+                .flatten()
+                .map(|group| group.as_str())
+                .collect::<Vec<_>>()
+                .join("."),
+        };
+
+        Version::parse(&version).ok()
+    }
 }
 
 impl GitOps {
     pub fn new() -> Self {
-        Self { work_dir: None }
+        Self {
+            work_dir: None,
+            token_env: None,
+            remote: "origin".to_string(),
+            legacy_tags: Vec::new(),
+            non_interactive: false,
+        }
     }
 
+    /// Also recognize tags following older schemes (invalid patterns are skipped)
+    pub fn with_legacy_tags(mut self, schemes: &[LegacyTagScheme]) -> Self {
+        self.legacy_tags = schemes.iter().filter_map(LegacyTag::compile).collect();
+        self
+    }
+
+    /// Authenticate HTTPS pushes with the token in `env_var`, if it is set
+    pub fn with_token_env(mut self, env_var: Option<&str>) -> Self {
+        self.token_env = env_var.map(str::to_string);
+        self
+    }
+
+    /// Make git fail instead of prompting for credentials (`--non-interactive`)
+    pub fn with_non_interactive(mut self, enabled: bool) -> Self {
+        self.non_interactive = enabled;
+        self
+    }
+
+    /// Push to `remote` instead of `origin`
+    pub fn with_remote(mut self, remote: &str) -> Self {
+        self.remote = remote.to_string();
+        self
+    }
+
+    /// Run git in `dir` instead of the current directory
     pub fn with_work_dir<S: Into<String>>(mut self, dir: S) -> Self {
         self.work_dir = Some(dir.into());
         self
     }
 
-    fn run_git(&self, args: &[&str]) -> Result<String> {
+    /// A git command, with the prompts turned off for non-interactive runs
+    fn command(&self) -> Command {
         let mut cmd = Command::new("git");
+        if self.non_interactive {
+            cmd.env("GIT_TERMINAL_PROMPT", "0");
+            if std::env::var_os("GIT_SSH_COMMAND").is_none() {
+                cmd.env("GIT_SSH_COMMAND", "ssh -o BatchMode=yes");
+            }
+        }
+        cmd
+    }
+
+    fn run_git(&self, args: &[&str]) -> Result<String> {
+        let mut cmd = self.command();
 
         if let Some(ref dir) = self.work_dir {
             cmd.current_dir(dir);
         }
 
-        let output = cmd
-            .args(args)
-            .output()
-            .map_err(|e| ReleaserError::GitError(format!("Failed to run git: {}", e)))?;
+        // The helper reads the token from the environment so it never shows up in argv
+        if let Some(ref var) = self.token_env {
+            if std::env::var(var).is_ok_and(|token| !token.is_empty()) {
+                cmd.args(["-c", "credential.helper="]);
+                cmd.arg("-c").arg(format!(
+                    "credential.helper=!f() {{ test \"$1\" = get && echo username=x-access-token && echo \"password=${}\"; }}; f",
+                    var
+                ));
+            }
+        }
+
+        cmd.args(args);
+        let output = fixtures::run_command("git", args, &mut cmd).map_err(|e| {
+            if e.kind() == std::io::ErrorKind::NotFound {
+                // Only reads go through libgit2; anything that writes needs the binary
+                ReleaserError::GitError(format!(
+                    "`git {}` needs git on PATH, which was not found",
+                    args.first().copied().unwrap_or_default()
+                ))
+            } else {
+                ReleaserError::GitError(format!("Failed to run git: {}", e))
+            }
+        })?;
 
-        if !output.status.success() {
+        if !output.success {
             let stderr = String::from_utf8_lossy(&output.stderr);
-            return Err(ReleaserError::GitError(format!(
-                "git {} failed: {}",
-                args.join(" "),
-                stderr
-            )));
+            return Err(ReleaserError::CommandFailed {
+                command: format!("git {}", args.join(" ")),
+                message: stderr.trim().to_string(),
+            });
         }
 
         Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
     }
 
+    /// The repository opened with libgit2 for reads, unless git commands are
+    /// being recorded or replayed
+    #[cfg(feature = "libgit2")]
+    fn repository(&self) -> Option<git2::Repository> {
+        if fixtures::active() {
+            return None;
+        }
+        library::open(self.work_dir.as_deref())
+    }
+
     /// Check if we're in a git repository
     pub fn is_repo(&self) -> bool {
+        #[cfg(feature = "libgit2")]
+        if self.repository().is_some() {
+            return true;
+        }
         self.run_git(&["rev-parse", "--git-dir"]).is_ok()
     }
 
-    /// Get current branch name
-    pub fn current_branch(&self) -> Result<String> {
-        self.run_git(&["rev-parse", "--abbrev-ref", "HEAD"])
+    /// Get current branch name, or `None` when HEAD is detached (typical for CI checkouts)
+    pub fn current_branch(&self) -> Result<Option<String>> {
+        #[cfg(feature = "libgit2")]
+        if let Some(repo) = self.repository() {
+            return library::current_branch(&repo);
+        }
+        match self.run_git(&["symbolic-ref", "--quiet", "--short", "HEAD"]) {
+            Ok(branch) => Ok(Some(branch)),
+            Err(_) => {
+                // Distinguish a detached HEAD from a repository without commits
+                self.run_git(&["rev-parse", "--verify", "HEAD"])?;
+                Ok(None)
+            }
+        }
+    }
+
+    /// Default branch of the remote, as recorded in `<remote>/HEAD` by
+    /// `git clone` (or `git remote set-head`); `None` when it isn't known
+    pub fn default_branch(&self) -> Option<String> {
+        #[cfg(feature = "libgit2")]
+        if let Some(repo) = self.repository() {
+            return library::default_branch(&repo, &self.remote);
+        }
+        let head = format!("refs/remotes/{}/HEAD", self.remote);
+        let target = self
+            .run_git(&["symbolic-ref", "--quiet", "--short", &head])
+            .ok()?;
+        let branch = target.strip_prefix(&format!("{}/", self.remote))?;
+        Some(branch.to_string())
+    }
+
+    /// Switch to a branch; uncommitted changes are carried over
+    pub fn checkout(&self, branch: &str) -> Result<()> {
+        self.run_git(&["checkout", branch])?;
+        Ok(())
+    }
+
+    /// Create `branch` at HEAD and switch to it; uncommitted changes are carried over
+    pub fn create_branch(&self, branch: &str) -> Result<()> {
+        self.run_git(&["checkout", "-b", branch])?;
+        Ok(())
+    }
+
+    /// Files with uncommitted changes, untracked ones included (ignored ones are not)
+    pub fn dirty_files(&self) -> Result<Vec<DirtyFile>> {
+        #[cfg(feature = "libgit2")]
+        if let Some(repo) = self.repository() {
+            return library::dirty_files(&repo);
+        }
+
+        let output = self.run_git(&["status", "--porcelain=v2", "-z", "--untracked-files=all"])?;
+        Ok(parse_status(&output))
     }
 
-    /// Check if working directory is clean
-    pub fn is_clean(&self) -> Result<bool> {
-        let status = self.run_git(&["status", "--porcelain"])?;
-        Ok(status.is_empty())
+    /// Stash the changes to `paths` (untracked files included) and the index
+    pub fn stash(&self, paths: &[&str], message: &str) -> Result<()> {
+        let mut args = vec!["stash", "push", "--include-untracked", "-m", message, "--"];
+        args.extend(paths);
+        self.run_git(&args)?;
+        Ok(())
+    }
+
+    /// Restore the latest stash, staged changes included
+    pub fn stash_pop(&self) -> Result<()> {
+        self.run_git(&["stash", "pop", "--index"])?;
+        Ok(())
     }
 
     /// Stage a file
@@ -81,25 +293,183 @@ impl GitOps {
         Ok(())
     }
 
-    /// Push commits and tags
-    pub fn push(&self, include_tags: bool) -> Result<()> {
-        self.run_git(&["push"])?;
+    /// URL of the remote, `None` when it isn't configured
+    pub fn remote_url(&self) -> Option<String> {
+        self.run_git(&["remote", "get-url", &self.remote]).ok()
+    }
+
+    /// Check that the remote accepts a push before anything is committed or tagged
+    pub fn check_push_access(&self, target: Option<&str>) -> Result<()> {
+        let remote = self.remote.as_str();
+        let refspec = match target {
+            Some(branch) => Some(format!("HEAD:refs/heads/{}", branch)),
+            None => self.current_branch()?,
+        };
+        let args: Vec<&str> = match &refspec {
+            Some(refspec) => vec!["push", "--dry-run", remote, refspec],
+            None => vec!["push", "--dry-run", remote, "--tags"],
+        };
+
+        self.run_git(&args).map(|_| ()).map_err(|e| {
+            let remote = self
+                .run_git(&["remote", "get-url", remote])
+                .unwrap_or_else(|_| remote.to_string());
+            let hint = if remote.starts_with("http") {
+                "export a token and set git.token_env, or configure a credential helper"
+            } else {
+                "load a deploy key into ssh-agent or switch the remote to HTTPS with git.token_env"
+            };
+            ReleaserError::GitError(format!(
+                "Cannot push to {} ({}); {}",
+                remote,
+                e.to_string().trim(),
+                hint
+            ))
+        })
+    }
+
+    /// Push commits and tags to the remote. With a `target` branch, HEAD is pushed to
+    /// that branch, which also works from a detached HEAD; otherwise the current
+    /// branch is pushed to the branch of the same name, whatever its upstream.
+    pub fn push(&self, target: Option<&str>, include_tags: bool) -> Result<()> {
+        let remote = self.remote.as_str();
+        match target {
+            Some(branch) => {
+                let refspec = format!("HEAD:refs/heads/{}", branch);
+                self.run_git(&["push", remote, &refspec])?;
+            }
+            None => match self.current_branch()? {
+                Some(branch) => {
+                    self.run_git(&["push", remote, &branch])?;
+                }
+                None if !include_tags => {
+                    return Err(ReleaserError::GitError(
+                        "HEAD is detached (CI checkout?); pass --push-to <branch> or set git.branch to choose where to push".to_string(),
+                    ));
+                }
+                // Tag-only context: the tag carries the release commit to the remote
+                None => {}
+            },
+        }
+
         if include_tags {
-            self.run_git(&["push", "--tags"])?;
+            self.run_git(&["push", remote, "--tags"])?;
         }
         Ok(())
     }
 
-    /// Get the latest tag
-    pub fn latest_tag(&self) -> Result<Option<String>> {
-        match self.run_git(&["describe", "--tags", "--abbrev=0"]) {
-            Ok(tag) => Ok(Some(tag)),
-            Err(_) => Ok(None), // No tags exist
+    /// Commits of `branch` on the remote missing from HEAD, after fetching it;
+    /// `None` when the remote has no such branch
+    pub fn commits_behind(&self, branch: &str) -> Result<Option<usize>> {
+        let remote = self.remote.as_str();
+        let refspec = format!("refs/heads/{}", branch);
+        if self
+            .run_git(&["ls-remote", "--heads", remote, &refspec])?
+            .is_empty()
+        {
+            return Ok(None);
         }
+
+        self.run_git(&["fetch", "--quiet", remote, &refspec])?;
+        let count = self.run_git(&["rev-list", "--count", "HEAD..FETCH_HEAD"])?;
+        count
+            .parse()
+            .map(Some)
+            .map_err(|_| ReleaserError::GitError(format!("Unexpected rev-list output: {}", count)))
+    }
+
+    /// Replay local commits on top of `branch` of the remote; uncommitted
+    /// changes are stashed meanwhile
+    pub fn pull_rebase(&self, branch: &str) -> Result<()> {
+        self.run_git(&["pull", "--rebase", "--autostash", &self.remote, branch])?;
+        Ok(())
+    }
+
+    /// Full SHA of the commit HEAD points at
+    pub fn head_commit(&self) -> Result<String> {
+        self.run_git(&["rev-parse", "HEAD"])
+    }
+
+    /// Subject line of a commit
+    pub fn commit_subject(&self, rev: &str) -> Result<String> {
+        self.run_git(&["log", "-1", "--format=%s", rev])
+    }
+
+    /// Whether the commit has no parent, i.e. it is the first of the history
+    pub fn is_root_commit(&self, rev: &str) -> Result<bool> {
+        let parents = self.run_git(&["rev-list", "--parents", "-n", "1", rev])?;
+        Ok(parents.split_whitespace().count() == 1)
+    }
+
+    /// Whether the commit changes files, and only files matching `paths`
+    pub fn commit_only_touches(&self, rev: &str, paths: &[String]) -> Result<bool> {
+        if paths.is_empty() {
+            return Ok(false);
+        }
+        let changed = |paths: &[String]| -> Result<usize> {
+            let mut args = vec![
+                "diff-tree",
+                "-r",
+                "--root",
+                "--no-commit-id",
+                "--name-only",
+                rev,
+                "--",
+            ];
+            args.extend(paths.iter().map(String::as_str));
+            Ok(self.run_git(&args)?.lines().count())
+        };
+        let all = changed(&[])?;
+        Ok(all > 0 && changed(paths)? == all)
+    }
+
+    /// Whether a remote-tracking branch contains the commit
+    pub fn is_pushed(&self, rev: &str) -> Result<bool> {
+        Ok(!self
+            .run_git(&["branch", "-r", "--contains", rev])?
+            .is_empty())
+    }
+
+    /// Whether the remote has `tag`
+    pub fn remote_has_tag(&self, tag: &str) -> Result<bool> {
+        let refname = format!("refs/tags/{}", tag);
+        Ok(!self
+            .run_git(&["ls-remote", "--tags", &self.remote, &refname])?
+            .is_empty())
+    }
+
+    /// Delete a local tag
+    pub fn delete_tag(&self, tag: &str) -> Result<()> {
+        self.run_git(&["tag", "-d", tag])?;
+        Ok(())
+    }
+
+    /// Delete a tag on the remote
+    pub fn delete_remote_tag(&self, tag: &str) -> Result<()> {
+        let refname = format!("refs/tags/{}", tag);
+        self.run_git(&["push", &self.remote, "--delete", &refname])?;
+        Ok(())
+    }
+
+    /// Commit the inverse of `rev` with git's default revert message
+    pub fn revert(&self, rev: &str) -> Result<()> {
+        self.run_git(&["revert", "--no-edit", rev])?;
+        Ok(())
+    }
+
+    /// Move the current branch to `rev`, keeping uncommitted changes (refused
+    /// when they touch files that differ between HEAD and `rev`)
+    pub fn reset_keep(&self, rev: &str) -> Result<()> {
+        self.run_git(&["reset", "--keep", rev])?;
+        Ok(())
     }
 
     /// Get all tags matching a pattern
     pub fn tags(&self, pattern: Option<&str>) -> Result<Vec<String>> {
+        #[cfg(feature = "libgit2")]
+        if let Some(repo) = self.repository() {
+            return library::tags(&repo, pattern);
+        }
         let args = match pattern {
             Some(p) => vec!["tag", "-l", p],
             None => vec!["tag", "-l"],
@@ -110,26 +480,19 @@ impl GitOps {
     }
 
     /// Get all version tags, sorted by version (descending)
-    /// Recognizes tags like: v1.2.3, 1.2.3, v1.2.3-beta, etc.
-    pub fn get_version_tags(&self, prefix: &str) -> Result<Vec<(String, crate::version::Version)>> {
-        let all_tags = self.tags(None)?;
+    /// Recognizes tags like: v1.2.3, 1.2.3, v1.2.3-beta, etc., plus legacy schemes
+    pub fn get_version_tags(&self, prefix: &str) -> Result<Vec<(String, Version)>> {
+        // List only the prefixed tags, unless legacy schemes need to see every name.
+        // Tag names can't contain glob characters, so the prefix matches literally.
+        let pattern =
+            (self.legacy_tags.is_empty() && !prefix.is_empty()).then(|| format!("{}*", prefix));
+        let all_tags = self.tags(pattern.as_deref())?;
 
-        let mut version_tags: Vec<(String, crate::version::Version)> = all_tags
+        let mut version_tags: Vec<(String, Version)> = all_tags
             .into_iter()
             .filter_map(|tag| {
-                // Remove prefix if present
-                let version_str = if prefix.is_empty() {
-                    tag.clone()
-                } else if tag.starts_with(prefix) {
-                    tag[prefix.len()..].to_string()
-                } else {
-                    return None;
-                };
-
-                // Try to parse as version
-                crate::version::Version::parse(&version_str)
-                    .ok()
-                    .map(|v| (tag, v))
+                let version = self.version_of_tag(&tag, prefix)?;
+                Some((tag, version))
             })
             .collect();
 
@@ -139,36 +502,167 @@ impl GitOps {
         Ok(version_tags)
     }
 
-    /// Show the contents of a file at a given git reference
+    /// Map every tag to the commit it points at (annotated tags are peeled)
+    pub fn tag_commits(&self) -> Result<HashMap<String, String>> {
+        #[cfg(feature = "libgit2")]
+        if let Some(repo) = self.repository() {
+            return library::tag_commits(&repo);
+        }
+        let output = self.run_git(&[
+            "for-each-ref",
+            "--format=%(refname:short)%09%(objectname)%09%(*objectname)",
+            "refs/tags",
+        ])?;
+
+        Ok(output
+            .lines()
+            .filter_map(|line| {
+                let mut parts = line.split('\t');
+                let tag = parts.next()?;
+                let object = parts.next()?;
+                let peeled = parts.next().filter(|p| !p.is_empty()).unwrap_or(object);
+                Some((tag.to_string(), peeled.to_string()))
+            })
+            .collect())
+    }
+
+    /// Creation date (%Y-%m-%d) of every tag: the tagger date of annotated
+    /// tags, the commit date of lightweight ones
+    pub fn tag_dates(&self) -> Result<HashMap<String, String>> {
+        #[cfg(feature = "libgit2")]
+        if let Some(repo) = self.repository() {
+            return library::tag_dates(&repo);
+        }
+        let output = self.run_git(&[
+            "for-each-ref",
+            "--format=%(refname:short)%09%(creatordate:short)",
+            "refs/tags",
+        ])?;
+
+        Ok(output
+            .lines()
+            .filter_map(|line| {
+                let (tag, date) = line.split_once('\t')?;
+                Some((tag.to_string(), date.to_string()))
+            })
+            .collect())
+    }
+
+    /// Last commit of HEAD made before `date` (%Y-%m-%d), if any
+    pub fn commit_before(&self, date: &str) -> Result<Option<String>> {
+        let before = format!("--before={} 00:00", date);
+        let output = self.run_git(&["rev-list", "-1", &before, "HEAD"])?;
+        Ok(Some(output).filter(|sha| !sha.is_empty()))
+    }
+
+    /// Commits of HEAD since `date` (%Y-%m-%d) touching any of `paths`, newest
+    /// first, as (short SHA, date, subject)
+    pub fn commits_since(
+        &self,
+        date: &str,
+        paths: &[String],
+    ) -> Result<Vec<(String, String, String)>> {
+        let since = format!("--since={} 00:00", date);
+        let mut args = vec!["log", &since, "--format=%h%x09%cs%x09%s", "--"];
+        args.extend(paths.iter().map(String::as_str));
+
+        Ok(self
+            .run_git(&args)?
+            .lines()
+            .filter_map(|line| {
+                let mut parts = line.splitn(3, '\t');
+                Some((
+                    parts.next()?.to_string(),
+                    parts.next()?.to_string(),
+                    parts.next()?.to_string(),
+                ))
+            })
+            .collect())
+    }
+
+    /// Authors of the commits after `tag` (of all commits without one), once
+    /// each and without bots
+    pub fn authors_since(&self, tag: Option<&str>) -> Result<Vec<String>> {
+        #[cfg(feature = "libgit2")]
+        let names = match self.repository() {
+            Some(repo) => library::authors_since(&repo, tag)?,
+            None => self.authors_from_log(tag)?,
+        };
+        #[cfg(not(feature = "libgit2"))]
+        let names = self.authors_from_log(tag)?;
+
+        let mut authors: Vec<String> = Vec::new();
+        for author in names.iter().map(|name| name.trim()) {
+            if author.is_empty()
+                || author.ends_with("[bot]")
+                || authors.iter().any(|known| known == author)
+            {
+                continue;
+            }
+            authors.push(author.to_string());
+        }
+        Ok(authors)
+    }
+
+    fn authors_from_log(&self, tag: Option<&str>) -> Result<Vec<String>> {
+        let range = tag.map(|tag| format!("{}..HEAD", tag));
+        let mut args = vec!["log", "--format=%aN"];
+        args.extend(range.as_deref());
+        Ok(self.run_git(&args)?.lines().map(str::to_string).collect())
+    }
+
+    /// Show the contents of a file at a given git reference; like a checked out
+    /// file, `path` is relative to the work directory, not the repository root
     pub fn show_file_at_ref(&self, reference: &str, path: &str) -> Result<String> {
-        self.run_git(&["show", &format!("{}:{}", reference, path)])
+        #[cfg(feature = "libgit2")]
+        if let Some(repo) = self.repository() {
+            return library::show_file_at_ref(&repo, self.work_dir.as_deref(), reference, path);
+        }
+        self.run_git(&["show", &format!("{}:./{}", reference, path)])
+    }
+
+    /// Message of an annotated tag, `None` for lightweight tags
+    pub fn tag_message(&self, tag: &str) -> Result<Option<String>> {
+        #[cfg(feature = "libgit2")]
+        if let Some(repo) = self.repository() {
+            return library::tag_message(&repo, tag);
+        }
+        // `%(*objecttype)` is only set for annotated tags
+        let message = self.run_git(&[
+            "for-each-ref",
+            "--format=%(if)%(*objecttype)%(then)%(contents)%(end)",
+            &format!("refs/tags/{}", tag),
+        ])?;
+        Ok(Some(message).filter(|m| !m.is_empty()))
     }
 
     /// Get the date of a tag in %Y-%m-%d format
     pub fn tag_date(&self, tag: &str) -> Result<String> {
+        #[cfg(feature = "libgit2")]
+        if let Some(repo) = self.repository() {
+            return library::commit_date(&repo, tag);
+        }
         self.run_git(&["log", "-1", "--format=%cs", tag])
     }
 
+    /// Version of a tag under the current scheme, falling back to legacy schemes
+    fn version_of_tag(&self, tag: &str, prefix: &str) -> Option<Version> {
+        let current = tag
+            .strip_prefix(prefix)
+            .and_then(|version| Version::parse(version).ok());
+
+        current.or_else(|| {
+            self.legacy_tags
+                .iter()
+                .find_map(|legacy| legacy.version_of(tag))
+        })
+    }
+
     /// Get the latest version from git tags
-    pub fn get_latest_version(&self, prefix: &str) -> Result<Option<crate::version::Version>> {
+    pub fn get_latest_version(&self, prefix: &str) -> Result<Option<Version>> {
         let version_tags = self.get_version_tags(prefix)?;
         Ok(version_tags.into_iter().next().map(|(_, v)| v))
     }
-
-    /// Generate commit message from updates
-    pub fn generate_commit_message(updates: &[VersionUpdate], template: &str) -> String {
-        let packages_str = updates
-            .iter()
-            .map(|u| format!("{} = {}", u.package_name, u.new_version))
-            .collect::<Vec<_>>()
-            .join(", ");
-
-        let date = current_date();
-
-        template
-            .replace("{packages}", &packages_str)
-            .replace("{date}", &date)
-    }
 }
 
 impl Default for GitOps {
@@ -177,8 +671,84 @@ impl Default for GitOps {
     }
 }
 
-fn current_date() -> String {
-    Local::now().format("%Y-%m-%d").to_string()
+/// Entries of `git status --porcelain=v2 -z`
+fn parse_status(output: &str) -> Vec<DirtyFile> {
+    let mut files = Vec::new();
+    let mut entries = output.split('\0').filter(|entry| !entry.is_empty());
+    while let Some(entry) = entries.next() {
+        // Ordinary, renamed and unmerged entries: "<kind> <XY> <fields...> <path>"
+        let (xy, path) = match entry.split_at(1) {
+            ("1", rest) => (rest.split(' ').nth(1), rest.splitn(9, ' ').nth(8)),
+            ("2", rest) => {
+                // The original path of the rename follows
+                entries.next();
+                (rest.split(' ').nth(1), rest.splitn(10, ' ').nth(9))
+            }
+            ("u", rest) => (Some("U"), rest.splitn(11, ' ').nth(10)),
+            ("?", rest) => (Some(".."), rest.get(1..)),
+            _ => continue,
+        };
+        if let (Some(xy), Some(path)) = (xy, path) {
+            files.push(DirtyFile {
+                path: path.to_string(),
+                staged: !xy.starts_with('.'),
+            });
+        }
+    }
+    files
+}
+
+/// Trailer key of the release summary in annotated tag messages
+const TAG_METADATA_TRAILER: &str = "Bldr-Metadata";
+
+/// Pin changes of a release, embedded in its annotated tag message as a
+/// JSON trailer so the tag describes the release without the changelog file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TagMetadata {
+    pub version: String,
+    pub date: String,
+    /// Tag of the release the changes are relative to (none for the first release)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous: Option<String>,
+    #[serde(default)]
+    pub updated: Vec<VersionUpdate>,
+    #[serde(default)]
+    pub added: Vec<PinnedPackage>,
+    #[serde(default)]
+    pub removed: Vec<PinnedPackage>,
+}
+
+impl TagMetadata {
+    /// `Bldr-Metadata: {...}` line to append to the tag message
+    pub fn to_trailer(&self) -> String {
+        format!(
+            "{}: {}",
+            TAG_METADATA_TRAILER,
+            serde_json::to_string(self).unwrap_or_default()
+        )
+    }
+
+    /// Read the metadata back from a tag message; `None` for tags without it
+    pub fn parse(message: &str) -> Option<Self> {
+        message.lines().rev().find_map(|line| {
+            let json = line
+                .strip_prefix(TAG_METADATA_TRAILER)?
+                .strip_prefix(':')?
+                .trim();
+            serde_json::from_str(json).ok()
+        })
+    }
+
+    /// Tag message without the metadata trailer
+    pub fn strip(message: &str) -> String {
+        message
+            .lines()
+            .filter(|line| !line.starts_with(TAG_METADATA_TRAILER))
+            .collect::<Vec<_>>()
+            .join("\n")
+            .trim()
+            .to_string()
+    }
 }
 
 /// GitHub CLI operations
@@ -187,32 +757,43 @@ pub struct GitHubOps;
 impl GitHubOps {
     /// Check if gh CLI is available
     pub fn is_available() -> bool {
-        Command::new("gh")
-            .arg("--version")
-            .output()
-            .map(|o| o.status.success())
+        fixtures::run_command("gh", &["--version"], Command::new("gh").arg("--version"))
+            .map(|o| o.success)
             .unwrap_or(false)
     }
 
     /// Check if authenticated
     pub fn is_authenticated() -> Result<bool> {
-        let output = Command::new("gh")
-            .args(["auth", "status"])
-            .output()
+        let args = ["auth", "status"];
+        let output = fixtures::run_command("gh", &args, Command::new("gh").args(args))
             .map_err(|e| ReleaserError::GitError(format!("Failed to run gh: {}", e)))?;
 
-        Ok(output.status.success())
+        Ok(output.success)
     }
 
     /// Create a release
+    /// Create a release for an already pushed tag. `--verify-tag` keeps gh from creating
+    /// a missing tag on the default branch, and `repo` avoids relying on the local remote.
     pub fn create_release(
         tag: &str,
+        repo: Option<&str>,
         title: Option<&str>,
         notes: Option<&str>,
         draft: bool,
         prerelease: bool,
+        assets: &[PathBuf],
     ) -> Result<()> {
-        let mut args = vec!["release", "create", tag];
+        let assets: Vec<String> = assets
+            .iter()
+            .map(|path| path.to_string_lossy().into_owned())
+            .collect();
+        let mut args = vec!["release", "create", tag, "--verify-tag"];
+        args.extend(assets.iter().map(String::as_str));
+
+        if let Some(r) = repo {
+            args.push("--repo");
+            args.push(r);
+        }
 
         if let Some(t) = title {
             args.push("--title");
@@ -230,41 +811,402 @@ impl GitHubOps {
 
         if prerelease {
             args.push("--prerelease");
+            args.push("--latest=false");
         }
 
-        let output = Command::new("gh")
-            .args(&args)
-            .output()
+        let output = fixtures::run_command("gh", &args, Command::new("gh").args(&args))
             .map_err(|e| ReleaserError::GitError(format!("Failed to run gh: {}", e)))?;
 
-        if !output.status.success() {
+        if !output.success {
             let stderr = String::from_utf8_lossy(&output.stderr);
-            return Err(ReleaserError::GitError(format!(
-                "gh release create failed: {}",
-                stderr
-            )));
+            return Err(ReleaserError::CommandFailed {
+                command: "gh release create".to_string(),
+                message: stderr.trim().to_string(),
+            });
         }
 
         Ok(())
     }
+
+    /// Change the title and notes of the release of `tag`, or publish it when
+    /// it is a draft
+    pub fn edit_release(
+        tag: &str,
+        repo: Option<&str>,
+        title: Option<&str>,
+        notes: Option<&str>,
+        publish: bool,
+    ) -> Result<()> {
+        let mut args = vec!["release", "edit", tag];
+        if let Some(r) = repo {
+            args.push("--repo");
+            args.push(r);
+        }
+        if let Some(t) = title {
+            args.push("--title");
+            args.push(t);
+        }
+        if let Some(n) = notes {
+            args.push("--notes");
+            args.push(n);
+        }
+        if publish {
+            args.push("--draft=false");
+        }
+
+        let output = fixtures::run_command("gh", &args, Command::new("gh").args(&args))
+            .map_err(|e| ReleaserError::GitError(format!("Failed to run gh: {}", e)))?;
+
+        if !output.success {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(ReleaserError::CommandFailed {
+                command: "gh release edit".to_string(),
+                message: stderr.trim().to_string(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Open a pull request from `head` into `base` and return its URL
+    pub fn create_pull_request(
+        repo: Option<&str>,
+        head: &str,
+        base: &str,
+        title: &str,
+        body: &str,
+        draft: bool,
+    ) -> Result<String> {
+        let mut args = vec![
+            "pr", "create", "--head", head, "--base", base, "--title", title, "--body", body,
+        ];
+        if let Some(r) = repo {
+            args.push("--repo");
+            args.push(r);
+        }
+        if draft {
+            args.push("--draft");
+        }
+
+        let output = fixtures::run_command("gh", &args, Command::new("gh").args(&args))
+            .map_err(|e| ReleaserError::GitError(format!("Failed to run gh: {}", e)))?;
+
+        if !output.success {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(ReleaserError::CommandFailed {
+                command: "gh pr create".to_string(),
+                message: stderr.trim().to_string(),
+            });
+        }
+
+        // gh prints the URL of the new pull request last
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        Ok(stdout.lines().last().unwrap_or_default().trim().to_string())
+    }
+
+    /// Delete the release of `tag` (the tag itself is left alone); `false` when
+    /// there is no such release
+    pub fn delete_release(tag: &str, repo: Option<&str>) -> Result<bool> {
+        let mut args = vec!["release", "delete", tag, "--yes"];
+        if let Some(r) = repo {
+            args.push("--repo");
+            args.push(r);
+        }
+
+        let output = fixtures::run_command("gh", &args, Command::new("gh").args(&args))
+            .map_err(|e| ReleaserError::GitError(format!("Failed to run gh: {}", e)))?;
+
+        if !output.success {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            if stderr.contains("release not found") {
+                return Ok(false);
+            }
+            return Err(ReleaserError::CommandFailed {
+                command: "gh release delete".to_string(),
+                message: stderr.trim().to_string(),
+            });
+        }
+
+        Ok(true)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testing::temp_dir;
 
     #[test]
-    fn generates_commit_message_with_current_date() {
-        let updates = vec![VersionUpdate {
-            package_name: "example".to_string(),
-            old_version: "0.1.0".to_string(),
-            new_version: "0.2.0".to_string(),
-        }];
-
-        let message = GitOps::generate_commit_message(&updates, "Release on {date}: {packages}");
-
-        let expected_date = Local::now().format("%Y-%m-%d").to_string();
-        assert!(message.contains(&expected_date));
-        assert!(message.contains("example = 0.2.0"));
+    fn disables_prompts_per_command() {
+        let prompt = |git: &GitOps| {
+            let cmd = git.command();
+            let envs: HashMap<_, _> = cmd.get_envs().collect();
+            envs.get(std::ffi::OsStr::new("GIT_TERMINAL_PROMPT"))
+                .map(|value| value.map(|v| v.to_os_string()))
+        };
+
+        assert_eq!(
+            prompt(&GitOps::new().with_non_interactive(true)),
+            Some(Some("0".into()))
+        );
+        assert_eq!(prompt(&GitOps::new()), None);
+    }
+
+    #[test]
+    fn detects_detached_head() {
+        let tmp = temp_dir("git");
+        let dir = tmp.path();
+        let git = GitOps::new().with_work_dir(dir.to_string_lossy());
+
+        git.run_git(&["init", "-q", "-b", "main"]).unwrap();
+        git.run_git(&[
+            "-c",
+            "user.name=bldr",
+            "-c",
+            "user.email=bldr@example.com",
+            "commit",
+            "-q",
+            "--allow-empty",
+            "-m",
+            "init",
+        ])
+        .unwrap();
+        assert_eq!(git.current_branch().unwrap().as_deref(), Some("main"));
+
+        std::fs::write(dir.join("notes.txt"), "wip").unwrap();
+        git.stash(&["notes.txt"], "wip").unwrap();
+        assert!(git.dirty_files().unwrap().is_empty());
+        git.stash_pop().unwrap();
+        assert_eq!(git.dirty_files().unwrap().len(), 1);
+        std::fs::remove_file(dir.join("notes.txt")).unwrap();
+
+        for tag in ["v1.0.0", "v1.1.0", "1.2.0", "docs-v2", "vnext"] {
+            git.run_git(&["tag", tag]).unwrap();
+        }
+        let names = |tags: Vec<(String, Version)>| -> Vec<String> {
+            tags.into_iter().map(|(tag, _)| tag).collect()
+        };
+        assert_eq!(
+            names(git.get_version_tags("v").unwrap()),
+            ["v1.1.0", "v1.0.0"]
+        );
+        assert_eq!(names(git.get_version_tags("").unwrap())[0], "1.2.0");
+
+        git.run_git(&["checkout", "-q", "--detach"]).unwrap();
+        assert_eq!(git.current_branch().unwrap(), None);
+        assert!(git.push(None, false).is_err());
+
+        let err = git.check_push_access(Some("main")).unwrap_err();
+        assert!(err.to_string().contains("Cannot push to origin"));
+        let err = GitOps::new()
+            .with_work_dir(dir.to_string_lossy())
+            .with_remote("upstream")
+            .check_push_access(None)
+            .unwrap_err();
+        assert!(err.to_string().contains("Cannot push to upstream"));
+    }
+
+    #[test]
+    fn shows_files_relative_to_the_work_dir() {
+        let tmp = temp_dir("git-subdir");
+        let dir = tmp.path();
+        std::fs::create_dir_all(dir.join("cust/a")).unwrap();
+        let root = GitOps::new().with_work_dir(dir.to_string_lossy());
+        root.run_git(&["init", "-q", "-b", "main"]).unwrap();
+        std::fs::write(dir.join("versions.cfg"), "[versions]\nsix = 1.0\n").unwrap();
+        std::fs::write(dir.join("cust/a/versions.cfg"), "[versions]\nsix = 2.0\n").unwrap();
+        root.run_git(&["add", "."]).unwrap();
+        root.run_git(&[
+            "-c",
+            "user.name=bldr",
+            "-c",
+            "user.email=bldr@example.com",
+            "commit",
+            "-q",
+            "-m",
+            "init",
+        ])
+        .unwrap();
+        root.run_git(&["tag", "a-1.0.0"]).unwrap();
+
+        // A workspace project in a subdirectory reads its own files at a tag
+        let project = GitOps::new().with_work_dir(dir.join("cust/a").to_string_lossy());
+        let content = project.show_file_at_ref("a-1.0.0", "versions.cfg").unwrap();
+        assert!(content.contains("six = 2.0"));
+        let content = project
+            .show_file_at_ref("a-1.0.0", "../../versions.cfg")
+            .unwrap();
+        assert!(content.contains("six = 1.0"));
+        let content = root.show_file_at_ref("a-1.0.0", "versions.cfg").unwrap();
+        assert!(content.contains("six = 1.0"));
+    }
+
+    #[test]
+    fn counts_commits_behind_the_remote() {
+        let tmp = temp_dir("git-remote");
+        let dir = tmp.path();
+        let at = |name: &str| GitOps::new().with_work_dir(dir.join(name).to_string_lossy());
+        let commit = |git: &GitOps| {
+            git.run_git(&[
+                "-c",
+                "user.name=bldr",
+                "-c",
+                "user.email=bldr@example.com",
+                "commit",
+                "-q",
+                "--allow-empty",
+                "-m",
+                "change",
+            ])
+            .unwrap()
+        };
+
+        let root = GitOps::new().with_work_dir(dir.to_string_lossy());
+        root.run_git(&["init", "-q", "--bare", "-b", "main", "remote.git"])
+            .unwrap();
+        root.run_git(&["clone", "-q", "remote.git", "ahead"])
+            .unwrap();
+        let ahead = at("ahead");
+        commit(&ahead);
+        ahead.run_git(&["push", "-q", "origin", "main"]).unwrap();
+        root.run_git(&["clone", "-q", "remote.git", "stale"])
+            .unwrap();
+        let stale = at("stale");
+        assert_eq!(stale.default_branch().as_deref(), Some("main"));
+        assert_eq!(at("stale").with_remote("upstream").default_branch(), None);
+
+        commit(&ahead);
+        ahead.run_git(&["push", "-q", "origin", "main"]).unwrap();
+
+        assert_eq!(stale.commits_behind("main").unwrap(), Some(1));
+        assert_eq!(stale.commits_behind("missing").unwrap(), None);
+        stale.pull_rebase("main").unwrap();
+        assert_eq!(stale.commits_behind("main").unwrap(), Some(0));
+    }
+
+    #[test]
+    fn rolls_back_a_pushed_release() {
+        let tmp = temp_dir("git-rollback");
+        let dir = tmp.path();
+        let root = GitOps::new().with_work_dir(dir.to_string_lossy());
+        root.run_git(&["init", "-q", "--bare", "-b", "main", "remote.git"])
+            .unwrap();
+        root.run_git(&["clone", "-q", "remote.git", "work"])
+            .unwrap();
+        let git = GitOps::new().with_work_dir(dir.join("work").to_string_lossy());
+        let identity = ["-c", "user.name=bldr", "-c", "user.email=bldr@example.com"];
+
+        for version in ["1.0.0", "1.1.0"] {
+            std::fs::write(dir.join("work/versions.cfg"), version).unwrap();
+            git.add("versions.cfg").unwrap();
+            let mut args = identity.to_vec();
+            args.extend(["commit", "-q", "-m", version]);
+            git.run_git(&args).unwrap();
+            git.tag(version, None).unwrap();
+        }
+        git.run_git(&["push", "-q", "origin", "main", "--tags"])
+            .unwrap();
+
+        let release = git.head_commit().unwrap();
+        assert_eq!(git.commit_subject(&release).unwrap(), "1.1.0");
+        assert!(git.is_pushed(&release).unwrap());
+        assert!(git.remote_has_tag("1.1.0").unwrap());
+
+        git.delete_remote_tag("1.1.0").unwrap();
+        git.delete_tag("1.1.0").unwrap();
+        assert!(!git.remote_has_tag("1.1.0").unwrap());
+        assert_eq!(git.tags(None).unwrap(), vec!["1.0.0"]);
+
+        let mut args = identity.to_vec();
+        args.extend(["revert", "--no-edit", release.as_str()]);
+        git.run_git(&args).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(dir.join("work/versions.cfg")).unwrap(),
+            "1.0.0"
+        );
+
+        // A local release commit is dropped instead
+        git.reset_keep("HEAD~2").unwrap();
+        assert_eq!(git.commit_subject("HEAD").unwrap(), "1.0.0");
+    }
+
+    #[test]
+    fn parses_porcelain_status() {
+        let output = [
+            "1 .M N... 100644 100644 100644 3a1b 3a1b versions.cfg",
+            "1 M. N... 100644 100644 100644 3a1b 4c2d docs/my notes.md",
+            "2 R. N... 100644 100644 100644 3a1b 3a1b R100 sources.cfg",
+            "old-sources.cfg",
+            "? CHANGELOG.md",
+            "",
+        ]
+        .join("\0");
+
+        let file = |path: &str, staged| DirtyFile {
+            path: path.to_string(),
+            staged,
+        };
+        assert_eq!(
+            parse_status(&output),
+            vec![
+                file("versions.cfg", false),
+                file("docs/my notes.md", true),
+                file("sources.cfg", true),
+                file("CHANGELOG.md", false),
+            ]
+        );
+    }
+
+    #[test]
+    fn maps_legacy_tags_into_versions() {
+        let git = GitOps::new().with_legacy_tags(&[
+            LegacyTagScheme {
+                pattern: r"prod-(\d{4})-(\d{2})-(\d+)".to_string(),
+                version: None,
+            },
+            LegacyTagScheme {
+                pattern: r"release_(?P<year>\d+)_(?P<n>\d+)".to_string(),
+                version: Some("${year}.0.${n}".to_string()),
+            },
+        ]);
+
+        let version = |tag| git.version_of_tag(tag, "v").map(|v| v.to_string());
+
+        assert_eq!(version("v2025.1.0").as_deref(), Some("2025.1.0"));
+        assert_eq!(version("prod-2024-05-2").as_deref(), Some("2024.5.2"));
+        assert_eq!(version("release_2023_7").as_deref(), Some("2023.0.7"));
+        assert_eq!(version("prod-2024-05-2-hotfix"), None);
+        assert_eq!(version("2025.1.0"), None);
+    }
+
+    #[test]
+    fn round_trips_tag_metadata() {
+        let metadata = TagMetadata {
+            version: "1.8.0".to_string(),
+            date: "2025-06-03".to_string(),
+            previous: Some("v1.7.0".to_string()),
+            updated: vec![VersionUpdate {
+                package_name: "plone.api".to_string(),
+                old_version: "2.0.0".to_string(),
+                new_version: "2.1.0".to_string(),
+            }],
+            added: vec![PinnedPackage {
+                name: "plone.foo".to_string(),
+                version: "1.0.0".to_string(),
+            }],
+            removed: Vec::new(),
+        };
+        let message = format!(
+            "Release 1.8.0\n\n- plone.api 2.1.0\n\n{}",
+            metadata.to_trailer()
+        );
+
+        assert!(message.ends_with("\nBldr-Metadata: {\"version\":\"1.8.0\",\"date\":\"2025-06-03\",\"previous\":\"v1.7.0\",\"updated\":[{\"package_name\":\"plone.api\",\"old_version\":\"2.0.0\",\"new_version\":\"2.1.0\"}],\"added\":[{\"name\":\"plone.foo\",\"version\":\"1.0.0\"}],\"removed\":[]}"));
+        assert_eq!(TagMetadata::parse(&message), Some(metadata));
+        assert_eq!(TagMetadata::parse("Release 1.7.0"), None);
+        assert_eq!(
+            TagMetadata::strip(&message),
+            "Release 1.8.0\n\n- plone.api 2.1.0"
+        );
     }
 }
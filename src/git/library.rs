@@ -0,0 +1,301 @@
+//! Read-only repository access through libgit2, so tags, files at a tag and
+//! the worktree status don't need a `git` binary. Nothing here writes: staging,
+//! commits, tags, stashes, resets, reverts and pushes still run `git`, which
+//! honours hooks, signing and credential helpers.
+
+use std::collections::HashMap;
+use std::path::Component;
+
+use chrono::{FixedOffset, TimeZone};
+use git2::{Repository, Sort, Status, StatusOptions};
+
+use super::DirtyFile;
+use crate::error::{ReleaserError, Result};
+
+fn git_error(e: git2::Error) -> ReleaserError {
+    ReleaserError::GitError(e.message().to_string())
+}
+
+/// Repository containing `work_dir` (or the current directory, honouring
+/// `GIT_DIR` and friends)
+pub fn open(work_dir: Option<&str>) -> Option<Repository> {
+    match work_dir {
+        Some(dir) => Repository::discover(dir).ok(),
+        None => Repository::open_from_env().ok(),
+    }
+}
+
+/// Name of the checked out branch, `None` when HEAD is detached
+pub fn current_branch(repo: &Repository) -> Result<Option<String>> {
+    let head = repo.find_reference("HEAD").map_err(git_error)?;
+    if let Some(target) = head.symbolic_target() {
+        // Also covers a branch without commits yet, like `git symbolic-ref`
+        let branch = target.strip_prefix("refs/heads/").unwrap_or(target);
+        return Ok(Some(branch.to_string()));
+    }
+    repo.head().map_err(git_error)?;
+    Ok(None)
+}
+
+/// Branch `<remote>/HEAD` points at
+pub fn default_branch(repo: &Repository, remote: &str) -> Option<String> {
+    let head = repo
+        .find_reference(&format!("refs/remotes/{}/HEAD", remote))
+        .ok()?;
+    let prefix = format!("refs/remotes/{}/", remote);
+    let branch = head.symbolic_target()?.strip_prefix(&prefix)?;
+    Some(branch.to_string())
+}
+
+/// Files with uncommitted changes, untracked files listed one by one
+pub fn dirty_files(repo: &Repository) -> Result<Vec<DirtyFile>> {
+    let mut options = StatusOptions::new();
+    options
+        .include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .include_ignored(false);
+    let statuses = repo.statuses(Some(&mut options)).map_err(git_error)?;
+
+    let staged = Status::INDEX_NEW
+        | Status::INDEX_MODIFIED
+        | Status::INDEX_DELETED
+        | Status::INDEX_RENAMED
+        | Status::INDEX_TYPECHANGE
+        | Status::CONFLICTED;
+    Ok(statuses
+        .iter()
+        .filter_map(|entry| {
+            Some(DirtyFile {
+                path: entry.path()?.to_string(),
+                staged: entry.status().intersects(staged),
+            })
+        })
+        .collect())
+}
+
+/// Tag names matching a `git tag -l` style glob, sorted by name
+pub fn tags(repo: &Repository, pattern: Option<&str>) -> Result<Vec<String>> {
+    let names = repo.tag_names(pattern).map_err(git_error)?;
+    let mut tags: Vec<String> = names.iter().flatten().map(str::to_string).collect();
+    tags.sort();
+    Ok(tags)
+}
+
+/// Every tag with the object it points at directly
+fn tag_refs(repo: &Repository) -> Result<Vec<(String, git2::Object<'_>)>> {
+    let mut tags = Vec::new();
+    for reference in repo.references().map_err(git_error)? {
+        let reference = reference.map_err(git_error)?;
+        let Some(tag) = reference
+            .name()
+            .and_then(|name| name.strip_prefix("refs/tags/"))
+        else {
+            continue;
+        };
+        let tag = tag.to_string();
+        let Some(target) = reference.resolve().map_err(git_error)?.target() else {
+            continue;
+        };
+        tags.push((tag, repo.find_object(target, None).map_err(git_error)?));
+    }
+    Ok(tags)
+}
+
+/// Commit (or other object) every tag points at, annotated tags peeled
+pub fn tag_commits(repo: &Repository) -> Result<HashMap<String, String>> {
+    Ok(tag_refs(repo)?
+        .into_iter()
+        .map(|(tag, object)| {
+            let peeled = object
+                .as_tag()
+                .map_or(object.id(), |annotated| annotated.target_id());
+            (tag, peeled.to_string())
+        })
+        .collect())
+}
+
+/// Tagger date of annotated tags, commit date of lightweight ones
+pub fn tag_dates(repo: &Repository) -> Result<HashMap<String, String>> {
+    let mut dates = HashMap::new();
+    for (tag, object) in tag_refs(repo)? {
+        let time = match object.as_tag() {
+            Some(annotated) => annotated.tagger().map(|tagger| tagger.when()),
+            None => object
+                .peel_to_commit()
+                .ok()
+                .map(|commit| commit.committer().when()),
+        };
+        if let Some(date) = time.and_then(short_date) {
+            dates.insert(tag, date);
+        }
+    }
+    Ok(dates)
+}
+
+/// Message of an annotated tag, `None` for lightweight ones
+pub fn tag_message(repo: &Repository, tag: &str) -> Result<Option<String>> {
+    let object = repo
+        .revparse_single(&format!("refs/tags/{}", tag))
+        .map_err(git_error)?;
+    Ok(object
+        .as_tag()
+        .and_then(|annotated| annotated.message())
+        .map(|message| message.trim().to_string()))
+}
+
+/// Commit date of the commit `reference` resolves to
+pub fn commit_date(repo: &Repository, reference: &str) -> Result<String> {
+    let commit = repo
+        .revparse_single(reference)
+        .and_then(|object| object.peel_to_commit())
+        .map_err(git_error)?;
+    let date = short_date(commit.committer().when());
+    date.ok_or_else(|| ReleaserError::GitError(format!("Invalid commit date on {}", reference)))
+}
+
+/// Contents of `path` (relative to `work_dir`) at `reference`
+pub fn show_file_at_ref(
+    repo: &Repository,
+    work_dir: Option<&str>,
+    reference: &str,
+    path: &str,
+) -> Result<String> {
+    let path = repo_path(repo, work_dir, path)?;
+    let blob = repo
+        .revparse_single(&format!("{}:{}", reference, path))
+        .and_then(|object| object.peel_to_blob())
+        .map_err(git_error)?;
+    Ok(String::from_utf8_lossy(blob.content()).trim().to_string())
+}
+
+/// `path` relative to the repository root instead of `work_dir`, as revparse
+/// doesn't take the `ref:./path` form of `git show`
+fn repo_path(repo: &Repository, work_dir: Option<&str>, path: &str) -> Result<String> {
+    let root = repo.workdir().and_then(|root| root.canonicalize().ok());
+    let dir = std::path::Path::new(work_dir.unwrap_or(".")).canonicalize()?;
+    let prefix = root
+        .and_then(|root| dir.strip_prefix(root).ok().map(|p| p.to_path_buf()))
+        .unwrap_or_default();
+
+    let mut parts: Vec<String> = Vec::new();
+    for component in prefix.join(path).components() {
+        match component {
+            Component::ParentDir => {
+                parts.pop();
+            }
+            Component::Normal(part) => parts.push(part.to_string_lossy().into_owned()),
+            _ => {}
+        }
+    }
+    Ok(parts.join("/"))
+}
+
+/// Author names (mailmap applied) of the commits of HEAD not reachable from
+/// `tag`, newest first
+pub fn authors_since(repo: &Repository, tag: Option<&str>) -> Result<Vec<String>> {
+    let mut walk = repo.revwalk().map_err(git_error)?;
+    walk.set_sorting(Sort::TIME).map_err(git_error)?;
+    walk.push_head().map_err(git_error)?;
+    if let Some(tag) = tag {
+        let since = repo
+            .revparse_single(tag)
+            .and_then(|object| object.peel_to_commit())
+            .map_err(git_error)?;
+        walk.hide(since.id()).map_err(git_error)?;
+    }
+
+    let mailmap = repo.mailmap().map_err(git_error)?;
+    let mut authors = Vec::new();
+    for oid in walk {
+        let commit = repo
+            .find_commit(oid.map_err(git_error)?)
+            .map_err(git_error)?;
+        let author = commit.author_with_mailmap(&mailmap).map_err(git_error)?;
+        authors.push(String::from_utf8_lossy(author.name_bytes()).into_owned());
+    }
+    Ok(authors)
+}
+
+/// %Y-%m-%d in the timezone the date was recorded in, like `git log --format=%cs`
+fn short_date(time: git2::Time) -> Option<String> {
+    let offset = FixedOffset::east_opt(time.offset_minutes() * 60)?;
+    let date = offset.timestamp_opt(time.seconds(), 0).single()?;
+    Some(date.format("%Y-%m-%d").to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::temp_dir;
+    use git2::{Signature, Time};
+
+    #[test]
+    fn reads_tags_and_files_without_git() {
+        let tmp = temp_dir("libgit2");
+        let dir = tmp.path();
+        let repo = Repository::init(dir).unwrap();
+        repo.set_head("refs/heads/main").unwrap();
+        assert_eq!(current_branch(&repo).unwrap().as_deref(), Some("main"));
+
+        std::fs::write(dir.join("versions.cfg"), "[versions]\nplone.api = 2.0.0\n").unwrap();
+        let mut index = repo.index().unwrap();
+        index
+            .add_path(std::path::Path::new("versions.cfg"))
+            .unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        // 2025-06-02 23:30 UTC is already June 3rd in UTC+2
+        let signature =
+            Signature::new("bldr", "bldr@example.com", &Time::new(1748907000, 120)).unwrap();
+        let commit = repo
+            .commit(Some("HEAD"), &signature, &signature, "init", &tree, &[])
+            .unwrap();
+        let commit = repo.find_object(commit, None).unwrap();
+
+        repo.tag_lightweight("v1.0.0", &commit, false).unwrap();
+        let tagger = Signature::new("bldr", "bldr@example.com", &Time::new(1749600000, 0)).unwrap();
+        repo.tag("v1.1.0", &commit, &tagger, "Release 1.1.0", false)
+            .unwrap();
+        repo.tag_lightweight("other", &commit, false).unwrap();
+
+        assert_eq!(
+            tags(&repo, None).unwrap(),
+            vec!["other", "v1.0.0", "v1.1.0"]
+        );
+        assert_eq!(tags(&repo, Some("v*")).unwrap(), vec!["v1.0.0", "v1.1.0"]);
+
+        let commits = tag_commits(&repo).unwrap();
+        assert_eq!(commits["v1.1.0"], commit.id().to_string());
+        assert_eq!(commits["v1.0.0"], commit.id().to_string());
+
+        let dates = tag_dates(&repo).unwrap();
+        assert_eq!(dates["v1.0.0"], "2025-06-03");
+        assert_eq!(dates["v1.1.0"], "2025-06-11");
+        assert_eq!(commit_date(&repo, "v1.1.0").unwrap(), "2025-06-03");
+        assert_eq!(
+            tag_message(&repo, "v1.1.0").unwrap().as_deref(),
+            Some("Release 1.1.0")
+        );
+        assert_eq!(tag_message(&repo, "v1.0.0").unwrap(), None);
+
+        let work_dir = dir.to_str();
+        assert_eq!(
+            show_file_at_ref(&repo, work_dir, "v1.0.0", "versions.cfg").unwrap(),
+            "[versions]\nplone.api = 2.0.0"
+        );
+        assert!(show_file_at_ref(&repo, work_dir, "v1.0.0", "missing.cfg").is_err());
+
+        assert!(dirty_files(&repo).unwrap().is_empty());
+        std::fs::write(dir.join("new.cfg"), "").unwrap();
+        assert_eq!(
+            dirty_files(&repo).unwrap(),
+            vec![DirtyFile {
+                path: "new.cfg".to_string(),
+                staged: false
+            }]
+        );
+
+        assert_eq!(authors_since(&repo, None).unwrap(), vec!["bldr"]);
+        assert!(authors_since(&repo, Some("v1.0.0")).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,779 @@
+//! GitHub releases through the REST API with a token (`GITHUB_TOKEN`), so CI
+//! containers don't need the `gh` binary; the CLI is used when no token is set
+
+use std::path::{Path, PathBuf};
+
+use reqwest::header::{ACCEPT, AUTHORIZATION, CONTENT_TYPE};
+use reqwest::{Client, Method, RequestBuilder, StatusCode};
+use serde_json::{json, Value};
+
+use crate::config::{Config, GitHubConfig, HttpConfig};
+use crate::error::{ReleaserError, Result};
+use crate::git::{GitHubOps, GitOps};
+use crate::http::{self, RetryPolicy};
+
+const USER_AGENT: &str = concat!("bldr/", env!("CARGO_PKG_VERSION"));
+const API_VERSION_HEADER: &str = "X-GitHub-Api-Version";
+const API_VERSION: &str = "2022-11-28";
+
+/// Token variables read when `github.token_env` is not set, in order
+const DEFAULT_TOKEN_ENVS: [&str; 2] = ["GITHUB_TOKEN", "GH_TOKEN"];
+
+/// Where GitHub releases are made
+pub enum GitHub {
+    Api(GitHubClient),
+    /// The `gh` CLI, for `repo` (or the repository of the local remote)
+    Cli {
+        repo: Option<String>,
+    },
+}
+
+impl GitHub {
+    /// The REST API when a token and the repository are known, else an
+    /// installed and authenticated `gh`; `None` when neither is usable
+    pub fn connect(config: &Config, git: &GitOps) -> Result<Option<Self>> {
+        let repository = config
+            .github
+            .repository
+            .clone()
+            .or_else(|| git.remote_url().and_then(|url| repository_of(&url)));
+        if let (Some(token), Some(repository)) = (token(&config.github), repository) {
+            let client = GitHubClient::new(&config.github, &config.http, token, repository)?;
+            return Ok(Some(GitHub::Api(client)));
+        }
+
+        if GitHubOps::is_available() && GitHubOps::is_authenticated()? {
+            return Ok(Some(GitHub::Cli {
+                repo: config.github.repository.clone(),
+            }));
+        }
+        Ok(None)
+    }
+
+    /// Why `connect` found nothing to use
+    pub fn unavailable_reason() -> &'static str {
+        if GitHubOps::is_available() {
+            "not authenticated to GitHub (set GITHUB_TOKEN or run 'gh auth login')"
+        } else {
+            "no GITHUB_TOKEN set and GitHub CLI (gh) not found"
+        }
+    }
+
+    /// Create a release for an already pushed tag, with `assets` attached; a
+    /// `prerelease` is not marked as the latest release
+    pub async fn create_release(
+        &self,
+        tag: &str,
+        title: Option<&str>,
+        notes: Option<&str>,
+        draft: bool,
+        prerelease: bool,
+        assets: &[PathBuf],
+    ) -> Result<()> {
+        match self {
+            GitHub::Api(client) => {
+                client
+                    .create_release(tag, title, notes, draft, prerelease, assets)
+                    .await
+            }
+            GitHub::Cli { repo } => GitHubOps::create_release(
+                tag,
+                repo.as_deref(),
+                title,
+                notes,
+                draft,
+                prerelease,
+                assets,
+            ),
+        }
+    }
+
+    /// Delete the release of `tag` (not the tag); `false` when there is none
+    pub async fn delete_release(&self, tag: &str) -> Result<bool> {
+        match self {
+            GitHub::Api(client) => client.delete_release(tag).await,
+            GitHub::Cli { repo } => GitHubOps::delete_release(tag, repo.as_deref()),
+        }
+    }
+
+    /// Change the title and notes of the release of `tag`, or publish it when
+    /// it is a draft
+    pub async fn edit_release(
+        &self,
+        tag: &str,
+        title: Option<&str>,
+        notes: Option<&str>,
+        publish: bool,
+    ) -> Result<()> {
+        match self {
+            GitHub::Api(client) => client.edit_release(tag, title, notes, publish).await,
+            GitHub::Cli { repo } => {
+                GitHubOps::edit_release(tag, repo.as_deref(), title, notes, publish)
+            }
+        }
+    }
+
+    /// Open a pull request from the pushed branch `head` into `base` and
+    /// return its URL
+    pub async fn create_pull_request(
+        &self,
+        head: &str,
+        base: &str,
+        title: &str,
+        body: &str,
+        draft: bool,
+    ) -> Result<String> {
+        match self {
+            GitHub::Api(client) => {
+                client
+                    .create_pull_request(head, base, title, body, draft)
+                    .await
+            }
+            GitHub::Cli { repo } => {
+                GitHubOps::create_pull_request(repo.as_deref(), head, base, title, body, draft)
+            }
+        }
+    }
+}
+
+/// Token-authenticated client for the releases of one repository
+pub struct GitHubClient {
+    client: Client,
+    retry: RetryPolicy,
+    api_url: String,
+    token: String,
+    /// `owner/repo`
+    repository: String,
+}
+
+impl GitHubClient {
+    pub fn new(
+        config: &GitHubConfig,
+        http_config: &HttpConfig,
+        token: String,
+        repository: String,
+    ) -> Result<Self> {
+        let builder = Client::builder().user_agent(USER_AGENT);
+        Ok(Self {
+            client: http::configure_client(builder, http_config)?.build()?,
+            retry: RetryPolicy::from_config(http_config),
+            api_url: config.api_url.trim_end_matches('/').to_string(),
+            token,
+            repository,
+        })
+    }
+
+    /// Send a request to `endpoint` (relative to the repository) and return
+    /// the status with the parsed body (`Null` when empty)
+    async fn request(
+        &self,
+        method: Method,
+        endpoint: &str,
+        body: Option<&Value>,
+    ) -> Result<(StatusCode, Value)> {
+        let url = format!("{}/repos/{}/{}", self.api_url, self.repository, endpoint);
+        let mut request = self.client.request(method, &url);
+        if let Some(body) = body {
+            request = request.json(body);
+        }
+        self.send(request).await
+    }
+
+    async fn send(&self, request: RequestBuilder) -> Result<(StatusCode, Value)> {
+        let request = request
+            .header(ACCEPT, "application/vnd.github+json")
+            .header(API_VERSION_HEADER, API_VERSION)
+            .header(AUTHORIZATION, format!("Bearer {}", self.token));
+        let response = self.retry.send(request).await?;
+        let status = response.status();
+        let text = response.text().await?;
+        let value = serde_json::from_str(&text).unwrap_or(Value::Null);
+        Ok((status, value))
+    }
+
+    fn api_error(&self, endpoint: &str, status: StatusCode, body: &Value) -> ReleaserError {
+        let mut message = body["message"]
+            .as_str()
+            .unwrap_or_else(|| status.canonical_reason().unwrap_or("request failed"))
+            .to_string();
+        // Validation failures explain themselves in `errors`
+        let details: Vec<&str> = body["errors"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|error| error["message"].as_str().or(error["code"].as_str()))
+            .collect();
+        if !details.is_empty() {
+            message = format!("{} ({})", message, details.join(", "));
+        }
+        ReleaserError::GitHubApi {
+            endpoint: format!("repos/{}/{}", self.repository, endpoint),
+            status: status.as_u16(),
+            message,
+        }
+    }
+
+    /// Create a release with `assets` attached, refusing (like `gh
+    /// --verify-tag`) when the tag is not on GitHub, so no tag is made on the
+    /// default branch instead
+    pub async fn create_release(
+        &self,
+        tag: &str,
+        title: Option<&str>,
+        notes: Option<&str>,
+        draft: bool,
+        prerelease: bool,
+        assets: &[PathBuf],
+    ) -> Result<()> {
+        let endpoint = format!("git/ref/tags/{}", tag);
+        let (status, body) = self.request(Method::GET, &endpoint, None).await?;
+        if !status.is_success() {
+            return Err(self.api_error(&endpoint, status, &body));
+        }
+
+        let mut release = json!({
+            "tag_name": tag,
+            "draft": draft,
+            "prerelease": prerelease,
+        });
+        if prerelease {
+            release["make_latest"] = "false".into();
+        }
+        if let Some(title) = title {
+            release["name"] = title.into();
+        }
+        if let Some(notes) = notes {
+            release["body"] = notes.into();
+        }
+        let (status, body) = self
+            .request(Method::POST, "releases", Some(&release))
+            .await?;
+        if !status.is_success() {
+            return Err(self.api_error("releases", status, &body));
+        }
+
+        // `upload_url` is a template such as `.../releases/1/assets{?name,label}`
+        let id = body["id"].as_u64().unwrap_or_default();
+        let upload_url = body["upload_url"].as_str().unwrap_or_default();
+        let upload_url = upload_url.split('{').next().unwrap_or_default();
+        for (index, path) in assets.iter().enumerate() {
+            if let Err(e) = self.upload_asset(id, upload_url, path).await {
+                // The release stays: say which assets it lacks instead of leaving that unnoticed
+                let missing: Vec<String> = assets[index..]
+                    .iter()
+                    .map(|path| {
+                        asset_name(path).map_or_else(|_| path.display().to_string(), str::to_string)
+                    })
+                    .collect();
+                return Err(ReleaserError::PartialRelease {
+                    url: body["html_url"].as_str().unwrap_or(tag).to_string(),
+                    missing: missing.join(", "),
+                    source: Box::new(e),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    async fn upload_asset(&self, id: u64, upload_url: &str, path: &Path) -> Result<()> {
+        let name = asset_name(path)?;
+        let content = std::fs::read(path).map_err(|source| ReleaserError::FileError {
+            path: path.display().to_string(),
+            source,
+        })?;
+        let request = self
+            .client
+            .post(upload_url)
+            .query(&[("name", name)])
+            .header(CONTENT_TYPE, "application/octet-stream")
+            .body(content);
+        let (status, body) = self.send(request).await?;
+        if !status.is_success() {
+            let endpoint = format!("releases/{}/assets?name={}", id, name);
+            return Err(self.api_error(&endpoint, status, &body));
+        }
+        Ok(())
+    }
+
+    pub async fn delete_release(&self, tag: &str) -> Result<bool> {
+        let endpoint = format!("releases/tags/{}", tag);
+        let (status, body) = self.request(Method::GET, &endpoint, None).await?;
+        if status == StatusCode::NOT_FOUND {
+            return Ok(false);
+        }
+        let Some(id) = body["id"].as_u64().filter(|_| status.is_success()) else {
+            return Err(self.api_error(&endpoint, status, &body));
+        };
+
+        let endpoint = format!("releases/{}", id);
+        let (status, body) = self.request(Method::DELETE, &endpoint, None).await?;
+        if !status.is_success() {
+            return Err(self.api_error(&endpoint, status, &body));
+        }
+        Ok(true)
+    }
+
+    /// Id of the release of `tag`. Drafts have no tag yet for
+    /// `releases/tags/{tag}`, so they are looked up in the latest releases
+    async fn release_id(&self, tag: &str) -> Result<u64> {
+        let endpoint = format!("releases/tags/{}", tag);
+        let (status, body) = self.request(Method::GET, &endpoint, None).await?;
+        if let Some(id) = body["id"].as_u64().filter(|_| status.is_success()) {
+            return Ok(id);
+        }
+        if status != StatusCode::NOT_FOUND {
+            return Err(self.api_error(&endpoint, status, &body));
+        }
+
+        let listing = "releases?per_page=100";
+        let (status, releases) = self.request(Method::GET, listing, None).await?;
+        if !status.is_success() {
+            return Err(self.api_error(listing, status, &releases));
+        }
+        releases
+            .as_array()
+            .into_iter()
+            .flatten()
+            .find(|release| release["tag_name"] == tag)
+            .and_then(|release| release["id"].as_u64())
+            .ok_or_else(|| self.api_error(&endpoint, StatusCode::NOT_FOUND, &body))
+    }
+
+    pub async fn edit_release(
+        &self,
+        tag: &str,
+        title: Option<&str>,
+        notes: Option<&str>,
+        publish: bool,
+    ) -> Result<()> {
+        let id = self.release_id(tag).await?;
+        let mut release = json!({});
+        if let Some(title) = title {
+            release["name"] = title.into();
+        }
+        if let Some(notes) = notes {
+            release["body"] = notes.into();
+        }
+        if publish {
+            release["draft"] = false.into();
+        }
+
+        let endpoint = format!("releases/{}", id);
+        let (status, body) = self
+            .request(Method::PATCH, &endpoint, Some(&release))
+            .await?;
+        if !status.is_success() {
+            return Err(self.api_error(&endpoint, status, &body));
+        }
+        Ok(())
+    }
+
+    pub async fn create_pull_request(
+        &self,
+        head: &str,
+        base: &str,
+        title: &str,
+        body: &str,
+        draft: bool,
+    ) -> Result<String> {
+        let pull = json!({
+            "head": head,
+            "base": base,
+            "title": title,
+            "body": body,
+            "draft": draft,
+        });
+        let (status, response) = self.request(Method::POST, "pulls", Some(&pull)).await?;
+        if !status.is_success() {
+            return Err(self.api_error("pulls", status, &response));
+        }
+        Ok(response["html_url"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string())
+    }
+}
+
+/// Files to attach to a release, written to `dir` when generated: the
+/// `github.assets` files (relative to `work_dir`), the pins as `constraints.txt`,
+/// and a `SHA256SUMS` of all of them with `github.asset_checksums`
+pub fn release_assets(
+    work_dir: &Path,
+    config: &GitHubConfig,
+    constraints: Option<&str>,
+    dir: &Path,
+) -> Result<Vec<PathBuf>> {
+    let mut assets: Vec<PathBuf> = config.assets.iter().map(|a| work_dir.join(a)).collect();
+    if let Some(missing) = assets.iter().find(|path| !path.is_file()) {
+        return Err(ReleaserError::ConfigError(format!(
+            "Release asset {} not found (github.assets / --asset)",
+            missing.display()
+        )));
+    }
+
+    let write = |name: &str, content: &str| -> Result<PathBuf> {
+        std::fs::create_dir_all(dir)?;
+        let path = dir.join(name);
+        std::fs::write(&path, content)?;
+        Ok(path)
+    };
+    if let Some(constraints) = constraints {
+        assets.push(write("constraints.txt", constraints)?);
+    }
+
+    let mut names = Vec::new();
+    for path in &assets {
+        let name = asset_name(path)?;
+        if names.contains(&name) {
+            return Err(ReleaserError::ConfigError(format!(
+                "Two release assets are named {}; GitHub needs unique names",
+                name
+            )));
+        }
+        names.push(name);
+    }
+
+    if config.asset_checksums && !assets.is_empty() {
+        let mut sums = String::new();
+        for (path, name) in assets.iter().zip(&names) {
+            let content = std::fs::read(path)?;
+            sums.push_str(&format!(
+                "{}  {}\n",
+                crate::bundle::sha256_hex(&content),
+                name
+            ));
+        }
+        assets.push(write("SHA256SUMS", &sums)?);
+    }
+    Ok(assets)
+}
+
+/// File name an asset is uploaded under
+fn asset_name(path: &Path) -> Result<&str> {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| {
+            ReleaserError::ConfigError(format!("Invalid release asset path {}", path.display()))
+        })
+}
+
+/// Token from `github.token_env`, or from `GITHUB_TOKEN`/`GH_TOKEN`
+fn token(config: &GitHubConfig) -> Option<String> {
+    let names = match &config.token_env {
+        Some(name) => vec![name.as_str()],
+        None => DEFAULT_TOKEN_ENVS.to_vec(),
+    };
+    names
+        .into_iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .map(|token| token.trim().to_string())
+        .find(|token| !token.is_empty())
+}
+
+/// `owner/repo` of a GitHub remote URL (HTTPS, SSH or scp-like)
+fn repository_of(url: &str) -> Option<String> {
+    let url = url.trim().trim_end_matches('/');
+    let path = match url.split_once("://") {
+        Some((_, rest)) => rest.split_once('/')?.1,
+        None => url.split_once(':')?.1,
+    };
+    let path = path.trim_end_matches(".git");
+    let (owner, repo) = path.split_once('/')?;
+    if owner.is_empty() || repo.is_empty() || repo.contains('/') {
+        return None;
+    }
+    Some(format!("{}/{}", owner, repo))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::temp_dir;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn test_repository_of_remote_urls() {
+        for url in [
+            "https://github.com/plone/buildout.coredev.git",
+            "git@github.com:plone/buildout.coredev.git",
+            "ssh://git@github.com/plone/buildout.coredev",
+            "https://x-access-token@github.com/plone/buildout.coredev/",
+        ] {
+            assert_eq!(
+                repository_of(url).as_deref(),
+                Some("plone/buildout.coredev"),
+                "{}",
+                url
+            );
+        }
+        assert_eq!(repository_of("/srv/git/buildout.git"), None);
+    }
+
+    /// Answer each connection with the next canned response (`{url}` in a
+    /// body is the server's address) and return the request lines received
+    async fn serve(
+        responses: Vec<(u16, &'static str)>,
+    ) -> (String, tokio::task::JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let base = url.clone();
+        let handle = tokio::spawn(async move {
+            let mut requests = Vec::new();
+            for (status, body) in responses {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut buffer = vec![0; 16384];
+                let read = stream.read(&mut buffer).await.unwrap();
+                let request = String::from_utf8_lossy(&buffer[..read]).to_string();
+                requests.push(request.lines().next().unwrap_or_default().to_string());
+                assert!(request
+                    .to_lowercase()
+                    .contains("authorization: bearer secret"));
+                let body = body.replace("{url}", &base);
+                let response = format!(
+                    "HTTP/1.1 {} X\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+            requests
+        });
+        (url, handle)
+    }
+
+    fn client(api_url: String) -> GitHubClient {
+        let config = GitHubConfig {
+            api_url,
+            ..GitHubConfig::default()
+        };
+        let http = HttpConfig {
+            max_retries: 0,
+            ..HttpConfig::default()
+        };
+        GitHubClient::new(
+            &config,
+            &http,
+            "secret".to_string(),
+            "plone/site".to_string(),
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_releases_through_the_api() {
+        let (url, server) = serve(vec![
+            (200, r#"{"ref": "refs/tags/v1.0.0"}"#),
+            (
+                201,
+                r#"{"id": 7, "upload_url": "{url}/repos/plone/site/releases/7/assets{?name,label}"}"#,
+            ),
+            (201, r#"{"id": 70}"#),
+            (200, r#"{"id": 7}"#),
+            (204, ""),
+            (404, r#"{"message": "Not Found"}"#),
+            (200, r#"{"ref": "refs/tags/v1.0.0"}"#),
+            (
+                422,
+                r#"{"message": "Validation Failed", "errors": [{"code": "already_exists"}]}"#,
+            ),
+        ])
+        .await;
+        let github = client(url);
+        let tmp = temp_dir("github-assets");
+        let dir = tmp.path();
+        let asset = dir.join("versions.cfg");
+        std::fs::write(&asset, "[versions]\n").unwrap();
+
+        github
+            .create_release(
+                "v1.0.0",
+                Some("Release 1.0.0"),
+                Some("- notes"),
+                false,
+                false,
+                std::slice::from_ref(&asset),
+            )
+            .await
+            .unwrap();
+        assert!(github.delete_release("v1.0.0").await.unwrap());
+        assert!(!github.delete_release("v1.0.0").await.unwrap());
+
+        let err = github
+            .create_release("v1.0.0", None, None, false, true, &[])
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "GitHub API returned 422 for repos/plone/site/releases: Validation Failed (already_exists)"
+        );
+
+        assert_eq!(
+            server.await.unwrap(),
+            vec![
+                "GET /repos/plone/site/git/ref/tags/v1.0.0 HTTP/1.1",
+                "POST /repos/plone/site/releases HTTP/1.1",
+                "POST /repos/plone/site/releases/7/assets?name=versions.cfg HTTP/1.1",
+                "GET /repos/plone/site/releases/tags/v1.0.0 HTTP/1.1",
+                "DELETE /repos/plone/site/releases/7 HTTP/1.1",
+                "GET /repos/plone/site/releases/tags/v1.0.0 HTTP/1.1",
+                "GET /repos/plone/site/git/ref/tags/v1.0.0 HTTP/1.1",
+                "POST /repos/plone/site/releases HTTP/1.1",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_reports_assets_missing_from_a_created_release() {
+        let (url, server) = serve(vec![
+            (200, r#"{"ref": "refs/tags/v1.0.0"}"#),
+            (
+                201,
+                r#"{"id": 7, "html_url": "https://github.com/plone/site/releases/tag/v1.0.0", "upload_url": "{url}/repos/plone/site/releases/7/assets{?name,label}"}"#,
+            ),
+            (201, r#"{"id": 70}"#),
+            (502, r#"{"message": "Bad Gateway"}"#),
+        ])
+        .await;
+        let github = client(url);
+        let tmp = temp_dir("github-partial");
+        let dir = tmp.path();
+        let assets: Vec<PathBuf> = ["versions.cfg", "constraints.txt", "SHA256SUMS"]
+            .iter()
+            .map(|name| {
+                std::fs::write(dir.join(name), "content\n").unwrap();
+                dir.join(name)
+            })
+            .collect();
+
+        let err = github
+            .create_release("v1.0.0", None, None, false, false, &assets)
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), "partial_release");
+        assert_eq!(
+            err.to_string(),
+            "Release https://github.com/plone/site/releases/tag/v1.0.0 was created without constraints.txt, SHA256SUMS: GitHub API returned 502 for repos/plone/site/releases/7/assets?name=constraints.txt: Bad Gateway"
+        );
+        assert_eq!(server.await.unwrap().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_opens_pull_requests() {
+        let (url, server) = serve(vec![
+            (201, r#"{"html_url": "https://github.com/plone/site/pull/12"}"#),
+            (
+                422,
+                r#"{"message": "Validation Failed", "errors": [{"message": "A pull request already exists for plone:bldr/release-1.0.0."}]}"#,
+            ),
+        ])
+        .await;
+        let github = client(url);
+
+        let pull = github
+            .create_pull_request(
+                "bldr/release-1.0.0",
+                "main",
+                "Release 1.0.0",
+                "- notes",
+                false,
+            )
+            .await
+            .unwrap();
+        assert_eq!(pull, "https://github.com/plone/site/pull/12");
+        let err = github
+            .create_pull_request("bldr/release-1.0.0", "main", "Release 1.0.0", "", true)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("A pull request already exists"));
+
+        assert_eq!(
+            server.await.unwrap(),
+            vec![
+                "POST /repos/plone/site/pulls HTTP/1.1",
+                "POST /repos/plone/site/pulls HTTP/1.1",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_edits_and_publishes_releases() {
+        let (url, server) = serve(vec![
+            (200, r#"{"id": 7, "tag_name": "v1.0.0"}"#),
+            (200, r#"{"id": 7}"#),
+            // A draft is only found in the release list
+            (404, r#"{"message": "Not Found"}"#),
+            (200, r#"[{"id": 9, "tag_name": "v1.1.0", "draft": true}]"#),
+            (200, r#"{"id": 9}"#),
+            (404, r#"{"message": "Not Found"}"#),
+            (200, r#"[]"#),
+        ])
+        .await;
+        let github = client(url);
+
+        github
+            .edit_release("v1.0.0", None, Some("- fixed notes"), false)
+            .await
+            .unwrap();
+        github
+            .edit_release("v1.1.0", None, None, true)
+            .await
+            .unwrap();
+        let err = github
+            .edit_release("v2.0.0", Some("Release 2.0.0"), None, false)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "GitHub API returned 404 for repos/plone/site/releases/tags/v2.0.0: Not Found"
+        );
+
+        assert_eq!(
+            server.await.unwrap(),
+            vec![
+                "GET /repos/plone/site/releases/tags/v1.0.0 HTTP/1.1",
+                "PATCH /repos/plone/site/releases/7 HTTP/1.1",
+                "GET /repos/plone/site/releases/tags/v1.1.0 HTTP/1.1",
+                "GET /repos/plone/site/releases?per_page=100 HTTP/1.1",
+                "PATCH /repos/plone/site/releases/9 HTTP/1.1",
+                "GET /repos/plone/site/releases/tags/v2.0.0 HTTP/1.1",
+                "GET /repos/plone/site/releases?per_page=100 HTTP/1.1",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_release_assets_with_constraints_and_checksums() {
+        let tmp = temp_dir("release-assets");
+        let dir = tmp.path();
+        let changes = dir.join("CHANGES.md");
+        std::fs::write(&changes, "bldr").unwrap();
+        let mut config = GitHubConfig {
+            assets: vec!["CHANGES.md".to_string()],
+            asset_checksums: true,
+            ..GitHubConfig::default()
+        };
+        let generated = dir.join("generated");
+
+        let assets = release_assets(dir, &config, Some("plone.api==2.0.0\n"), &generated).unwrap();
+        assert_eq!(
+            assets,
+            vec![
+                changes.clone(),
+                generated.join("constraints.txt"),
+                generated.join("SHA256SUMS")
+            ]
+        );
+        let sums = std::fs::read_to_string(generated.join("SHA256SUMS")).unwrap();
+        assert!(sums.starts_with(&format!(
+            "{}  CHANGES.md\n",
+            crate::bundle::sha256_hex(b"bldr")
+        )));
+        assert!(sums.ends_with("  constraints.txt\n"));
+
+        config.assets.push("missing.cfg".to_string());
+        let err = release_assets(dir, &config, None, &generated).unwrap_err();
+        assert!(err.to_string().contains("missing.cfg not found"));
+    }
+}
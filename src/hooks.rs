@@ -0,0 +1,162 @@
+//! Shell commands of the `[hooks]` config section, run around release stages
+//! (e.g. `bin/buildout -N` and the test suite between updating pins and tagging)
+
+use std::process::Command;
+
+use colored::Colorize;
+
+use crate::buildout::VersionUpdate;
+use crate::config::{Config, HooksConfig};
+use crate::error::{ReleaserError, Result};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    /// Before pins are rewritten
+    PreUpdate,
+    /// After the versions files are saved
+    PostUpdate,
+    /// Before the release (or update) commit
+    PreCommit,
+    /// Before the release tag is created
+    PreTag,
+    /// After the tag is pushed and the GitHub release created
+    PostRelease,
+}
+
+impl Stage {
+    pub fn name(self) -> &'static str {
+        match self {
+            Stage::PreUpdate => "pre_update",
+            Stage::PostUpdate => "post_update",
+            Stage::PreCommit => "pre_commit",
+            Stage::PreTag => "pre_tag",
+            Stage::PostRelease => "post_release",
+        }
+    }
+
+    fn commands(self, hooks: &HooksConfig) -> &[String] {
+        match self {
+            Stage::PreUpdate => &hooks.pre_update,
+            Stage::PostUpdate => &hooks.post_update,
+            Stage::PreCommit => &hooks.pre_commit,
+            Stage::PreTag => &hooks.pre_tag,
+            Stage::PostRelease => &hooks.post_release,
+        }
+    }
+}
+
+/// What a hook is told about the run, through `BLDR_*` environment variables
+#[derive(Debug, Default)]
+pub struct HookContext<'a> {
+    /// Pending release version (none for plain updates)
+    pub version: Option<&'a str>,
+    /// Tag of the pending release
+    pub tag: Option<String>,
+    pub updates: &'a [VersionUpdate],
+}
+
+impl HookContext<'_> {
+    fn env(&self, stage: Stage) -> Vec<(&'static str, String)> {
+        let mut env = vec![
+            ("BLDR_HOOK", stage.name().to_string()),
+            (
+                "BLDR_PACKAGES",
+                self.updates
+                    .iter()
+                    .map(|u| format!("{}=={}", u.package_name, u.new_version))
+                    .collect::<Vec<_>>()
+                    .join(" "),
+            ),
+            (
+                "BLDR_UPDATES",
+                serde_json::to_string(self.updates).unwrap_or_default(),
+            ),
+        ];
+        if let Some(version) = self.version {
+            env.push(("BLDR_VERSION", version.to_string()));
+        }
+        if let Some(tag) = &self.tag {
+            env.push(("BLDR_TAG", tag.clone()));
+        }
+        env
+    }
+}
+
+/// Run the commands of `stage` in order with `sh -c` from the work directory;
+/// the first failure stops the run before anything else is written, committed
+/// or tagged
+pub fn run(config: &Config, stage: Stage, context: &HookContext) -> Result<()> {
+    let commands = stage.commands(&config.hooks);
+    if commands.is_empty() {
+        return Ok(());
+    }
+
+    let env = context.env(stage);
+    for command in commands {
+        println!("{} {}: {}", "→".cyan(), stage.name(), command);
+        let mut cmd = Command::new("sh");
+        if !config.work_dir.as_os_str().is_empty() {
+            cmd.current_dir(&config.work_dir);
+        }
+        let status = cmd
+            .arg("-c")
+            .arg(command)
+            .envs(env.iter().map(|(key, value)| (key, value)))
+            .status()
+            .map_err(|e| ReleaserError::CommandFailed {
+                command: format!("hooks.{}", stage.name()),
+                message: format!("cannot run `{}`: {}", command, e),
+            })?;
+
+        if !status.success() {
+            return Err(ReleaserError::CommandFailed {
+                command: format!("hooks.{}", stage.name()),
+                message: format!("`{}` exited with {}", command, status),
+            });
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ConfigTemplate;
+    use crate::testing::temp_dir;
+
+    #[test]
+    fn runs_hooks_with_release_environment() {
+        let tmp = temp_dir("hooks");
+        let out = tmp.path().join("out");
+        let mut config = Config::from_template(ConfigTemplate::Minimal);
+        config.hooks.pre_tag = vec![format!(
+            "echo \"$BLDR_HOOK $BLDR_VERSION $BLDR_TAG $BLDR_PACKAGES\" > {}",
+            out.display()
+        )];
+        config.hooks.post_release = vec!["exit 3".to_string(), "echo unreachable".to_string()];
+
+        let updates = [VersionUpdate {
+            package_name: "plone.api".to_string(),
+            old_version: "2.0.0".to_string(),
+            new_version: "2.1.0".to_string(),
+        }];
+        let context = HookContext {
+            version: Some("1.8.0"),
+            tag: Some("v1.8.0".to_string()),
+            updates: &updates,
+        };
+
+        run(&config, Stage::PreUpdate, &context).unwrap();
+        run(&config, Stage::PreTag, &context).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&out).unwrap(),
+            "pre_tag 1.8.0 v1.8.0 plone.api==2.1.0\n"
+        );
+
+        let err = run(&config, Stage::PostRelease, &context).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "hooks.post_release failed: `exit 3` exited with exit status: 3"
+        );
+    }
+}
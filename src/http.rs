@@ -0,0 +1,299 @@
+use crate::config::HttpConfig;
+use crate::error::{ReleaserError, Result};
+use crate::fixtures;
+use std::sync::OnceLock;
+use std::time::Duration;
+use tokio::time::sleep;
+
+static MAX_RETRIES_OVERRIDE: OnceLock<usize> = OnceLock::new();
+
+const USER_AGENT: &str = concat!("bldr/", env!("CARGO_PKG_VERSION"));
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+/// Longest wait before a retry, whatever the backoff or `Retry-After` asks for
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+const PROXY_ENV: &str = "BLDR_PROXY";
+const NO_PROXY_ENV: &str = "BLDR_NO_PROXY";
+const CA_BUNDLE_ENV: &str = "BLDR_CA_BUNDLE";
+
+/// Override the configured retry count for this process (`--max-retries`)
+pub fn set_max_retries_override(max_retries: usize) {
+    let _ = MAX_RETRIES_OVERRIDE.set(max_retries);
+}
+
+/// Apply proxy and extra root certificates to a client builder.
+/// Environment variables take precedence over the config file.
+pub fn configure_client(
+    mut builder: reqwest::ClientBuilder,
+    config: &HttpConfig,
+) -> Result<reqwest::ClientBuilder> {
+    if let Some(url) = setting(PROXY_ENV, config.proxy.as_ref()) {
+        let no_proxy = setting(NO_PROXY_ENV, config.no_proxy.as_ref())
+            .and_then(|hosts| reqwest::NoProxy::from_string(&hosts));
+        let proxy = reqwest::Proxy::all(&url)
+            .map_err(|e| ReleaserError::ConfigError(format!("Invalid proxy '{}': {}", url, e)))?
+            .no_proxy(no_proxy);
+        builder = builder.proxy(proxy);
+    }
+
+    if let Some(path) = setting(CA_BUNDLE_ENV, config.ca_bundle.as_ref()) {
+        let pem = std::fs::read(&path).map_err(|e| {
+            ReleaserError::ConfigError(format!("Cannot read CA bundle {}: {}", path, e))
+        })?;
+        let certificates = reqwest::Certificate::from_pem_bundle(&pem).map_err(|e| {
+            ReleaserError::ConfigError(format!("Invalid CA bundle {}: {}", path, e))
+        })?;
+        if certificates.is_empty() {
+            return Err(ReleaserError::ConfigError(format!(
+                "No certificates found in CA bundle {}",
+                path
+            )));
+        }
+        for certificate in certificates {
+            builder = builder.add_root_certificate(certificate);
+        }
+    }
+
+    Ok(builder)
+}
+
+/// Download a text document, such as an upstream buildout file
+pub async fn get_text(url: &str, config: &HttpConfig) -> Result<String> {
+    let builder = reqwest::Client::builder()
+        .user_agent(USER_AGENT)
+        .timeout(REQUEST_TIMEOUT);
+    let client = configure_client(builder, config)?.build()?;
+
+    let response = RetryPolicy::from_config(config)
+        .send(client.get(url))
+        .await?;
+    Ok(response.error_for_status()?.text().await?)
+}
+
+/// Non-empty environment variable, falling back to the config value
+fn setting(env: &str, config: Option<&String>) -> Option<String> {
+    std::env::var(env)
+        .ok()
+        .or_else(|| config.cloned())
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+/// Retry behavior for transient HTTP failures (network errors, 5xx, 429)
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    /// Retries after the first attempt
+    pub max_retries: usize,
+    /// Delay before the first retry, doubled on each following one (up to 30 s)
+    pub backoff: Duration,
+}
+
+impl RetryPolicy {
+    pub fn from_config(config: &HttpConfig) -> Self {
+        Self {
+            max_retries: MAX_RETRIES_OVERRIDE
+                .get()
+                .copied()
+                .unwrap_or(config.max_retries),
+            backoff: Duration::from_millis(config.retry_backoff_ms),
+        }
+    }
+
+    fn delay(&self, attempt: usize) -> Duration {
+        self.backoff
+            .saturating_mul(2u32.saturating_pow(attempt as u32))
+            .min(MAX_RETRY_DELAY)
+    }
+
+    /// Wait a 429 or 503 response asks for, in seconds or as an HTTP date
+    fn retry_after(headers: &reqwest::header::HeaderMap) -> Option<Duration> {
+        let value = headers
+            .get(reqwest::header::RETRY_AFTER)?
+            .to_str()
+            .ok()?
+            .trim();
+        let delay = match value.parse::<u64>() {
+            Ok(seconds) => Duration::from_secs(seconds),
+            Err(_) => {
+                let at = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+                (at.with_timezone(&chrono::Utc) - chrono::Utc::now())
+                    .to_std()
+                    .unwrap_or_default()
+            }
+        };
+        Some(delay.min(MAX_RETRY_DELAY))
+    }
+
+    fn is_retryable(status: reqwest::StatusCode) -> bool {
+        status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS
+    }
+
+    /// Whether sending the request twice has the effect of sending it once
+    fn is_idempotent(request: &reqwest::RequestBuilder) -> bool {
+        request
+            .try_clone()
+            .and_then(|request| request.build().ok())
+            .is_some_and(|request| request.method().is_idempotent())
+    }
+
+    /// Send a request, retrying transient failures with exponential backoff.
+    /// When retries run out on an error status, that last response is returned.
+    /// A POST is sent once: the server may have handled it despite the error, and
+    /// a retry would then create a second release or asset.
+    pub async fn send(&self, request: reqwest::RequestBuilder) -> Result<reqwest::Response> {
+        if let Some(response) = fixtures::replayed_response(&request)? {
+            return Ok(response);
+        }
+
+        let max_retries = if Self::is_idempotent(&request) {
+            self.max_retries
+        } else {
+            0
+        };
+        let mut attempt = 0;
+
+        loop {
+            let pending = request.try_clone().ok_or_else(|| {
+                ReleaserError::PyPiError("Request body cannot be retried".to_string())
+            })?;
+
+            let delay = match pending.send().await {
+                Ok(response) if Self::is_retryable(response.status()) && attempt < max_retries => {
+                    Self::retry_after(response.headers()).unwrap_or_else(|| self.delay(attempt))
+                }
+                Ok(response) => return fixtures::recorded_response(&request, response).await,
+                Err(err) if attempt < max_retries && !err.is_builder() => self.delay(attempt),
+                Err(err) => return Err(ReleaserError::HttpError(err)),
+            };
+
+            sleep(delay).await;
+            attempt += 1;
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::from_config(&HttpConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_doubles() {
+        let policy = RetryPolicy {
+            max_retries: 3,
+            backoff: Duration::from_millis(100),
+        };
+
+        assert_eq!(policy.delay(0), Duration::from_millis(100));
+        assert_eq!(policy.delay(1), Duration::from_millis(200));
+        assert_eq!(policy.delay(2), Duration::from_millis(400));
+    }
+
+    #[test]
+    fn test_backoff_is_capped() {
+        let policy = RetryPolicy {
+            max_retries: 15,
+            backoff: Duration::from_millis(300),
+        };
+
+        assert_eq!(policy.delay(14), MAX_RETRY_DELAY);
+        assert_eq!(policy.delay(usize::MAX), MAX_RETRY_DELAY);
+    }
+
+    #[test]
+    fn test_retry_after() {
+        use reqwest::header::{HeaderMap, HeaderValue, RETRY_AFTER};
+
+        let headers = |value: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(RETRY_AFTER, HeaderValue::from_str(value).unwrap());
+            headers
+        };
+
+        assert_eq!(
+            RetryPolicy::retry_after(&headers("5")),
+            Some(Duration::from_secs(5))
+        );
+        assert_eq!(
+            RetryPolicy::retry_after(&headers("3600")),
+            Some(MAX_RETRY_DELAY)
+        );
+        // A date in the past means retry now
+        assert_eq!(
+            RetryPolicy::retry_after(&headers("Wed, 21 Oct 2015 07:28:00 GMT")),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(RetryPolicy::retry_after(&headers("soon")), None);
+        assert_eq!(RetryPolicy::retry_after(&HeaderMap::new()), None);
+    }
+
+    #[tokio::test]
+    async fn test_retries_only_idempotent_requests() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let received = Arc::new(AtomicUsize::new(0));
+        let counter = received.clone();
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut buffer = vec![0; 4096];
+                let _ = stream.read(&mut buffer).await;
+                counter.fetch_add(1, Ordering::SeqCst);
+                let response = "HTTP/1.1 502 X\r\ncontent-length: 0\r\nconnection: close\r\n\r\n";
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+        });
+
+        let policy = RetryPolicy {
+            max_retries: 2,
+            backoff: Duration::from_millis(1),
+        };
+        let client = reqwest::Client::new();
+
+        let response = policy.send(client.get(&url)).await.unwrap();
+        assert_eq!(response.status(), 502);
+        assert_eq!(received.swap(0, Ordering::SeqCst), 3);
+
+        // The release may exist despite the error, so it is not created twice
+        let response = policy.send(client.post(&url).body("{}")).await.unwrap();
+        assert_eq!(response.status(), 502);
+        assert_eq!(received.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_configure_client_rejects_bad_settings() {
+        let config = HttpConfig {
+            ca_bundle: Some("/nonexistent/bldr-ca.pem".to_string()),
+            ..HttpConfig::default()
+        };
+        let err = configure_client(reqwest::Client::builder(), &config).unwrap_err();
+        assert!(err.to_string().contains("Cannot read CA bundle"));
+
+        let config = HttpConfig {
+            proxy: Some("http://proxy.example:3128".to_string()),
+            no_proxy: Some("localhost,.internal".to_string()),
+            ..HttpConfig::default()
+        };
+        assert!(configure_client(reqwest::Client::builder(), &config).is_ok());
+    }
+
+    #[test]
+    fn test_retryable_statuses() {
+        assert!(RetryPolicy::is_retryable(
+            reqwest::StatusCode::SERVICE_UNAVAILABLE
+        ));
+        assert!(RetryPolicy::is_retryable(
+            reqwest::StatusCode::TOO_MANY_REQUESTS
+        ));
+        assert!(!RetryPolicy::is_retryable(reqwest::StatusCode::NOT_FOUND));
+    }
+}
@@ -0,0 +1,129 @@
+//! Comparing our pins against a published known-good set (e.g. a Plone release's
+//! `versions.cfg`) for `bldr kgs sync`
+
+use crate::buildout::BuildoutVersions;
+use crate::config::HttpConfig;
+use crate::error::{ReleaserError, Result};
+use crate::http;
+use crate::pypi::normalize_project_name;
+use crate::version::python::compare_versions_lenient;
+use serde::Serialize;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// A package we pin to another version than the known-good set
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct KgsMismatch {
+    /// Name as written in our versions file
+    pub package: String,
+    pub ours: String,
+    pub kgs: String,
+}
+
+impl KgsMismatch {
+    /// Adopting the KGS version would move the pin forward
+    pub fn is_upgrade(&self) -> bool {
+        compare_versions_lenient(&self.kgs, &self.ours) == Ordering::Greater
+    }
+}
+
+/// Download a known-good set and the files it extends. Paths without a
+/// scheme are read from disk, relative to `dir`
+pub async fn fetch(location: &str, dir: &Path, config: &HttpConfig) -> Result<BuildoutVersions> {
+    if !location.contains("://") {
+        return BuildoutVersions::load_in(dir, location);
+    }
+
+    let mut remote = HashMap::new();
+    let mut pending = vec![location.to_string()];
+    loop {
+        for url in pending {
+            let content = http::get_text(&url, config).await.map_err(|e| {
+                ReleaserError::BuildoutParseError(format!("cannot download {}: {}", url, e))
+            })?;
+            remote.insert(url, content);
+        }
+
+        let kgs = BuildoutVersions::load_remote(location, &remote)?;
+        if kgs.unresolved_extends().is_empty() {
+            return Ok(kgs);
+        }
+        pending = kgs.unresolved_extends().to_vec();
+    }
+}
+
+/// Pins of our own files (not inherited from a remote file) that the
+/// known-good set pins differently, sorted by name
+pub fn compare(ours: &[BuildoutVersions], kgs: &BuildoutVersions) -> Vec<KgsMismatch> {
+    let kgs_pins: HashMap<String, &str> = kgs
+        .get_all_versions()
+        .map(|(name, version)| (normalize_project_name(name), version))
+        .collect();
+
+    let mut mismatches: Vec<KgsMismatch> = Vec::new();
+    for file in ours {
+        for (name, version) in file.get_all_versions() {
+            let key = normalize_project_name(name);
+            if file.upstream_source(name).is_some()
+                || mismatches
+                    .iter()
+                    .any(|m| normalize_project_name(&m.package) == key)
+            {
+                continue;
+            }
+            let Some(kgs_version) = kgs_pins.get(&key) else {
+                continue;
+            };
+            if compare_versions_lenient(version, kgs_version) != Ordering::Equal {
+                mismatches.push(KgsMismatch {
+                    package: name.to_string(),
+                    ours: version.to_string(),
+                    kgs: kgs_version.to_string(),
+                });
+            }
+        }
+    }
+
+    mismatches.sort_by_key(|m| m.package.to_lowercase());
+    mismatches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compare_with_kgs() {
+        let ours = BuildoutVersions::from_content(
+            "[versions]\nProducts.CMFPlone = 6.0.9\nplone.api = 2.1.0\nsix = 1.16.0\ncollective.foo = 1.0\n"
+                .to_string(),
+            "versions.cfg",
+        )
+        .unwrap();
+        let kgs = BuildoutVersions::from_content(
+            "[versions]\nproducts.cmfplone = 6.0.10\nplone.api = 2.0.0\nsix = 1.16\n".to_string(),
+            "https://dist.plone.org/release/6.0-latest/versions.cfg",
+        )
+        .unwrap();
+
+        let mismatches = compare(&[ours], &kgs);
+        assert_eq!(
+            mismatches,
+            vec![
+                KgsMismatch {
+                    package: "plone.api".to_string(),
+                    ours: "2.1.0".to_string(),
+                    kgs: "2.0.0".to_string(),
+                },
+                KgsMismatch {
+                    package: "Products.CMFPlone".to_string(),
+                    ours: "6.0.9".to_string(),
+                    kgs: "6.0.10".to_string(),
+                },
+            ]
+        );
+        assert!(!mismatches[0].is_upgrade());
+        assert!(mismatches[1].is_upgrade());
+    }
+}
@@ -0,0 +1,11 @@
+//! PEP 440 version parsing and constraint matching used by `bldr`, for tools
+//! that want the same version ordering without running the binary.
+//!
+//! The rest of the crate is the `bldr` binary; it mounts these modules from
+//! here so there is a single copy of the parser.
+
+pub mod error;
+
+pub mod version {
+    pub mod python;
+}
@@ -0,0 +1,119 @@
+use crate::pypi::PackageInfo;
+use serde::Serialize;
+
+/// License information for one tracked package
+#[derive(Debug, Clone, Serialize)]
+pub struct LicenseReport {
+    pub package: String,
+    pub version: String,
+    pub licenses: Vec<String>,
+    /// `None` when no allowlist is configured or the package is exempt
+    pub allowed: Option<bool>,
+}
+
+/// Collect license names from PyPI metadata: the SPDX expression if present,
+/// otherwise the trove classifiers, otherwise a short free-form `license` field
+pub fn licenses_from_info(info: &PackageInfo) -> Vec<String> {
+    if let Some(expression) = info
+        .license_expression
+        .as_deref()
+        .map(str::trim)
+        .filter(|e| !e.is_empty())
+    {
+        return vec![expression.to_string()];
+    }
+
+    let classifiers: Vec<String> = info
+        .classifiers
+        .iter()
+        .filter(|c| c.starts_with("License ::"))
+        .filter_map(|c| c.rsplit("::").next())
+        .map(|name| name.trim().to_string())
+        .collect();
+    if !classifiers.is_empty() {
+        return classifiers;
+    }
+
+    // The free-form field sometimes holds the full license text; only keep short names
+    info.license
+        .as_deref()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && l.len() <= 80 && !l.contains('\n'))
+        .map(|l| vec![l.to_string()])
+        .unwrap_or_default()
+}
+
+/// Whether any of a package's licenses is permitted by the allowlist.
+/// SPDX expressions are allowed when one `OR` alternative has all its `AND` terms allowed.
+pub fn is_allowed(licenses: &[String], allowlist: &[String]) -> bool {
+    let allowed = |name: &str| {
+        let name = name.trim().trim_matches(|c| c == '(' || c == ')').trim();
+        allowlist.iter().any(|a| a.eq_ignore_ascii_case(name))
+    };
+
+    licenses.iter().any(|license| {
+        allowed(license)
+            || license
+                .split(" OR ")
+                .any(|alternative| alternative.split(" AND ").all(allowed))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info(license: Option<&str>, expression: Option<&str>, classifiers: &[&str]) -> PackageInfo {
+        PackageInfo {
+            name: "example".to_string(),
+            version: "1.0".to_string(),
+            summary: None,
+            home_page: None,
+            project_urls: None,
+            license: license.map(str::to_string),
+            license_expression: expression.map(str::to_string),
+            classifiers: classifiers.iter().map(|c| c.to_string()).collect(),
+            requires_dist: None,
+        }
+    }
+
+    #[test]
+    fn test_licenses_from_info() {
+        let classified = info(
+            Some("GPL version 2"),
+            None,
+            &[
+                "Framework :: Plone",
+                "License :: OSI Approved :: GNU General Public License v2 (GPLv2)",
+            ],
+        );
+        assert_eq!(
+            licenses_from_info(&classified),
+            vec!["GNU General Public License v2 (GPLv2)"]
+        );
+
+        let spdx = info(
+            None,
+            Some("MIT OR Apache-2.0"),
+            &["License :: OSI Approved"],
+        );
+        assert_eq!(licenses_from_info(&spdx), vec!["MIT OR Apache-2.0"]);
+
+        let long_text = info(
+            Some("Copyright (c)\nPermission is hereby granted"),
+            None,
+            &[],
+        );
+        assert!(licenses_from_info(&long_text).is_empty());
+    }
+
+    #[test]
+    fn test_is_allowed() {
+        let allowlist = vec!["MIT".to_string(), "BSD-3-Clause".to_string()];
+
+        assert!(is_allowed(&["mit".to_string()], &allowlist));
+        assert!(is_allowed(&["GPL-2.0 OR MIT".to_string()], &allowlist));
+        assert!(!is_allowed(&["MIT AND GPL-2.0".to_string()], &allowlist));
+        assert!(!is_allowed(&[], &allowlist));
+    }
+}
@@ -1,71 +1,284 @@
+mod audit;
 mod buildout;
+mod bundle;
+mod cache;
 mod changelog;
 mod cli;
 mod config;
-mod error;
+mod diagnostics;
+mod export;
+mod fixtures;
 mod git;
+mod github;
+mod hooks;
+mod http;
+mod kgs;
+mod licenses;
+mod markup;
 mod pypi;
+mod report;
+mod stats;
+#[cfg(test)]
+mod testing;
 mod version;
+mod warnings;
+mod wheels;
+mod wizard;
 
 use clap::{CommandFactory, Parser};
 use colored::*;
-use dialoguer::{Confirm, MultiSelect};
+use dialoguer::{Confirm, MultiSelect, Select};
 use indicatif::{ProgressBar, ProgressStyle};
-use std::sync::Arc;
+use rust_buildout_releaser::error;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use tokio::sync::Semaphore;
 use tokio::task::JoinSet;
 
-use buildout::{BuildoutVersions, VersionUpdate};
-use changelog::{ChangelogCollector, ConsolidatedChangelog};
-use cli::{Cli, CliChangelogFormat, Commands};
-use config::{ChangelogFormat, Config, PackageConfig};
+use buildout::{find_pin_mismatches, BuildoutVersions, PinDiff, PinnedPackage, VersionUpdate};
+use cache::{ChangelogCache, SnapshotCache, DEFAULT_CACHE_DIR};
+use changelog::{ChangelogCollector, ConsolidatedChangelog, PackageChangelog, UnreleasedSection};
+use cli::{
+    BundleAction, ChangelogArgs, CheckSort, Cli, CliChangelogFormat, Commands, ErrorFormat,
+    ExportFormat, KgsAction, ProjectArgs, ReleaseAction, RollbackCommit,
+};
+use config::{
+    ChangelogFormat, Config, ConfigTemplate, GitConfig, PackageConfig, ProjectConfig,
+    WorkspaceConfig,
+};
 use error::{ReleaserError, Result};
-use git::{GitHubOps, GitOps};
-use pypi::{PyPiClient, VersionInfo};
+use git::{DirtyFile, GitOps, TagMetadata};
+use github::GitHub;
+use hooks::{HookContext, Stage};
+use pypi::{PackageInfo, PackageMetadataStore, PyPiClient, VersionInfo};
 use version::{MetadataUpdater, Version, VersionManager};
+use warnings::WarningKind;
 
 #[tokio::main]
 async fn main() {
-    if let Err(e) = run().await {
-        eprintln!("{} {}", "Error:".red().bold(), e);
+    let cli = Cli::parse();
+    let error_format = cli.error_format;
+    let result = run(cli).await;
+    warnings::print_summary();
+
+    if let Err(e) = &result {
+        match error_format {
+            ErrorFormat::Human => {
+                eprintln!("{} {}", "Error:".red().bold(), e);
+                if let Some(hint) = e.hint() {
+                    eprintln!("  {} {}", "hint:".cyan(), hint);
+                }
+            }
+            ErrorFormat::Json => eprintln!("{}", serde_json::json!({ "error": e.to_json() })),
+        }
         std::process::exit(1);
     }
+
+    let unmatched = result.unwrap_or_default();
+    if !unmatched.is_empty() {
+        eprintln!(
+            "{} --packages matched nothing for: {}",
+            "Error:".red().bold(),
+            unmatched.join(", ")
+        );
+        std::process::exit(UNMATCHED_FILTER_EXIT_CODE);
+    }
 }
 
-async fn run() -> Result<()> {
-    let cli = Cli::parse();
+/// Run the command; returns the `--packages` tokens that matched nothing
+async fn run(cli: Cli) -> Result<Vec<String>> {
+    // Like `git -C`, every relative path (the config included) resolves against the checkout
+    let work_dir = cli.repo.clone().unwrap_or_default();
+    if !work_dir.as_os_str().is_empty() && !work_dir.is_dir() {
+        return Err(ReleaserError::FileError {
+            path: work_dir.display().to_string(),
+            source: std::io::ErrorKind::NotFound.into(),
+        });
+    }
+    set_work_dir(work_dir.clone());
+
+    if let Some(max_retries) = cli.max_retries {
+        http::set_max_retries_override(max_retries);
+    }
+
+    set_non_interactive(cli.non_interactive);
+
+    if let Some(ref dir) = cli.record {
+        fixtures::start_recording(&work_dir.join(dir).to_string_lossy())?;
+    } else if let Some(ref dir) = cli.replay {
+        fixtures::start_replay(&work_dir.join(dir).to_string_lossy())?;
+    }
+
+    let projects = match &cli.command {
+        Commands::Check { projects, .. }
+        | Commands::Update { projects, .. }
+        | Commands::Release { projects, .. }
+        | Commands::UpdateRelease { projects, .. } => projects.clone(),
+        _ => ProjectArgs::default(),
+    };
+    if projects.is_set() {
+        return run_projects(cli, &projects).await;
+    }
+
+    dispatch(cli).await
+}
+
+/// Run the command once per selected project of the workspace file, in the
+/// project's directory and with its config and tag prefix. Every project is
+/// attempted; the run fails if any of them did. A `--packages` token is only
+/// unmatched when it matched nothing in every project.
+async fn run_projects(cli: Cli, projects: &ProjectArgs) -> Result<Vec<String>> {
+    let root = work_dir();
+    let workspace_file = root.join(projects.workspace.as_deref().unwrap_or(WORKSPACE_FILE));
+    let workspace = WorkspaceConfig::load(&workspace_file)?;
+    let base = workspace_file.parent().unwrap_or(&root).to_path_buf();
+
+    let mut failed = Vec::new();
+    let mut unmatched: Option<Vec<String>> = None;
+    for project in workspace.select(&projects.projects, projects.all)? {
+        println!(
+            "\n{}",
+            format!("▶ Project {} ({})", project.name, project.path)
+                .cyan()
+                .bold()
+        );
+
+        let dir = base.join(&project.path);
+        let result = if dir.is_dir() {
+            set_work_dir(dir);
+            set_active_project(Some(project.clone()));
+            let mut cli = cli.clone();
+            cli.config = project.config.clone();
+            dispatch(cli).await
+        } else {
+            Err(ReleaserError::FileError {
+                path: dir.display().to_string(),
+                source: std::io::ErrorKind::NotFound.into(),
+            })
+        };
+        set_active_project(None);
+        set_work_dir(root.clone());
+
+        match result {
+            Ok(tokens) => {
+                unmatched = Some(match unmatched {
+                    Some(previous) => previous
+                        .into_iter()
+                        .filter(|t| tokens.contains(t))
+                        .collect(),
+                    None => tokens,
+                });
+            }
+            Err(e) => {
+                eprintln!("{} {}: {}", "Error:".red().bold(), project.name, e);
+                failed.push(project.name.clone());
+            }
+        }
+    }
+
+    if failed.is_empty() {
+        Ok(unmatched.unwrap_or_default())
+    } else {
+        Err(ReleaserError::CommandFailed {
+            command: format!("{} project(s)", failed.len()),
+            message: failed.join(", "),
+        })
+    }
+}
 
-    match cli.command {
+/// Run one command; returns the `--packages` tokens that matched nothing
+async fn dispatch(cli: Cli) -> Result<Vec<String>> {
+    let mut unmatched = Vec::new();
+    let result = match cli.command {
         Commands::Completions { shell } => {
             let mut command = Cli::command();
             clap_complete::generate(shell, &mut command, "bldr", &mut std::io::stdout());
             Ok(())
         }
-        Commands::Init { force } => cmd_init(&cli.config, force),
-        Commands::Check { packages, json } => {
-            cmd_check(&cli.config, packages, json, cli.verbose).await
-        }
+        Commands::Init {
+            force,
+            template,
+            from,
+            interactive,
+        } => cmd_init(
+            &cli.config,
+            force,
+            template.map(Into::into),
+            from.as_deref(),
+            interactive,
+            cli.non_interactive,
+        ),
+        Commands::Check {
+            packages,
+            json,
+            wheels,
+            sort,
+            projects: _,
+        } => cmd_check(&cli.config, packages, json, wheels, sort, cli.verbose)
+            .await
+            .map(|tokens| unmatched = tokens),
         Commands::Update {
             packages,
             yes,
             dry_run,
             commit,
             push,
-        } => {
-            cmd_update(
-                &cli.config,
-                packages,
-                yes,
-                dry_run,
+            no_commit,
+            no_push,
+            push_to,
+            show_diff,
+            trailers,
+            pr,
+            projects: _,
+        } => cmd_update(
+            &cli.config,
+            packages,
+            yes,
+            dry_run,
+            show_diff,
+            GitFlags {
                 commit,
                 push,
-                cli.non_interactive,
-                cli.verbose,
+                no_commit,
+                no_push,
+            },
+            push_to.as_deref(),
+            &trailers,
+            pr,
+            cli.non_interactive,
+            cli.verbose,
+        )
+        .await
+        .map(|tokens| unmatched = tokens),
+        Commands::Release {
+            action:
+                Some(ReleaseAction::Edit {
+                    tag,
+                    notes_file,
+                    notes,
+                    title,
+                }),
+            ..
+        } => {
+            cmd_release_edit(
+                &cli.config,
+                &tag,
+                notes_file.as_deref(),
+                notes,
+                title.as_deref(),
+                false,
             )
             .await
         }
         Commands::Release {
+            action: Some(ReleaseAction::Publish { tag }),
+            ..
+        } => cmd_release_edit(&cli.config, &tag, None, None, None, true).await,
+        Commands::Release {
+            action: None,
             tag,
             bump,
             message,
@@ -73,19 +286,54 @@ async fn run() -> Result<()> {
             no_github,
             draft,
             no_metadata,
-        } => cmd_release(
-            &cli.config,
+            first,
+            changelog,
+            push_to,
+            trailers,
+            force_retag,
+            assets,
+            projects: _,
+        } => {
+            cmd_release(
+                &cli.config,
+                tag,
+                bump,
+                message.as_deref(),
+                no_push,
+                no_github,
+                draft,
+                no_metadata,
+                first,
+                &changelog,
+                push_to.as_deref(),
+                &trailers,
+                force_retag,
+                &assets,
+                cli.non_interactive,
+                cli.verbose,
+            )
+            .await
+        }
+        Commands::UpdateRelease {
             tag,
             bump,
-            message.as_deref(),
+            packages,
+            yes,
+            message,
             no_push,
             no_github,
             draft,
+            dry_run,
+            changelog,
             no_metadata,
-            cli.non_interactive,
-            cli.verbose,
-        ),
-        Commands::UpdateRelease {
+            push_to,
+            trailers,
+            force_retag,
+            assets,
+            pr,
+            projects: _,
+        } => cmd_update_release(
+            &cli.config,
             tag,
             bump,
             packages,
@@ -95,30 +343,35 @@ async fn run() -> Result<()> {
             no_github,
             draft,
             dry_run,
-            changelog,
-            no_changelog,
-            changelog_format,
-            changelog_file,
+            &changelog,
             no_metadata,
+            push_to.as_deref(),
+            &trailers,
+            force_retag,
+            &assets,
+            pr,
+            cli.non_interactive,
+            cli.verbose,
+        )
+        .await
+        .map(|tokens| unmatched = tokens),
+        Commands::Rollback {
+            tag,
+            commit,
+            local_only,
+            yes,
+            restore_backup,
+            dry_run,
         } => {
-            cmd_update_release(
+            cmd_rollback(
                 &cli.config,
-                tag,
-                bump,
-                packages,
+                tag.as_deref(),
+                commit,
+                local_only,
                 yes,
-                message,
-                no_push,
-                no_github,
-                draft,
+                restore_backup,
                 dry_run,
-                changelog,
-                no_changelog,
-                changelog_format,
-                changelog_file,
-                no_metadata,
                 cli.non_interactive,
-                cli.verbose,
             )
             .await
         }
@@ -127,21 +380,36 @@ async fn run() -> Result<()> {
             format,
             output,
             stdout,
+            json,
             release_version,
             rebuild,
-        } => {
-            cmd_changelog(
-                &cli.config,
-                packages,
-                format,
-                output,
-                stdout,
-                release_version,
-                rebuild,
-                cli.verbose,
-            )
-            .await
-        }
+            from_tag,
+            to_tag,
+            last,
+            no_cache,
+            refresh,
+            jobs,
+        } => cmd_changelog(
+            &cli.config,
+            packages,
+            format,
+            output,
+            stdout,
+            json,
+            release_version,
+            rebuild,
+            TagRange {
+                from: from_tag,
+                to: to_tag,
+                last,
+            },
+            no_cache,
+            refresh,
+            jobs,
+            cli.verbose,
+        )
+        .await
+        .map(|tokens| unmatched = tokens),
         Commands::Version { bump, list_levels } => {
             cmd_version(&cli.config, bump, list_levels, cli.verbose)
         }
@@ -150,25 +418,153 @@ async fn run() -> Result<()> {
             constraint,
             buildout_name,
             changelog_url,
-        } => cmd_add(
-            &cli.config,
-            &package,
-            constraint,
-            buildout_name,
-            changelog_url,
-        ),
+            pin,
+            pin_latest,
+        } => {
+            let pin = match (pin, pin_latest) {
+                (Some(version), _) => Some(PinRequest::Version(version)),
+                (None, true) => Some(PinRequest::Latest),
+                (None, false) => None,
+            };
+            cmd_add(
+                &cli.config,
+                &package,
+                constraint,
+                buildout_name,
+                changelog_url,
+                pin,
+            )
+            .await
+        }
         Commands::Remove { package } => cmd_remove(&cli.config, &package),
         Commands::List { detailed } => cmd_list(&cli.config, detailed).await,
-        Commands::Info { package, versions } => cmd_info(&package, versions).await,
-    }
+        Commands::Info {
+            package,
+            versions,
+            stats,
+            all,
+            json,
+        } => match package {
+            Some(package) if !all => cmd_info(&cli.config, &package, versions, stats).await,
+            _ => cmd_info_all(&cli.config, json, cli.verbose).await,
+        },
+        Commands::Licenses {
+            packages,
+            json,
+            check,
+        } => cmd_licenses(&cli.config, packages, json, check, cli.verbose)
+            .await
+            .map(|tokens| unmatched = tokens),
+        Commands::Bundle {
+            action:
+                Some(BundleAction::Verify {
+                    dir,
+                    versions_file,
+                    json,
+                }),
+            ..
+        } => cmd_bundle_verify(&cli.config, &dir, versions_file, json),
+        Commands::Bundle {
+            action: None,
+            packages,
+            output,
+            tar,
+        } => cmd_bundle(&cli.config, packages, output, tar, cli.verbose)
+            .await
+            .map(|tokens| unmatched = tokens),
+        Commands::Diagnostics { output, offline } => {
+            cmd_diagnostics(&cli.config, output, offline, cli.verbose).await
+        }
+        Commands::Export {
+            format,
+            hashes,
+            packages,
+            output,
+        } => cmd_export(&cli.config, format, hashes, packages, output)
+            .await
+            .map(|tokens| unmatched = tokens),
+        Commands::Audit {
+            packages,
+            json,
+            ignore,
+        } => cmd_audit(&cli.config, packages, json, ignore)
+            .await
+            .map(|tokens| unmatched = tokens),
+        Commands::Set {
+            package,
+            version,
+            section,
+            commit,
+            no_commit,
+            no_push,
+            trailers,
+        } => {
+            cmd_set(
+                &cli.config,
+                &package,
+                &version,
+                section.as_deref(),
+                GitFlags {
+                    commit,
+                    push: false,
+                    no_commit,
+                    no_push,
+                },
+                &trailers,
+                cli.non_interactive,
+            )
+            .await
+        }
+        Commands::Fmt { check } => cmd_fmt(&cli.config, check),
+        Commands::Kgs {
+            action:
+                KgsAction::Sync {
+                    url,
+                    packages,
+                    all,
+                    dry_run,
+                    json,
+                },
+        } => cmd_kgs_sync(
+            &cli.config,
+            url,
+            packages,
+            all,
+            dry_run,
+            json,
+            cli.non_interactive,
+        )
+        .await
+        .map(|tokens| unmatched = tokens),
+        Commands::Report {
+            since,
+            output,
+            offline,
+            json,
+        } => cmd_report(&cli.config, &since, output, offline, json, cli.verbose).await,
+        Commands::Diff { from, to, json } => cmd_diff(&cli.config, &from, to.as_deref(), json),
+        Commands::History { limit, json } => cmd_history(&cli.config, limit, json, cli.verbose),
+        Commands::Migrate { yes, dry_run } => {
+            cmd_migrate(&cli.config, yes, cli.non_interactive, dry_run, cli.verbose).await
+        }
+    };
+
+    result.map(|()| unmatched)
 }
 
 // ============================================================================
 // Command Implementations
 // ============================================================================
 
-fn cmd_init(config_path: &str, force: bool) -> Result<()> {
-    let path = std::path::Path::new(config_path);
+fn cmd_init(
+    config_path: &str,
+    force: bool,
+    template: Option<ConfigTemplate>,
+    from: Option<&str>,
+    interactive: bool,
+    non_interactive: bool,
+) -> Result<()> {
+    let path = work_dir().join(config_path);
 
     if path.exists() && !force {
         return Err(ReleaserError::ConfigError(format!(
@@ -177,137 +573,547 @@ fn cmd_init(config_path: &str, force: bool) -> Result<()> {
         )));
     }
 
-    Config::create_default(path)?;
-    println!("{} Created config file: {}", "✓".green(), config_path);
-    println!("  Edit this file to configure your packages and settings.");
-
-    Ok(())
-}
+    let wizard = wizard::Wizard::new(non_interactive);
+    let template = match template {
+        Some(template) => template,
+        None if interactive => wizard.template()?,
+        None => ConfigTemplate::Minimal,
+    };
 
-async fn rebuild_changelog_from_tags(
-    config: &Config,
-    packages_to_check: &[PackageConfig],
-    format: ChangelogFormat,
-    output_file: Option<String>,
-    verbose: bool,
-) -> Result<()> {
-    let git = GitOps::new();
+    let mut config = Config::from_template(template);
+    config.work_dir = work_dir();
+    let from = if interactive {
+        let seed_from = wizard.run(&mut config, from.is_none())?;
+        from.map(str::to_string).or(seed_from)
+    } else {
+        from.map(str::to_string)
+    };
 
-    if !git.is_repo() {
-        return Err(ReleaserError::GitError(
-            "Rebuild requires running inside a git repository".to_string(),
-        ));
+    let mut seeded = 0;
+    if let Some(from) = from.as_deref() {
+        let versions = BuildoutVersions::load_in(&config.work_dir, from)?;
+        config.versions_file = from.to_string();
+        seeded = config.seed_packages(versions.get_all_versions().map(|(name, _)| (name, None)));
     }
+    config.save(&path)?;
 
-    let mut version_tags = git.get_version_tags(&config.github.tag_prefix)?;
+    println!("{} Created config file: {}", "✓".green(), config_path);
+    if interactive {
+        wizard::summarize(&config);
+    }
+    if seeded > 0 {
+        println!(
+            "  Tracking {} packages pinned in {}",
+            seeded, config.versions_file
+        );
+    }
 
-    if version_tags.len() < 2 {
-        return Err(ReleaserError::GitError(
-            "Need at least two version tags to rebuild changelog".to_string(),
-        ));
+    if let Some(kgs) = template.kgs_url() {
+        let versions_path = config.resolve(&config.versions_file);
+        if !versions_path.exists() {
+            std::fs::write(
+                &versions_path,
+                format!("[buildout]\nextends = {}\n\n[versions]\n", kgs),
+            )?;
+            println!(
+                "{} Created {} extending {}",
+                "✓".green(),
+                config.versions_file,
+                kgs
+            );
+        } else if !std::fs::read_to_string(versions_path)?.contains(kgs) {
+            println!(
+                "  {} {} does not extend {}; add it to `extends =` to follow the known-good set.",
+                "Note:".yellow(),
+                config.versions_file,
+                kgs
+            );
+        }
     }
+    println!("  Edit this file to configure your packages and settings.");
 
-    // Sort ascending (oldest first) for a full rebuild
-    version_tags.reverse();
+    Ok(())
+}
 
-    let versions_file = &config.versions_file;
-    let mut snapshots = Vec::new();
+/// Bounds applied to the tags walked by a changelog rebuild
+#[derive(Debug, Default, Clone)]
+struct TagRange {
+    from: Option<String>,
+    to: Option<String>,
+    last: Option<usize>,
+}
 
-    for (tag, _) in &version_tags {
-        if verbose {
-            println!("Loading versions from tag {}...", tag);
+impl TagRange {
+    /// Narrow ascending version tags to the requested range.
+    /// The first returned tag is the baseline snapshot the next ones are diffed against.
+    fn select(&self, tags: Vec<(String, Version)>, prefix: &str) -> Result<Vec<(String, Version)>> {
+        let position = |name: &str| {
+            tags.iter()
+                .position(|(tag, _)| tag == name || *tag == format!("{}{}", prefix, name))
+                .ok_or_else(|| ReleaserError::GitError(format!("Tag '{}' not found", name)))
+        };
+
+        let start = match &self.from {
+            Some(name) => position(name)?,
+            None => 0,
+        };
+        let end = match &self.to {
+            Some(name) => position(name)? + 1,
+            None => tags.len(),
+        };
+
+        if start >= end {
+            return Err(ReleaserError::GitError(
+                "--from-tag must be older than --to-tag".to_string(),
+            ));
         }
 
-        let content = git.show_file_at_ref(tag, versions_file)?;
-        snapshots.push(BuildoutVersions::from_content(
-            content,
-            format!("{}@{}", versions_file, tag),
-        )?);
-    }
+        let start = match self.last {
+            // N releases need N + 1 snapshots
+            Some(n) => end.saturating_sub(n + 1).max(start),
+            None => start,
+        };
 
-    let collector = ChangelogCollector::with_config(&config.changelog);
-    let mut rendered_entries = Vec::new();
+        Ok(tags.into_iter().skip(start).take(end - start).collect())
+    }
+}
 
-    for window in snapshots.windows(2).zip(version_tags.windows(2)) {
-        let (versions_pair, tag_pair) = window;
-        let previous = &versions_pair[0];
-        let current = &versions_pair[1];
+/// Where a changelog goes and how it is gathered, from the command flags and `changelog.*`
+#[derive(Debug, Clone)]
+struct ChangelogPlan {
+    format: ChangelogFormat,
+    /// File a new entry is prepended to, and a rebuilt history is written to
+    file: Option<String>,
+    /// Directory `file` is relative to
+    dir: PathBuf,
+    /// Print the changelog; it is always printed when there is no file
+    stdout: bool,
+    /// Regenerate the history from the existing tags before adding the new entry
+    rebuild: bool,
+    /// Enforce `changelog.max_missing_fraction`
+    require: bool,
+    allow_missing: bool,
+    /// Download changelogs again instead of revalidating cached copies
+    refresh: bool,
+    /// Print JSON instead of the rendered changelog, without progress messages
+    json: bool,
+}
 
-        let current_tag = &tag_pair[1].0;
-        let release_version = if config.github.tag_prefix.is_empty() {
-            current_tag.clone()
-        } else {
-            current_tag
-                .strip_prefix(&config.github.tag_prefix)
-                .unwrap_or(current_tag)
-                .to_string()
-        };
+impl ChangelogPlan {
+    /// Plan of `release`/`update-release`, or `None` when no changelog is collected.
+    /// `enabled` applies without `--changelog` or `--no-changelog`
+    fn for_release(config: &Config, args: &ChangelogArgs, enabled: bool) -> Option<Self> {
+        let collect = !args.no_changelog && (args.changelog || enabled);
+        collect.then(|| ChangelogPlan {
+            format: args
+                .changelog_format
+                .map(Into::into)
+                .unwrap_or_else(|| config.changelog.format_enum()),
+            file: args
+                .changelog_file
+                .clone()
+                .or_else(|| config.changelog.output_file.clone()),
+            dir: config.work_dir.clone(),
+            stdout: args.changelog_stdout,
+            rebuild: args.rebuild_changelog,
+            require: args.require_changelogs || config.changelog.required,
+            allow_missing: args.allow_missing,
+            refresh: args.refresh,
+            json: false,
+        })
+    }
 
-        let mut updates = Vec::new();
+    /// The history rebuilt from every tag, when asked for
+    async fn rebuild_history(
+        &self,
+        config: &Config,
+        packages_filter: Option<&str>,
+        verbose: bool,
+    ) -> Result<Option<String>> {
+        if !self.rebuild {
+            return Ok(None);
+        }
 
-        for pkg in packages_to_check {
-            let name = pkg.buildout_name();
-            let old_version = previous.get_version(name);
-            let new_version = current.get_version(name);
+        rebuild_changelog_from_tags(
+            config,
+            packages_filter,
+            self.format,
+            self.file.as_deref(),
+            &TagRange::default(),
+            true,
+            self.refresh,
+            1,
+            verbose,
+        )
+        .await
+    }
 
-            if let (Some(old_version), Some(new_version)) = (old_version, new_version) {
-                if old_version != new_version {
-                    updates.push(VersionUpdate {
-                        package_name: name.to_string(),
-                        old_version: old_version.to_string(),
-                        new_version: new_version.to_string(),
-                    });
-                }
+    /// Write the rebuilt history, then prepend the new entry, and print them
+    fn emit(&self, rebuilt: Option<&str>, entry: Option<&ConsolidatedChangelog>) -> Result<()> {
+        if self.json {
+            if let Some(entry) = entry {
+                let mut report = entry.to_json();
+                report["warnings"] = serde_json::json!(warnings::take());
+                println!("{}", serde_json::to_string_pretty(&report).unwrap());
             }
+            return Ok(());
         }
 
-        if updates.is_empty() {
-            continue;
+        if let Some(path) = &self.file {
+            if let Some(history) = rebuilt {
+                std::fs::write(self.dir.join(path), history.trim_end())?;
+                println!("{} Rebuilt changelog saved to: {}", "✓".green(), path);
+            }
+            if let Some(entry) = entry {
+                entry.save_to_file(self.dir.join(path), self.format)?;
+                println!("{} Saved changelog to: {}", "✓".green(), path);
+            }
         }
 
-        if verbose {
-            println!(
-                "Generating changelog for {} ({} updates)...",
-                current_tag,
-                updates.len()
-            );
+        if self.stdout || self.file.is_none() {
+            // Oldest first, as combine_rendered_changelog_entries expects
+            let rendered: Vec<String> = rebuilt
+                .map(str::to_string)
+                .into_iter()
+                .chain(entry.map(|entry| entry.render(self.format)))
+                .collect();
+            if !rendered.is_empty() {
+                println!("\n{}", "═".repeat(60));
+                println!("{}", combine_rendered_changelog_entries(rendered));
+            }
         }
 
-        let changelogs = collector
-            .collect_changelogs(&updates, &config.packages)
-            .await?;
+        Ok(())
+    }
+}
 
-        let date = git.tag_date(current_tag).unwrap_or_else(|_| current_date());
+/// Fetch the changelogs of `updates` and consolidate them with the pin changes under
+/// `version`; the one pipeline behind `changelog`, `release` and `update-release`
+async fn collect_changelog(
+    config: &Config,
+    store: &PackageMetadataStore,
+    updates: &[VersionUpdate],
+    pin_diff: PinDiff,
+    version: &str,
+    plan: &ChangelogPlan,
+) -> Result<ConsolidatedChangelog> {
+    let collector = changelog_collector(config, plan.refresh)?.with_store(store);
+    let spinner = create_spinner("Fetching changelogs from packages...");
+    let changelogs = collector
+        .collect_changelogs(updates, &config.packages)
+        .await?;
+    spinner.finish_with_message("Changelog collection complete");
 
-        let consolidated = ConsolidatedChangelog::with_templates(
-            &release_version,
-            &date,
-            changelogs,
-            &config.changelog,
+    let found_count = changelogs.iter().filter(|c| !c.entries.is_empty()).count();
+    if !plan.json {
+        println!(
+            "{} Found changelog entries for {}/{} packages",
+            "✓".green(),
+            found_count,
+            changelogs.len()
+        );
+    }
+
+    if plan.require {
+        check_changelog_completeness(
+            &changelogs,
+            config.changelog.max_missing_fraction,
+            plan.allow_missing,
+        )?;
+    }
+
+    let authors = if config.changelog.contributors {
+        authors_since_latest_tag(config)
+    } else {
+        Vec::new()
+    };
+
+    Ok(ConsolidatedChangelog::with_templates(
+        version,
+        &current_date(),
+        changelogs,
+        &config.changelog,
+    )
+    .with_package_overrides(&config.packages)
+    .with_pin_changes(pin_diff)
+    .with_contributors(authors))
+}
+
+/// Render the changelog of every release in `range`, newest first, or `None` when
+/// no release changed anything. An interrupted rebuild leaves what it generated
+/// in `<partial_base>.partial`
+#[allow(clippy::too_many_arguments)]
+async fn rebuild_changelog_from_tags(
+    config: &Config,
+    packages_filter: Option<&str>,
+    format: ChangelogFormat,
+    partial_base: Option<&str>,
+    range: &TagRange,
+    use_cache: bool,
+    refresh: bool,
+    jobs: usize,
+    verbose: bool,
+) -> Result<Option<String>> {
+    let git = git_for(config);
+    // Callers report the tokens that matched nothing
+    let (packages_to_check, _) = filter_packages(&config.packages, packages_filter);
+
+    if !git.is_repo() {
+        return Err(ReleaserError::GitError(
+            "Rebuild requires running inside a git repository".to_string(),
+        ));
+    }
+
+    let mut version_tags = git.get_version_tags(&config.github.tag_prefix)?;
+
+    // Sort ascending (oldest first) for a full rebuild
+    version_tags.reverse();
+
+    let version_tags = range.select(version_tags, &config.github.tag_prefix)?;
+
+    if version_tags.len() < 2 {
+        return Err(ReleaserError::GitError(
+            "Need at least two version tags to rebuild changelog".to_string(),
+        ));
+    }
+
+    if verbose {
+        println!(
+            "Rebuilding from {} to {} ({} tags)",
+            version_tags[0].0,
+            version_tags[version_tags.len() - 1].0,
+            version_tags.len()
+        );
+    }
+
+    let cache = SnapshotCache::new(config.resolve(DEFAULT_CACHE_DIR));
+    let tag_commits = if use_cache {
+        git.tag_commits().unwrap_or_default()
+    } else {
+        Default::default()
+    };
+    let mut snapshots = Vec::new();
+
+    for (tag, version) in &version_tags {
+        let versions_file = config.history.versions_file_for(
+            version,
+            &config.github.tag_prefix,
+            &config.versions_file,
         );
+        let commit = tag_commits.get(tag);
+
+        if let Some(snapshot) = commit.and_then(|sha| cache.get(sha, versions_file)) {
+            if verbose {
+                println!("Using cached {} for tag {}", versions_file, tag);
+            }
+            snapshots.push((tag.clone(), snapshot));
+            continue;
+        }
+
+        if verbose {
+            println!("Loading {} from tag {}...", versions_file, tag);
+        }
+
+        match git.show_file_at_ref(tag, versions_file) {
+            Ok(content) => {
+                let snapshot =
+                    BuildoutVersions::from_content(content, format!("{}@{}", versions_file, tag))?;
+                if let Some(sha) = commit {
+                    if let Err(e) = cache.put(sha, versions_file, &snapshot) {
+                        if verbose {
+                            println!("Could not cache snapshot for {}: {}", tag, e);
+                        }
+                    }
+                }
+                snapshots.push((tag.clone(), snapshot));
+            }
+            Err(_) => warnings::warn(
+                WarningKind::History,
+                Some(tag),
+                format!(
+                    "skipped, {} not found (add a [[history.paths]] entry if it moved)",
+                    versions_file
+                ),
+            ),
+        }
+    }
+
+    if snapshots.len() < 2 {
+        return Err(ReleaserError::GitError(
+            "Need at least two version tags containing the versions file to rebuild changelog"
+                .to_string(),
+        ));
+    }
+
+    // Work out what changed between consecutive tags before hitting the network
+    let mut windows = Vec::new();
+
+    for pair in snapshots.windows(2) {
+        let previous = &pair[0].1;
+        let (current_tag, current) = &pair[1];
+        let release_version = if config.github.tag_prefix.is_empty() {
+            current_tag.clone()
+        } else {
+            current_tag
+                .strip_prefix(&config.github.tag_prefix)
+                .unwrap_or(current_tag)
+                .to_string()
+        };
+
+        let mut updates = Vec::new();
+
+        for pkg in &packages_to_check {
+            let name = pkg.buildout_name();
+            let old_version = previous.get_version(name);
+            let new_version = current.get_version(name);
+
+            if let (Some(old_version), Some(new_version)) = (old_version, new_version) {
+                if old_version != new_version {
+                    updates.push(VersionUpdate {
+                        package_name: name.to_string(),
+                        old_version: old_version.to_string(),
+                        new_version: new_version.to_string(),
+                    });
+                }
+            }
+        }
+
+        let mut pin_diff = current.diff_pins(previous);
+        pin_diff.retain(|name| include_pin_change(config, packages_filter, name));
+
+        if updates.is_empty() && pin_diff.is_empty() {
+            continue;
+        }
 
-        rendered_entries.push(consolidated.render(format));
+        windows.push((current_tag.clone(), release_version, updates, pin_diff));
     }
 
-    if rendered_entries.is_empty() {
+    if windows.is_empty() {
         println!("{}", "No changelog entries generated from tags.".yellow());
-        return Ok(());
+        return Ok(None);
     }
 
-    let combined_output = combine_rendered_changelog_entries(rendered_entries);
+    let total = windows.len();
+    let collector = Arc::new(changelog_collector(config, refresh)?);
+    let shared_config = Arc::new(config.clone());
+    let semaphore = Arc::new(Semaphore::new(jobs.max(1)));
+    let progress = create_progress_bar(total, "Rebuilding changelog");
+    let mut join_set = JoinSet::new();
+
+    for (index, (tag, release_version, updates, pin_diff)) in windows.into_iter().enumerate() {
+        let collector = collector.clone();
+        let config = shared_config.clone();
+        let semaphore = semaphore.clone();
+        let progress = progress.clone();
 
-    match output_file {
-        Some(path) => {
-            std::fs::write(&path, combined_output.trim_end())?;
-            println!("\n{} Rebuilt changelog saved to: {}", "✓".green(), path);
+        join_set.spawn(async move {
+            let _permit = semaphore.acquire_owned().await.map_err(|_| {
+                ReleaserError::GitError("Failed to acquire rebuild job permit".to_string())
+            })?;
+
+            let status = format!(
+                "{}: {} updates, {} added, {} removed",
+                tag,
+                updates.len(),
+                pin_diff.added.len(),
+                pin_diff.removed.len()
+            );
+            match progress.as_ref() {
+                Some(pb) => {
+                    pb.set_message(format!("Collecting changelogs for {}", status));
+                    if verbose {
+                        pb.println(format!("Generating changelog for {}", status));
+                    }
+                }
+                None if verbose => println!("Generating changelog for {}", status),
+                None => {}
+            }
+
+            let changelogs = collector
+                .collect_changelogs(&updates, &config.packages)
+                .await?;
+
+            let date = git_for(&config)
+                .tag_date(&tag)
+                .unwrap_or_else(|_| current_date());
+
+            let rendered = ConsolidatedChangelog::with_templates(
+                &release_version,
+                &date,
+                changelogs,
+                &config.changelog,
+            )
+            .with_package_overrides(&config.packages)
+            .with_pin_changes(pin_diff)
+            .render(format);
+
+            if let Some(pb) = progress.as_ref() {
+                pb.inc(1);
+            }
+
+            Ok::<(usize, String), ReleaserError>((index, rendered))
+        });
+    }
+
+    let mut results: Vec<Option<String>> = vec![None; total];
+    let mut interrupted = false;
+    let ctrl_c = tokio::signal::ctrl_c();
+    tokio::pin!(ctrl_c);
+
+    loop {
+        tokio::select! {
+            joined = join_set.join_next() => match joined {
+                None => break,
+                Some(Ok(Ok((index, rendered)))) => results[index] = Some(rendered),
+                Some(Ok(Err(err))) => return Err(err),
+                Some(Err(err)) => {
+                    return Err(ReleaserError::GitError(format!(
+                        "Failed to join rebuild task: {}",
+                        err
+                    )))
+                }
+            },
+            _ = &mut ctrl_c => {
+                interrupted = true;
+                join_set.abort_all();
+                break;
+            }
         }
-        None => {
-            println!("\n{}", "═".repeat(60));
-            println!("{}", combined_output.trim_end());
+    }
+
+    if let Some(pb) = progress {
+        if interrupted {
+            pb.abandon_with_message("Rebuild interrupted");
+        } else {
+            pb.finish_with_message("Rebuild complete");
         }
     }
 
-    Ok(())
+    let rendered_entries: Vec<String> = results.into_iter().flatten().collect();
+
+    if interrupted {
+        let partial_path = format!("{}.partial", partial_base.unwrap_or("CHANGELOG.md"));
+        let generated = rendered_entries.len();
+
+        if generated > 0 {
+            let partial_output = combine_rendered_changelog_entries(rendered_entries);
+            std::fs::write(config.resolve(&partial_path), partial_output.trim_end())?;
+            println!(
+                "\n{} Interrupted: wrote {}/{} release entries to {}",
+                "⚠".yellow(),
+                generated,
+                total,
+                partial_path
+            );
+        }
+
+        return Err(ReleaserError::IoError(std::io::Error::new(
+            std::io::ErrorKind::Interrupted,
+            "Changelog rebuild interrupted",
+        )));
+    }
+
+    Ok(Some(combine_rendered_changelog_entries(rendered_entries)))
 }
 
 fn combine_rendered_changelog_entries(entries: Vec<String>) -> String {
@@ -321,7 +1127,533 @@ fn combine_rendered_changelog_entries(entries: Vec<String>) -> String {
 
 #[cfg(test)]
 mod tests {
-    use super::combine_rendered_changelog_entries;
+    use super::{
+        check_changelog_completeness, classify_update, combine_rendered_changelog_entries,
+        ensure_release_branch, ensure_tag_available, filter_packages, generate_release_notes,
+        git_for, homepage, is_bldr_commit, is_prerelease, is_upgrade, pin_changes_between,
+        push_target, release_history, run, ChangelogArgs, ChangelogFormat, ChangelogPlan, Cli,
+        CliChangelogFormat, Config, ConfigTemplate, GitOps, PackageChangelog, PackageConfig,
+        PackageInfo, PullRequestBranch, TagMetadata, TagRange, UpdateKind, Version, VersionUpdate,
+    };
+    use crate::testing::temp_dir;
+    use clap::Parser;
+    use std::sync::Mutex;
+    use tempfile::TempDir;
+
+    fn ascending_tags(names: &[&str]) -> Vec<(String, Version)> {
+        names
+            .iter()
+            .map(|name| (name.to_string(), Version::parse(name).unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn test_release_notes_skip_excluded_packages() {
+        let config: Config = toml::from_str(
+            r#"
+            versions_file = "versions.cfg"
+
+            [[packages]]
+            name = "plone.api"
+
+            [[packages]]
+            name = "Products.CMFPlone"
+            include_in_changelog = false
+            "#,
+        )
+        .unwrap();
+        let update = |name: &str| VersionUpdate {
+            package_name: name.to_string(),
+            old_version: "1.0".to_string(),
+            new_version: "1.1".to_string(),
+        };
+
+        let notes = generate_release_notes(
+            &config,
+            &[update("plone.api"), update("products.cmfplone")],
+            "1.2.0",
+        );
+        assert!(notes.contains("- **plone.api**: 1.0 → 1.1"));
+        assert!(!notes.to_lowercase().contains("cmfplone"));
+    }
+
+    #[test]
+    fn test_changelog_plan_for_release() {
+        let mut config: Config = toml::from_str("versions_file = \"versions.cfg\"").unwrap();
+        config.changelog.required = true;
+
+        // `release` collects only when asked, `update-release` follows changelog.enabled
+        let args = ChangelogArgs::default();
+        assert!(ChangelogPlan::for_release(&config, &args, false).is_none());
+        let plan = ChangelogPlan::for_release(&config, &args, true).unwrap();
+        assert_eq!(plan.file.as_deref(), Some("CHANGELOG.md"));
+        assert!(plan.require && !plan.stdout && !plan.rebuild);
+
+        let args = ChangelogArgs {
+            changelog: true,
+            changelog_format: Some(CliChangelogFormat::Rst),
+            changelog_file: Some("CHANGES.rst".to_string()),
+            changelog_stdout: true,
+            ..Default::default()
+        };
+        let plan = ChangelogPlan::for_release(&config, &args, false).unwrap();
+        assert_eq!(plan.format, ChangelogFormat::Rst);
+        assert_eq!(plan.file.as_deref(), Some("CHANGES.rst"));
+        assert!(plan.stdout);
+
+        let args = ChangelogArgs {
+            no_changelog: true,
+            ..Default::default()
+        };
+        assert!(ChangelogPlan::for_release(&config, &args, true).is_none());
+    }
+
+    #[test]
+    fn test_filter_packages_matches_buildout_and_normalized_names() {
+        let packages: Vec<PackageConfig> = [
+            "name = \"plone.api\"",
+            "name = \"Pillow\"\nbuildout_name = \"pillow-simd\"",
+            "name = \"six\"",
+        ]
+        .iter()
+        .map(|entry| toml::from_str(entry).unwrap())
+        .collect();
+
+        let (selected, unmatched) =
+            filter_packages(&packages, Some("Plone_API, pillow-simd,missing"));
+        let names: Vec<&str> = selected.iter().map(|p| p.name.as_str()).collect();
+
+        assert_eq!(names, vec!["plone.api", "Pillow"]);
+        assert_eq!(unmatched, vec!["missing"]);
+    }
+
+    fn tag_names(tags: &[(String, Version)]) -> Vec<&str> {
+        tags.iter().map(|(tag, _)| tag.as_str()).collect()
+    }
+
+    #[test]
+    fn selects_tag_range_bounds() {
+        let tags = ascending_tags(&["v1.0.0", "v1.1.0", "v1.2.0", "v2.0.0"]);
+
+        let range = TagRange {
+            from: Some("1.1.0".to_string()),
+            to: Some("v1.2.0".to_string()),
+            last: None,
+        };
+        let selected = range.select(tags.clone(), "v").unwrap();
+        assert_eq!(tag_names(&selected), vec!["v1.1.0", "v1.2.0"]);
+
+        let range = TagRange {
+            last: Some(2),
+            ..TagRange::default()
+        };
+        let selected = range.select(tags.clone(), "v").unwrap();
+        assert_eq!(tag_names(&selected), vec!["v1.1.0", "v1.2.0", "v2.0.0"]);
+
+        let range = TagRange {
+            from: Some("v9.9.9".to_string()),
+            ..TagRange::default()
+        };
+        assert!(range.select(tags, "v").is_err());
+    }
+
+    #[test]
+    fn enforces_changelog_completeness_threshold() {
+        let changelog = |name: &str, entries: usize| PackageChangelog {
+            package_name: name.to_string(),
+            old_version: "1.0".to_string(),
+            new_version: "1.1".to_string(),
+            entries: (0..entries)
+                .map(|_| crate::changelog::ChangelogEntry {
+                    version: "1.1".to_string(),
+                    date: None,
+                    content: "- Fix".to_string(),
+                    markup: None,
+                })
+                .collect(),
+            compare_url: None,
+            truncated: false,
+        };
+        let changelogs = vec![changelog("a", 1), changelog("b", 0)];
+
+        assert!(check_changelog_completeness(&changelogs, 0.0, false).is_err());
+        assert!(check_changelog_completeness(&changelogs, 0.0, true).is_ok());
+        assert!(check_changelog_completeness(&changelogs, 0.5, false).is_ok());
+    }
+
+    fn git_at(dir: &std::path::Path, args: &[&str]) {
+        let status = std::process::Command::new("git")
+            .current_dir(dir)
+            .args(["-c", "user.name=bldr", "-c", "user.email=bldr@example.com"])
+            .args(args)
+            .status()
+            .unwrap();
+        assert!(status.success());
+    }
+
+    /// Repository on `main` with releases v1.0.0, v1.1.0 (annotated) and
+    /// v1.2.0 (annotated, with a metadata trailer) of versions.cfg
+    fn release_repo(name: &str) -> (TempDir, Config, GitOps) {
+        let tmp = temp_dir(name);
+        let dir = tmp.path();
+        let metadata = TagMetadata {
+            version: "1.2.0".to_string(),
+            date: "2025-06-03".to_string(),
+            previous: Some("v1.1.0".to_string()),
+            updated: vec![VersionUpdate {
+                package_name: "plone.api".to_string(),
+                old_version: "2.1.0".to_string(),
+                new_version: "2.2.0".to_string(),
+            }],
+            added: Vec::new(),
+            removed: Vec::new(),
+        };
+
+        git_at(dir, &["init", "-q", "-b", "main"]);
+        let releases = [
+            ("1.0.0", "plone.api = 2.0.0\n", None),
+            (
+                "1.1.0",
+                "plone.api = 2.1.0\nplone.foo = 1.0\n",
+                Some("Release 1.1.0".to_string()),
+            ),
+            (
+                "1.2.0",
+                "plone.api = 2.2.0\nplone.foo = 1.0\n",
+                Some(format!("Release 1.2.0\n\n{}", metadata.to_trailer())),
+            ),
+        ];
+        for (version, pins, message) in releases {
+            std::fs::write(dir.join("versions.cfg"), format!("[versions]\n{}", pins)).unwrap();
+            git_at(dir, &["add", "versions.cfg"]);
+            git_at(dir, &["commit", "-q", "-m", version]);
+            let tag = format!("v{}", version);
+            match &message {
+                Some(message) => git_at(dir, &["tag", "-a", &tag, "-m", message]),
+                None => git_at(dir, &["tag", &tag]),
+            }
+        }
+
+        let mut config = Config::from_template(ConfigTemplate::Minimal);
+        config.github.tag_prefix = "v".to_string();
+        config.work_dir = dir.to_path_buf();
+        let git = git_for(&config);
+        (tmp, config, git)
+    }
+
+    #[test]
+    fn test_release_history() {
+        let (_tmp, config, git) = release_repo("history");
+        let history = release_history(&config, &git, None, false).unwrap();
+
+        let tags: Vec<&str> = history.iter().map(|entry| entry.tag.as_str()).collect();
+        assert_eq!(tags, vec!["v1.2.0", "v1.1.0", "v1.0.0"]);
+        // From the metadata trailer, which is left out of the message
+        assert_eq!(history[0].updated[0].new_version, "2.2.0");
+        assert_eq!(history[0].message.as_deref(), Some("Release 1.2.0"));
+        // From the versions file at the tags
+        assert_eq!(history[1].previous.as_deref(), Some("v1.0.0"));
+        assert_eq!(history[1].updated[0].old_version, "2.0.0");
+        assert_eq!(history[1].pin_changes.added[0].name, "plone.foo");
+        assert!(history[2].updated.is_empty());
+        assert_eq!(history[2].message, None);
+
+        let latest = release_history(&config, &git, Some(2), false).unwrap();
+        assert_eq!(latest.len(), 2);
+        assert_eq!(latest[1].updated[0].new_version, "2.1.0");
+    }
+
+    #[test]
+    fn test_ensure_tag_available() {
+        let (tmp, config, git) = release_repo("tag-available");
+        let dir = tmp.path();
+        let remote_tmp = temp_dir("tag-available-remote");
+        let remote = remote_tmp.path();
+        git_at(remote, &["init", "-q", "--bare"]);
+        git_at(dir, &["remote", "add", "origin", remote.to_str().unwrap()]);
+        git_at(dir, &["push", "-q", "origin", "v1.1.0", "v1.2.0"]);
+        git_at(dir, &["tag", "-d", "v1.1.0"]);
+
+        let err = ensure_tag_available(&config, &git, "1.2.0", false, false).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Tag v1.2.0 already exists locally"));
+        let err = ensure_tag_available(&config, &git, "1.2.0", true, false).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Tag v1.2.0 already exists locally and on origin"));
+        // Deleted locally, still on the remote
+        assert!(ensure_tag_available(&config, &git, "1.1.0", false, false).is_ok());
+        let err = ensure_tag_available(&config, &git, "1.1.0", true, false).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Tag v1.1.0 already exists on origin"));
+
+        assert!(ensure_tag_available(&config, &git, "1.2.0", true, true).is_ok());
+        assert!(ensure_tag_available(&config, &git, "1.3.0", true, false).is_ok());
+    }
+
+    #[test]
+    fn test_rollback_keeps_a_user_commit() {
+        let (tmp, config, git) = release_repo("rollback-user");
+        let dir = tmp.path();
+        std::fs::write(
+            dir.join("bldr.toml"),
+            "versions_file = \"versions.cfg\"\n\n[github]\ntag_prefix = \"v\"\n",
+        )
+        .unwrap();
+        std::fs::write(dir.join("README.md"), "Docs\n").unwrap();
+        git_at(dir, &["add", "."]);
+        git_at(dir, &["commit", "-q", "-m", "Update docs"]);
+        // `release` made no commit, so the tag is on the user's commit
+        git_at(dir, &["tag", "v1.3.0"]);
+        let user_commit = git.head_commit().unwrap();
+        assert!(!is_bldr_commit(&config, &git, &user_commit).unwrap());
+
+        // Pins rewritten without a commit come back from their backup
+        let pinned = std::fs::read_to_string(dir.join("versions.cfg")).unwrap();
+        std::fs::write(dir.join("versions.cfg.bak"), &pinned).unwrap();
+        std::fs::write(dir.join("versions.cfg"), "[versions]\nplone.api = 9.0.0\n").unwrap();
+
+        let repo = dir.to_str().unwrap();
+        run_cli(&[
+            "-C",
+            repo,
+            "rollback",
+            "--local-only",
+            "--yes",
+            "--restore-backup",
+        ])
+        .unwrap();
+        assert_eq!(git.head_commit().unwrap(), user_commit);
+        assert!(!git.tag_commits().unwrap().contains_key("v1.3.0"));
+        assert_eq!(
+            std::fs::read_to_string(dir.join("versions.cfg")).unwrap(),
+            pinned
+        );
+        assert!(!dir.join("versions.cfg.bak").exists());
+
+        // A release commit at HEAD that was never pushed is dropped
+        std::fs::write(dir.join("versions.cfg"), "[versions]\nplone.api = 2.3.0\n").unwrap();
+        git_at(dir, &["commit", "-q", "-am", "Release 1.3.0"]);
+        git_at(dir, &["tag", "v1.3.0"]);
+        assert!(is_bldr_commit(&config, &git, &git.head_commit().unwrap()).unwrap());
+        run_cli(&["-C", repo, "rollback", "--local-only", "--yes"]).unwrap();
+        assert_eq!(git.head_commit().unwrap(), user_commit);
+    }
+
+    #[test]
+    fn test_rollback_undoes_the_commit_before_deleting_tags() {
+        let (tmp, config, git) = release_repo("rollback-order");
+        let dir = tmp.path();
+        std::fs::write(dir.join("versions.cfg"), "[versions]\nplone.api = 2.3.0\n").unwrap();
+        git_at(dir, &["commit", "-q", "-am", "Release 1.3.0"]);
+        git_at(dir, &["tag", "v1.3.0"]);
+        let release_commit = git.head_commit().unwrap();
+        assert!(is_bldr_commit(&config, &git, &release_commit).unwrap());
+
+        // `reset --keep` refuses to drop a commit whose files have local changes
+        std::fs::write(dir.join("versions.cfg"), "[versions]\nplone.api = 2.4.0\n").unwrap();
+        let repo = dir.to_str().unwrap();
+        assert!(run_cli(&["-C", repo, "rollback", "--local-only", "--yes"]).is_err());
+        assert_eq!(git.head_commit().unwrap(), release_commit);
+        assert!(git.tag_commits().unwrap().contains_key("v1.3.0"));
+    }
+
+    #[test]
+    fn test_rollback_reverts_a_first_release() {
+        let tmp = temp_dir("rollback-first");
+        let dir = tmp.path();
+        std::fs::write(
+            dir.join("bldr.toml"),
+            "versions_file = \"versions.cfg\"\n\n[github]\ntag_prefix = \"v\"\n",
+        )
+        .unwrap();
+        std::fs::write(dir.join("versions.cfg"), "[versions]\nplone.api = 2.0.0\n").unwrap();
+        git_at(dir, &["init", "-q", "-b", "main"]);
+        git_at(dir, &["config", "user.name", "bldr"]);
+        git_at(dir, &["config", "user.email", "bldr@example.com"]);
+        git_at(dir, &["add", "versions.cfg"]);
+        git_at(dir, &["commit", "-q", "-m", "Release 1.0.0"]);
+        git_at(dir, &["tag", "v1.0.0"]);
+        let git = GitOps::new().with_work_dir(dir.to_string_lossy());
+        let release_commit = git.head_commit().unwrap();
+        assert!(git.is_root_commit(&release_commit).unwrap());
+
+        let repo = dir.to_str().unwrap();
+        let err = run_cli(&[
+            "-C",
+            repo,
+            "rollback",
+            "--local-only",
+            "--yes",
+            "--commit",
+            "reset",
+        ])
+        .unwrap_err();
+        assert!(err.to_string().contains("first commit of the repository"));
+        assert!(git.tag_commits().unwrap().contains_key("v1.0.0"));
+
+        // Without --commit, the first commit is reverted instead of reset
+        run_cli(&["-C", repo, "rollback", "--local-only", "--yes"]).unwrap();
+        assert!(!git.tag_commits().unwrap().contains_key("v1.0.0"));
+        assert!(!git.is_root_commit(&git.head_commit().unwrap()).unwrap());
+        assert!(!dir.join("versions.cfg").exists());
+    }
+
+    #[test]
+    fn test_pull_request_branch() {
+        let (tmp, mut config, git) = release_repo("pr-branch");
+
+        let dir = tmp.path();
+
+        let branch = PullRequestBranch::plan(&config, &git, "release-1.3.0").unwrap();
+        assert_eq!(branch.name, "bldr/release-1.3.0");
+        assert_eq!(branch.base, "main");
+        assert_eq!(branch.previous.as_deref(), Some("main"));
+        branch.start(&git).unwrap();
+        assert_eq!(
+            git.current_branch().unwrap().as_deref(),
+            Some("bldr/release-1.3.0")
+        );
+
+        // A branch left over from an earlier run is not reused
+        git_at(dir, &["checkout", "-q", "main"]);
+        let again = PullRequestBranch::plan(&config, &git, "release-1.3.0").unwrap();
+        let err = again.start(&git).unwrap_err();
+        assert!(err.to_string().contains("already exists"), "{}", err);
+        assert_eq!(git.current_branch().unwrap().as_deref(), Some("main"));
+
+        git_at(dir, &["checkout", "-q", "--detach"]);
+        let err = PullRequestBranch::plan(&config, &git, "release-1.3.0").err();
+        assert!(err.unwrap().to_string().contains("set git.branch"));
+        config.git.branch = Some("stable".to_string());
+        let branch = PullRequestBranch::plan(&config, &git, "release-1.3.0").unwrap();
+        assert_eq!(branch.base, "stable");
+        assert_eq!(branch.previous, None);
+    }
+
+    #[test]
+    fn test_ensure_release_branch() {
+        let (tmp, mut config, git) = release_repo("release-branch");
+        let dir = tmp.path();
+        git_at(dir, &["branch", "stable"]);
+
+        // Without git.branch any branch is fine, and HEAD is pushed as is
+        assert!(ensure_release_branch(&config, &git).is_ok());
+        assert_eq!(push_target(&config, None), None);
+
+        config.git.branch = Some("stable".to_string());
+        let err = ensure_release_branch(&config, &git).unwrap_err();
+        assert!(err.to_string().contains("On branch 'main'"), "{}", err);
+        assert_eq!(git.current_branch().unwrap().as_deref(), Some("main"));
+        assert_eq!(push_target(&config, None), Some("stable"));
+        assert_eq!(push_target(&config, Some("hotfix")), Some("hotfix"));
+
+        // A detached HEAD is pushed to git.branch
+        git_at(dir, &["checkout", "-q", "--detach"]);
+        assert!(ensure_release_branch(&config, &git).is_ok());
+
+        git_at(dir, &["checkout", "-q", "main"]);
+        config.git.switch_branch = true;
+        ensure_release_branch(&config, &git).unwrap();
+        assert_eq!(git.current_branch().unwrap().as_deref(), Some("stable"));
+        assert!(ensure_release_branch(&config, &git).is_ok());
+    }
+
+    #[test]
+    fn test_pin_changes_between() {
+        let (tmp, config, git) = release_repo("pin-changes");
+
+        let dir = tmp.path();
+
+        let (updated, pin_changes) =
+            pin_changes_between(&config, &git, "v1.0.0", Some("v1.2.0")).unwrap();
+        assert_eq!(updated[0].old_version, "2.0.0");
+        assert_eq!(updated[0].new_version, "2.2.0");
+        assert_eq!(pin_changes.added[0].name, "plone.foo");
+        assert!(pin_changes.removed.is_empty());
+        assert!(pin_changes_between(&config, &git, "v0.9.0", Some("v1.2.0")).is_err());
+
+        // Against the working tree without a second ref
+        std::fs::write(dir.join("versions.cfg"), "[versions]\nplone.api = 2.3.0\n").unwrap();
+        let (updated, pin_changes) = pin_changes_between(&config, &git, "v1.2.0", None).unwrap();
+        assert_eq!(updated[0].new_version, "2.3.0");
+        assert_eq!(pin_changes.removed[0].name, "plone.foo");
+    }
+
+    #[test]
+    fn test_info_all() {
+        let parse = |args: &[&str]| {
+            Cli::try_parse_from(std::iter::once("bldr").chain(args.iter().copied()))
+        };
+        assert!(parse(&["info", "--all", "--json"]).is_ok());
+        assert!(parse(&["info"]).is_err());
+        assert!(parse(&["info", "six", "--all"]).is_err());
+        assert!(parse(&["info", "--all", "--versions"]).is_err());
+        assert!(parse(&["info", "six", "--json"]).is_err());
+
+        let info = |value: serde_json::Value| -> PackageInfo {
+            let mut fields = serde_json::json!({"name": "six", "version": "1.17.0"});
+            fields
+                .as_object_mut()
+                .unwrap()
+                .extend(value.as_object().unwrap().clone());
+            serde_json::from_value(fields).unwrap()
+        };
+        let both = info(serde_json::json!({
+            "home_page": "https://legacy.example.com",
+            "project_urls": {"Homepage": "https://example.com"},
+        }));
+        assert_eq!(homepage(&both), Some("https://example.com"));
+        // Only the legacy field, as on older uploads
+        let legacy = info(serde_json::json!({"home_page": "https://legacy.example.com"}));
+        assert_eq!(homepage(&legacy), Some("https://legacy.example.com"));
+        let empty = info(serde_json::json!({"home_page": ""}));
+        assert_eq!(homepage(&empty), None);
+    }
+
+    #[test]
+    fn test_pin_changes_in_subdirectory_project() {
+        let tmp = temp_dir("subdir-project");
+        let dir = tmp.path();
+        let project = dir.join("cust/a");
+        std::fs::create_dir_all(&project).unwrap();
+        git_at(dir, &["init", "-q", "-b", "main"]);
+        for (version, pin) in [("1.0.0", "2.0.0"), ("1.1.0", "2.1.0")] {
+            std::fs::write(
+                project.join("versions.cfg"),
+                format!("[versions]\nplone.api = {}\n", pin),
+            )
+            .unwrap();
+            git_at(dir, &["add", "."]);
+            git_at(dir, &["commit", "-q", "-m", version]);
+            git_at(dir, &["tag", &format!("a-{}", version)]);
+        }
+
+        let mut config = Config::from_template(ConfigTemplate::Minimal);
+        config.work_dir = project;
+        let git = git_for(&config);
+        let (updated, _) = pin_changes_between(&config, &git, "a-1.0.0", Some("a-1.1.0")).unwrap();
+        assert_eq!(updated[0].old_version, "2.0.0");
+        assert_eq!(updated[0].new_version, "2.1.0");
+    }
+
+    #[test]
+    fn test_is_prerelease() {
+        for version in [
+            "2.0.0rc1",
+            "2.0.0b2",
+            "2.0.0.dev3",
+            "2.0.0-beta.1",
+            "v2.0.0a1",
+        ] {
+            assert!(is_prerelease(version), "{}", version);
+        }
+        for version in ["2.0.0", "v2.0.0", "2.0.0.post1", "not-a-version"] {
+            assert!(!is_prerelease(version), "{}", version);
+        }
+    }
 
     #[test]
     fn combines_entries_with_newest_first() {
@@ -351,19 +1683,303 @@ mod tests {
             "## 2.1.0\n\n- Improvements\n\n## 2.0.0\n\n- Major updates"
         );
     }
-}
 
-async fn cmd_check(
+    #[test]
+    fn classifies_updates_by_changed_segment() {
+        assert_eq!(classify_update(Some("1.2.3"), "2.0.0"), UpdateKind::Major);
+        assert_eq!(classify_update(Some("1.2.3"), "1.3"), UpdateKind::Minor);
+        assert_eq!(
+            classify_update(Some("1.2.3"), "1.2.4.post1"),
+            UpdateKind::Patch
+        );
+        assert_eq!(classify_update(Some("1.2"), "1.2"), UpdateKind::UpToDate);
+        assert_eq!(classify_update(None, "1.0"), UpdateKind::Unpinned);
+        // A pin newer than the cooldown allows is not downgraded
+        assert_eq!(
+            classify_update(Some("2.1.0"), "2.0.0"),
+            UpdateKind::UpToDate
+        );
+        assert!(!is_upgrade("2.1.0", "2.0.0"));
+        assert!(!is_upgrade("1.0", "1.0.0"));
+        assert!(is_upgrade("2.0.0", "2.1.0"));
+        assert!(UpdateKind::Security < UpdateKind::Major);
+    }
+
+    /// `run` sets the process-wide work directory, so tests calling it take turns
+    static RUN_LOCK: Mutex<()> = Mutex::new(());
+
+    fn run_cli(args: &[&str]) -> super::Result<Vec<String>> {
+        let _guard = RUN_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let cli = Cli::parse_from(std::iter::once("bldr").chain(args.iter().copied()));
+        tokio::runtime::Runtime::new().unwrap().block_on(run(cli))
+    }
+
+    #[test]
+    fn test_runs_in_repo_dir() {
+        let tmp = temp_dir("repo-dir");
+        let dir = tmp.path();
+        std::fs::write(dir.join("bldr.toml"), "versions_file = \"versions.cfg\"\n").unwrap();
+        std::fs::write(dir.join("versions.cfg"), "[versions]\nsix = 1.16.0\n").unwrap();
+        git_at(dir, &["init", "-q", "-b", "main"]);
+        git_at(dir, &["add", "."]);
+        git_at(dir, &["commit", "-q", "-m", "Initial"]);
+        git_at(dir, &["tag", "v1.0.0"]);
+        let cwd = std::env::current_dir().unwrap();
+
+        let repo = dir.to_str().unwrap();
+        run_cli(&["-C", repo, "add", "plone.api", "--pin", "2.0.0"]).unwrap();
+
+        assert_eq!(std::env::current_dir().unwrap(), cwd);
+        let config = Config::load_in(dir, "bldr.toml").unwrap();
+        assert_eq!(config.versions_file, "versions.cfg");
+        assert_eq!(config.packages[0].name, "plone.api");
+        let versions = std::fs::read_to_string(dir.join("versions.cfg")).unwrap();
+        assert!(versions.contains("plone.api = 2.0.0"));
+
+        // Git runs in the work directory too
+        let tags = git_for(&config).get_version_tags("v").unwrap();
+        assert_eq!(tags[0].0, "v1.0.0");
+
+        let missing = dir.join("missing");
+        assert!(run_cli(&["-C", missing.to_str().unwrap(), "list"]).is_err());
+    }
+
+    #[test]
+    fn test_init_defaults_to_minimal() {
+        let tmp = temp_dir("init-default");
+        let dir = tmp.path();
+        let repo = dir.to_str().unwrap();
+
+        run_cli(&["-C", repo, "init"]).unwrap();
+        let config = Config::load_in(dir, "bldr.toml").unwrap();
+        assert!(!config.annotate_pins);
+        assert!(!config.follow_extends);
+        assert!(config.metadata_files.is_empty());
+
+        run_cli(&["-C", repo, "init", "--force", "--template", "full"]).unwrap();
+        let config = Config::load_in(dir, "bldr.toml").unwrap();
+        assert!(config.annotate_pins);
+        assert!(config.follow_extends);
+    }
+
+    #[test]
+    fn test_add_with_pin() {
+        let tmp = temp_dir("add-pin");
+        let dir = tmp.path();
+        std::fs::write(dir.join("bldr.toml"), "versions_file = \"versions.cfg\"\n").unwrap();
+        std::fs::write(dir.join("versions.cfg"), "[versions]\nsix = 1.16.0\n").unwrap();
+        let repo = dir.to_str().unwrap();
+        let tracked = || {
+            let config = Config::load_in(dir, "bldr.toml").unwrap();
+            config
+                .packages
+                .into_iter()
+                .map(|p| p.name)
+                .collect::<Vec<_>>()
+        };
+
+        // A pin that can't be written leaves the config untouched
+        let err = run_cli(&["-C", repo, "add", "plone.api", "--pin", "latest"]).unwrap_err();
+        assert!(err.to_string().contains("not a valid version"), "{}", err);
+        let err = run_cli(&["-C", repo, "add", "six", "--pin", "1.17.0"]).unwrap_err();
+        assert!(
+            err.to_string().contains("already pinned to 1.16.0"),
+            "{}",
+            err
+        );
+        assert!(tracked().is_empty());
+
+        run_cli(&["-C", repo, "add", "plone.api", "--pin", "2.0.0"]).unwrap();
+        assert_eq!(tracked(), vec!["plone.api"]);
+        let versions = std::fs::read_to_string(dir.join("versions.cfg")).unwrap();
+        assert!(versions.contains("plone.api = 2.0.0"));
+        assert!(versions.contains("six = 1.16.0"));
+    }
+
+    #[test]
+    fn test_rebuild_caches_snapshots_in_repo_dir() {
+        let (tmp, _, _) = release_repo("rebuild-cache");
+        let dir = tmp.path();
+        std::fs::write(
+            dir.join("bldr.toml"),
+            "versions_file = \"versions.cfg\"\n\n[github]\ntag_prefix = \"v\"\n",
+        )
+        .unwrap();
+
+        // Run from the crate checkout, pointing at the repository with -C
+        let repo = dir.to_str().unwrap();
+        run_cli(&["-C", repo, "changelog", "--rebuild", "-o", "HISTORY.md"]).unwrap();
+
+        let cached: Vec<_> = std::fs::read_dir(dir.join(".bldr/cache/snapshots"))
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(cached.len(), 3);
+        let cwd_cache = std::path::Path::new(".bldr/cache/snapshots");
+        assert!(cached.iter().all(|name| !cwd_cache.join(name).exists()));
+    }
+
+    #[test]
+    fn test_rebuild_changelog_in_parallel() {
+        let (tmp, _, _) = release_repo("rebuild-jobs");
+        let dir = tmp.path();
+        std::fs::write(
+            dir.join("bldr.toml"),
+            "versions_file = \"versions.cfg\"\n\n[github]\ntag_prefix = \"v\"\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("versions.cfg"),
+            "[versions]\nplone.api = 2.2.0\nplone.bar = 1.0\nplone.foo = 1.0\n",
+        )
+        .unwrap();
+        git_at(dir, &["commit", "-q", "-am", "1.3.0"]);
+        git_at(dir, &["tag", "v1.3.0"]);
+        let repo = dir.to_str().unwrap();
+
+        // No tracked packages: only pins added, so nothing is fetched
+        run_cli(&[
+            "-C",
+            repo,
+            "changelog",
+            "--rebuild",
+            "--jobs",
+            "4",
+            "-o",
+            "HISTORY.md",
+        ])
+        .unwrap();
+        let history = std::fs::read_to_string(dir.join("HISTORY.md")).unwrap();
+        let newest = history.find("Release 1.3.0").unwrap();
+        let older = history.find("Release 1.1.0").unwrap();
+        assert!(newest < older, "{}", history);
+        assert!(history.contains("plone.bar"), "{}", history);
+        assert!(!dir.join("HISTORY.md.partial").exists());
+        // Snapshots are cached in the repository, not in the crate checkout
+        assert!(dir.join(".bldr/cache/snapshots").is_dir());
+
+        assert!(Cli::try_parse_from(["bldr", "changelog", "--jobs", "4"]).is_err());
+    }
+
+    #[test]
+    fn test_set_pin() {
+        let (tmp, _, _) = release_repo("set-pin");
+        let dir = tmp.path();
+        std::fs::write(
+            dir.join("bldr.toml"),
+            "versions_file = \"versions.cfg\"\n\n[[packages]]\nname = \"plone.api\"\n",
+        )
+        .unwrap();
+        git_at(dir, &["config", "user.name", "bldr"]);
+        git_at(dir, &["config", "user.email", "bldr@example.com"]);
+        git_at(dir, &["add", "bldr.toml"]);
+        git_at(dir, &["commit", "-q", "-m", "Configure bldr"]);
+        let repo = dir.to_str().unwrap();
+        let versions = || std::fs::read_to_string(dir.join("versions.cfg")).unwrap();
+
+        run_cli(&[
+            "-C",
+            repo,
+            "--non-interactive",
+            "set",
+            "plone.api",
+            "2.1.0",
+            "--commit",
+        ])
+        .unwrap();
+        assert!(versions().contains("plone.api = 2.1.0"));
+        let config = Config::load_in(dir, "bldr.toml").unwrap();
+        let git = git_for(&config);
+        assert!(git.dirty_files().unwrap().is_empty());
+
+        // Unpinned packages are added; pinning the same version again is a no-op
+        run_cli(&["-C", repo, "set", "plone.bar", "1.0"]).unwrap();
+        assert!(versions().contains("plone.bar = 1.0"));
+        assert!(!git.dirty_files().unwrap().is_empty());
+        run_cli(&["-C", repo, "set", "plone.bar", "1.0"]).unwrap();
+        assert_eq!(versions().matches("plone.bar").count(), 1);
+
+        let err = run_cli(&["-C", repo, "set", "plone.api", "latest"]).unwrap_err();
+        assert!(err.to_string().contains("not a valid version"), "{}", err);
+        assert!(versions().contains("plone.api = 2.1.0"));
+    }
+
+    #[test]
+    fn test_runs_projects_with_relative_configs() {
+        let tmp = temp_dir("projects");
+        let dir = tmp.path();
+        std::fs::create_dir_all(dir.join("a/conf")).unwrap();
+        std::fs::create_dir_all(dir.join("b")).unwrap();
+        std::fs::write(
+            dir.join("projects.toml"),
+            r#"
+            [[projects]]
+            name = "a"
+            path = "a"
+            config = "conf/bldr.toml"
+
+            [[projects]]
+            name = "b"
+            path = "b"
+
+            [[projects]]
+            name = "c"
+            path = "c"
+            "#,
+        )
+        .unwrap();
+        // track_all reads the versions file, so a wrong directory fails the run
+        let config = "versions_file = \"versions.cfg\"\ntrack_all = true\n";
+        std::fs::write(dir.join("a/conf/bldr.toml"), config).unwrap();
+        std::fs::write(dir.join("b/bldr.toml"), config).unwrap();
+        std::fs::write(dir.join("a/versions.cfg"), "[versions]\n").unwrap();
+        std::fs::write(dir.join("b/versions.cfg"), "[versions]\n").unwrap();
+        let cwd = std::env::current_dir().unwrap();
+
+        let root = dir.to_str().unwrap();
+        let workspace = ["--workspace", "projects.toml"];
+        run_cli(&[&["-C", root, "check", "--project", "a,b"], &workspace[..]].concat()).unwrap();
+        assert_eq!(std::env::current_dir().unwrap(), cwd);
+
+        // A token is reported once it matched nothing in every project
+        let args = ["-C", root, "check", "--project", "a,b", "--packages", "six"];
+        let unmatched = run_cli(&[&args[..], &workspace[..]].concat()).unwrap();
+        assert_eq!(unmatched, vec!["six"]);
+
+        // The missing project fails the run without stopping the others
+        let err = run_cli(&[&["-C", root, "check", "--all"], &workspace[..]].concat()).unwrap_err();
+        assert!(err.to_string().contains("1 project(s)"), "{}", err);
+        assert_eq!(std::env::current_dir().unwrap(), cwd);
+    }
+}
+
+async fn cmd_check(
     config_path: &str,
     packages_filter: Option<String>,
     json_output: bool,
+    check_wheels: bool,
+    sort: CheckSort,
     verbose: bool,
-) -> Result<()> {
-    let config = Config::load(config_path)?;
-    let pypi = PyPiClient::new()?;
-    let buildout = BuildoutVersions::load(&config.versions_file)?;
+) -> Result<Vec<String>> {
+    let config = load_config(config_path)?;
+    let wheel_target = if check_wheels {
+        Some(wheels::WheelTarget::from_config(&config.pypi)?)
+    } else {
+        None
+    };
+    let pypi = PyPiClient::with_config(&config.pypi, &config.http)?;
+    let files = load_versions_files(&config).await?;
+    for mismatch in find_pin_mismatches(&files) {
+        warnings::warn(
+            WarningKind::Pins,
+            Some(&mismatch.package),
+            format!("pinned differently: {}", describe_pins(&mismatch.pins)),
+        );
+    }
+    warn_renamed_pins(&config, &files);
 
-    let packages_to_check = filter_packages(&config.packages, packages_filter.as_deref());
+    let (selected, unmatched) = filter_packages(&config.packages, packages_filter.as_deref());
+    let packages_to_check = skip_source_checkouts(&config, &files, selected)?;
 
     let progress = if !json_output {
         create_progress_bar(packages_to_check.len(), "Checking packages")
@@ -371,128 +1987,511 @@ async fn cmd_check(
         None
     };
 
-    let latest_versions =
-        fetch_latest_versions(&pypi, &packages_to_check, progress.clone(), verbose).await?;
+    let latest_versions = fetch_latest_versions(
+        &config,
+        &pypi,
+        &packages_to_check,
+        progress.clone(),
+        verbose,
+    )
+    .await?;
 
     let mut updates = Vec::new();
 
     for (pkg_config, latest) in packages_to_check.iter().zip(latest_versions) {
-        let current = buildout.get_version(pkg_config.buildout_name());
-        let has_update = current.map_or(true, |c| c != latest.version);
+        let Some(latest) = latest else {
+            continue;
+        };
+        let current = package_pin(&files, pkg_config);
+        let has_update = current.is_none_or(|current| is_upgrade(current, &latest.version));
 
         updates.push(UpdateInfo {
             package: pkg_config.name.clone(),
             buildout_name: pkg_config.buildout_name().to_string(),
+            kind: classify_update(current, &latest.version),
             current_version: current.map(|s| s.to_string()),
             latest_version: latest.version,
+            released: latest.uploaded.map(|t| t.date_naive().to_string()),
             has_update,
+            vulnerabilities: Vec::new(),
+            availability: None,
+            upstream: pin_upstream(&files, pkg_config.buildout_name()).map(str::to_string),
         });
     }
 
+    mark_security_updates(&config, &mut updates).await;
+
+    if let Some(target) = wheel_target {
+        let latest: Vec<PinnedPackage> = updates
+            .iter()
+            .map(|u| PinnedPackage {
+                name: u.package.clone(),
+                version: u.latest_version.clone(),
+            })
+            .collect();
+        let target = Arc::new(target);
+        let availability = pypi
+            .map_packages(
+                &latest,
+                config.pypi.effective_concurrency(),
+                move |client, pin| {
+                    let target = target.clone();
+                    async move {
+                        let files = client.release_files(&pin.name, &pin.version).await?;
+                        Ok(target.availability(&files))
+                    }
+                },
+                |_: &PinnedPackage| {},
+            )
+            .await?;
+
+        for (update, availability) in updates.iter_mut().zip(availability) {
+            update.availability = Some(availability);
+        }
+    }
+
     if let Some(pb) = progress {
         pb.finish_with_message("Package check complete");
     }
 
     if json_output {
-        println!("{}", serde_json::to_string_pretty(&updates).unwrap());
+        let report = CheckReport {
+            packages: updates,
+            warnings: warnings::take(),
+        };
+        println!("{}", serde_json::to_string_pretty(&report).unwrap());
     } else {
-        print_update_table(&updates);
+        match sort {
+            CheckSort::Severity => print_grouped_updates(&updates, verbose),
+            CheckSort::Name | CheckSort::Age => print_update_table(&mut updates, sort),
+        }
+        print_wheel_problems(&updates);
     }
 
-    Ok(())
+    Ok(unmatched)
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn cmd_update(
     config_path: &str,
     packages_filter: Option<String>,
     auto_confirm: bool,
     dry_run: bool,
-    commit: bool,
-    push: bool,
+    show_diff: bool,
+    git_flags: GitFlags,
+    push_to: Option<&str>,
+    trailers: &[String],
+    pr: bool,
     non_interactive: bool,
     verbose: bool,
-) -> Result<()> {
-    let config = Config::load(config_path)?;
+) -> Result<Vec<String>> {
+    let mut config = load_config(config_path)?;
+    config.git.resolve_trailers(trailers)?;
+    let (packages, unmatched) = filter_packages(&config.packages, packages_filter.as_deref());
 
-    let commit = commit || push;
-    let git = GitOps::new();
+    let (commit, push) = if pr {
+        (true, true)
+    } else {
+        git_flags.resolve(&config)
+    };
+    let git = git_for(&config);
 
-    if commit {
+    let mut pull_request = None;
+    let mut github = None;
+    let workspace = if commit {
         if !git.is_repo() {
             return Err(ReleaserError::GitError(
                 "Not in a git repository".to_string(),
             ));
         }
 
-        if !git.is_clean()? {
-            if non_interactive {
-                return Err(ReleaserError::GitError(
-                    "Uncommitted changes detected. Clean your workspace or rerun without --non-interactive.".to_string(),
-                ));
+        if !dry_run {
+            ensure_release_branch(&config, &git)?;
+        }
+
+        if pr {
+            let suffix = format!("update-{}", chrono::Local::now().format("%Y%m%d-%H%M%S"));
+            let branch = PullRequestBranch::plan(&config, &git, &suffix)?;
+            if !dry_run {
+                github = Some(require_github(&config, &git, "--pr opens a pull request")?);
             }
+            pull_request = Some(branch);
+        }
 
-            println!("{}", "Warning: You have uncommitted changes.".yellow());
-            let proceed = Confirm::new()
-                .with_prompt("Do you want to continue? (changes will be included in the commit)")
-                .default(false)
-                .interact()
-                .map_err(|e| {
-                    ReleaserError::IoError(std::io::Error::new(
-                        std::io::ErrorKind::Other,
-                        e.to_string(),
-                    ))
-                })?;
+        if push && non_interactive && !dry_run {
+            let target = match &pull_request {
+                Some(branch) => Some(branch.name.as_str()),
+                None => push_target(&config, push_to),
+            };
+            git.check_push_access(target)?;
+        }
+        if push && !dry_run {
+            ensure_up_to_date(&config, &git, push_to)?;
+        }
 
-            if !proceed {
-                println!("Aborted.");
-                return Ok(());
-            }
+        if dry_run {
+            Workspace::Ready
+        } else {
+            let mut commit_files = versions_file_paths(&config)?;
+            commit_files.extend(release_commit_files(&config, false));
+            prepare_workspace(&git, &commit_files, non_interactive, false)?
         }
+    } else {
+        Workspace::Ready
+    };
+    if matches!(workspace, Workspace::Aborted) {
+        println!("Aborted.");
+        return Ok(unmatched);
+    }
+
+    if !dry_run {
+        hooks::run(&config, Stage::PreUpdate, &HookContext::default())?;
     }
 
+    let store = PackageMetadataStore::new();
     let updates = perform_update(
         &config,
-        packages_filter,
+        &store,
+        packages,
         auto_confirm || non_interactive,
         dry_run,
+        show_diff,
         verbose,
     )
     .await?;
 
     if updates.is_empty() {
-        return Ok(());
+        return Ok(unmatched);
     }
 
     if dry_run {
-        if commit {
+        if let Some(branch) = &pull_request {
+            println!(
+                "{}",
+                format!(
+                    "Dry run: would commit to {} and open a pull request into {}.",
+                    branch.name, branch.base
+                )
+                .yellow()
+            );
+        } else if commit {
             println!("{}", "Dry run: skipping commit/push actions.".yellow());
         }
-        return Ok(());
+        return Ok(unmatched);
     }
 
+    let hook_context = HookContext {
+        updates: &updates,
+        ..Default::default()
+    };
+    hooks::run(&config, Stage::PostUpdate, &hook_context)?;
+
+    let unreleased_file = if config.changelog.unreleased_section {
+        add_unreleased_entries(&config, &store, &updates).await?
+    } else {
+        None
+    };
+
     if commit {
-        let commit_message =
-            generate_commit_message(&updates, config.git.effective_commit_template(), None);
+        let commit_message = generate_commit_message(&updates, &config.git, None);
         if verbose {
             println!("Commit message: {}", commit_message);
         }
+        if let Some(branch) = &pull_request {
+            branch.start(&git)?;
+        }
 
-        git.add(&config.versions_file)?;
-        println!("{} Staged {}", "✓".green(), config.versions_file);
+        for file in versions_file_paths(&config)? {
+            git.add(&file)?;
+            println!("{} Staged {}", "✓".green(), file);
+        }
+        if let (Some(file), true) = (&unreleased_file, config.changelog.include_in_commit) {
+            git.add(file)?;
+            println!("{} Staged {}", "✓".green(), file);
+        }
 
+        hooks::run(&config, Stage::PreCommit, &hook_context)?;
         git.commit(&commit_message)?;
         println!("{} Committed changes", "✓".green());
 
-        if push {
-            git.push(false)?;
+        if let (Some(branch), Some(github)) = (&pull_request, &github) {
+            let title = commit_message.lines().next().unwrap_or_default();
+            let description = pull_request_description(&config, &store, &updates).await?;
+            branch
+                .open(&config, &git, github, title, &description, false)
+                .await?;
+        } else if push {
+            git.push(push_target(&config, push_to), false)?;
             println!("{} Pushed to remote", "✓".green());
         }
     }
 
-    Ok(())
+    Ok(unmatched)
+}
+
+/// Description of an update pull request: the consolidated changelog of
+/// `updates` in Markdown, or the list of new pins without changelogs
+async fn pull_request_description(
+    config: &Config,
+    store: &PackageMetadataStore,
+    updates: &[VersionUpdate],
+) -> Result<String> {
+    let Some(plan) =
+        ChangelogPlan::for_release(config, &ChangelogArgs::default(), config.changelog.enabled)
+    else {
+        return Ok(generate_release_notes(config, updates, "Unreleased"));
+    };
+    let entry = collect_changelog(
+        config,
+        store,
+        updates,
+        PinDiff::default(),
+        "Unreleased",
+        &plan,
+    )
+    .await?;
+    Ok(entry.render_body(ChangelogFormat::Markdown))
+}
+
+/// Add the changelog of `updates` to the Unreleased section of the changelog
+/// file, and return that file
+async fn add_unreleased_entries(
+    config: &Config,
+    store: &PackageMetadataStore,
+    updates: &[VersionUpdate],
+) -> Result<Option<String>> {
+    let Some(plan) = ChangelogPlan::for_release(config, &ChangelogArgs::default(), true) else {
+        return Ok(None);
+    };
+    let Some(file) = plan.file.clone() else {
+        warnings::warn(
+            WarningKind::Changelog,
+            None,
+            "changelog.unreleased_section needs changelog.output_file; entries were not recorded",
+        );
+        return Ok(None);
+    };
+
+    let entry = collect_changelog(
+        config,
+        store,
+        updates,
+        PinDiff::default(),
+        "Unreleased",
+        &plan,
+    )
+    .await?;
+    UnreleasedSection::new(&config.changelog, plan.format)
+        .append(config.resolve(&file), &entry.render_body(plan.format))?;
+    println!(
+        "{} Added changelog entries to the Unreleased section of {}",
+        "✓".green(),
+        file
+    );
+    Ok(Some(file))
+}
+
+/// Turn the Unreleased section of the changelog file into the entry of
+/// `version`, and return that entry; `None` when there is nothing to promote
+fn promote_unreleased(
+    config: &Config,
+    plan: &ChangelogPlan,
+    version: &str,
+) -> Result<Option<String>> {
+    let Some(file) = plan.file.as_deref() else {
+        return Ok(None);
+    };
+    if !config.changelog.unreleased_section || plan.rebuild {
+        return Ok(None);
+    }
+
+    let heading = ConsolidatedChangelog::with_templates(
+        version,
+        &current_date(),
+        Vec::new(),
+        &config.changelog,
+    )
+    .heading(plan.format);
+    let Some(entries) = UnreleasedSection::new(&config.changelog, plan.format)
+        .promote(config.resolve(file), &heading)?
+    else {
+        return Ok(None);
+    };
+
+    println!(
+        "{} Promoted the Unreleased section of {} to {}",
+        "✓".green(),
+        file,
+        version
+    );
+    let entry = format!("{}{}", heading, entries);
+    if plan.stdout {
+        println!("\n{}", "═".repeat(60));
+        println!("{}", entry);
+    }
+    Ok(Some(entry))
+}
+
+/// Commit and push flags of `update` and `set`
+#[derive(Debug, Clone, Copy)]
+struct GitFlags {
+    commit: bool,
+    push: bool,
+    no_commit: bool,
+    no_push: bool,
+}
+
+impl GitFlags {
+    /// Whether to commit and push, falling back to `git.auto_commit`/`git.auto_push`
+    fn resolve(self, config: &Config) -> (bool, bool) {
+        config
+            .git
+            .commit_and_push(self.commit, self.push, self.no_commit, self.no_push)
+    }
+}
+
+/// Uncommitted changes once `prepare_workspace` dealt with them
+enum Workspace {
+    Ready,
+    /// Set aside until the value is dropped
+    Stashed(StashedChanges),
+    Aborted,
+}
+
+/// Changes stashed by `prepare_workspace`, restored when dropped, so also when
+/// the run fails after stashing
+struct StashedChanges;
+
+impl Drop for StashedChanges {
+    fn drop(&mut self) {
+        match git_in(&work_dir()).stash_pop() {
+            Ok(()) => println!("{} Restored the stashed changes", "✓".green()),
+            Err(e) => warnings::warn(
+                WarningKind::Git,
+                None,
+                format!(
+                    "could not restore the stashed changes ({}); they are kept in `git stash list`",
+                    e
+                ),
+            ),
+        }
+    }
+}
+
+/// Sort uncommitted changes into those the commit would sweep in (staged
+/// files, and edits to the `commit_files` bldr stages) and unrelated ones,
+/// which stay out of it. Only the former need a decision: include them, stash
+/// them until the commit is made, or abort. Non-interactive runs refuse them;
+/// `auto_confirm` includes them.
+fn prepare_workspace(
+    git: &GitOps,
+    commit_files: &[String],
+    non_interactive: bool,
+    auto_confirm: bool,
+) -> Result<Workspace> {
+    let is_commit_file = |path: &str| {
+        let path = std::path::Path::new(path);
+        commit_files
+            .iter()
+            .any(|file| std::path::Path::new(file.trim_start_matches("./")) == path)
+    };
+    let (swept, unrelated): (Vec<DirtyFile>, Vec<DirtyFile>) = git
+        .dirty_files()?
+        .into_iter()
+        .partition(|file| file.staged || is_commit_file(&file.path));
+
+    if !unrelated.is_empty() {
+        println!(
+            "{} {} unrelated uncommitted file(s) stay out of the commit",
+            "ℹ".cyan(),
+            unrelated.len()
+        );
+    }
+    if swept.is_empty() {
+        return Ok(Workspace::Ready);
+    }
+
+    println!(
+        "{}",
+        "These uncommitted changes would go into the commit:".yellow()
+    );
+    for file in &swept {
+        let staged = if file.staged { " (staged)" } else { "" };
+        println!("  {}{}", file.path, staged.dimmed());
+    }
+
+    if non_interactive {
+        return Err(ReleaserError::GitError(format!(
+            "Uncommitted changes to {} would go into the commit. Commit or stash them, or rerun without --non-interactive.",
+            swept
+                .iter()
+                .map(|file| file.path.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )));
+    }
+    if auto_confirm {
+        return Ok(Workspace::Ready);
+    }
+
+    // bldr rewrites the files it commits, so popping a stash of them would
+    // conflict: only the other (staged) files can be set aside
+    let stashable: Vec<&str> = swept
+        .iter()
+        .filter(|file| !is_commit_file(&file.path))
+        .map(|file| file.path.as_str())
+        .collect();
+    let stash_item = if stashable.len() == swept.len() {
+        "Stash them and restore them after the commit".to_string()
+    } else {
+        format!(
+            "Stash the {} file(s) bldr doesn't rewrite and restore them after the commit",
+            stashable.len()
+        )
+    };
+    let mut items = vec!["Include them in the commit".to_string()];
+    if !stashable.is_empty() {
+        items.push(stash_item);
+    }
+    items.push("Abort".to_string());
+
+    let choice = Select::new()
+        .with_prompt("What should happen to them?")
+        .items(&items)
+        .default(1)
+        .interact()
+        .map_err(|e| ReleaserError::IoError(std::io::Error::other(e.to_string())))?;
+
+    match choice {
+        0 => Ok(Workspace::Ready),
+        1 if !stashable.is_empty() => {
+            git.stash(&stashable, "bldr: changes set aside during the release")?;
+            println!("{} Stashed {} file(s)", "✓".green(), stashable.len());
+            Ok(Workspace::Stashed(StashedChanges))
+        }
+        _ => Ok(Workspace::Aborted),
+    }
 }
 
-fn cmd_release(
+/// Files bldr may stage besides the versions files: the changelog and, for
+/// releases, the metadata files
+fn release_commit_files(config: &Config, metadata: bool) -> Vec<String> {
+    let changelog = config
+        .changelog
+        .output_file
+        .iter()
+        .filter(|_| config.changelog.include_in_commit)
+        .cloned();
+    let metadata_files = config
+        .metadata_files
+        .iter()
+        .filter(|_| metadata)
+        .map(|file| file.path.clone());
+    changelog.chain(metadata_files).collect()
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn cmd_release(
     config_path: &str,
     tag: Option<String>,
     bump: Option<String>,
@@ -501,11 +2500,19 @@ fn cmd_release(
     no_github: bool,
     draft: bool,
     no_metadata: bool,
+    first: bool,
+    changelog_args: &ChangelogArgs,
+    push_to: Option<&str>,
+    trailers: &[String],
+    force_retag: bool,
+    assets: &[String],
     non_interactive: bool,
     verbose: bool,
 ) -> Result<()> {
-    let config = Config::load(config_path)?;
-    let git = GitOps::new();
+    let mut config = load_config(config_path)?;
+    config.git.resolve_trailers(trailers)?;
+    config.github.assets.extend(assets.iter().cloned());
+    let git = git_for(&config);
 
     // Verify we're in a git repo
     if !git.is_repo() {
@@ -514,41 +2521,48 @@ fn cmd_release(
         ));
     }
 
-    // Resolve version
-    let version_str = resolve_version(&config, &git, tag, bump, verbose)?;
-
-    // Check for uncommitted changes
-    if !git.is_clean()? {
-        if non_interactive {
-            return Err(ReleaserError::GitError(
-                "Uncommitted changes detected. Clean your workspace or rerun without --non-interactive.".to_string(),
-            ));
-        }
+    ensure_release_branch(&config, &git)?;
 
-        println!("{}", "Warning: You have uncommitted changes.".yellow());
+    if !no_push && non_interactive {
+        git.check_push_access(push_target(&config, push_to))?;
+    }
+    if !no_push {
+        ensure_up_to_date(&config, &git, push_to)?;
+    }
 
-        let proceed = Confirm::new()
-            .with_prompt("Do you want to continue?")
-            .default(false)
-            .interact()
-            .map_err(|e| {
-                ReleaserError::IoError(std::io::Error::new(
-                    std::io::ErrorKind::Other,
-                    e.to_string(),
-                ))
-            })?;
+    ensure_pins_in_sync(&load_versions_files(&config).await?)?;
 
-        if !proceed {
-            println!("Aborted.");
-            return Ok(());
+    if first {
+        if let Some(latest) = git.get_latest_version(&config.github.tag_prefix)? {
+            return Err(ReleaserError::GitError(format!(
+                "--first can only be used before any release; found existing version {}",
+                latest
+            )));
         }
     }
 
+    // Resolve version
+    let version_str = resolve_version(&config, &git, tag, bump, verbose)?;
+    ensure_tag_available(&config, &git, &version_str, !no_push, force_retag)?;
+
+    // Check for uncommitted changes
+    let commit_files = release_commit_files(&config, !no_metadata);
+    let workspace = prepare_workspace(&git, &commit_files, non_interactive, false)?;
+    if matches!(workspace, Workspace::Aborted) {
+        println!("Aborted.");
+        return Ok(());
+    }
+
     // Update metadata files
     let updated_metadata = if !no_metadata && !config.metadata_files.is_empty() {
         let date = current_date();
         println!("{}", "Updating metadata files...".cyan());
-        let files = MetadataUpdater::update_all(&config.metadata_files, &version_str, &date)?;
+        let files = MetadataUpdater::update_all(
+            &config.work_dir,
+            &config.metadata_files,
+            &version_str,
+            &date,
+        )?;
         for file in &files {
             println!("{} Updated {}", "✓".green(), file);
         }
@@ -562,32 +2576,253 @@ fn cmd_release(
         git.add(file)?;
     }
 
+    if first {
+        return perform_first_release(
+            &config,
+            &git,
+            &version_str,
+            message,
+            &updated_metadata,
+            no_push,
+            push_to,
+            no_github,
+            draft,
+            force_retag,
+            verbose,
+        )
+        .await;
+    }
+
+    // Changelog of the pins changed since the last release
+    let mut release_notes = None;
+    let mut staged_changelog = false;
+    // The Unreleased section is promoted unless `--no-changelog` is given
+    let plan =
+        ChangelogPlan::for_release(&config, changelog_args, config.changelog.unreleased_section);
+    let updates = if plan.is_some() || config.github.release_title_template.contains("{updates}") {
+        updates_since_latest_tag(&config, &git)
+    } else {
+        Vec::new()
+    };
+    if let Some(plan) = plan {
+        let promoted = promote_unreleased(&config, &plan, &version_str)?;
+        if let (Some(entry), Some(file)) = (promoted, &plan.file) {
+            if config.changelog.include_in_commit {
+                git.add(file)?;
+                staged_changelog = true;
+            }
+            if config.changelog.use_as_release_notes {
+                release_notes = Some(entry);
+            }
+        } else {
+            let pin_diff = pin_changes_since_latest_tag(&config, &git, None);
+
+            let entry = if updates.is_empty() && pin_diff.is_empty() {
+                println!(
+                    "{}",
+                    "No pin changes since the last release; no changelog entry added.".yellow()
+                );
+                None
+            } else {
+                let store = PackageMetadataStore::new();
+                Some(
+                    collect_changelog(&config, &store, &updates, pin_diff, &version_str, &plan)
+                        .await?,
+                )
+            };
+            let rebuilt = plan.rebuild_history(&config, None, verbose).await?;
+
+            if entry.is_some() || rebuilt.is_some() {
+                plan.emit(rebuilt.as_deref(), entry.as_ref())?;
+                if let (Some(file), true) = (&plan.file, config.changelog.include_in_commit) {
+                    git.add(file)?;
+                    staged_changelog = true;
+                }
+            }
+            if config.changelog.use_as_release_notes {
+                release_notes = entry.map(|entry| entry.render(plan.format));
+            }
+        }
+    }
+
     // Commit if we have changes
-    if !updated_metadata.is_empty() {
-        let commit_msg = format!("Bump version to {}", version_str);
+    if !updated_metadata.is_empty() || staged_changelog {
+        let commit_msg = config
+            .git
+            .append_trailers(&format!("Bump version to {}", version_str));
+        let hook_context = HookContext {
+            version: Some(&version_str),
+            tag: Some(format!("{}{}", config.github.tag_prefix, version_str)),
+            updates: &updates,
+        };
+        hooks::run(&config, Stage::PreCommit, &hook_context)?;
         git.commit(&commit_msg)?;
-        println!("{} Committed metadata changes", "✓".green());
+        println!("{} Committed release changes", "✓".green());
     }
 
     perform_release(
         &config,
         &version_str,
-        message,
+        message.or(release_notes.as_deref()),
+        &updates,
         no_push,
+        push_to,
         no_github,
         draft,
+        force_retag,
         verbose,
     )
+    .await
 }
 
-fn cmd_version(
-    config_path: &str,
+/// Seed the changelog with every current pin, commit it and tag the first release
+#[allow(clippy::too_many_arguments)]
+async fn perform_first_release(
+    config: &Config,
+    git: &GitOps,
+    version_str: &str,
+    message: Option<&str>,
+    updated_metadata: &[String],
+    no_push: bool,
+    push_to: Option<&str>,
+    no_github: bool,
+    draft: bool,
+    force_retag: bool,
+    verbose: bool,
+) -> Result<()> {
+    // Linked files are in sync, so each shared pin is listed once
+    let mut pins: Vec<PinnedPackage> = Vec::new();
+    for buildout in load_versions_files(config).await? {
+        for (name, version) in buildout.get_all_versions() {
+            if !pins.iter().any(|p| p.name == name) {
+                pins.push(PinnedPackage {
+                    name: name.to_string(),
+                    version: version.to_string(),
+                });
+            }
+        }
+    }
+
+    let format = config.changelog.format_enum();
+    let changelog = ConsolidatedChangelog::initial_release(
+        version_str,
+        &current_date(),
+        pins,
+        &config.changelog,
+    );
+
+    if verbose {
+        println!(
+            "Initial release lists {} pinned package(s)",
+            changelog.initial_pins.len()
+        );
+    }
+
+    let mut staged_changelog = false;
+    if let Some(ref file_path) = config.changelog.output_file {
+        changelog.save_to_file(config.resolve(file_path), format)?;
+        println!("{} Initialized changelog: {}", "✓".green(), file_path);
+
+        if config.changelog.include_in_commit {
+            git.add(file_path)?;
+            staged_changelog = true;
+        }
+    }
+
+    if staged_changelog || !updated_metadata.is_empty() {
+        let hook_context = HookContext {
+            version: Some(version_str),
+            tag: Some(format!("{}{}", config.github.tag_prefix, version_str)),
+            updates: &[],
+        };
+        hooks::run(config, Stage::PreCommit, &hook_context)?;
+        git.commit(
+            &config
+                .git
+                .append_trailers(&format!("Initial release {}", version_str)),
+        )?;
+        println!("{} Committed initial release", "✓".green());
+    }
+
+    let release_notes = if config.changelog.use_as_release_notes {
+        changelog.render(format)
+    } else {
+        format!("Initial release {}", version_str)
+    };
+
+    perform_release(
+        config,
+        version_str,
+        Some(message.unwrap_or(&release_notes)),
+        &[],
+        no_push,
+        push_to,
+        no_github,
+        draft,
+        force_retag,
+        verbose,
+    )
+    .await
+}
+
+/// Change the notes or title of an existing GitHub release (`release edit`),
+/// or publish a draft (`release publish`)
+async fn cmd_release_edit(
+    config_path: &str,
+    tag: &str,
+    notes_file: Option<&str>,
+    notes: Option<String>,
+    title: Option<&str>,
+    publish: bool,
+) -> Result<()> {
+    let config = load_config(config_path)?;
+    let git = git_for(&config);
+
+    let notes =
+        match notes_file {
+            Some(path) => Some(std::fs::read_to_string(config.resolve(path)).map_err(
+                |source| ReleaserError::FileError {
+                    path: path.to_string(),
+                    source,
+                },
+            )?),
+            None => notes,
+        };
+    if !publish && notes.is_none() && title.is_none() {
+        return Err(ReleaserError::ConfigError(
+            "Nothing to change; pass --notes-file, --notes or --title".to_string(),
+        ));
+    }
+
+    // Accept the version as well as the full tag
+    let prefix = &config.github.tag_prefix;
+    let tag = if tag.starts_with(prefix.as_str()) {
+        tag.to_string()
+    } else {
+        format!("{}{}", prefix, tag)
+    };
+    let notes = notes.map(|notes| fit_release_notes(&config, &tag, notes.trim_end()));
+
+    let github = require_github(&config, &git, "Editing a release uses GitHub")?;
+    github
+        .edit_release(&tag, title, notes.as_deref(), publish)
+        .await?;
+    if publish {
+        println!("{} Published the GitHub release of {}", "✓".green(), tag);
+    } else {
+        println!("{} Updated the GitHub release of {}", "✓".green(), tag);
+    }
+    Ok(())
+}
+
+fn cmd_version(
+    config_path: &str,
     bump: Option<String>,
     list_levels: bool,
     verbose: bool,
 ) -> Result<()> {
-    let config = Config::load(config_path)?;
-    let git = GitOps::new();
+    let config = read_config(config_path)?;
+    let git = git_for(&config);
     let version_manager = VersionManager::new(&config.version);
 
     if verbose {
@@ -604,6 +2839,9 @@ fn cmd_version(
                 config::VersionBumpType::Major => "X.0.0 (breaking changes)",
                 config::VersionBumpType::Minor => "0.X.0 (new features)",
                 config::VersionBumpType::Patch => "0.0.X (bug fixes)",
+                config::VersionBumpType::Prerelease => "0.0.X-rc.N (next pre-release)",
+                config::VersionBumpType::Build => "0.0.0+stamp (same version, new build)",
+                config::VersionBumpType::None => "0.0.0 (re-tag, with --force-retag)",
             };
             println!("  {:<12} → {}", name.yellow(), desc);
         }
@@ -629,6 +2867,9 @@ fn cmd_version(
         None => {
             println!("{}", "No version tags found.".yellow());
             println!("First release will be: {}", "0.1.0".green());
+            println!(
+                "  Run 'bldr release --first --tag <version>' to seed the changelog and tag it"
+            );
 
             if let Some(level) = bump {
                 let initial = Version::new(0, 0, 0);
@@ -642,6 +2883,7 @@ fn cmd_version(
     Ok(())
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn cmd_update_release(
     config_path: &str,
     tag: Option<String>,
@@ -653,16 +2895,21 @@ async fn cmd_update_release(
     no_github: bool,
     draft: bool,
     dry_run: bool,
-    changelog_flag: bool,
-    no_changelog_flag: bool,
-    changelog_format_override: Option<CliChangelogFormat>,
-    changelog_file_override: Option<String>,
+    changelog_args: &ChangelogArgs,
     no_metadata: bool,
+    push_to: Option<&str>,
+    trailers: &[String],
+    force_retag: bool,
+    assets: &[String],
+    pr: bool,
     non_interactive: bool,
     verbose: bool,
-) -> Result<()> {
-    let config = Config::load(config_path)?;
-    let git = GitOps::new();
+) -> Result<Vec<String>> {
+    let mut config = load_config(config_path)?;
+    config.git.resolve_trailers(trailers)?;
+    let (packages, unmatched) = filter_packages(&config.packages, packages_filter.as_deref());
+    config.github.assets.extend(assets.iter().cloned());
+    let git = git_for(&config);
 
     // Verify we're in a git repo
     if !git.is_repo() {
@@ -671,61 +2918,89 @@ async fn cmd_update_release(
         ));
     }
 
+    if !dry_run {
+        ensure_release_branch(&config, &git)?;
+    }
+
+    if !no_push && !dry_run && non_interactive && !pr {
+        git.check_push_access(push_target(&config, push_to))?;
+    }
+    if !no_push && !dry_run {
+        ensure_up_to_date(&config, &git, push_to)?;
+    }
+
     // Resolve version
     let version_str = resolve_version(&config, &git, tag, bump, verbose)?;
+    ensure_tag_available(
+        &config,
+        &git,
+        &version_str,
+        !no_push && !dry_run,
+        force_retag,
+    )?;
 
-    let auto_confirm = auto_confirm || non_interactive;
-
-    // Determine changelog settings
-    let collect_changelog = if no_changelog_flag {
-        false
-    } else if changelog_flag {
-        true
+    let (pull_request, github) = if pr {
+        let branch = PullRequestBranch::plan(&config, &git, &format!("release-{}", version_str))?;
+        let github = if dry_run {
+            None
+        } else {
+            if non_interactive {
+                git.check_push_access(Some(&branch.name))?;
+            }
+            Some(require_github(&config, &git, "--pr opens a pull request")?)
+        };
+        (Some(branch), github)
     } else {
-        config.changelog.enabled
+        (None, None)
     };
 
-    let changelog_format = changelog_format_override
-        .map(|f| f.into())
-        .unwrap_or_else(|| config.changelog.format_enum());
+    let auto_confirm = auto_confirm || non_interactive;
 
-    let changelog_file = changelog_file_override.or_else(|| config.changelog.output_file.clone());
+    let changelog_plan =
+        ChangelogPlan::for_release(&config, changelog_args, config.changelog.enabled);
+    let collects_changelog = changelog_plan.is_some();
 
     // Check for uncommitted changes
-    if !git.is_clean()? {
-        if non_interactive {
-            return Err(ReleaserError::GitError(
-                "Uncommitted changes detected. Clean your workspace or rerun without --non-interactive.".to_string(),
-            ));
-        }
-
-        println!("{}", "Warning: You have uncommitted changes.".yellow());
-
-        if !auto_confirm {
-            let proceed = Confirm::new()
-                .with_prompt("Do you want to continue? (changes will be included in the commit)")
-                .default(false)
-                .interact()
-                .map_err(|e| {
-                    ReleaserError::IoError(std::io::Error::new(
-                        std::io::ErrorKind::Other,
-                        e.to_string(),
-                    ))
-                })?;
-
-            if !proceed {
-                println!("Aborted.");
-                return Ok(());
-            }
-        }
+    let workspace = if dry_run {
+        Workspace::Ready
+    } else {
+        let mut commit_files = versions_file_paths(&config)?;
+        commit_files.extend(release_commit_files(&config, !no_metadata));
+        prepare_workspace(&git, &commit_files, non_interactive, auto_confirm)?
+    };
+    if matches!(workspace, Workspace::Aborted) {
+        println!("Aborted.");
+        return Ok(unmatched);
     }
 
     println!("{}", "═".repeat(60).cyan());
     println!("{}", " STEP 1: Update Packages".cyan().bold());
     println!("{}", "═".repeat(60).cyan());
 
+    // Metadata fetched while updating is reused when collecting changelogs
+    let store = PackageMetadataStore::new();
+
+    let full_tag = format!("{}{}", config.github.tag_prefix, version_str);
+    if !dry_run {
+        let hook_context = HookContext {
+            version: Some(&version_str),
+            tag: Some(full_tag.clone()),
+            updates: &[],
+        };
+        hooks::run(&config, Stage::PreUpdate, &hook_context)?;
+    }
+
     // Perform updates
-    let updates = perform_update(&config, packages_filter, auto_confirm, dry_run, verbose).await?;
+    let updates = perform_update(
+        &config,
+        &store,
+        packages,
+        auto_confirm,
+        dry_run,
+        false,
+        verbose,
+    )
+    .await?;
 
     if updates.is_empty() {
         if !auto_confirm {
@@ -733,59 +3008,48 @@ async fn cmd_update_release(
                 .with_prompt("No package updates. Do you still want to create a release?")
                 .default(false)
                 .interact()
-                .map_err(|e| {
-                    ReleaserError::IoError(std::io::Error::new(
-                        std::io::ErrorKind::Other,
-                        e.to_string(),
-                    ))
-                })?;
+                .map_err(|e| ReleaserError::IoError(std::io::Error::other(e.to_string())))?;
 
             if !proceed {
                 println!("Aborted.");
-                return Ok(());
+                return Ok(unmatched);
             }
         } else {
             println!("{}", "No updates available, skipping release.".yellow());
-            return Ok(());
+            return Ok(unmatched);
         }
     }
 
-    // Collect changelogs
-    let consolidated_changelog = if collect_changelog && !updates.is_empty() {
-        println!("\n{}", "═".repeat(60).cyan());
-        println!("{}", " STEP 2: Collecting Changelogs".cyan().bold());
-        println!("{}", "═".repeat(60).cyan());
-
-        let collector = ChangelogCollector::with_config(&config.changelog);
-        let spinner = create_spinner("Fetching changelogs from packages...");
-
-        let changelogs = collector
-            .collect_changelogs(&updates, &config.packages)
-            .await?;
+    let hook_context = HookContext {
+        version: Some(&version_str),
+        tag: Some(full_tag.clone()),
+        updates: &updates,
+    };
+    if !dry_run {
+        hooks::run(&config, Stage::PostUpdate, &hook_context)?;
+    }
 
-        spinner.finish_with_message("Changelog collection complete");
+    let pin_diff = if collects_changelog {
+        pin_changes_since_latest_tag(&config, &git, packages_filter.as_deref())
+    } else {
+        PinDiff::default()
+    };
 
-        let found_count = changelogs.iter().filter(|c| !c.entries.is_empty()).count();
-        println!(
-            "{} Found changelog entries for {}/{} packages",
-            "✓".green(),
-            found_count,
-            changelogs.len()
-        );
+    // Collect changelogs
+    let consolidated_changelog = match &changelog_plan {
+        Some(plan) if !updates.is_empty() || !pin_diff.is_empty() => {
+            println!("\n{}", "═".repeat(60).cyan());
+            println!("{}", " STEP 2: Collecting Changelogs".cyan().bold());
+            println!("{}", "═".repeat(60).cyan());
 
-        Some(ConsolidatedChangelog::with_templates(
-            &version_str,
-            &current_date(),
-            changelogs,
-            &config.changelog,
-        ))
-    } else {
-        None
+            Some(collect_changelog(&config, &store, &updates, pin_diff, &version_str, plan).await?)
+        }
+        _ => None,
     };
 
     // Update metadata files
     let updated_metadata = if !no_metadata && !config.metadata_files.is_empty() && !dry_run {
-        let step = if collect_changelog { 3 } else { 2 };
+        let step = if collects_changelog { 3 } else { 2 };
         println!("\n{}", "═".repeat(60).cyan());
         println!(
             "{}",
@@ -796,7 +3060,12 @@ async fn cmd_update_release(
         println!("{}", "═".repeat(60).cyan());
 
         let date = current_date();
-        let files = MetadataUpdater::update_all(&config.metadata_files, &version_str, &date)?;
+        let files = MetadataUpdater::update_all(
+            &config.work_dir,
+            &config.metadata_files,
+            &version_str,
+            &date,
+        )?;
         for file in &files {
             println!("{} Updated {}", "✓".green(), file);
         }
@@ -810,16 +3079,12 @@ async fn cmd_update_release(
         println!("{}", " DRY RUN: Release Preview".cyan().bold());
         println!("{}", "═".repeat(60).cyan());
 
-        let commit_message = generate_commit_message(
-            &updates,
-            config.git.effective_commit_template(),
-            custom_message.as_deref(),
-        );
-        let full_tag = format!("{}{}", config.github.tag_prefix, version_str);
+        let commit_message =
+            generate_commit_message(&updates, &config.git, custom_message.as_deref());
 
         println!("\nWould perform the following actions:");
         println!("  Version: {}", version_str.yellow());
-        println!("  1. Stage file: {}", config.versions_file);
+        println!("  1. Stage files: {}", config.versions_files().join(", "));
 
         if !no_metadata {
             for meta in &config.metadata_files {
@@ -828,24 +3093,31 @@ async fn cmd_update_release(
         }
 
         println!("  3. Commit with message:");
-        println!("     {}", commit_message.dimmed());
-        println!("  4. Create tag: {}", full_tag.yellow());
+        println!("     {}", commit_message.replace('\n', "\n     ").dimmed());
+        if let Some(branch) = &pull_request {
+            println!(
+                "  4. Push to {} and open a pull request into {}",
+                branch.name, branch.base
+            );
+        } else {
+            println!("  4. Create tag: {}", full_tag.yellow());
+        }
 
-        if !no_push {
+        if !no_push && pull_request.is_none() {
             println!("  5. Push to remote (with tags)");
         }
 
-        if !no_github && config.github.create_release {
+        if !no_github && config.github.create_release && pull_request.is_none() {
             println!(
                 "  6. Create GitHub release{}",
-                if draft { " (draft)" } else { "" }
+                release_label(draft, is_prerelease(&version_str))
             );
         }
 
-        if let Some(ref changelog) = consolidated_changelog {
+        if let (Some(plan), Some(changelog)) = (&changelog_plan, &consolidated_changelog) {
             println!("\n{}", "Generated Changelog:".cyan().bold());
             println!("{}", "-".repeat(40));
-            let output = changelog.render(changelog_format);
+            let output = changelog.render(plan.format);
             for (i, line) in output.lines().enumerate() {
                 if i >= 50 {
                     println!("... (truncated)");
@@ -856,18 +3128,22 @@ async fn cmd_update_release(
         }
 
         println!("\n{}", "Dry run complete - no changes made.".yellow());
-        return Ok(());
+        return Ok(unmatched);
     }
 
     // Save changelog
-    if let Some(ref changelog) = consolidated_changelog {
-        if let Some(ref file_path) = changelog_file {
-            changelog.save_to_file(file_path, changelog_format)?;
-            println!("{} Saved changelog to: {}", "✓".green(), file_path);
+    let mut changelog_file = None;
+    if let Some(plan) = &changelog_plan {
+        let rebuilt = plan
+            .rebuild_history(&config, packages_filter.as_deref(), verbose)
+            .await?;
+        if rebuilt.is_some() || consolidated_changelog.is_some() {
+            plan.emit(rebuilt.as_deref(), consolidated_changelog.as_ref())?;
+            changelog_file = plan.file.clone();
         }
     }
 
-    let step_num = if collect_changelog { 4 } else { 3 };
+    let step_num = if collects_changelog { 4 } else { 3 };
     println!("\n{}", "═".repeat(60).cyan());
     println!(
         "{}",
@@ -876,19 +3152,17 @@ async fn cmd_update_release(
     println!("{}", "═".repeat(60).cyan());
 
     // Generate commit message
-    let commit_message = generate_commit_message(
-        &updates,
-        config.git.effective_commit_template(),
-        custom_message.as_deref(),
-    );
+    let commit_message = generate_commit_message(&updates, &config.git, custom_message.as_deref());
 
     if verbose {
         println!("Commit message: {}", commit_message);
     }
 
     // Stage files
-    git.add(&config.versions_file)?;
-    println!("{} Staged {}", "✓".green(), config.versions_file);
+    for file in versions_file_paths(&config)? {
+        git.add(&file)?;
+        println!("{} Staged {}", "✓".green(), file);
+    }
 
     // Stage changelog
     if config.changelog.include_in_commit {
@@ -911,45 +3185,64 @@ async fn cmd_update_release(
     }
 
     // Commit
+    hooks::run(&config, Stage::PreCommit, &hook_context)?;
+    if let Some(branch) = &pull_request {
+        branch.start(&git)?;
+    }
     git.commit(&commit_message)?;
     println!("{} Committed changes", "✓".green());
 
     let step_num = step_num + 1;
+    let step = if pull_request.is_some() {
+        "Open Pull Request"
+    } else {
+        "Create Release"
+    };
     println!("\n{}", "═".repeat(60).cyan());
-    println!(
-        "{}",
-        format!(" STEP {}: Create Release", step_num).cyan().bold()
-    );
+    println!("{}", format!(" STEP {}: {}", step_num, step).cyan().bold());
     println!("{}", "═".repeat(60).cyan());
 
     // Create release message
-    let release_notes = if config.changelog.use_as_release_notes {
-        if let Some(ref changelog) = consolidated_changelog {
-            changelog.render(changelog_format)
-        } else {
-            generate_release_notes(&updates, &version_str)
+    let release_notes = match (&changelog_plan, &consolidated_changelog) {
+        (Some(plan), Some(changelog)) if config.changelog.use_as_release_notes => {
+            changelog.render(plan.format)
         }
-    } else {
-        generate_release_notes(&updates, &version_str)
+        _ => generate_release_notes(&config, &updates, &version_str),
     };
 
     let release_message = custom_message.as_deref().unwrap_or(&release_notes);
 
+    if let (Some(branch), Some(github)) = (&pull_request, &github) {
+        let title = config.release_title(&version_str, &current_date(), updates.len());
+        branch
+            .open(&config, &git, github, &title, release_message, draft)
+            .await?;
+        println!(
+            "\n{} Tag the merge commit as {} once the pull request is merged",
+            "ℹ".cyan(),
+            full_tag.yellow()
+        );
+        return Ok(unmatched);
+    }
+
     perform_release(
         &config,
         &version_str,
         Some(release_message),
+        &updates,
         no_push,
+        push_to,
         no_github,
         draft,
+        force_retag,
         verbose,
-    )?;
+    )
+    .await?;
 
     println!("\n{}", "═".repeat(60).green());
     println!("{}", " Release Complete!".green().bold());
     println!("{}", "═".repeat(60).green());
 
-    let full_tag = format!("{}{}", config.github.tag_prefix, version_str);
     println!("\nSummary:");
     println!("  • Version: {}", version_str.yellow());
     println!("  • Updated {} package(s)", updates.len());
@@ -969,62 +3262,88 @@ async fn cmd_update_release(
     if !no_github && config.github.create_release {
         println!(
             "  • Created GitHub release{}",
-            if draft { " (draft)" } else { "" }
+            release_label(draft, is_prerelease(&version_str))
         );
     }
 
-    Ok(())
+    Ok(unmatched)
 }
+#[allow(clippy::too_many_arguments)]
 async fn cmd_changelog(
     config_path: &str,
     packages_filter: Option<String>,
     format_override: Option<CliChangelogFormat>,
     output_file_override: Option<String>,
-    force_stdout: bool,
+    stdout: bool,
+    json_output: bool,
     release_version: Option<String>,
     rebuild: bool,
+    tag_range: TagRange,
+    no_cache: bool,
+    refresh: bool,
+    jobs: usize,
     verbose: bool,
-) -> Result<()> {
-    let config = Config::load(config_path)?;
-
-    let format = format_override
-        .map(|f| f.into())
-        .unwrap_or_else(|| config.changelog.format_enum());
-
-    let output_file = if force_stdout {
-        None
-    } else {
-        output_file_override.or_else(|| config.changelog.output_file.clone())
+) -> Result<Vec<String>> {
+    let config = load_config(config_path)?;
+    let (packages_to_check, unmatched) =
+        filter_packages(&config.packages, packages_filter.as_deref());
+
+    // `--stdout` alone replaces the configured file; with `--output` both are written
+    let file = match output_file_override {
+        Some(path) => Some(path),
+        None if stdout || json_output => None,
+        None => config.changelog.output_file.clone(),
+    };
+    let plan = ChangelogPlan {
+        format: format_override
+            .map(|f| f.into())
+            .unwrap_or_else(|| config.changelog.format_enum()),
+        file,
+        dir: config.work_dir.clone(),
+        stdout,
+        rebuild,
+        require: false,
+        allow_missing: false,
+        refresh,
+        json: json_output,
     };
-
-    let packages_to_check = filter_packages(&config.packages, packages_filter.as_deref());
 
     if rebuild {
-        return rebuild_changelog_from_tags(
+        let rebuilt = rebuild_changelog_from_tags(
             &config,
-            &packages_to_check,
-            format,
-            output_file,
+            packages_filter.as_deref(),
+            plan.format,
+            plan.file.as_deref(),
+            &tag_range,
+            !no_cache,
+            refresh,
+            jobs,
             verbose,
         )
-        .await;
+        .await?;
+        if let Some(history) = rebuilt {
+            plan.emit(Some(&history), None)?;
+        }
+        return Ok(unmatched);
     }
 
-    let pypi = PyPiClient::new()?;
-    let buildout = BuildoutVersions::load(&config.versions_file)?;
+    let pypi = PyPiClient::with_config(&config.pypi, &config.http)?;
+    let files = load_versions_files(&config).await?;
 
-    println!("{}", "Checking for updates...".cyan());
+    if !json_output {
+        println!("{}", "Checking for updates...".cyan());
+    }
 
     let latest_versions =
-        fetch_latest_versions(&pypi, &packages_to_check, None, verbose).await?;
+        fetch_latest_versions(&config, &pypi, &packages_to_check, None, verbose).await?;
 
     let mut updates = Vec::new();
 
     for (pkg_config, latest) in packages_to_check.iter().zip(latest_versions) {
-        let current = buildout.get_version(pkg_config.buildout_name());
+        let current = package_pin(&files, pkg_config);
 
-        if let Some(current_version) = current {
-            if current_version != latest.version {
+        if let (Some(current_version), Some(latest)) = (current, latest) {
+            if is_upgrade(current_version, &latest.version) {
                 updates.push(VersionUpdate {
                     package_name: pkg_config.buildout_name().to_string(),
                     old_version: current_version.to_string(),
@@ -1034,62 +3353,666 @@ async fn cmd_changelog(
         }
     }
 
-    if updates.is_empty() {
+    let pin_diff =
+        pin_changes_since_latest_tag(&config, &git_for(&config), packages_filter.as_deref());
+
+    // With --json, an up-to-date tree still prints an (empty) document
+    if updates.is_empty() && pin_diff.is_empty() && !json_output {
         println!("{}", "All packages are up to date!".green());
-        return Ok(());
+        return Ok(unmatched);
     }
+    if !json_output {
+        println!(
+            "\n{} Found {} package(s) with updates",
+            "✓".green(),
+            updates.len()
+        );
 
-    println!(
-        "\n{} Found {} package(s) with updates",
-        "✓".green(),
-        updates.len()
-    );
+        if !pin_diff.is_empty() {
+            println!(
+                "{} Found {} added and {} removed pin(s) since the last release",
+                "✓".green(),
+                pin_diff.added.len(),
+                pin_diff.removed.len()
+            );
+        }
+    }
+
+    let version = release_version.unwrap_or_else(|| "UNRELEASED".to_string());
+    let consolidated = collect_changelog(
+        &config,
+        &PackageMetadataStore::new(),
+        &updates,
+        pin_diff,
+        &version,
+        &plan,
+    )
+    .await?;
 
-    println!("{}", "\nFetching changelogs...".cyan());
+    plan.emit(None, Some(&consolidated))?;
+    Ok(unmatched)
+}
 
-    let collector = ChangelogCollector::with_config(&config.changelog);
-    let changelogs = collector
-        .collect_changelogs(&updates, &config.packages)
-        .await?;
+/// Compare the pins with a known-good set and adopt its versions for the chosen packages
+async fn cmd_kgs_sync(
+    config_path: &str,
+    url: Option<String>,
+    packages_filter: Option<String>,
+    all: bool,
+    dry_run: bool,
+    json_output: bool,
+    non_interactive: bool,
+) -> Result<Vec<String>> {
+    let config = load_config(config_path)?;
+    let Some(url) = url.or_else(|| config.kgs_url.clone()) else {
+        return Err(ReleaserError::ConfigError(
+            "no known-good set to compare with: pass --url or set kgs_url".to_string(),
+        ));
+    };
+
+    let mut files = load_versions_files(&config).await?;
+    if !json_output {
+        println!("Comparing pins with {}", url.cyan());
+    }
+    let kgs = kgs::fetch(&url, &config.work_dir, &config.http).await?;
+    let mismatches = kgs::compare(&files, &kgs);
+
+    if json_output {
+        let report = serde_json::json!({
+            "kgs": url,
+            "mismatches": mismatches,
+            "warnings": warnings::take(),
+        });
+        println!("{}", serde_json::to_string_pretty(&report).unwrap());
+        return Ok(Vec::new());
+    }
+
+    if mismatches.is_empty() {
+        println!("{} Every shared pin matches the KGS", "✓".green());
+        return Ok(Vec::new());
+    }
 
-    let found_count = changelogs.iter().filter(|c| !c.entries.is_empty()).count();
     println!(
-        "{} Found changelog entries for {}/{} packages",
-        "✓".green(),
-        found_count,
-        changelogs.len()
+        "\n{}",
+        format!("{} pin(s) differ from the KGS:", mismatches.len())
+            .yellow()
+            .bold()
     );
+    let name_width = mismatches
+        .iter()
+        .map(|m| m.package.len())
+        .max()
+        .unwrap_or(0);
+    for mismatch in &mismatches {
+        let kgs_version = if mismatch.is_upgrade() {
+            mismatch.kgs.green()
+        } else {
+            format!("{} (older)", mismatch.kgs).yellow()
+        };
+        println!(
+            "  {:width$}  {} → {}",
+            mismatch.package,
+            mismatch.ours.dimmed(),
+            kgs_version,
+            width = name_width
+        );
+    }
 
-    let version = release_version.unwrap_or_else(|| "UNRELEASED".to_string());
-    let consolidated = ConsolidatedChangelog::with_templates(
-        &version,
-        &current_date(),
-        changelogs,
-        &config.changelog,
-    );
+    if dry_run {
+        return Ok(Vec::new());
+    }
 
-    match output_file {
-        Some(path) => {
-            consolidated.save_to_file(&path, format)?;
-            println!("\n{} Changelog saved to: {}", "✓".green(), path);
-        }
-        None => {
-            println!("\n{}", "═".repeat(60));
-            println!("{}", consolidated.render(format));
-        }
+    let (selected, unmatched): (Vec<&kgs::KgsMismatch>, _) = if all {
+        (mismatches.iter().collect(), Vec::new())
+    } else if let Some(filter) = packages_filter.as_deref() {
+        let matches = |m: &kgs::KgsMismatch, token: &str| {
+            pypi::normalize_project_name(&m.package) == pypi::normalize_project_name(token)
+        };
+        let unmatched = unmatched_tokens(
+            filter,
+            |token| mismatches.iter().any(|m| matches(m, token)),
+            "does not differ from the KGS",
+        );
+        let selected = mismatches
+            .iter()
+            .filter(|m| filter_tokens(filter).any(|token| matches(m, token)))
+            .collect();
+        (selected, unmatched)
+    } else if non_interactive {
+        println!("\nPass --packages or --all to adopt KGS versions.");
+        return Ok(Vec::new());
+    } else {
+        // Older KGS versions are usually deliberate overrides, so only upgrades are preselected
+        let items: Vec<String> = mismatches
+            .iter()
+            .map(|m| format!("{}: {} → {}", m.package, m.ours, m.kgs))
+            .collect();
+        let defaults: Vec<bool> = mismatches.iter().map(|m| m.is_upgrade()).collect();
+        let selections = MultiSelect::new()
+            .with_prompt("Adopt the KGS version for")
+            .items(&items)
+            .defaults(&defaults)
+            .interact()
+            .map_err(|e| ReleaserError::IoError(std::io::Error::other(e.to_string())))?;
+        let selected = selections.into_iter().map(|i| &mismatches[i]).collect();
+        (selected, Vec::new())
+    };
+
+    if selected.is_empty() {
+        println!("No packages selected.");
+        return Ok(unmatched);
     }
 
-    Ok(())
-}
+    for file in files.iter_mut() {
+        file.set_annotate_updates(config.annotate_pins);
+        file.set_keep_backup(config.backup_pins);
+    }
+    for mismatch in &selected {
+        for file in files.iter_mut() {
+            file.update_version_in(&mismatch.package, &mismatch.kgs, None)?;
+        }
+    }
+    for file in &files {
+        file.save()?;
+    }
 
-fn cmd_add(
+    println!(
+        "\n{} Adopted {} KGS version(s)",
+        "✓".green(),
+        selected.len()
+    );
+    Ok(unmatched)
+}
+
+async fn cmd_report(
+    config_path: &str,
+    since: &str,
+    output: Option<String>,
+    offline: bool,
+    json_output: bool,
+    verbose: bool,
+) -> Result<()> {
+    use report::{Report, ReportCommit, ReportRelease};
+
+    let config = load_config(config_path)?;
+    let git = git_for(&config);
+    if !git.is_repo() {
+        return Err(ReleaserError::GitError(
+            "Not in a git repository".to_string(),
+        ));
+    }
+
+    let today = chrono::Local::now().date_naive();
+    let since = report::parse_since(since, today)?
+        .format("%Y-%m-%d")
+        .to_string();
+
+    let tag_dates = git.tag_dates()?;
+    let releases: Vec<ReportRelease> = git
+        .get_version_tags(&config.github.tag_prefix)?
+        .into_iter()
+        .filter_map(|(tag, _)| {
+            let date = tag_dates.get(&tag)?.clone();
+            (date >= since).then_some(ReportRelease { tag, date })
+        })
+        .collect();
+
+    let commits: Vec<ReportCommit> = git
+        .commits_since(&since, &versions_file_paths(&config)?)?
+        .into_iter()
+        .map(|(sha, date, subject)| ReportCommit { sha, date, subject })
+        .collect();
+
+    // Net changes: each versions file as of the last commit before the window
+    // against the working tree
+    let mut updated: Vec<VersionUpdate> = Vec::new();
+    let mut pin_changes = PinDiff::default();
+    if let Some(base) = git.commit_before(&since)? {
+        for path in config.versions_files() {
+            let Ok(previous) = git.show_file_at_ref(&base, path).and_then(|content| {
+                BuildoutVersions::from_content(content, format!("{}@{}", path, base))
+            }) else {
+                continue;
+            };
+            let current = BuildoutVersions::load_in(&config.work_dir, path)?;
+            for update in current.changed_pins(&previous) {
+                if !updated
+                    .iter()
+                    .any(|u| u.package_name == update.package_name)
+                {
+                    updated.push(update);
+                }
+            }
+            pin_changes.merge(current.diff_pins(&previous));
+        }
+    } else if verbose {
+        println!("No commit before {}, skipping pin changes", since);
+    }
+
+    let (pending, advisories) = if offline {
+        (None, None)
+    } else {
+        let spinner = (!json_output).then(|| create_spinner("Checking PyPI and OSV..."));
+        let files = load_versions_files(&config).await?;
+
+        let pypi = PyPiClient::with_config(&config.pypi, &config.http)?;
+        let pending =
+            match fetch_latest_versions(&config, &pypi, &config.packages, None, verbose).await {
+                Ok(latest) => Some(
+                    config
+                        .packages
+                        .iter()
+                        .zip(latest)
+                        .filter_map(|(package, latest)| {
+                            let pinned = package_pin(&files, package)?;
+                            let latest = latest?;
+                            is_upgrade(pinned, &latest.version).then(|| VersionUpdate {
+                                package_name: package.buildout_name().to_string(),
+                                old_version: pinned.to_string(),
+                                new_version: latest.version,
+                            })
+                        })
+                        .collect(),
+                ),
+                Err(e) => {
+                    warnings::warn(
+                        WarningKind::PyPi,
+                        None,
+                        format!("pending updates not checked: {}", e),
+                    );
+                    None
+                }
+            };
+
+        let mut pins: Vec<PinnedPackage> = Vec::new();
+        for buildout in &files {
+            for (name, version) in buildout.get_all_versions() {
+                if !pins.iter().any(|p| p.name == name) {
+                    pins.push(PinnedPackage {
+                        name: name.to_string(),
+                        version: version.to_string(),
+                    });
+                }
+            }
+        }
+        let osv = audit::OsvClient::with_config(&config.audit, &config.http)?;
+        let advisories = match osv.audit(&pins, &config.audit.ignore).await {
+            // Only advisories published within the window count as new
+            Ok(findings) => Some(
+                findings
+                    .into_iter()
+                    .filter_map(|mut finding| {
+                        finding.vulnerabilities.retain(|v| {
+                            v.published
+                                .as_deref()
+                                .and_then(|p| p.get(..10))
+                                .is_some_and(|date| date >= since.as_str())
+                        });
+                        (!finding.vulnerabilities.is_empty()).then_some(finding)
+                    })
+                    .collect(),
+            ),
+            Err(e) => {
+                warnings::warn(
+                    WarningKind::Audit,
+                    None,
+                    format!("advisories not checked: {}", e),
+                );
+                None
+            }
+        };
+
+        if let Some(spinner) = spinner {
+            spinner.finish_and_clear();
+        }
+        (pending, advisories)
+    };
+
+    let report = Report {
+        since,
+        until: today.format("%Y-%m-%d").to_string(),
+        releases,
+        commits,
+        updated,
+        pin_changes,
+        pending,
+        advisories,
+    };
+
+    let rendered = if json_output {
+        let mut value = serde_json::to_value(&report).unwrap();
+        value["warnings"] = serde_json::json!(warnings::take());
+        serde_json::to_string_pretty(&value).unwrap() + "\n"
+    } else {
+        report.to_markdown()
+    };
+
+    match output {
+        Some(path) => {
+            std::fs::write(config.resolve(&path), rendered)?;
+            if !json_output {
+                println!("{} Report written to {}", "✓".green(), path);
+            }
+        }
+        None => print!("{}", rendered),
+    }
+
+    Ok(())
+}
+
+fn cmd_diff(config_path: &str, from: &str, to: Option<&str>, json_output: bool) -> Result<()> {
+    let config = load_config(config_path)?;
+    let git = git_for(&config);
+    if !git.is_repo() {
+        return Err(ReleaserError::GitError(
+            "Not in a git repository".to_string(),
+        ));
+    }
+
+    let (updated, pin_changes) = pin_changes_between(&config, &git, from, to)?;
+    let to_label = to.unwrap_or("working tree");
+
+    if json_output {
+        let report = PinChangesReport {
+            from,
+            to: to_label,
+            updated: &updated,
+            pin_changes: &pin_changes,
+        };
+        println!("{}", serde_json::to_string_pretty(&report).unwrap());
+        return Ok(());
+    }
+
+    if updated.is_empty() && pin_changes.is_empty() {
+        println!(
+            "{} No pin changes between {} and {}",
+            "✓".green(),
+            from,
+            to_label
+        );
+        return Ok(());
+    }
+
+    println!("Pin changes from {} to {}:\n", from.bold(), to_label.bold());
+    for update in &updated {
+        println!(
+            "    {:<30} {:<15} → {}",
+            update.package_name,
+            update.old_version.dimmed(),
+            update.new_version.green()
+        );
+    }
+    for pin in &pin_changes.added {
+        println!("  {} {:<30} {}", "+".green(), pin.name, pin.version);
+    }
+    for pin in &pin_changes.removed {
+        println!("  {} {:<30} {}", "-".red(), pin.name, pin.version.dimmed());
+    }
+    println!(
+        "\n{} updated, {} added, {} removed",
+        updated.len(),
+        pin_changes.added.len(),
+        pin_changes.removed.len()
+    );
+
+    Ok(())
+}
+
+/// Pins updated, added and removed in the versions files between two refs
+/// (`to` defaults to the working tree). A version tag reads the versions file
+/// from its `[[history.paths]]` entry; linked files missing at a ref are skipped.
+fn pin_changes_between(
+    config: &Config,
+    git: &GitOps,
+    from: &str,
+    to: Option<&str>,
+) -> Result<(Vec<VersionUpdate>, PinDiff)> {
+    let prefix = &config.github.tag_prefix;
+    let version_tags = git.get_version_tags(prefix)?;
+    let main_file = |reference: &str| {
+        version_tags
+            .iter()
+            .find(|(tag, _)| tag == reference)
+            .map_or(config.versions_file.as_str(), |(_, version)| {
+                config
+                    .history
+                    .versions_file_for(version, prefix, &config.versions_file)
+            })
+    };
+    let load = |reference: Option<&str>, path: &str| match reference {
+        Some(reference) => git
+            .show_file_at_ref(reference, path)
+            .map_err(|_| ReleaserError::GitError(format!("{} not found at {}", path, reference)))
+            .and_then(|content| {
+                BuildoutVersions::from_content(content, format!("{}@{}", path, reference))
+            }),
+        None => BuildoutVersions::load_in(&config.work_dir, path),
+    };
+
+    let mut updated: Vec<VersionUpdate> = Vec::new();
+    let mut pin_changes = PinDiff::default();
+    for (index, path) in config.versions_files().into_iter().enumerate() {
+        let (before, after) = if index == 0 {
+            (
+                load(Some(from), main_file(from))?,
+                load(to, to.map_or(path, main_file))?,
+            )
+        } else {
+            match (load(Some(from), path), load(to, path)) {
+                (Ok(before), Ok(after)) => (before, after),
+                _ => continue,
+            }
+        };
+
+        for update in after.changed_pins(&before) {
+            if !updated
+                .iter()
+                .any(|u| u.package_name == update.package_name)
+            {
+                updated.push(update);
+            }
+        }
+        pin_changes.merge(after.diff_pins(&before));
+    }
+
+    Ok((updated, pin_changes))
+}
+
+fn cmd_history(
+    config_path: &str,
+    limit: Option<usize>,
+    json_output: bool,
+    verbose: bool,
+) -> Result<()> {
+    let config = load_config(config_path)?;
+    let git = git_for(&config);
+    if !git.is_repo() {
+        return Err(ReleaserError::GitError(
+            "Not in a git repository".to_string(),
+        ));
+    }
+
+    let history = release_history(&config, &git, limit, verbose)?;
+
+    if json_output {
+        let value = serde_json::json!({
+            "releases": history,
+            "warnings": warnings::take(),
+        });
+        println!("{}", serde_json::to_string_pretty(&value).unwrap());
+        return Ok(());
+    }
+
+    if history.is_empty() {
+        println!("{}", "No release tags found.".yellow());
+        return Ok(());
+    }
+
+    for entry in &history {
+        println!(
+            "{} {}",
+            entry.tag.bold(),
+            entry.date.as_deref().unwrap_or("unknown date").dimmed()
+        );
+        for update in &entry.updated {
+            println!(
+                "  {} {} → {}",
+                update.package_name,
+                update.old_version.dimmed(),
+                update.new_version.green()
+            );
+        }
+        for pin in &entry.pin_changes.added {
+            println!("  {} {} {}", "+".green(), pin.name, pin.version);
+        }
+        for pin in &entry.pin_changes.removed {
+            println!("  {} {} {}", "-".red(), pin.name, pin.version.dimmed());
+        }
+        if entry.updated.is_empty() && entry.pin_changes.is_empty() {
+            println!("  {}", "no pin changes".dimmed());
+        }
+        if let Some(message) = &entry.message {
+            println!("    {}", message.replace('\n', "\n    ").dimmed());
+        }
+        println!();
+    }
+
+    Ok(())
+}
+
+/// Releases newest first: the pin changes come from the tag's metadata
+/// trailer when it has one, otherwise from diffing the versions file at the
+/// tag against the previous one
+fn release_history(
+    config: &Config,
+    git: &GitOps,
+    limit: Option<usize>,
+    verbose: bool,
+) -> Result<Vec<HistoryEntry>> {
+    let prefix = &config.github.tag_prefix;
+    let mut version_tags = git.get_version_tags(prefix)?;
+    version_tags.reverse();
+    let dates = git.tag_dates()?;
+    let first = limit.map_or(0, |n| version_tags.len().saturating_sub(n));
+
+    let snapshot = |(tag, version): &(String, Version)| {
+        let path = config
+            .history
+            .versions_file_for(version, prefix, &config.versions_file);
+        if verbose {
+            println!("Loading {} from tag {}...", path, tag);
+        }
+        let loaded = git.show_file_at_ref(tag, path).and_then(|content| {
+            BuildoutVersions::from_content(content, format!("{}@{}", path, tag))
+        });
+        if loaded.is_err() {
+            warnings::warn(
+                WarningKind::History,
+                Some(tag),
+                format!(
+                    "{} not found, pin changes unknown (add a [[history.paths]] entry if it moved)",
+                    path
+                ),
+            );
+        }
+        loaded.ok()
+    };
+
+    let mut history = Vec::new();
+    // Snapshot of the previous tag, kept for the next diff
+    let mut previous_snapshot: Option<(usize, Option<BuildoutVersions>)> = None;
+    for index in first..version_tags.len() {
+        let (tag, version) = &version_tags[index];
+        let previous = index.checked_sub(1).map(|i| version_tags[i].0.clone());
+        let message = git.tag_message(tag)?;
+
+        let (updated, pin_changes) = match message.as_deref().and_then(TagMetadata::parse) {
+            Some(metadata) => (
+                metadata.updated,
+                PinDiff {
+                    added: metadata.added,
+                    removed: metadata.removed,
+                },
+            ),
+            None => {
+                let current = snapshot(&version_tags[index]);
+                let before = match (index.checked_sub(1), previous_snapshot.take()) {
+                    (Some(i), Some((cached, loaded))) if cached == i => loaded,
+                    (Some(i), _) => snapshot(&version_tags[i]),
+                    (None, _) => None,
+                };
+                let changes = match (&current, &before) {
+                    (Some(current), Some(before)) => {
+                        (current.changed_pins(before), current.diff_pins(before))
+                    }
+                    _ => Default::default(),
+                };
+                previous_snapshot = Some((index, current));
+                changes
+            }
+        };
+
+        history.push(HistoryEntry {
+            tag: tag.clone(),
+            version: version.to_string(),
+            date: dates.get(tag).cloned(),
+            previous,
+            updated,
+            pin_changes,
+            message: message
+                .map(|message| TagMetadata::strip(&message))
+                .filter(|message| !message.is_empty()),
+        });
+    }
+
+    history.reverse();
+    Ok(history)
+}
+
+fn cmd_fmt(config_path: &str, check: bool) -> Result<()> {
+    let config = read_config(config_path)?;
+
+    let mut unformatted = Vec::new();
+    for path in versions_file_paths(&config)? {
+        let mut versions = BuildoutVersions::load_in(&config.work_dir, &path)?;
+        versions.set_keep_backup(config.backup_pins);
+        if !versions.format_versions()? {
+            continue;
+        }
+
+        if check {
+            println!("{} {} is not formatted", "✗".red(), path);
+        } else {
+            versions.save()?;
+            println!("{} Formatted {}", "✓".green(), path);
+        }
+        unformatted.push(path);
+    }
+
+    if unformatted.is_empty() {
+        println!("{} Versions files are formatted", "✓".green());
+    } else if check {
+        return Err(ReleaserError::NotFormatted(unformatted.join(", ")));
+    }
+
+    Ok(())
+}
+
+/// Version `add` should also pin in the versions file
+enum PinRequest {
+    Version(String),
+    Latest,
+}
+
+async fn cmd_add(
     config_path: &str,
     package: &str,
     constraint: Option<String>,
     buildout_name: Option<String>,
     changelog_url: Option<String>,
+    pin: Option<PinRequest>,
 ) -> Result<()> {
-    let mut config = Config::load(config_path)?;
+    let mut config = read_config(config_path)?;
 
     if config.packages.iter().any(|p| p.name == package) {
         return Err(ReleaserError::ConfigError(format!(
@@ -1098,520 +4021,2761 @@ fn cmd_add(
         )));
     }
 
-    config.packages.push(PackageConfig {
-        name: package.to_string(),
-        version_constraint: constraint,
-        buildout_name,
-        allow_prerelease: false,
-        changelog_url,
-        include_in_changelog: true,
-    });
+    let added = PackageConfig {
+        name: package.to_string(),
+        version_constraint: constraint,
+        buildout_name,
+        allow_prerelease: false,
+        changelog_url,
+        changelog_urls: Vec::new(),
+        min_release_age_days: None,
+        include_in_changelog: true,
+        changelog_from: Vec::new(),
+        package_template: None,
+        entry_template: None,
+        max_entries: None,
+        renamed_from: None,
+        section: None,
+        discovered: false,
+    };
+
+    // Resolve and insert the pin first, so a failure leaves the config untouched
+    let pinned = match pin {
+        Some(pin) => {
+            let version = match pin {
+                PinRequest::Version(version) => {
+                    if version::python::parse_python_version(&version).is_none() {
+                        return Err(ReleaserError::VersionError(format!(
+                            "'{}' is not a valid version",
+                            version
+                        )));
+                    }
+                    version
+                }
+                PinRequest::Latest => {
+                    let pypi = PyPiClient::with_config(&config.pypi, &config.http)?;
+                    pypi.latest_for(&added)
+                        .await?
+                        .map(|latest| latest.version)
+                        .ok_or_else(|| {
+                            ReleaserError::PyPiError(format!(
+                                "No release of {} is past the cooldown yet; pass a version to pin",
+                                added.name
+                            ))
+                        })?
+                }
+            };
+
+            let mut versions = BuildoutVersions::load_in(&config.work_dir, &config.versions_file)?;
+            versions.set_keep_backup(config.backup_pins);
+            if !versions.add_version(added.buildout_name(), &version, None)? {
+                return Err(ReleaserError::ConfigError(format!(
+                    "'{}' is already pinned to {} in {}; use `bldr set` to change it",
+                    added.buildout_name(),
+                    versions
+                        .get_version(added.buildout_name())
+                        .unwrap_or_default(),
+                    config.versions_file
+                )));
+            }
+            Some((versions, version))
+        }
+        None => None,
+    };
+
+    // The pin is written first, so a failure to write it leaves the config untouched
+    if let Some((versions, version)) = pinned {
+        versions.save()?;
+        println!(
+            "{} Pinned {} = {} in {}",
+            "✓".green(),
+            added.buildout_name(),
+            version,
+            config.versions_file
+        );
+    }
+
+    config.packages.push(added);
+    config.save(config.resolve(config_path))?;
+    println!("{} Added package: {}", "✓".green(), package);
+
+    Ok(())
+}
+
+fn cmd_remove(config_path: &str, package: &str) -> Result<()> {
+    let mut config = read_config(config_path)?;
+
+    let initial_len = config.packages.len();
+    config.packages.retain(|p| p.name != package);
+
+    if config.packages.len() == initial_len {
+        return Err(ReleaserError::ConfigError(format!(
+            "Package '{}' not found in configuration",
+            package
+        )));
+    }
+
+    config.save(config.resolve(config_path))?;
+    println!("{} Removed package: {}", "✓".green(), package);
+
+    Ok(())
+}
+
+async fn cmd_migrate(
+    config_path: &str,
+    yes: bool,
+    non_interactive: bool,
+    dry_run: bool,
+    verbose: bool,
+) -> Result<()> {
+    let mut config = read_config(config_path)?;
+    let mut files = load_versions_files(&config).await?;
+
+    let renames: Vec<(PackageConfig, String, String)> = pending_renames(&config, &files)
+        .into_iter()
+        .map(|(package, old_name, old_version)| {
+            (
+                package.clone(),
+                old_name.to_string(),
+                old_version.to_string(),
+            )
+        })
+        .collect();
+
+    if renames.is_empty() {
+        println!("{}", "No renamed packages are still pinned.".green());
+        return Ok(());
+    }
+
+    let pypi = PyPiClient::with_config(&config.pypi, &config.http)?;
+    let successors: Vec<PackageConfig> = renames.iter().map(|(p, _, _)| p.clone()).collect();
+    let latest = fetch_latest_versions(&config, &pypi, &successors, None, verbose).await?;
+
+    // A successor with no release past the cooldown keeps the old pin for now
+    let renames: Vec<_> = renames
+        .into_iter()
+        .zip(latest)
+        .filter_map(|(rename, latest)| Some((rename, latest?)))
+        .collect();
+    if renames.is_empty() {
+        println!(
+            "{}",
+            "No renamed package has a release past the cooldown yet.".green()
+        );
+        return Ok(());
+    }
+
+    println!("{}", "Renamed packages:".cyan().bold());
+    for ((package, old_name, old_version), latest) in &renames {
+        println!(
+            "  {} {} → {} {}",
+            old_name.yellow(),
+            old_version,
+            package.buildout_name().green(),
+            latest.version
+        );
+    }
+
+    if dry_run {
+        println!("\n{}", "Dry run - no files were modified.".yellow());
+        return Ok(());
+    }
+
+    if !yes {
+        if non_interactive {
+            return Err(ReleaserError::ConfigError(
+                "Replacing renamed pins needs --yes when running non-interactively".to_string(),
+            ));
+        }
+        let proceed = Confirm::new()
+            .with_prompt("Replace these pins?")
+            .default(true)
+            .interact()
+            .map_err(|e| ReleaserError::IoError(std::io::Error::other(e.to_string())))?;
+
+        if !proceed {
+            println!("Aborted.");
+            return Ok(());
+        }
+    }
+
+    for ((package, old_name, _), latest) in &renames {
+        for buildout in files.iter_mut() {
+            buildout.rename_pin(old_name, package.buildout_name(), &latest.version)?;
+        }
+    }
+    for buildout in files.iter_mut() {
+        buildout.set_keep_backup(config.backup_pins);
+        buildout.save()?;
+    }
+
+    // Entries still tracking the old name would only ever report a dead package
+    let tracked = config.packages.len();
+    config.packages.retain(|p| {
+        !renames
+            .iter()
+            .any(|((_, old_name, _), _)| p.name == *old_name || p.buildout_name() == old_name)
+    });
+    if config.packages.len() != tracked {
+        config.save(config.resolve(config_path))?;
+        println!(
+            "{} Removed {} entry(ies) for old package names",
+            "✓".green(),
+            tracked - config.packages.len()
+        );
+    }
+
+    println!("{} Migrated {} pin(s)", "✓".green(), renames.len());
+
+    Ok(())
+}
+
+async fn cmd_set(
+    config_path: &str,
+    package: &str,
+    version: &str,
+    section: Option<&str>,
+    git_flags: GitFlags,
+    trailers: &[String],
+    non_interactive: bool,
+) -> Result<()> {
+    let mut config = load_config(config_path)?;
+    config.git.resolve_trailers(trailers)?;
+    let (commit, push) = git_flags.resolve(&config);
+
+    if version::python::parse_python_version(version).is_none() {
+        return Err(ReleaserError::VersionError(format!(
+            "'{}' is not a valid version",
+            version
+        )));
+    }
+
+    // Tracked packages are pinned under their buildout name and section
+    let tracked = config.packages.iter().find(|p| matches_filter(p, package));
+    let name = tracked.map_or(package, |p| p.buildout_name());
+    let section = section.or_else(|| tracked.and_then(|p| p.section.as_deref()));
+
+    let git = git_for(&config);
+    let workspace = if commit {
+        if !git.is_repo() {
+            return Err(ReleaserError::GitError(
+                "Not in a git repository".to_string(),
+            ));
+        }
+        ensure_release_branch(&config, &git)?;
+        if push {
+            ensure_up_to_date(&config, &git, None)?;
+        }
+        prepare_workspace(&git, &versions_file_paths(&config)?, non_interactive, false)?
+    } else {
+        Workspace::Ready
+    };
+    if matches!(workspace, Workspace::Aborted) {
+        println!("Aborted.");
+        return Ok(());
+    }
+
+    let mut files = load_versions_files(&config).await?;
+    let mut applied = None;
+    for buildout in files.iter_mut() {
+        buildout.set_annotate_updates(config.annotate_pins);
+        buildout.set_keep_backup(config.backup_pins);
+        if let Some(update) = buildout.update_version_in(name, version, section)? {
+            applied.get_or_insert(update);
+        }
+    }
+
+    let update = match applied {
+        Some(update) => update,
+        None if files
+            .iter()
+            .any(|f| f.get_version_in(name, section) == Some(version)) =>
+        {
+            println!("{} is already pinned to {}", name, version);
+            return Ok(());
+        }
+        None => {
+            files[0].add_version(name, version, section)?;
+            VersionUpdate {
+                package_name: name.to_string(),
+                old_version: String::new(),
+                new_version: version.to_string(),
+            }
+        }
+    };
+
+    let updates = std::slice::from_ref(&update);
+    let hook_context = HookContext {
+        updates,
+        ..Default::default()
+    };
+    hooks::run(&config, Stage::PreUpdate, &hook_context)?;
+    for buildout in &files {
+        buildout.save()?;
+    }
+    hooks::run(&config, Stage::PostUpdate, &hook_context)?;
+
+    if update.old_version.is_empty() {
+        println!("{} Added {} = {}", "✓".green(), name, version);
+    } else {
+        println!(
+            "{} Set {} {} → {}",
+            "✓".green(),
+            name,
+            update.old_version.dimmed(),
+            version.green()
+        );
+    }
+
+    if commit {
+        for file in versions_file_paths(&config)? {
+            git.add(&file)?;
+        }
+        let message = generate_commit_message(updates, &config.git, None);
+        hooks::run(&config, Stage::PreCommit, &hook_context)?;
+        git.commit(&message)?;
+        println!("{} Committed: {}", "✓".green(), message);
+
+        if push {
+            git.push(push_target(&config, None), false)?;
+            println!("{} Pushed to remote", "✓".green());
+        }
+    }
+
+    Ok(())
+}
+
+async fn cmd_list(config_path: &str, detailed: bool) -> Result<()> {
+    let config = load_config(config_path)?;
+    let buildout = load_versions(&config, &config.versions_file).await.ok();
+
+    if config.packages.is_empty() {
+        println!("No packages configured.");
+        return Ok(());
+    }
+
+    println!("{}", "Tracked packages:".cyan().bold());
+
+    for pkg in &config.packages {
+        let current_version = buildout
+            .as_ref()
+            .and_then(|b| b.get_version(pkg.buildout_name()))
+            .unwrap_or("not set");
+
+        if detailed {
+            println!("\n  {}", pkg.name.yellow().bold());
+            println!("    Current version: {}", current_version);
+            if let Some(ref constraint) = pkg.version_constraint {
+                println!("    Constraint: {}", constraint);
+            }
+            if let Some(ref bn) = pkg.buildout_name {
+                println!("    Buildout name: {}", bn);
+            }
+            if pkg.discovered {
+                println!("    Tracked via: track_all");
+            }
+            if pkg.allow_prerelease {
+                println!("    Pre-releases: allowed");
+            }
+            if let Some(ref url) = pkg.changelog_url {
+                println!("    Changelog URL: {}", url);
+            }
+            for url in &pkg.changelog_urls {
+                println!("    Additional changelog: {}", url);
+            }
+        } else {
+            let constraint_str = pkg
+                .version_constraint
+                .as_ref()
+                .map(|c| format!(" ({})", c))
+                .unwrap_or_default();
+
+            println!(
+                "  {} = {}{}",
+                pkg.buildout_name(),
+                current_version,
+                constraint_str.dimmed()
+            );
+        }
+    }
+
+    Ok(())
+}
+
+async fn cmd_info(
+    config_path: &str,
+    package: &str,
+    show_versions: bool,
+    show_stats: bool,
+) -> Result<()> {
+    let (pypi_config, http_config) = read_config(config_path)
+        .map(|c| (c.pypi, c.http))
+        .unwrap_or_default();
+    let pypi = PyPiClient::with_config(&pypi_config, &http_config)?;
+    let info = pypi.get_package_info(package).await?;
+
+    println!("{}", info.info.name.yellow().bold());
+    println!("  Latest version: {}", info.info.version.green());
+
+    if let Some(ref summary) = info.info.summary {
+        println!("  Summary: {}", summary);
+    }
+
+    if let Some(homepage) = homepage(&info.info) {
+        println!("  Homepage: {}", homepage);
+    }
+
+    if show_versions {
+        println!("\n  {}", "Available versions:".cyan());
+
+        let mut versions: Vec<_> = info.releases.keys().collect();
+        versions.sort_by(
+            |a, b| match (semver::Version::parse(a), semver::Version::parse(b)) {
+                (Ok(va), Ok(vb)) => vb.cmp(&va),
+                _ => b.cmp(a),
+            },
+        );
+
+        for version in versions.iter().take(20) {
+            let yanked = info
+                .releases
+                .get(*version)
+                .map(|r| r.iter().all(|ri| ri.yanked))
+                .unwrap_or(false);
+
+            if yanked {
+                println!("    {} {}", version, "(yanked)".red());
+            } else {
+                println!("    {}", version);
+            }
+        }
+
+        if versions.len() > 20 {
+            println!("    ... and {} more", versions.len() - 20);
+        }
+    }
+
+    if show_stats {
+        let stats = stats::StatsClient::with_config(&pypi_config, &http_config)?;
+        match stats.weekly_downloads(package).await {
+            Ok(weeks) => print_download_stats(&weeks, &stats::release_dates(&info.releases)),
+            Err(e) => warnings::warn(
+                WarningKind::Stats,
+                Some(package),
+                format!("download stats unavailable: {}", e),
+            ),
+        }
+    }
+
+    Ok(())
+}
+
+fn print_download_stats(
+    weeks: &[stats::WeeklyDownloads],
+    releases: &[(chrono::NaiveDate, String)],
+) {
+    let Some(last) = weeks.last() else {
+        return;
+    };
+
+    let trend = match stats::trend(weeks) {
+        Some(change) if change >= 0.0 => format!("▲ {:.0}%", change).green(),
+        Some(change) => format!("▼ {:.0}%", -change).red(),
+        None => "".normal(),
+    };
+    println!(
+        "  Downloads: {} in the week of {} {}",
+        last.downloads.to_string().green(),
+        last.week_start,
+        trend
+    );
+
+    // pypistats has no per-version counts; mark release weeks to show adoption instead
+    println!("\n  {}", "Weekly downloads (all versions):".cyan());
+    for week in weeks.iter().rev().take(8) {
+        let released = stats::released_in_week(releases, week.week_start);
+        let marker = if released.is_empty() {
+            String::new()
+        } else {
+            format!("  ← {} released", released.join(", "))
+        };
+        println!(
+            "    {}  {:>12}{}",
+            week.week_start,
+            week.downloads,
+            marker.dimmed()
+        );
+    }
+}
+
+/// `Homepage` project URL, or the legacy `home_page` field
+fn homepage(info: &PackageInfo) -> Option<&str> {
+    info.project_urls
+        .as_ref()
+        .and_then(|urls| urls.get("Homepage"))
+        .or(info.home_page.as_ref())
+        .map(String::as_str)
+        .filter(|url| !url.is_empty())
+}
+
+/// One line of `info --all`
+#[derive(serde::Serialize)]
+struct PackageReport {
+    package: String,
+    pinned: Option<String>,
+    latest: String,
+    summary: Option<String>,
+    homepage: Option<String>,
+    licenses: Vec<String>,
+}
+
+/// PyPI info of every tracked package, fetched concurrently
+async fn cmd_info_all(config_path: &str, json_output: bool, verbose: bool) -> Result<()> {
+    let config = load_config(config_path)?;
+    let pypi = PyPiClient::with_config(&config.pypi, &config.http)?;
+    let files = load_versions_files(&config).await?;
+
+    let progress = if !json_output {
+        create_progress_bar(config.packages.len(), "Fetching package info")
+    } else {
+        None
+    };
+    let on_checked = {
+        let progress = progress.clone();
+        move |package: &PackageConfig| {
+            if let Some(pb) = progress.as_ref() {
+                pb.inc(1);
+                if verbose {
+                    pb.println(format!("Fetched {}", package.name));
+                }
+            }
+        }
+    };
+
+    // A package missing from the index is reported, not fatal
+    let infos = pypi
+        .map_packages(
+            &config.packages,
+            config.pypi.effective_concurrency(),
+            |client, package| async move { Ok(client.get_package_info(&package.name).await) },
+            on_checked,
+        )
+        .await?;
+
+    if let Some(pb) = progress {
+        pb.finish_and_clear();
+    }
+
+    let mut reports = Vec::new();
+    for (package, info) in config.packages.iter().zip(infos) {
+        let info = match info {
+            Ok(info) => info.info,
+            Err(e) => {
+                warnings::warn(WarningKind::PyPi, Some(&package.name), e.to_string());
+                continue;
+            }
+        };
+        reports.push(PackageReport {
+            package: package.name.clone(),
+            pinned: package_pin(&files, package).map(str::to_string),
+            summary: info.summary.clone().filter(|s| !s.trim().is_empty()),
+            homepage: homepage(&info).map(str::to_string),
+            licenses: licenses::licenses_from_info(&info),
+            latest: info.version,
+        });
+    }
+
+    if json_output {
+        let report = serde_json::json!({
+            "packages": reports,
+            "warnings": warnings::take(),
+        });
+        println!("{}", serde_json::to_string_pretty(&report).unwrap());
+        return Ok(());
+    }
+
+    for report in &reports {
+        let pinned = match report.pinned.as_deref() {
+            Some(pinned) if pinned != report.latest => format!(" (pinned {})", pinned.yellow()),
+            Some(_) => String::new(),
+            None => format!(" {}", "(not pinned)".dimmed()),
+        };
+        println!(
+            "{} {}{}",
+            report.package.bold(),
+            report.latest.green(),
+            pinned
+        );
+        if let Some(summary) = &report.summary {
+            println!("  Summary: {}", summary);
+        }
+        if let Some(homepage) = &report.homepage {
+            println!("  Homepage: {}", homepage);
+        }
+        let license = if report.licenses.is_empty() {
+            "unknown".dimmed().to_string()
+        } else {
+            report.licenses.join(", ")
+        };
+        println!("  License: {}", license);
+    }
+
+    Ok(())
+}
+
+async fn cmd_licenses(
+    config_path: &str,
+    packages_filter: Option<String>,
+    json_output: bool,
+    check: bool,
+    verbose: bool,
+) -> Result<Vec<String>> {
+    let config = load_config(config_path)?;
+    let pypi = PyPiClient::with_config(&config.pypi, &config.http)?;
+    let buildout = load_versions(&config, &config.versions_file).await?;
+
+    if check && config.licenses.allowed.is_empty() {
+        return Err(ReleaserError::ConfigError(
+            "--check needs a [licenses] allowed list in the config".to_string(),
+        ));
+    }
+
+    let (packages, unmatched) = filter_packages(&config.packages, packages_filter.as_deref());
+    let pinned: Arc<HashMap<String, String>> = Arc::new(
+        packages
+            .iter()
+            .filter_map(|p| {
+                buildout
+                    .get_version(p.buildout_name())
+                    .map(|v| (p.name.clone(), v.to_string()))
+            })
+            .collect(),
+    );
+
+    let progress = if !json_output {
+        create_progress_bar(packages.len(), "Fetching licenses")
+    } else {
+        None
+    };
+    let on_checked = {
+        let progress = progress.clone();
+        move |package: &PackageConfig| {
+            if let Some(pb) = progress.as_ref() {
+                pb.inc(1);
+                if verbose {
+                    pb.println(format!("Fetched {}", package.name));
+                }
+            }
+        }
+    };
+
+    let infos = pypi
+        .map_packages(
+            &packages,
+            config.pypi.effective_concurrency(),
+            move |client, package| {
+                let version = pinned.get(&package.name).cloned();
+                async move {
+                    match version {
+                        Some(version) => client.get_release_info(&package.name, &version).await,
+                        None => client.get_package_info(&package.name).await,
+                    }
+                }
+            },
+            on_checked,
+        )
+        .await?;
+
+    if let Some(pb) = progress {
+        pb.finish_and_clear();
+    }
+
+    let reports: Vec<licenses::LicenseReport> = packages
+        .iter()
+        .zip(infos)
+        .map(|(package, info)| {
+            let found = licenses::licenses_from_info(&info.info);
+            let exempt = config
+                .licenses
+                .exceptions
+                .iter()
+                .any(|e| e.eq_ignore_ascii_case(&package.name));
+            let allowed = (!config.licenses.allowed.is_empty() && !exempt)
+                .then(|| licenses::is_allowed(&found, &config.licenses.allowed));
+
+            licenses::LicenseReport {
+                package: package.name.clone(),
+                version: info.info.version,
+                licenses: found,
+                allowed,
+            }
+        })
+        .collect();
+
+    if json_output {
+        let report = serde_json::json!({
+            "packages": reports,
+            "warnings": warnings::take(),
+        });
+        println!("{}", serde_json::to_string_pretty(&report).unwrap());
+    } else {
+        let name_width = reports.iter().map(|r| r.package.len()).max().unwrap_or(0);
+        for report in &reports {
+            let license = if report.licenses.is_empty() {
+                "unknown".dimmed().to_string()
+            } else {
+                report.licenses.join(", ")
+            };
+            let marker = match report.allowed {
+                Some(true) => "✓".green(),
+                Some(false) => "✗".red(),
+                None => " ".normal(),
+            };
+            println!(
+                "{} {:width$}  {}  {}",
+                marker,
+                report.package,
+                report.version.dimmed(),
+                license,
+                width = name_width
+            );
+        }
+    }
+
+    let rejected: Vec<&str> = reports
+        .iter()
+        .filter(|r| r.allowed == Some(false))
+        .map(|r| r.package.as_str())
+        .collect();
+
+    if check && !rejected.is_empty() {
+        return Err(ReleaserError::LicenseError(format!(
+            "{} packages have licenses outside the allowlist: {}",
+            rejected.len(),
+            rejected.join(", ")
+        )));
+    }
+
+    Ok(unmatched)
+}
+
+async fn cmd_bundle(
+    config_path: &str,
+    packages_filter: Option<String>,
+    output: Option<String>,
+    tar: bool,
+    verbose: bool,
+) -> Result<Vec<String>> {
+    let config = load_config(config_path)?;
+    let pypi = PyPiClient::with_config(&config.pypi, &config.http)?;
+    let buildout = load_versions(&config, &config.versions_file).await?;
+
+    let release = git_for(&config)
+        .get_version_tags(&config.github.tag_prefix)
+        .ok()
+        .and_then(|tags| tags.into_iter().next())
+        .map(|(tag, _)| tag);
+    let dir = std::path::PathBuf::from(output.unwrap_or_else(|| match release {
+        Some(ref tag) => format!("bundle-{}", tag),
+        None => "bundle".to_string(),
+    }));
+    let dir = config.resolve(dir);
+    std::fs::create_dir_all(&dir)?;
+
+    let (selected, unmatched) = filter_packages(&config.packages, packages_filter.as_deref());
+    let mut packages = Vec::new();
+    let mut pins = HashMap::new();
+    for package in selected {
+        match buildout.get_version(package.buildout_name()) {
+            Some(version) => {
+                pins.insert(
+                    package.name.clone(),
+                    (package.buildout_name().to_string(), version.to_string()),
+                );
+                packages.push(package);
+            }
+            None => warnings::warn(
+                WarningKind::Bundle,
+                Some(&package.name),
+                "not pinned in the versions file, skipped",
+            ),
+        }
+    }
+    let pins = Arc::new(pins);
+
+    let progress = create_progress_bar(packages.len(), "Downloading release files");
+    let on_checked = {
+        let progress = progress.clone();
+        move |package: &PackageConfig| {
+            if let Some(pb) = progress.as_ref() {
+                pb.inc(1);
+                if verbose {
+                    pb.println(format!("Downloaded {}", package.name));
+                }
+            }
+        }
+    };
+
+    let target = Arc::new(dir.clone());
+    let downloaded = pypi
+        .map_packages(
+            &packages,
+            config.pypi.effective_concurrency(),
+            move |client, package| {
+                let (buildout_name, version) = pins.get(&package.name).cloned().unwrap_or_default();
+                let target = target.clone();
+                async move {
+                    bundle::download_release(
+                        &client,
+                        &package.name,
+                        &buildout_name,
+                        &version,
+                        &target,
+                    )
+                    .await
+                }
+            },
+            on_checked,
+        )
+        .await?;
+
+    if let Some(pb) = progress {
+        pb.finish_with_message("Downloads complete");
+    }
+
+    let files: Vec<bundle::BundleFile> = downloaded.into_iter().flatten().collect();
+    let manifest = bundle::BundleManifest {
+        release,
+        created: chrono::Utc::now().to_rfc3339(),
+        files,
+    };
+    let manifest_path = bundle::write_manifest(&dir, &manifest)?;
+
+    println!(
+        "{} Bundled {} files for {} packages in {}",
+        "✓".green(),
+        manifest.files.len(),
+        packages.len(),
+        dir.display()
+    );
+    if verbose {
+        println!("  Manifest: {}", manifest_path.display());
+    }
+
+    if tar {
+        let archive = bundle::create_tarball(&dir)?;
+        println!("{} Wrote {}", "✓".green(), archive.display());
+    }
+
+    Ok(unmatched)
+}
+
+fn cmd_bundle_verify(
+    config_path: &str,
+    dir: &str,
+    versions_file: Option<String>,
+    json_output: bool,
+) -> Result<()> {
+    // The target host may only have the versions file, not a bldr.toml
+    let versions_file = match versions_file {
+        Some(path) => path,
+        None => read_config(config_path)?.versions_file,
+    };
+    let buildout = BuildoutVersions::load_in(&work_dir(), &versions_file)?;
+
+    let dir = work_dir().join(dir);
+    let manifest = bundle::load_manifest(&dir)?;
+    let drift = bundle::verify(&dir, &manifest, &buildout)?;
+
+    if json_output {
+        let report = serde_json::json!({
+            "release": manifest.release,
+            "files": manifest.files.len(),
+            "drift": drift,
+        });
+        println!("{}", serde_json::to_string_pretty(&report).unwrap());
+    } else if drift.is_empty() {
+        println!(
+            "{} {} files in {} match the manifest and {}",
+            "✓".green(),
+            manifest.files.len(),
+            dir.display(),
+            versions_file
+        );
+    } else {
+        for item in &drift {
+            println!("{} {}", "✗".red(), item);
+        }
+    }
+
+    if !drift.is_empty() {
+        return Err(ReleaserError::BundleError(format!(
+            "{} problems found in {}",
+            drift.len(),
+            dir.display()
+        )));
+    }
+
+    Ok(())
+}
+
+async fn cmd_diagnostics(
+    config_path: &str,
+    output: Option<String>,
+    offline: bool,
+    verbose: bool,
+) -> Result<()> {
+    use diagnostics::Severity;
+
+    let config = load_config(config_path)?;
+    let files = load_versions_files(&config).await?;
+    let paths = config.versions_files();
+
+    let mut found = Vec::new();
+    for (path, buildout) in paths.iter().zip(&files) {
+        found.extend(diagnostics::duplicates(path, buildout));
+    }
+
+    if !offline {
+        let pypi = PyPiClient::with_config(&config.pypi, &config.http)?;
+        let spinner = output
+            .is_some()
+            .then(|| create_spinner("Checking pins against PyPI and OSV..."));
+
+        // Outdated: tracked packages with a newer matching release
+        let latest_versions =
+            fetch_latest_versions(&config, &pypi, &config.packages, None, verbose).await?;
+        for (package, latest) in config.packages.iter().zip(&latest_versions) {
+            let Some(latest) = latest else {
+                continue;
+            };
+            for buildout in &files {
+                let name = package.buildout_name();
+                match buildout.get_version(name) {
+                    Some(pinned) if is_upgrade(pinned, &latest.version) => {
+                        found.extend(diagnostics::at_pin(
+                            buildout,
+                            name,
+                            Severity::Information,
+                            "outdated",
+                            format!(
+                                "{} {} is available (pinned {})",
+                                name, latest.version, pinned
+                            ),
+                        ));
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        // Yanked: every file of the pinned release of a tracked package was yanked
+        let tracked: Vec<PinnedPackage> = config
+            .packages
+            .iter()
+            .filter_map(|p| {
+                package_pin(&files, p).map(|version| PinnedPackage {
+                    name: p.buildout_name().to_string(),
+                    version: version.to_string(),
+                })
+            })
+            .collect();
+        let yanked = pypi
+            .map_packages(
+                &tracked,
+                config.pypi.effective_concurrency(),
+                |client, pin| async move {
+                    let files = client.release_files(&pin.name, &pin.version).await;
+                    Ok(files
+                        .map(|f| !f.is_empty() && f.iter().all(|r| r.yanked))
+                        .unwrap_or(false))
+                },
+                |_: &PinnedPackage| {},
+            )
+            .await?;
+        for (pin, _) in tracked.iter().zip(yanked).filter(|(_, yanked)| *yanked) {
+            for buildout in &files {
+                if buildout.get_version(&pin.name) == Some(pin.version.as_str()) {
+                    found.extend(diagnostics::at_pin(
+                        buildout,
+                        &pin.name,
+                        Severity::Warning,
+                        "yanked",
+                        format!("{} {} was yanked from PyPI", pin.name, pin.version),
+                    ));
+                }
+            }
+        }
+
+        // Vulnerable: any pin with a known advisory
+        let mut pins: Vec<PinnedPackage> = Vec::new();
+        for buildout in &files {
+            for (name, version) in buildout.get_all_versions() {
+                if !pins.iter().any(|p| p.name == name && p.version == version) {
+                    pins.push(PinnedPackage {
+                        name: name.to_string(),
+                        version: version.to_string(),
+                    });
+                }
+            }
+        }
+        let osv = audit::OsvClient::with_config(&config.audit, &config.http)?;
+        for finding in osv.audit(&pins, &config.audit.ignore).await? {
+            let ids: Vec<&str> = finding
+                .vulnerabilities
+                .iter()
+                .map(|v| v.id.as_str())
+                .collect();
+            for buildout in &files {
+                if buildout.get_version(&finding.package) == Some(finding.version.as_str()) {
+                    found.extend(diagnostics::at_pin(
+                        buildout,
+                        &finding.package,
+                        Severity::Error,
+                        "vulnerable",
+                        format!(
+                            "{} {} has known vulnerabilities: {}",
+                            finding.package,
+                            finding.version,
+                            ids.join(", ")
+                        ),
+                    ));
+                }
+            }
+        }
+
+        if let Some(spinner) = spinner {
+            spinner.finish_and_clear();
+        }
+    }
+
+    let report = diagnostics::DiagnosticsReport::new(found);
+    let content = serde_json::to_string_pretty(&report).unwrap();
+
+    match output {
+        Some(path) => {
+            std::fs::write(config.resolve(&path), content + "\n")?;
+            println!(
+                "{} Wrote {} diagnostics to {}",
+                "✓".green(),
+                report.diagnostics.len(),
+                path
+            );
+        }
+        None => println!("{}", content),
+    }
+
+    Ok(())
+}
+
+async fn cmd_export(
+    config_path: &str,
+    format: ExportFormat,
+    hashes: bool,
+    packages_filter: Option<String>,
+    output: Option<String>,
+) -> Result<Vec<String>> {
+    let config = load_config(config_path)?;
+    let buildout = load_versions(&config, &config.versions_file).await?;
+
+    let (pins, unmatched) = filter_pins(&buildout, packages_filter.as_deref());
+
+    let digests = if hashes {
+        let pypi = PyPiClient::with_config(&config.pypi, &config.http)?;
+        let progress = create_progress_bar(pins.len(), "Fetching release hashes");
+        let on_checked = {
+            let progress = progress.clone();
+            move |_: &PinnedPackage| {
+                if let Some(pb) = progress.as_ref() {
+                    pb.inc(1);
+                }
+            }
+        };
+
+        let digests = pypi
+            .map_packages(
+                &pins,
+                config.pypi.effective_concurrency(),
+                |client, pin| async move { export::fetch_hashes(&client, &pin).await },
+                on_checked,
+            )
+            .await?;
+
+        if let Some(pb) = progress {
+            pb.finish_and_clear();
+        }
+        digests
+    } else {
+        vec![Vec::new(); pins.len()]
+    };
+
+    let exported: Vec<export::ExportedPin> = pins
+        .into_iter()
+        .zip(digests)
+        .map(|(pin, hashes)| export::ExportedPin {
+            name: pin.name,
+            version: pin.version,
+            hashes,
+        })
+        .collect();
+
+    let content = match format {
+        ExportFormat::Constraints => export::render_constraints(&exported, &config.versions_file),
+        ExportFormat::Requirements => export::render_requirements(&exported, &config.versions_file),
+    };
+
+    match output {
+        Some(path) => {
+            std::fs::write(config.resolve(&path), content)?;
+            println!(
+                "{} Exported {} pins to {}",
+                "✓".green(),
+                exported.len(),
+                path
+            );
+        }
+        None => print!("{}", content),
+    }
+
+    Ok(unmatched)
+}
+
+async fn cmd_audit(
+    config_path: &str,
+    packages_filter: Option<String>,
+    json_output: bool,
+    extra_ignore: Vec<String>,
+) -> Result<Vec<String>> {
+    let config = load_config(config_path)?;
+    let buildout = load_versions(&config, &config.versions_file).await?;
+
+    let (pins, unmatched) = filter_pins(&buildout, packages_filter.as_deref());
+
+    let mut ignore = config.audit.ignore.clone();
+    ignore.extend(extra_ignore);
+
+    let spinner = if json_output {
+        None
+    } else {
+        Some(create_spinner(&format!(
+            "Auditing {} pinned packages...",
+            pins.len()
+        )))
+    };
+
+    let osv = audit::OsvClient::with_config(&config.audit, &config.http)?;
+    let findings = osv.audit(&pins, &ignore).await?;
+
+    if let Some(spinner) = spinner {
+        spinner.finish_and_clear();
+    }
+
+    let vulnerability_count: usize = findings.iter().map(|f| f.vulnerabilities.len()).sum();
+
+    if json_output {
+        let report = serde_json::json!({
+            "audited": pins.len(),
+            "findings": findings,
+            "warnings": warnings::take(),
+        });
+        println!("{}", serde_json::to_string_pretty(&report).unwrap());
+    } else if findings.is_empty() {
+        println!(
+            "{} No known vulnerabilities in {} pinned packages",
+            "✓".green(),
+            pins.len()
+        );
+    } else {
+        for finding in &findings {
+            println!(
+                "{} {}",
+                finding.package.yellow().bold(),
+                finding.version.dimmed()
+            );
+            for vuln in &finding.vulnerabilities {
+                let severity = vuln.severity.as_deref().unwrap_or("UNKNOWN");
+                println!("  {} [{}]", vuln.id.red(), severity);
+                if let Some(ref summary) = vuln.summary {
+                    println!("    {}", summary);
+                }
+                if !vuln.fixed_versions.is_empty() {
+                    println!("    Fixed in: {}", vuln.fixed_versions.join(", ").green());
+                }
+            }
+        }
+    }
+
+    if vulnerability_count > 0 {
+        return Err(ReleaserError::AuditError(format!(
+            "{} known vulnerabilities in {} packages",
+            vulnerability_count,
+            findings.len()
+        )));
+    }
+
+    Ok(unmatched)
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// Resolve version from tag or bump
+fn resolve_version(
+    config: &Config,
+    git: &GitOps,
+    tag: Option<String>,
+    bump: Option<String>,
+    verbose: bool,
+) -> Result<String> {
+    // Explicit tag takes precedence
+    if let Some(tag) = tag {
+        return Ok(tag);
+    }
+
+    // Bump from latest git tag
+    if let Some(level) = bump {
+        let version_manager = VersionManager::new(&config.version);
+        let bump_type = version_manager.get_bump_type(&level)?;
+
+        let current = git.get_latest_version(&config.github.tag_prefix)?;
+
+        let next = match current {
+            Some(version) => {
+                if verbose {
+                    println!(
+                        "Current version (from tag): {} → bumping {}",
+                        version, level
+                    );
+                }
+                version.bump(bump_type)
+            }
+            None => {
+                if verbose {
+                    println!("No existing version tags found, starting from 0.0.0");
+                }
+                // Start from 0.0.0 and bump
+                Version::new(0, 0, 0).bump(bump_type)
+            }
+        };
+
+        if verbose {
+            println!("Next version: {}", next);
+        }
+
+        return Ok(next.to_string());
+    }
+
+    Err(ReleaserError::ConfigError(
+        "Either --tag or --bump must be specified".to_string(),
+    ))
+}
+
+/// Whether `version` has a pre-release segment (`2.0.0rc1`, `2.0.0-beta.1`,
+/// `2.0.0.dev3`)
+fn is_prerelease(version: &str) -> bool {
+    Version::parse(version).is_ok_and(|version| version.prerelease().is_some())
+}
+
+/// How a GitHub release is described in the output
+fn release_label(draft: bool, prerelease: bool) -> &'static str {
+    match (draft, prerelease) {
+        (true, true) => " (draft prerelease)",
+        (true, false) => " (draft)",
+        (false, true) => " (prerelease)",
+        (false, false) => "",
+    }
+}
+
+fn create_progress_bar(len: usize, message: &str) -> Option<ProgressBar> {
+    if len == 0 {
+        return None;
+    }
+
+    let pb = ProgressBar::new(len as u64);
+    pb.set_style(
+        ProgressStyle::with_template(
+            " {msg}\n {spinner:.cyan} [{bar:40.cyan/blue}] {pos}/{len}",
+        )
+        .expect("progress template should be valid")
+        .progress_chars("=>-"),
+    );
+    pb.set_message(message.to_string());
+    pb.enable_steady_tick(Duration::from_millis(120));
+
+    Some(pb)
+}
+
+fn create_spinner(message: &str) -> ProgressBar {
+    let pb = ProgressBar::new_spinner();
+    pb.set_style(
+        ProgressStyle::with_template(" {spinner:.cyan} {msg}")
+            .expect("spinner template should be valid")
+            .tick_chars("⠁⠂⠄⡀⢀⠠⠐⠈ "),
+    );
+    pb.set_message(message.to_string());
+    pb.enable_steady_tick(Duration::from_millis(120));
+    pb
+}
+
+async fn fetch_latest_versions(
+    config: &Config,
+    pypi: &PyPiClient,
+    packages: &[PackageConfig],
+    progress: Option<ProgressBar>,
+    verbose: bool,
+) -> Result<Vec<Option<VersionInfo>>> {
+    if let Some(pb) = progress.as_ref() {
+        pb.set_message(format!("Checking {} package(s)...", packages.len()));
+    }
+
+    pypi.check_many(
+        packages,
+        config.pypi.effective_concurrency(),
+        move |package| {
+            if let Some(pb) = progress.as_ref() {
+                pb.inc(1);
+                if verbose {
+                    pb.println(format!("Checked {}", package.name));
+                }
+            } else if verbose {
+                println!("Checked {}", package.name);
+            }
+        },
+    )
+    .await
+}
+
+async fn perform_update(
+    config: &Config,
+    store: &PackageMetadataStore,
+    packages: Vec<PackageConfig>,
+    auto_confirm: bool,
+    dry_run: bool,
+    show_diff: bool,
+    verbose: bool,
+) -> Result<Vec<VersionUpdate>> {
+    let pypi = PyPiClient::with_config(&config.pypi, &config.http)?.with_store(store);
+    let mut files = load_versions_files(config).await?;
+    ensure_pins_in_sync(&files)?;
+    for file in &mut files {
+        file.set_annotate_updates(config.annotate_pins);
+        file.set_keep_backup(config.backup_pins);
+    }
+
+    let packages_to_check = skip_source_checkouts(config, &files, packages)?;
+
+    let mut available_updates = Vec::new();
+
+    println!("{}", "Checking for updates...".cyan());
+
+    let progress = create_progress_bar(packages_to_check.len(), "Checking packages");
+
+    let latest_versions =
+        fetch_latest_versions(config, &pypi, &packages_to_check, progress.clone(), verbose).await?;
+
+    for (pkg_config, latest) in packages_to_check.iter().zip(latest_versions) {
+        let current = package_pin(&files, pkg_config);
+
+        if let (Some(current_version), Some(latest)) = (current, latest) {
+            if is_upgrade(current_version, &latest.version) {
+                available_updates.push((
+                    pkg_config.buildout_name().to_string(),
+                    current_version.to_string(),
+                    latest.version,
+                ));
+            }
+        }
+    }
+
+    if let Some(pb) = progress {
+        pb.finish_with_message("Update check complete");
+    }
+
+    if available_updates.is_empty() {
+        println!("{}", "All packages are up to date!".green());
+        return Ok(Vec::new());
+    }
+
+    println!("\n{}", "Available updates:".yellow().bold());
+    for (name, current, latest) in &available_updates {
+        println!("  {} {} → {}", name, current.dimmed(), latest.green());
+    }
+
+    let selected_updates = if auto_confirm {
+        available_updates.clone()
+    } else {
+        let items: Vec<String> = available_updates
+            .iter()
+            .map(|(name, current, latest)| format!("{}: {} → {}", name, current, latest))
+            .collect();
+
+        let selections = MultiSelect::new()
+            .with_prompt("Select packages to update")
+            .items(&items)
+            .defaults(&vec![true; items.len()])
+            .interact()
+            .map_err(|e| ReleaserError::IoError(std::io::Error::other(e.to_string())))?;
+
+        selections
+            .iter()
+            .map(|&i| available_updates[i].clone())
+            .collect()
+    };
+
+    if selected_updates.is_empty() {
+        println!("No updates selected.");
+        return Ok(Vec::new());
+    }
+
+    let mut applied_updates = Vec::new();
+
+    for (name, _current, latest) in &selected_updates {
+        let section = packages_to_check
+            .iter()
+            .find(|p| p.buildout_name() == name)
+            .and_then(|p| p.section.as_deref());
+
+        // Apply to every file pinning the package; report the update once
+        let mut applied = None;
+        for buildout in files.iter_mut() {
+            if let Some(update) = buildout.update_version_in(name, latest, section)? {
+                applied.get_or_insert(update);
+            }
+        }
+
+        if let Some(update) = applied {
+            applied_updates.push(update);
+            if verbose {
+                println!("  {} Updated {} to {}", "✓".green(), name, latest);
+            }
+        }
+    }
+
+    report_dependency_conflicts(config, &pypi, &files[0], &applied_updates, verbose).await;
+
+    if show_diff || !auto_confirm {
+        let diff: String = files.iter().map(BuildoutVersions::unified_diff).collect();
+        println!();
+        print_diff(&diff);
+    }
+
+    if !auto_confirm && !dry_run {
+        let write = Confirm::new()
+            .with_prompt("Write these changes?")
+            .default(true)
+            .interact()
+            .map_err(|e| ReleaserError::IoError(std::io::Error::other(e.to_string())))?;
+        if !write {
+            println!("Aborted, no files were modified.");
+            return Ok(Vec::new());
+        }
+    }
+
+    if dry_run {
+        println!("\n{}", "Dry run - no files were modified.".yellow());
+        println!("Would update:");
+        for update in &applied_updates {
+            println!(
+                "  {} {} → {}",
+                update.package_name, update.old_version, update.new_version
+            );
+        }
+    } else {
+        for buildout in &files {
+            buildout.save()?;
+        }
+        println!(
+            "\n{} Updated {} package(s)",
+            "✓".green(),
+            applied_updates.len()
+        );
+    }
+
+    Ok(applied_updates)
+}
+
+/// Warn when updated packages require newer versions than are currently pinned
+async fn report_dependency_conflicts(
+    config: &Config,
+    pypi: &PyPiClient,
+    buildout: &BuildoutVersions,
+    updates: &[VersionUpdate],
+    verbose: bool,
+) {
+    let updated: Vec<PackageConfig> = updates
+        .iter()
+        .filter_map(|u| {
+            config
+                .packages
+                .iter()
+                .find(|p| p.buildout_name() == u.package_name)
+                .cloned()
+        })
+        .collect();
+    let new_versions: Arc<HashMap<String, String>> = Arc::new(
+        updates
+            .iter()
+            .map(|u| (u.package_name.clone(), u.new_version.clone()))
+            .collect(),
+    );
+
+    let infos = pypi
+        .map_packages(
+            &updated,
+            config.pypi.effective_concurrency(),
+            move |client, package| {
+                let version = new_versions
+                    .get(package.buildout_name())
+                    .cloned()
+                    .unwrap_or_default();
+                async move { client.get_release_info(&package.name, &version).await }
+            },
+            |_| {},
+        )
+        .await;
+
+    let infos = match infos {
+        Ok(infos) => infos,
+        Err(e) => {
+            warnings::warn(
+                WarningKind::Dependency,
+                None,
+                format!("could not check dependency requirements: {}", e),
+            );
+            return;
+        }
+    };
+
+    let pins: HashMap<String, (&str, &str)> = buildout
+        .get_all_versions()
+        .map(|(name, version)| (pypi::normalize_project_name(name), (name, version)))
+        .collect();
+
+    let mut conflicts = Vec::new();
+    for (package, info) in updated.iter().zip(infos) {
+        for requirement in info
+            .info
+            .requires_dist
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|line| pypi::parse_requirement(line))
+        {
+            let Some((pin_name, pinned)) =
+                pins.get(&pypi::normalize_project_name(&requirement.name))
+            else {
+                continue;
+            };
+
+            match version::python::satisfies(pinned, &requirement.specifier) {
+                Some(false) => conflicts.push((
+                    package.buildout_name().to_string(),
+                    info.info.version.clone(),
+                    pin_name.to_string(),
+                    pinned.to_string(),
+                    requirement.specifier,
+                )),
+                None if verbose => println!(
+                    "  Could not evaluate {} {} required by {}",
+                    requirement.name, requirement.specifier, package.name
+                ),
+                _ => {}
+            }
+        }
+    }
+
+    if conflicts.is_empty() {
+        return;
+    }
+
+    println!("\n{}", "Dependencies needing attention:".yellow().bold());
+    for (package, version, dependency, pinned, specifier) in conflicts {
+        println!(
+            "  {} {} requires {} {} (pinned: {})",
+            package,
+            version.green(),
+            dependency.yellow(),
+            specifier,
+            pinned.red()
+        );
+        warnings::warn(
+            WarningKind::Dependency,
+            Some(&dependency),
+            format!(
+                "{} {} requires {}, pinned {}",
+                package, version, specifier, pinned
+            ),
+        );
+    }
+}
+
+/// Generated release assets, removed when dropped, so also when the release fails
+struct AssetsDir(PathBuf);
+
+impl Drop for AssetsDir {
+    fn drop(&mut self) {
+        std::fs::remove_dir_all(&self.0).ok();
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn perform_release(
+    config: &Config,
+    tag: &str,
+    message: Option<&str>,
+    updates: &[VersionUpdate],
+    no_push: bool,
+    push_to: Option<&str>,
+    no_github: bool,
+    draft: bool,
+    force_retag: bool,
+    verbose: bool,
+) -> Result<()> {
+    let git = git_for(config);
+
+    if !git.is_repo() {
+        return Err(ReleaserError::GitError(
+            "Not in a git repository".to_string(),
+        ));
+    }
+
+    let full_tag = format!("{}{}", config.github.tag_prefix, tag);
+    let default_message = format!("Release {}", tag);
+    let release_message = message.unwrap_or(&default_message);
+    let annotation = config.tag_message(tag, &current_date(), release_message);
+
+    if verbose {
+        println!("Creating tag: {}", full_tag);
+    }
+
+    let tag_message = if config.github.lightweight_tags {
+        if config.github.tag_metadata {
+            warnings::warn(
+                WarningKind::Git,
+                Some(&full_tag),
+                "github.tag_metadata needs annotated tags; lightweight tag created without metadata",
+            );
+        }
+        None
+    } else if config.github.tag_metadata {
+        let metadata = tag_metadata(config, &git, tag);
+        Some(format!(
+            "{}\n\n{}",
+            annotation.trim_end(),
+            metadata.to_trailer()
+        ))
+    } else {
+        Some(annotation)
+    };
+
+    let hook_context = HookContext {
+        version: Some(tag),
+        tag: Some(full_tag.clone()),
+        updates,
+    };
+    hooks::run(config, Stage::PreTag, &hook_context)?;
+
+    // Gathered before tagging, so a missing file stops the release early
+    let assets_dir =
+        AssetsDir(std::env::temp_dir().join(format!("bldr-assets-{}", std::process::id())));
+    let assets = if !no_github && config.github.create_release {
+        let constraints = if config.github.asset_constraints {
+            let buildout = load_versions(config, &config.versions_file).await?;
+            let pins: Vec<export::ExportedPin> = filter_pins(&buildout, None)
+                .0
+                .into_iter()
+                .map(|pin| export::ExportedPin {
+                    name: pin.name,
+                    version: pin.version,
+                    hashes: Vec::new(),
+                })
+                .collect();
+            Some(export::render_constraints(&pins, &config.versions_file))
+        } else {
+            None
+        };
+        github::release_assets(
+            &config.work_dir,
+            &config.github,
+            constraints.as_deref(),
+            &assets_dir.0,
+        )?
+    } else {
+        Vec::new()
+    };
+
+    if force_retag {
+        if !git.tags(Some(&full_tag))?.is_empty() {
+            git.delete_tag(&full_tag)?;
+        }
+        if !no_push && git.remote_has_tag(&full_tag).unwrap_or(false) {
+            git.delete_remote_tag(&full_tag)?;
+            println!(
+                "{} Deleted the previous {} on {}",
+                "✓".green(),
+                full_tag,
+                config.git.remote
+            );
+        }
+    }
+    git.tag(&full_tag, tag_message.as_deref())?;
+    println!("{} Created tag: {}", "✓".green(), full_tag);
+
+    if !no_push {
+        if verbose {
+            println!("Pushing to remote...");
+        }
+        let target = push_target(config, push_to);
+        if target.is_none() && git.current_branch()?.is_none() {
+            warnings::warn(
+                WarningKind::Git,
+                Some(&full_tag),
+                "HEAD is detached; pushed tags only (use --push-to <branch> to push the commit to a branch)",
+            );
+        }
+        git.push(target, true)?;
+        println!("{} Pushed to remote", "✓".green());
+    }
+
+    if !no_github && config.github.create_release {
+        if let Some(github) = GitHub::connect(config, &git)? {
+            if verbose {
+                println!("Creating GitHub release...");
+            }
+
+            let notes = fit_release_notes(config, &full_tag, release_message);
+            // rc, beta and dev versions don't become the latest release
+            let prerelease = is_prerelease(tag);
+
+            // A moved tag replaces the release made for the old one
+            if force_retag && github.delete_release(&full_tag).await? {
+                println!("{} Deleted the previous GitHub release", "✓".green());
+            }
+            github
+                .create_release(
+                    &full_tag,
+                    Some(&config.release_title(tag, &current_date(), updates.len())),
+                    Some(&notes),
+                    draft,
+                    prerelease,
+                    &assets,
+                )
+                .await?;
+
+            println!(
+                "{} Created GitHub release{}",
+                "✓".green(),
+                release_label(draft, prerelease)
+            );
+            for asset in &assets {
+                if let Some(name) = asset.file_name() {
+                    println!("  {} {}", "↑".cyan(), name.to_string_lossy());
+                }
+            }
+        } else {
+            warnings::warn(
+                WarningKind::GitHub,
+                None,
+                format!("{}; skipped GitHub release", GitHub::unavailable_reason()),
+            );
+        }
+    }
+
+    hooks::run(config, Stage::PostRelease, &hook_context)?;
+
+    Ok(())
+}
+
+/// `notes` cut to `changelog.max_release_notes_chars`, pointing to the
+/// changelog file as of `full_tag`, which holds what GitHub can't
+fn fit_release_notes(config: &Config, full_tag: &str, notes: &str) -> String {
+    let full_changelog = config
+        .github
+        .repository
+        .as_deref()
+        .zip(config.changelog.output_file.as_deref())
+        .map(|(repo, file)| format!("https://github.com/{}/blob/{}/{}", repo, full_tag, file));
+    let fitted = changelog::truncate_release_notes(
+        notes,
+        config.changelog.max_release_notes_chars,
+        full_changelog.as_deref(),
+    );
+    if fitted != notes {
+        warnings::warn(
+            WarningKind::GitHub,
+            None,
+            format!(
+                "release notes cut to {} characters (changelog.max_release_notes_chars)",
+                config.changelog.max_release_notes_chars
+            ),
+        );
+    }
+    fitted
+}
+
+/// Refuse a release whose tag already exists locally or, when `check_remote`,
+/// on the remote, before any file is written or commit made. With
+/// `force_retag` the tag is moved instead, once the release is tagged.
+fn ensure_tag_available(
+    config: &Config,
+    git: &GitOps,
+    version: &str,
+    check_remote: bool,
+    force_retag: bool,
+) -> Result<()> {
+    let full_tag = format!("{}{}", config.github.tag_prefix, version);
+    let mut found = Vec::new();
+    if !git.tags(Some(&full_tag))?.is_empty() {
+        found.push("locally".to_string());
+    }
+    if check_remote {
+        match git.remote_has_tag(&full_tag) {
+            Ok(true) => found.push(format!("on {}", config.git.remote)),
+            Ok(false) => {}
+            Err(e) => warnings::warn(
+                WarningKind::Git,
+                Some(&full_tag),
+                format!("could not look for the tag on {}: {}", config.git.remote, e),
+            ),
+        }
+    }
+    if found.is_empty() {
+        return Ok(());
+    }
+
+    let location = found.join(" and ");
+    if force_retag {
+        warnings::warn(
+            WarningKind::Git,
+            Some(&full_tag),
+            format!("tag exists {}; moving it to this release", location),
+        );
+        return Ok(());
+    }
+    Err(ReleaserError::GitError(format!(
+        "Tag {} already exists {}. Release another version (--tag/--bump), remove it with `bldr rollback --tag {}`, or pass --force-retag to move it",
+        full_tag, location, full_tag
+    )))
+}
+
+/// Undo a release: revert or drop the release commit, then delete its GitHub
+/// release and tags, so a failing revert or reset leaves the release intact.
+/// With `restore_backup`, the `.bak` copies of the versions files are put back
+/// last. Each step is listed first and confirmed unless `yes`.
+#[allow(clippy::too_many_arguments)]
+async fn cmd_rollback(
+    config_path: &str,
+    tag: Option<&str>,
+    commit: Option<RollbackCommit>,
+    local_only: bool,
+    yes: bool,
+    restore_backup: bool,
+    dry_run: bool,
+    non_interactive: bool,
+) -> Result<()> {
+    let config = load_config(config_path)?;
+    let git = git_for(&config);
+
+    if !git.is_repo() {
+        return Err(ReleaserError::GitError(
+            "Not in a git repository".to_string(),
+        ));
+    }
+
+    let tag_commits = git.tag_commits()?;
+    let tag = match tag {
+        // Accept the version as well as the full tag
+        Some(tag) if tag_commits.contains_key(tag) => tag.to_string(),
+        Some(tag) => format!("{}{}", config.github.tag_prefix, tag),
+        None => git
+            .get_version_tags(&config.github.tag_prefix)?
+            .into_iter()
+            .next()
+            .map(|(tag, _)| tag)
+            .ok_or_else(|| ReleaserError::GitError("No release tag to roll back".to_string()))?,
+    };
+    let Some(release_commit) = tag_commits.get(&tag) else {
+        return Err(ReleaserError::GitError(format!("No tag '{}'", tag)));
+    };
+
+    let at_head = git.head_commit()? == *release_commit;
+    let pushed = git.is_pushed(release_commit)?;
+    let remote_tag = !local_only && git.remote_has_tag(&tag)?;
+    // Without a release commit the tag sits on one of the user's commits, which stays
+    let bldr_commit = is_bldr_commit(&config, &git, release_commit)?;
+    // The first commit of the history (a `--first` release) has no parent to reset to
+    let root = git.is_root_commit(release_commit)?;
+    let commit = commit.unwrap_or(match (bldr_commit, at_head, pushed) {
+        (true, true, false) if root => RollbackCommit::Revert,
+        (true, true, false) => RollbackCommit::Reset,
+        (true, true, true) => RollbackCommit::Revert,
+        _ => RollbackCommit::Keep,
+    });
+    if commit == RollbackCommit::Reset {
+        if root {
+            return Err(ReleaserError::GitError(format!(
+                "The commit of {} is the first commit of the repository and cannot be dropped; revert it instead (--commit revert)",
+                tag
+            )));
+        }
+        if pushed {
+            return Err(ReleaserError::GitError(format!(
+                "The commit of {} is on the remote; revert it instead (--commit revert)",
+                tag
+            )));
+        }
+        if !at_head {
+            return Err(ReleaserError::GitError(format!(
+                "Commits were made after {}; revert its commit instead (--commit revert)",
+                tag
+            )));
+        }
+    }
+
+    let subject = git.commit_subject(release_commit)?;
+    let short = &release_commit[..release_commit.len().min(8)];
+    println!(
+        "Rolling back {} ({} {})",
+        tag.yellow(),
+        short,
+        subject.dimmed()
+    );
+    let push_revert = commit == RollbackCommit::Revert && pushed && !local_only;
+    match commit {
+        RollbackCommit::Revert if root => println!(
+            "  • Revert the release commit (the first commit of the repository can't be dropped)"
+        ),
+        RollbackCommit::Revert => println!("  • Revert the release commit"),
+        RollbackCommit::Reset => println!("  • Drop the release commit (git reset --keep)"),
+        RollbackCommit::Keep if !bldr_commit => {
+            println!("  • Keep the tagged commit (it changes files bldr doesn't commit)")
+        }
+        RollbackCommit::Keep => println!("  • Keep the release commit"),
+    }
+    if !local_only && config.github.create_release {
+        println!("  • Delete the GitHub release of {}", tag);
+    }
+    if remote_tag {
+        println!("  • Delete {} on {}", tag, config.git.remote);
+    }
+    println!("  • Delete the local tag {}", tag);
+    if push_revert {
+        println!("  • Push the revert");
+    }
+    let backups: Vec<String> = if restore_backup {
+        versions_file_paths(&config)?
+            .into_iter()
+            .filter(|path| buildout::backup_path(&config.resolve(path)).is_file())
+            .collect()
+    } else {
+        Vec::new()
+    };
+    for path in &backups {
+        println!("  • Restore {} from {}.bak", path, path);
+    }
+    if restore_backup && backups.is_empty() {
+        println!("  • No versions file backup to restore");
+    }
+
+    if dry_run {
+        println!("{}", "Dry run: nothing was changed.".yellow());
+        return Ok(());
+    }
+
+    if !yes {
+        if non_interactive {
+            return Err(ReleaserError::GitError(
+                "Rolling back a release needs --yes when running non-interactively".to_string(),
+            ));
+        }
+        let proceed = Confirm::new()
+            .with_prompt(format!("Roll back {}?", tag))
+            .default(false)
+            .interact()
+            .map_err(|e| ReleaserError::IoError(std::io::Error::other(e.to_string())))?;
+        if !proceed {
+            println!("Aborted.");
+            return Ok(());
+        }
+    }
+
+    match commit {
+        RollbackCommit::Revert => {
+            git.revert(release_commit)?;
+            println!("{} Reverted {}", "✓".green(), subject);
+        }
+        RollbackCommit::Reset => {
+            git.reset_keep(&format!("{}~1", release_commit))?;
+            println!("{} Dropped {}", "✓".green(), subject);
+        }
+        RollbackCommit::Keep => {}
+    }
+
+    if !local_only && config.github.create_release {
+        match GitHub::connect(&config, &git)? {
+            None => warnings::warn(
+                WarningKind::GitHub,
+                Some(&tag),
+                format!(
+                    "{}; delete the GitHub release by hand",
+                    GitHub::unavailable_reason()
+                ),
+            ),
+            Some(github) if github.delete_release(&tag).await? => {
+                println!("{} Deleted the GitHub release", "✓".green())
+            }
+            Some(_) => println!("{} No GitHub release for {}", "ℹ".cyan(), tag),
+        }
+    }
+    if remote_tag {
+        git.delete_remote_tag(&tag)?;
+        println!("{} Deleted {} on {}", "✓".green(), tag, config.git.remote);
+    }
+    git.delete_tag(&tag)?;
+    println!("{} Deleted the local tag {}", "✓".green(), tag);
+
+    if push_revert {
+        git.push(push_target(&config, None), false)?;
+        println!("{} Pushed the revert", "✓".green());
+    }
 
-    config.save(config_path)?;
-    println!("{} Added package: {}", "✓".green(), package);
+    for path in &backups {
+        if buildout::restore_backup(&config.resolve(path))? {
+            println!("{} Restored {} from {}.bak", "✓".green(), path, path);
+        }
+    }
 
     Ok(())
 }
 
-fn cmd_remove(config_path: &str, package: &str) -> Result<()> {
-    let mut config = Config::load(config_path)?;
+/// Whether the commit only changes files bldr commits (versions files, the
+/// changelog and metadata files), i.e. it is a release or update commit
+fn is_bldr_commit(config: &Config, git: &GitOps, commit: &str) -> Result<bool> {
+    let mut files = versions_file_paths(config)?;
+    files.extend(release_commit_files(config, true));
+    git.commit_only_touches(commit, &files)
+}
 
-    let initial_len = config.packages.len();
-    config.packages.retain(|p| p.name != package);
+/// Branch to push HEAD to: an explicit `--push-to`, else `git.branch`
+fn push_target<'a>(config: &'a Config, push_to: Option<&'a str>) -> Option<&'a str> {
+    push_to.or(config.git.branch.as_deref())
+}
 
-    if config.packages.len() == initial_len {
-        return Err(ReleaserError::ConfigError(format!(
-            "Package '{}' not found in configuration",
-            package
-        )));
+/// Refuse to commit from a branch other than `git.branch` (or switch to it with
+/// `git.switch_branch`). A detached HEAD is accepted, as its commit is pushed to `git.branch`.
+/// Without `git.branch`, committing outside the remote's default branch is only warned about.
+fn ensure_release_branch(config: &Config, git: &GitOps) -> Result<()> {
+    let Some(branch) = config.git.branch.as_deref() else {
+        let current = git.current_branch().ok().flatten();
+        if let (Some(current), Some(default)) = (current, git.default_branch()) {
+            if current != default {
+                warnings::warn(
+                    WarningKind::Git,
+                    Some(&current),
+                    format!(
+                        "not the default branch '{}' of {}; set git.branch to refuse commits elsewhere",
+                        default, config.git.remote
+                    ),
+                );
+            }
+        }
+        return Ok(());
+    };
+    let Some(current) = git.current_branch()? else {
+        return Ok(());
+    };
+    if current == branch {
+        return Ok(());
     }
 
-    config.save(config_path)?;
-    println!("{} Removed package: {}", "✓".green(), package);
+    if !config.git.switch_branch {
+        return Err(ReleaserError::GitError(format!(
+            "On branch '{}', but releases are made from '{}' (git.branch); switch branches or set git.switch_branch",
+            current, branch
+        )));
+    }
 
+    git.checkout(branch)?;
+    println!("{} Switched from {} to {}", "✓".green(), current, branch);
     Ok(())
 }
 
-async fn cmd_list(config_path: &str, detailed: bool) -> Result<()> {
-    let config = Config::load(config_path)?;
-    let buildout = BuildoutVersions::load(&config.versions_file).ok();
+/// Branch a `--pr` run commits to instead of the release branch
+struct PullRequestBranch {
+    name: String,
+    /// Branch the pull request is opened against
+    base: String,
+    /// Branch checked out when the run started, switched back to once the
+    /// pull request is open
+    previous: Option<String>,
+}
 
-    if config.packages.is_empty() {
-        println!("No packages configured.");
-        return Ok(());
+impl PullRequestBranch {
+    /// `<git.pr_branch_prefix><suffix>`, to be merged into `git.branch` (or
+    /// the current branch, or the default branch of a detached HEAD)
+    fn plan(config: &Config, git: &GitOps, suffix: &str) -> Result<Self> {
+        let previous = git.current_branch()?;
+        let base = config
+            .git
+            .branch
+            .clone()
+            .or_else(|| previous.clone())
+            .or_else(|| git.default_branch())
+            .ok_or_else(|| {
+                ReleaserError::GitError(
+                    "HEAD is detached; set git.branch to choose the base of the pull request"
+                        .to_string(),
+                )
+            })?;
+        Ok(Self {
+            name: format!("{}{}", config.git.pr_branch_prefix, suffix),
+            base,
+            previous,
+        })
     }
 
-    println!("{}", "Tracked packages:".cyan().bold());
+    /// Switch to the new branch, taking the uncommitted changes along
+    fn start(&self, git: &GitOps) -> Result<()> {
+        git.create_branch(&self.name)?;
+        println!("{} Created branch {}", "✓".green(), self.name);
+        Ok(())
+    }
 
-    for pkg in &config.packages {
-        let current_version = buildout
-            .as_ref()
-            .and_then(|b| b.get_version(pkg.buildout_name()))
-            .unwrap_or("not set");
+    /// Push the branch, open the pull request and switch back to the branch
+    /// the run started on
+    async fn open(
+        &self,
+        config: &Config,
+        git: &GitOps,
+        github: &GitHub,
+        title: &str,
+        body: &str,
+        draft: bool,
+    ) -> Result<String> {
+        git.push(Some(&self.name), false)?;
+        println!(
+            "{} Pushed {} to {}",
+            "✓".green(),
+            self.name,
+            config.git.remote
+        );
 
-        if detailed {
-            println!("\n  {}", pkg.name.yellow().bold());
-            println!("    Current version: {}", current_version);
-            if let Some(ref constraint) = pkg.version_constraint {
-                println!("    Constraint: {}", constraint);
-            }
-            if let Some(ref bn) = pkg.buildout_name {
-                println!("    Buildout name: {}", bn);
-            }
-            if pkg.allow_prerelease {
-                println!("    Pre-releases: allowed");
-            }
-            if let Some(ref url) = pkg.changelog_url {
-                println!("    Changelog URL: {}", url);
-            }
-        } else {
-            let constraint_str = pkg
-                .version_constraint
-                .as_ref()
-                .map(|c| format!(" ({})", c))
-                .unwrap_or_default();
+        let body =
+            changelog::truncate_release_notes(body, config.changelog.max_release_notes_chars, None);
+        let url = github
+            .create_pull_request(&self.name, &self.base, title, &body, draft)
+            .await?;
+        println!(
+            "{} Opened pull request into {}: {}",
+            "✓".green(),
+            self.base,
+            url
+        );
 
-            println!(
-                "  {} = {}{}",
-                pkg.buildout_name(),
-                current_version,
-                constraint_str.dimmed()
-            );
+        if let Some(previous) = &self.previous {
+            git.checkout(previous)?;
+            println!("{} Switched back to {}", "✓".green(), previous);
         }
+        Ok(url)
     }
-
-    Ok(())
 }
 
-async fn cmd_info(package: &str, show_versions: bool) -> Result<()> {
-    let pypi = PyPiClient::new()?;
-    let info = pypi.get_package_info(package).await?;
-
-    println!("{}", info.info.name.yellow().bold());
-    println!("  Latest version: {}", info.info.version.green());
+/// GitHub access for a command that can't go on without it; `purpose` says
+/// what needs it
+fn require_github(config: &Config, git: &GitOps, purpose: &str) -> Result<GitHub> {
+    GitHub::connect(config, git)?.ok_or_else(|| {
+        ReleaserError::ConfigError(format!("{}, but {}", purpose, GitHub::unavailable_reason()))
+    })
+}
 
-    if let Some(ref summary) = info.info.summary {
-        println!("  Summary: {}", summary);
+/// Refuse to release from a checkout behind the branch it pushes to (or rebase
+/// onto it with `git.auto_rebase`): the push would only be rejected after the
+/// release commit and tag are made
+fn ensure_up_to_date(config: &Config, git: &GitOps, push_to: Option<&str>) -> Result<()> {
+    if !config.git.check_remote {
+        return Ok(());
     }
-
-    if let Some(ref urls) = info.info.project_urls {
-        if let Some(homepage) = urls.get("Homepage").or(info.info.home_page.as_ref()) {
-            println!("  Homepage: {}", homepage);
-        }
+    let branch = match push_target(config, push_to) {
+        Some(branch) => branch.to_string(),
+        None => match git.current_branch()? {
+            Some(branch) => branch,
+            None => return Ok(()),
+        },
+    };
+    let Some(behind) = git.commits_behind(&branch)? else {
+        return Ok(());
+    };
+    if behind == 0 {
+        return Ok(());
     }
 
-    if show_versions {
-        println!("\n  {}", "Available versions:".cyan());
+    let upstream = format!("{}/{}", config.git.remote, branch);
+    if !config.git.auto_rebase {
+        return Err(ReleaserError::GitError(format!(
+            "The checkout is {} commit(s) behind {}; pull them first or set git.auto_rebase",
+            behind, upstream
+        )));
+    }
 
-        let mut versions: Vec<_> = info.releases.keys().collect();
-        versions.sort_by(
-            |a, b| match (semver::Version::parse(a), semver::Version::parse(b)) {
-                (Ok(va), Ok(vb)) => vb.cmp(&va),
-                _ => b.cmp(a),
-            },
-        );
+    git.pull_rebase(&branch)?;
+    println!(
+        "{} Rebased onto {} ({} new commit(s))",
+        "✓".green(),
+        upstream,
+        behind
+    );
+    Ok(())
+}
 
-        for version in versions.iter().take(20) {
-            let yanked = info
-                .releases
-                .get(*version)
-                .map(|r| r.iter().all(|ri| ri.yanked))
-                .unwrap_or(false);
+/// Enforce required-changelog mode: fail (or only warn with `allow_missing`) when the
+/// share of updated packages without entries exceeds `max_missing_fraction`
+fn check_changelog_completeness(
+    changelogs: &[PackageChangelog],
+    max_missing_fraction: f64,
+    allow_missing: bool,
+) -> Result<()> {
+    let missing: Vec<&str> = changelogs
+        .iter()
+        .filter(|c| c.entries.is_empty())
+        .map(|c| c.package_name.as_str())
+        .collect();
 
-            if yanked {
-                println!("    {} {}", version, "(yanked)".red());
-            } else {
-                println!("    {}", version);
-            }
-        }
+    if missing.is_empty() {
+        return Ok(());
+    }
 
-        if versions.len() > 20 {
-            println!("    ... and {} more", versions.len() - 20);
-        }
+    let fraction = missing.len() as f64 / changelogs.len() as f64;
+    if fraction <= max_missing_fraction {
+        return Ok(());
     }
 
-    Ok(())
-}
+    let message = format!(
+        "{}/{} updated packages have no changelog entries ({})",
+        missing.len(),
+        changelogs.len(),
+        missing.join(", ")
+    );
 
-// ============================================================================
-// Helper Functions
-// ============================================================================
+    if allow_missing {
+        warnings::warn(WarningKind::Changelog, None, message);
+        Ok(())
+    } else {
+        Err(ReleaserError::ChangelogIncomplete(format!(
+            "{}; rerun with --allow-missing to continue anyway",
+            message
+        )))
+    }
+}
 
-/// Resolve version from tag or bump
-fn resolve_version(
-    config: &Config,
-    git: &GitOps,
-    tag: Option<String>,
-    bump: Option<String>,
-    verbose: bool,
-) -> Result<String> {
-    // Explicit tag takes precedence
-    if let Some(tag) = tag {
-        return Ok(tag);
+/// Pins added or removed in the versions file since the latest version tag
+fn pin_changes_since_latest_tag(config: &Config, git: &GitOps, filter: Option<&str>) -> PinDiff {
+    let mut diff = PinDiff::default();
+    for (previous, current) in snapshots_since_latest_tag(config, git) {
+        diff.merge(current.diff_pins(&previous));
     }
 
-    // Bump from latest git tag
-    if let Some(level) = bump {
-        let version_manager = VersionManager::new(&config.version);
-        let bump_type = version_manager.get_bump_type(&level)?;
+    diff.retain(|name| include_pin_change(config, filter, name));
+    diff
+}
 
-        let current = git.get_latest_version(&config.github.tag_prefix)?;
+/// Version changes of tracked packages since the latest release tag
+/// Summary of every pin change since the latest tag, embedded in the new tag
+fn tag_metadata(config: &Config, git: &GitOps, version: &str) -> TagMetadata {
+    let previous = git
+        .get_version_tags(&config.github.tag_prefix)
+        .ok()
+        .and_then(|tags| tags.into_iter().next())
+        .map(|(tag, _)| tag);
+
+    let mut updated: Vec<VersionUpdate> = Vec::new();
+    let mut diff = PinDiff::default();
+    for (before, after) in snapshots_since_latest_tag(config, git) {
+        for update in after.changed_pins(&before) {
+            if !updated
+                .iter()
+                .any(|u| u.package_name == update.package_name)
+            {
+                updated.push(update);
+            }
+        }
+        diff.merge(after.diff_pins(&before));
+    }
+    updated.sort_by_key(|update| update.package_name.to_lowercase());
+
+    TagMetadata {
+        version: version.to_string(),
+        date: current_date(),
+        previous,
+        updated,
+        added: diff.added,
+        removed: diff.removed,
+    }
+}
 
-        let next = match current {
-            Some(version) => {
-                if verbose {
-                    println!(
-                        "Current version (from tag): {} → bumping {}",
-                        version, level
-                    );
-                }
-                version.bump(bump_type)
+fn updates_since_latest_tag(config: &Config, git: &GitOps) -> Vec<VersionUpdate> {
+    let mut updates: Vec<VersionUpdate> = Vec::new();
+    for (previous, current) in snapshots_since_latest_tag(config, git) {
+        for package in &config.packages {
+            let name = package.buildout_name();
+            if updates.iter().any(|u| u.package_name == name) {
+                continue;
             }
-            None => {
-                if verbose {
-                    println!("No existing version tags found, starting from 0.0.0");
+            if let (Some(old), Some(new)) = (previous.get_version(name), current.get_version(name))
+            {
+                if old != new {
+                    updates.push(VersionUpdate {
+                        package_name: name.to_string(),
+                        old_version: old.to_string(),
+                        new_version: new.to_string(),
+                    });
                 }
-                // Start from 0.0.0 and bump
-                Version::new(0, 0, 0).bump(bump_type)
             }
-        };
-
-        if verbose {
-            println!("Next version: {}", next);
         }
-
-        return Ok(next.to_string());
     }
+    updates
+}
 
-    Err(ReleaserError::ConfigError(
-        "Either --tag or --bump must be specified".to_string(),
-    ))
+/// Authors of our commits since the latest release tag (all of them before
+/// the first release)
+fn authors_since_latest_tag(config: &Config) -> Vec<String> {
+    let git = git_for(config);
+    let latest_tag = match git.get_version_tags(&config.github.tag_prefix) {
+        Ok(tags) => tags.into_iter().next().map(|(tag, _)| tag),
+        Err(_) => None,
+    };
+    git.authors_since(latest_tag.as_deref()).unwrap_or_default()
 }
 
-fn create_progress_bar(len: usize, message: &str) -> Option<ProgressBar> {
-    if len == 0 {
-        return None;
-    }
+/// Each versions file at the latest release tag and in the working tree;
+/// empty before the first release
+fn snapshots_since_latest_tag(
+    config: &Config,
+    git: &GitOps,
+) -> Vec<(BuildoutVersions, BuildoutVersions)> {
+    let latest_tag = match git.get_version_tags(&config.github.tag_prefix) {
+        Ok(tags) => tags.into_iter().next().map(|(tag, _)| tag),
+        Err(_) => None,
+    };
 
-    let pb = ProgressBar::new(len as u64);
-    pb.set_style(
-        ProgressStyle::with_template(
-            " {msg}\n {spinner:.cyan} [{bar:40.cyan/blue}] {pos}/{len}",
-        )
-        .expect("progress template should be valid")
-        .progress_chars("=>-"),
-    );
-    pb.set_message(message.to_string());
-    pb.enable_steady_tick(Duration::from_millis(120));
+    let Some(tag) = latest_tag else {
+        return Vec::new();
+    };
 
-    Some(pb)
+    config
+        .versions_files()
+        .into_iter()
+        .filter_map(|versions_file| {
+            let previous = git
+                .show_file_at_ref(&tag, versions_file)
+                .and_then(|content| {
+                    BuildoutVersions::from_content(content, format!("{}@{}", versions_file, tag))
+                });
+            let current = BuildoutVersions::load_in(&config.work_dir, versions_file);
+            Some((previous.ok()?, current.ok()?))
+        })
+        .collect()
 }
 
-fn create_spinner(message: &str) -> ProgressBar {
-    let pb = ProgressBar::new_spinner();
-    pb.set_style(
-        ProgressStyle::with_template(" {spinner:.cyan} {msg}")
-            .expect("spinner template should be valid")
-            .tick_chars("⠁⠂⠄⡀⢀⠠⠐⠈ "),
-    );
-    pb.set_message(message.to_string());
-    pb.enable_steady_tick(Duration::from_millis(120));
-    pb
+/// Changelog collector reusing the raw changelogs cached under ~/.cache/bldr
+fn changelog_collector(config: &Config, refresh: bool) -> Result<ChangelogCollector> {
+    let collector = ChangelogCollector::with_config(&config.changelog, &config.http)?;
+    // Fixtures must capture full responses, not revalidations against a local cache
+    Ok(match ChangelogCache::user() {
+        Some(cache) if !fixtures::active() => collector.with_cache(cache.refresh(refresh)),
+        _ => collector,
+    })
 }
 
-fn pypi_concurrency_limit() -> usize {
-    std::thread::available_parallelism()
-        .map(|count| (count.get() * 4).clamp(4, 32))
-        .unwrap_or(8)
+/// Load the main versions file followed by every linked one
+async fn load_versions_files(config: &Config) -> Result<Vec<BuildoutVersions>> {
+    let mut files = Vec::new();
+    for path in config.versions_files() {
+        files.push(load_versions(config, path).await?);
+    }
+    Ok(files)
 }
 
-async fn fetch_latest_versions(
-    pypi: &PyPiClient,
-    packages: &[PackageConfig],
-    progress: Option<ProgressBar>,
-    verbose: bool,
-) -> Result<Vec<VersionInfo>> {
-    if packages.is_empty() {
-        return Ok(Vec::new());
+/// Load one versions file, following its `extends` chain when configured
+async fn load_versions(config: &Config, path: &str) -> Result<BuildoutVersions> {
+    if !config.follow_extends {
+        return BuildoutVersions::load_in(&config.work_dir, path);
     }
 
-    let concurrency = pypi_concurrency_limit().min(packages.len());
-    let semaphore = Arc::new(Semaphore::new(concurrency));
-    let mut join_set = JoinSet::new();
+    // Downloaded files may extend further URLs, so reload until nothing is missing
+    let mut remote = HashMap::new();
+    loop {
+        let versions = BuildoutVersions::load_with_extends_in(&config.work_dir, path, &remote)?;
+        if versions.unresolved_extends().is_empty() {
+            return Ok(versions);
+        }
 
-    for (index, pkg_config) in packages.iter().cloned().enumerate() {
-        let pypi = pypi.clone();
-        let progress = progress.clone();
-        let permit = semaphore.clone().acquire_owned().await.map_err(|_| {
-            ReleaserError::PyPiError("Failed to acquire PyPI concurrency permit".to_string())
-        })?;
+        if !config.remote_extends {
+            for url in versions.unresolved_extends() {
+                warnings::warn(
+                    WarningKind::Pins,
+                    Some(path),
+                    format!(
+                        "not following remote extends {} (set remote_extends = true)",
+                        url
+                    ),
+                );
+            }
+            return Ok(versions);
+        }
 
-        join_set.spawn(async move {
-            let _permit = permit;
+        for url in versions.unresolved_extends() {
+            let content = http::get_text(url, &config.http).await.map_err(|e| {
+                ReleaserError::BuildoutParseError(format!("{} extends {}: {}", path, url, e))
+            })?;
+            remote.insert(url.clone(), content);
+        }
+    }
+}
 
-            if let Some(pb) = progress.as_ref() {
-                pb.set_message(format!("Checking {}...", pkg_config.name));
-                if verbose {
-                    pb.println(format!("Checking {}...", pkg_config.name));
-                }
-            } else if verbose {
-                println!("Checking {}...", pkg_config.name);
-            }
+/// The config as written, with its paths resolving against the work directory
+fn read_config(config_path: &str) -> Result<Config> {
+    Config::load_in(&work_dir(), config_path)
+}
 
-            let latest = match &pkg_config.version_constraint {
-                Some(constraint) => {
-                    pypi.get_matching_version(
-                        &pkg_config.name,
-                        constraint,
-                        pkg_config.allow_prerelease,
-                    )
-                    .await?
-                }
-                None => {
-                    pypi.get_latest_version(&pkg_config.name, pkg_config.allow_prerelease)
-                        .await?
-                }
-            };
+/// Git in the work directory, set up from the config
+fn git_for(config: &Config) -> GitOps {
+    git_in(&config.work_dir)
+        .with_token_env(config.git.token_env.as_deref())
+        .with_remote(&config.git.remote)
+        .with_legacy_tags(&config.github.legacy_tags)
+}
+
+/// Git in `dir`, or in the current directory when it is empty
+fn git_in(dir: &std::path::Path) -> GitOps {
+    let git = GitOps::new().with_non_interactive(NON_INTERACTIVE.load(Ordering::Relaxed));
+    if dir.as_os_str().is_empty() {
+        git
+    } else {
+        git.with_work_dir(dir.to_string_lossy())
+    }
+}
 
-            if let Some(pb) = progress {
-                pb.inc(1);
-            }
+/// Load the config; with `track_all`, every local pin becomes a tracked package
+fn load_config(config_path: &str) -> Result<Config> {
+    let mut config = read_config(config_path)?;
+    if let Some(project) = ACTIVE_PROJECT.lock().ok().and_then(|p| p.clone()) {
+        config.github.tag_prefix = project.effective_tag_prefix(&config.github.tag_prefix);
+    }
+    if !config.track_all {
+        return Ok(config);
+    }
 
-            Ok::<(usize, VersionInfo), ReleaserError>((index, latest))
-        });
+    // Pins inherited from remote files belong to upstream, so only local files count
+    let mut pinned: Vec<String> = Vec::new();
+    for path in config.versions_files() {
+        let file = if config.follow_extends {
+            BuildoutVersions::load_with_extends_in(&config.work_dir, path, &HashMap::new())?
+        } else {
+            BuildoutVersions::load_in(&config.work_dir, path)?
+        };
+        pinned.extend(file.get_all_versions().map(|(name, _)| name.to_string()));
     }
+    config.track_pins(pinned.iter().map(String::as_str));
 
-    let mut results = vec![None; packages.len()];
+    Ok(config)
+}
 
-    while let Some(joined) = join_set.join_next().await {
-        match joined {
-            Ok(Ok((index, latest))) => {
-                results[index] = Some(latest);
-            }
-            Ok(Err(err)) => return Err(err),
-            Err(err) => {
-                return Err(ReleaserError::PyPiError(format!(
-                    "Failed to join PyPI request task: {}",
-                    err
-                )))
+/// Every local file holding pins, including extended ones, for staging
+fn versions_file_paths(config: &Config) -> Result<Vec<String>> {
+    let mut paths: Vec<String> = Vec::new();
+    for path in config.versions_files() {
+        // Remote files are never staged, so they don't need to be downloaded here
+        let file = if config.follow_extends {
+            BuildoutVersions::load_with_extends_in(&config.work_dir, path, &HashMap::new())?
+        } else {
+            BuildoutVersions::load_in(&config.work_dir, path)?
+        };
+        for path in file.paths() {
+            if !paths.iter().any(|p| p == path) {
+                paths.push(path.to_string());
             }
         }
     }
-
-    results
-        .into_iter()
-        .enumerate()
-        .map(|(index, latest)| {
-            latest.ok_or_else(|| {
-                ReleaserError::PyPiError(format!("Missing PyPI result for index {}", index))
-            })
-        })
-        .collect()
+    Ok(paths)
 }
 
-async fn perform_update(
+/// Leave out packages mr.developer checks out from source (`[sources]`):
+/// their pin is not what gets installed, so bumping it means nothing
+fn skip_source_checkouts(
     config: &Config,
-    packages_filter: Option<String>,
-    auto_confirm: bool,
-    dry_run: bool,
-    verbose: bool,
-) -> Result<Vec<VersionUpdate>> {
-    let pypi = PyPiClient::new()?;
-    let mut buildout = BuildoutVersions::load(&config.versions_file)?;
+    files: &[BuildoutVersions],
+    packages: Vec<PackageConfig>,
+) -> Result<Vec<PackageConfig>> {
+    let extra = config
+        .sources_files
+        .iter()
+        .map(|path| BuildoutVersions::load_in(&config.work_dir, path))
+        .collect::<Result<Vec<_>>>()?;
+
+    let normalize = pypi::normalize_project_name;
+    let mut sources: HashMap<String, &str> = HashMap::new();
+    for (name, path) in files
+        .iter()
+        .chain(extra.iter())
+        .flat_map(|file| file.source_checkouts())
+    {
+        sources.entry(normalize(name)).or_insert(path);
+    }
+    if sources.is_empty() {
+        return Ok(packages);
+    }
 
-    let packages_to_check = filter_packages(&config.packages, packages_filter.as_deref());
+    Ok(packages
+        .into_iter()
+        .filter(|package| {
+            let Some(path) = sources.get(&normalize(package.buildout_name())) else {
+                return true;
+            };
+            warnings::warn(
+                WarningKind::Pins,
+                Some(package.buildout_name()),
+                format!("checked out from source ([sources] in {}), skipped", path),
+            );
+            false
+        })
+        .collect())
+}
 
-    let mut available_updates = Vec::new();
+/// Current pin of a package, from the first versions file that has it
+fn pinned_version<'a>(files: &'a [BuildoutVersions], name: &str) -> Option<&'a str> {
+    files.iter().find_map(|file| file.get_version(name))
+}
 
-    println!("{}", "Checking for updates...".cyan());
+/// Current pin of a tracked package, in its configured section if it has one
+fn package_pin<'a>(files: &'a [BuildoutVersions], package: &PackageConfig) -> Option<&'a str> {
+    files
+        .iter()
+        .find_map(|file| file.get_version_in(package.buildout_name(), package.section.as_deref()))
+}
 
-    let progress = create_progress_bar(packages_to_check.len(), "Checking packages");
+/// Upstream file the effective pin of a package comes from, if it is inherited
+fn pin_upstream<'a>(files: &'a [BuildoutVersions], name: &str) -> Option<&'a str> {
+    files
+        .iter()
+        .find(|file| file.get_version(name).is_some())
+        .and_then(|file| file.upstream_source(name))
+}
 
-    let latest_versions =
-        fetch_latest_versions(&pypi, &packages_to_check, progress.clone(), verbose).await?;
+/// Renamed packages whose old name is still pinned (and the new one is not),
+/// with the old name and its pinned version
+fn pending_renames<'a>(
+    config: &'a Config,
+    files: &'a [BuildoutVersions],
+) -> Vec<(&'a PackageConfig, &'a str, &'a str)> {
+    config
+        .packages
+        .iter()
+        .filter(|p| pinned_version(files, p.buildout_name()).is_none())
+        .filter_map(|p| {
+            let old_name = p.renamed_from.as_deref()?;
+            let version = pinned_version(files, old_name)?;
+            Some((p, old_name, version))
+        })
+        .collect()
+}
 
-    for (pkg_config, latest) in packages_to_check.iter().zip(latest_versions) {
-        let current = buildout.get_version(pkg_config.buildout_name());
+/// Warn about pins of package names that were renamed or replaced by a fork
+fn warn_renamed_pins(config: &Config, files: &[BuildoutVersions]) {
+    for (package, old_name, version) in pending_renames(config, files) {
+        warnings::warn(
+            WarningKind::Pins,
+            Some(old_name),
+            format!(
+                "{} is pinned but the package continues as {}; run `bldr migrate` to swap the pin",
+                version,
+                package.buildout_name()
+            ),
+        );
+    }
 
-        if let Some(current_version) = current {
-            if current_version != latest.version {
-                available_updates.push((
-                    pkg_config.buildout_name().to_string(),
-                    current_version.to_string(),
-                    latest.version,
-                ));
-            }
+    for package in &config.packages {
+        let Some(old_name) = package.renamed_from.as_deref() else {
+            continue;
+        };
+        if pinned_version(files, package.buildout_name()).is_some()
+            && pinned_version(files, old_name).is_some()
+        {
+            warnings::warn(
+                WarningKind::Pins,
+                Some(old_name),
+                format!(
+                    "dead pin: the package continues as {}, which is pinned too; remove it",
+                    package.buildout_name()
+                ),
+            );
         }
     }
+}
 
-    if let Some(pb) = progress {
-        pb.finish_with_message("Update check complete");
+/// Fail when linked versions files pin a shared package to different versions
+fn ensure_pins_in_sync(files: &[BuildoutVersions]) -> Result<()> {
+    let mismatches = find_pin_mismatches(files);
+    if mismatches.is_empty() {
+        return Ok(());
     }
 
-    if available_updates.is_empty() {
-        println!("{}", "All packages are up to date!".green());
-        return Ok(Vec::new());
+    let details: Vec<String> = mismatches
+        .iter()
+        .map(|m| format!("{} ({})", m.package, describe_pins(&m.pins)))
+        .collect();
+    Err(ReleaserError::PinsOutOfSync(details.join("; ")))
+}
+
+fn describe_pins(pins: &[(String, String)]) -> String {
+    pins.iter()
+        .map(|(file, version)| format!("{} in {}", version, file))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Whether an added or removed pin should be reported in the changelog
+fn include_pin_change(config: &Config, filter: Option<&str>, name: &str) -> bool {
+    let package = config.packages.iter().find(|p| p.is_named(name));
+
+    if matches!(package, Some(p) if !p.include_in_changelog) {
+        return false;
     }
 
-    println!("\n{}", "Available updates:".yellow().bold());
-    for (name, current, latest) in &available_updates {
-        println!("  {} {} → {}", name, current.dimmed(), latest.green());
+    match filter {
+        Some(f) => filter_tokens(f).any(|token| {
+            pypi::normalize_project_name(token) == pypi::normalize_project_name(name)
+                || package.is_some_and(|p| matches_filter(p, token))
+        }),
+        None => true,
     }
+}
 
-    let selected_updates = if auto_confirm {
-        available_updates.clone()
-    } else {
-        let items: Vec<String> = available_updates
-            .iter()
-            .map(|(name, current, latest)| format!("{}: {} → {}", name, current, latest))
-            .collect();
+/// Exit status when a `--packages` token matched nothing (after the command ran)
+const UNMATCHED_FILTER_EXIT_CODE: i32 = 3;
 
-        let selections = MultiSelect::new()
-            .with_prompt("Select packages to update")
-            .items(&items)
-            .defaults(&vec![true; items.len()])
-            .interact()
-            .map_err(|e| {
-                ReleaserError::IoError(std::io::Error::new(
-                    std::io::ErrorKind::Other,
-                    e.to_string(),
-                ))
-            })?;
+/// Workspace file read by `--project` and `--all`
+const WORKSPACE_FILE: &str = "bldr-workspace.toml";
 
-        selections
-            .iter()
-            .map(|&i| available_updates[i].clone())
-            .collect()
-    };
+/// Workspace project the command currently runs for
+static ACTIVE_PROJECT: Mutex<Option<ProjectConfig>> = Mutex::new(None);
 
-    if selected_updates.is_empty() {
-        println!("No updates selected.");
-        return Ok(Vec::new());
+fn set_active_project(project: Option<ProjectConfig>) {
+    if let Ok(mut active) = ACTIVE_PROJECT.lock() {
+        *active = project;
     }
+}
 
-    let mut applied_updates = Vec::new();
-
-    for (name, _current, latest) in &selected_updates {
-        if let Some(update) = buildout.update_version(name, latest)? {
-            applied_updates.push(update);
-            if verbose {
-                println!("  {} Updated {} to {}", "✓".green(), name, latest);
-            }
-        }
-    }
+/// Directory relative paths resolve against (`-C`, or the project being run)
+static WORK_DIR: Mutex<Option<PathBuf>> = Mutex::new(None);
 
-    if dry_run {
-        println!("\n{}", "Dry run - no files were modified.".yellow());
-        println!("Would update:");
-        for update in &applied_updates {
-            println!(
-                "  {} {} → {}",
-                update.package_name, update.old_version, update.new_version
-            );
-        }
-    } else {
-        buildout.save()?;
-        println!(
-            "\n{} Updated {} package(s)",
-            "✓".green(),
-            applied_updates.len()
-        );
+fn set_work_dir(dir: PathBuf) {
+    if let Ok(mut work_dir) = WORK_DIR.lock() {
+        *work_dir = Some(dir);
     }
+}
 
-    Ok(applied_updates)
+fn work_dir() -> PathBuf {
+    WORK_DIR
+        .lock()
+        .ok()
+        .and_then(|dir| dir.clone())
+        .unwrap_or_default()
 }
 
-fn perform_release(
-    config: &Config,
-    tag: &str,
-    message: Option<&str>,
-    no_push: bool,
-    no_github: bool,
-    draft: bool,
-    verbose: bool,
-) -> Result<()> {
-    let git = GitOps::new();
+/// `--non-interactive` of the running command; git fails instead of prompting
+static NON_INTERACTIVE: AtomicBool = AtomicBool::new(false);
 
-    if !git.is_repo() {
-        return Err(ReleaserError::GitError(
-            "Not in a git repository".to_string(),
-        ));
-    }
+fn set_non_interactive(enabled: bool) {
+    NON_INTERACTIVE.store(enabled, Ordering::Relaxed);
+}
 
-    let full_tag = format!("{}{}", config.github.tag_prefix, tag);
-    let default_message = format!("Release {}", tag);
-    let release_message = message.unwrap_or(&default_message);
+fn filter_tokens(filter: &str) -> impl Iterator<Item = &str> {
+    filter.split(',').map(str::trim).filter(|t| !t.is_empty())
+}
 
-    if verbose {
-        println!("Creating tag: {}", full_tag);
-    }
+/// Whether a `--packages` token names a package by its PyPI or buildout name
+fn matches_filter(package: &PackageConfig, token: &str) -> bool {
+    let token = pypi::normalize_project_name(token);
+    pypi::normalize_project_name(&package.name) == token
+        || pypi::normalize_project_name(package.buildout_name()) == token
+}
 
-    git.tag(&full_tag, Some(release_message))?;
-    println!("{} Created tag: {}", "✓".green(), full_tag);
+/// Packages selected by `--packages` (all without a filter), and the tokens
+/// that matched none of them
+fn filter_packages(
+    packages: &[PackageConfig],
+    filter: Option<&str>,
+) -> (Vec<PackageConfig>, Vec<String>) {
+    let Some(filter) = filter else {
+        return (packages.to_vec(), Vec::new());
+    };
 
-    if !no_push {
-        if verbose {
-            println!("Pushing to remote...");
-        }
-        git.push(true)?;
-        println!("{} Pushed to remote", "✓".green());
-    }
+    let unmatched = unmatched_tokens(
+        filter,
+        |token| packages.iter().any(|p| matches_filter(p, token)),
+        "matched no tracked package",
+    );
+    let selected = packages
+        .iter()
+        .filter(|p| filter_tokens(filter).any(|token| matches_filter(p, token)))
+        .cloned()
+        .collect();
 
-    if !no_github && config.github.create_release {
-        if !GitHubOps::is_available() {
-            println!(
-                "{} GitHub CLI (gh) not found, skipping GitHub release",
-                "⚠".yellow()
-            );
-        } else if !GitHubOps::is_authenticated()? {
-            println!(
-                "{} Not authenticated to GitHub, skipping release",
-                "⚠".yellow()
-            );
-            println!("  Run 'gh auth login' to authenticate");
-        } else {
-            if verbose {
-                println!("Creating GitHub release...");
-            }
+    (selected, unmatched)
+}
 
-            GitHubOps::create_release(
-                &full_tag,
-                Some(&format!("Release {}", tag)),
-                Some(release_message),
-                draft,
-                false,
-            )?;
+/// Pins of the versions file selected by `--packages` (all without a filter), by
+/// name, and the tokens that matched none of them
+fn filter_pins(
+    buildout: &BuildoutVersions,
+    filter: Option<&str>,
+) -> (Vec<PinnedPackage>, Vec<String>) {
+    let same = |token: &str, name: &str| {
+        pypi::normalize_project_name(token) == pypi::normalize_project_name(name)
+    };
 
-            println!("{} Created GitHub release", "✓".green());
-        }
-    }
+    let mut pins: Vec<PinnedPackage> = buildout
+        .get_all_versions()
+        .filter(|(name, _)| filter.is_none_or(|f| filter_tokens(f).any(|t| same(t, name))))
+        .map(|(name, version)| PinnedPackage {
+            name: name.to_string(),
+            version: version.to_string(),
+        })
+        .collect();
+    pins.sort_by_key(|pin| pin.name.to_lowercase());
+
+    let unmatched = match filter {
+        Some(filter) => unmatched_tokens(
+            filter,
+            |token| pins.iter().any(|pin| same(token, &pin.name)),
+            "matched no pin in the versions file",
+        ),
+        None => Vec::new(),
+    };
 
-    Ok(())
+    (pins, unmatched)
 }
 
-fn filter_packages(packages: &[PackageConfig], filter: Option<&str>) -> Vec<PackageConfig> {
-    match filter {
-        Some(f) => {
-            let names: Vec<&str> = f.split(',').map(|s| s.trim()).collect();
-            packages
-                .iter()
-                .filter(|p| names.contains(&p.name.as_str()))
-                .cloned()
-                .collect()
+/// Warn about the `--packages` tokens `matched` rejects and return them for the
+/// exit status
+fn unmatched_tokens(filter: &str, matched: impl Fn(&str) -> bool, message: &str) -> Vec<String> {
+    let mut unmatched: Vec<String> = Vec::new();
+    for token in filter_tokens(filter).filter(|token| !matched(token)) {
+        warnings::warn(WarningKind::Filter, Some(token), message);
+        if !unmatched.iter().any(|t| t == token) {
+            unmatched.push(token.to_string());
         }
-        None => packages.to_vec(),
     }
+    unmatched
 }
 
+/// Commit message from `git.commit_template` (or the `custom` subject), the
+/// `git.commit_body` and the trailers
 fn generate_commit_message(
     updates: &[VersionUpdate],
-    template: &str,
+    git: &GitConfig,
     custom: Option<&str>,
 ) -> String {
-    if let Some(msg) = custom {
-        return msg.to_string();
-    }
-
     let packages_str = match updates.len() {
         0 => String::new(),
         1 => format!("{} = {}", updates[0].package_name, updates[0].new_version),
@@ -1630,25 +6794,60 @@ fn generate_commit_message(
         }
     };
 
-    let effective_template = if template.trim().is_empty() {
-        "Use {packages}"
-    } else {
+    let date = current_date();
+    let expand = |template: &str| {
         template
+            .replace("{packages}", &packages_str)
+            .replace("{date}", &date)
     };
 
-    let date = current_date();
+    let mut message = match custom {
+        Some(msg) => msg.to_string(),
+        None => expand(git.effective_commit_template()),
+    };
+    if !git.commit_body.trim().is_empty() {
+        message = format!(
+            "{}\n\n{}",
+            message.trim_end(),
+            expand(git.commit_body.trim())
+        );
+    }
+    git.append_trailers(&message)
+}
 
-    effective_template
-        .replace("{packages}", &packages_str)
-        .replace("{date}", &date)
+/// Print a unified diff, colored like `git diff`
+fn print_diff(diff: &str) {
+    for line in diff.lines() {
+        if line.starts_with("+++") || line.starts_with("---") {
+            println!("{}", line.bold());
+        } else if line.starts_with('+') {
+            println!("{}", line.green());
+        } else if line.starts_with('-') {
+            println!("{}", line.red());
+        } else if line.starts_with("@@") {
+            println!("{}", line.cyan());
+        } else {
+            println!("{}", line);
+        }
+    }
 }
 
-fn generate_release_notes(updates: &[VersionUpdate], tag: &str) -> String {
+/// Release notes listing the updates, without packages excluded from the changelog
+fn generate_release_notes(config: &Config, updates: &[VersionUpdate], tag: &str) -> String {
     let mut notes = format!("## Release {}\n\n", tag);
 
-    if !updates.is_empty() {
+    let listed: Vec<&VersionUpdate> = updates
+        .iter()
+        .filter(|update| {
+            !config
+                .packages
+                .iter()
+                .any(|p| p.is_named(&update.package_name) && !p.include_in_changelog)
+        })
+        .collect();
+    if !listed.is_empty() {
         notes.push_str("### Package Updates\n\n");
-        for update in updates {
+        for update in listed {
             notes.push_str(&format!(
                 "- **{}**: {} → {}\n",
                 update.package_name, update.old_version, update.new_version
@@ -1713,36 +6912,253 @@ fn is_leap_year(year: i32) -> bool {
 struct UpdateInfo {
     package: String,
     buildout_name: String,
+    kind: UpdateKind,
     current_version: Option<String>,
     latest_version: String,
+    /// Upload date of the latest version
+    released: Option<String>,
     has_update: bool,
+    /// Advisories affecting the current pin that the update moves away from
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    vulnerabilities: Vec<String>,
+    /// Installable files of the latest version for the target (`check --wheels`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    availability: Option<wheels::Availability>,
+    /// Remote file the current pin is inherited from, rather than pinned by us
+    #[serde(skip_serializing_if = "Option::is_none")]
+    upstream: Option<String>,
+}
+
+/// `bldr diff --json`
+#[derive(serde::Serialize)]
+struct PinChangesReport<'a> {
+    from: &'a str,
+    to: &'a str,
+    updated: &'a [VersionUpdate],
+    #[serde(flatten)]
+    pin_changes: &'a PinDiff,
+}
+
+/// A release tag as listed by `bldr history`
+#[derive(serde::Serialize)]
+struct HistoryEntry {
+    tag: String,
+    version: String,
+    date: Option<String>,
+    /// Tag of the release before it
+    previous: Option<String>,
+    updated: Vec<VersionUpdate>,
+    #[serde(flatten)]
+    pin_changes: PinDiff,
+    /// Annotation of the tag, without the metadata trailer
+    message: Option<String>,
+}
+
+#[derive(serde::Serialize)]
+struct CheckReport {
+    packages: Vec<UpdateInfo>,
+    warnings: Vec<warnings::Warning>,
+}
+
+/// How far an available update moves from the current pin, most urgent first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+enum UpdateKind {
+    /// The current pin has known vulnerabilities
+    Security,
+    Major,
+    Minor,
+    Patch,
+    /// Not pinned in any versions file yet
+    Unpinned,
+    UpToDate,
+}
+
+impl UpdateKind {
+    fn label(self) -> ColoredString {
+        match self {
+            UpdateKind::Security => "security".red().bold(),
+            UpdateKind::Major => "major".magenta(),
+            UpdateKind::Minor => "minor".yellow(),
+            UpdateKind::Patch => "patch".cyan(),
+            UpdateKind::Unpinned => "not pinned".blue(),
+            UpdateKind::UpToDate => "up to date".green(),
+        }
+    }
+
+    fn heading(self, count: usize) -> ColoredString {
+        let title = match self {
+            UpdateKind::Security => "Security updates",
+            UpdateKind::Major => "Major updates",
+            UpdateKind::Minor => "Minor updates",
+            UpdateKind::Patch => "Patch updates",
+            UpdateKind::Unpinned => "Not pinned",
+            UpdateKind::UpToDate => "Up to date",
+        };
+        let text = format!("{} ({})", title, count);
+        match self {
+            UpdateKind::Security => text.red().bold(),
+            UpdateKind::Major => text.magenta().bold(),
+            UpdateKind::Minor => text.yellow().bold(),
+            UpdateKind::Patch => text.cyan().bold(),
+            UpdateKind::Unpinned => text.blue().bold(),
+            UpdateKind::UpToDate => text.green().bold(),
+        }
+    }
+}
+
+/// Whether `latest` sorts above the pin (PEP 440). With a cooldown the newest
+/// eligible release can be older than a pin set by hand, which is kept
+fn is_upgrade(current: &str, latest: &str) -> bool {
+    version::python::compare_python_versions(latest, current) == Some(std::cmp::Ordering::Greater)
+}
+
+/// Classify an update by the first release segment that changes
+fn classify_update(current: Option<&str>, latest: &str) -> UpdateKind {
+    let Some(current) = current else {
+        return UpdateKind::Unpinned;
+    };
+    if !is_upgrade(current, latest) {
+        return UpdateKind::UpToDate;
+    }
+
+    match (
+        version::python::parse_python_version(current),
+        version::python::parse_python_version(latest),
+    ) {
+        (Some(c), Some(l)) if c.major != l.major => UpdateKind::Major,
+        (Some(c), Some(l)) if c.minor != l.minor => UpdateKind::Minor,
+        (Some(_), Some(_)) => UpdateKind::Patch,
+        // Unparseable versions: assume the worst
+        _ => UpdateKind::Major,
+    }
 }
 
-fn print_update_table(updates: &[UpdateInfo]) {
-    let has_updates = updates.iter().any(|u| u.has_update);
+/// Promote updates whose current pin has known vulnerabilities (OSV) to security updates
+async fn mark_security_updates(config: &Config, updates: &mut [UpdateInfo]) {
+    let pins: Vec<PinnedPackage> = updates
+        .iter()
+        .filter(|u| u.has_update)
+        .filter_map(|u| {
+            u.current_version.as_ref().map(|version| PinnedPackage {
+                name: u.package.clone(),
+                version: version.clone(),
+            })
+        })
+        .collect();
+    if pins.is_empty() {
+        return;
+    }
+
+    let findings = match audit::OsvClient::with_config(&config.audit, &config.http) {
+        Ok(osv) => osv.audit(&pins, &config.audit.ignore).await,
+        Err(e) => Err(e),
+    };
+
+    match findings {
+        Ok(findings) => {
+            for finding in findings {
+                if let Some(update) = updates.iter_mut().find(|u| u.package == finding.package) {
+                    update.kind = UpdateKind::Security;
+                    update.vulnerabilities =
+                        finding.vulnerabilities.into_iter().map(|v| v.id).collect();
+                }
+            }
+        }
+        Err(e) => warnings::warn(
+            WarningKind::Audit,
+            None,
+            format!("could not look up security advisories: {}", e),
+        ),
+    }
+}
 
-    if !has_updates {
+/// One section per update kind; up-to-date packages are only counted unless verbose
+fn print_grouped_updates(updates: &[UpdateInfo], verbose: bool) {
+    if !updates.iter().any(|u| u.has_update) {
         println!("{}", "All packages are up to date!".green());
         return;
     }
 
+    let mut kinds: Vec<UpdateKind> = updates.iter().map(|u| u.kind).collect();
+    kinds.sort();
+    kinds.dedup();
+
+    for kind in kinds {
+        let mut rows: Vec<&UpdateInfo> = updates.iter().filter(|u| u.kind == kind).collect();
+        rows.sort_by_key(|u| u.buildout_name.to_lowercase());
+
+        println!("\n{}", kind.heading(rows.len()));
+        if kind == UpdateKind::UpToDate && !verbose {
+            continue;
+        }
+
+        for update in rows {
+            let current = update.current_version.as_deref().unwrap_or("not set");
+            let released = update.released.as_deref().unwrap_or("");
+            println!(
+                "  {:<30} {:<15} → {:<15} {}",
+                update.buildout_name,
+                current,
+                update.latest_version,
+                released.dimmed()
+            );
+            if !update.vulnerabilities.is_empty() {
+                println!("    fixes {}", update.vulnerabilities.join(", ").red());
+            }
+            if let Some(ref upstream) = update.upstream {
+                println!("    {}", format!("inherited from {}", upstream).dimmed());
+            }
+        }
+    }
+}
+
+/// Flat table ordered by name or by how long the latest version has been out
+fn print_update_table(updates: &mut [UpdateInfo], sort: CheckSort) {
+    match sort {
+        CheckSort::Age => updates.sort_by(|a, b| {
+            // Oldest release first; unknown dates last
+            (a.released.is_none(), &a.released).cmp(&(b.released.is_none(), &b.released))
+        }),
+        _ => updates.sort_by_key(|u| u.buildout_name.to_lowercase()),
+    }
+
     println!(
-        "\n{:<30} {:<15} {:<15} {}",
-        "Package", "Current", "Latest", "Status"
+        "\n{:<30} {:<15} {:<15} {:<12} Kind",
+        "Package", "Current", "Latest", "Released"
     );
-    println!("{}", "-".repeat(70));
+    println!("{}", "-".repeat(85));
 
-    for update in updates {
+    for update in updates.iter() {
         let current = update.current_version.as_deref().unwrap_or("not set");
-        let status = if update.has_update {
-            "UPDATE AVAILABLE".yellow()
-        } else {
-            "up to date".green()
-        };
+        println!(
+            "{:<30} {:<15} {:<15} {:<12} {}",
+            update.buildout_name,
+            current,
+            update.latest_version,
+            update.released.as_deref().unwrap_or("-"),
+            update.kind.label()
+        );
+        if let Some(ref upstream) = update.upstream {
+            println!("  {}", format!("inherited from {}", upstream).dimmed());
+        }
+    }
+}
 
+/// List latest versions that would not install from a wheel on the target
+fn print_wheel_problems(updates: &[UpdateInfo]) {
+    for update in updates {
+        let problem = match update.availability {
+            Some(wheels::Availability::SdistOnly) => "no compatible wheel, would build from source",
+            Some(wheels::Availability::Unavailable) => "no compatible wheel or sdist",
+            _ => continue,
+        };
         println!(
-            "{:<30} {:<15} {:<15} {}",
-            update.buildout_name, current, update.latest_version, status
+            "{} {} {}: {}",
+            "⚠".yellow(),
+            update.buildout_name,
+            update.latest_version,
+            problem
         );
     }
 }
@@ -0,0 +1,271 @@
+//! Lightweight conversion of upstream changelog entries between Markdown and
+//! reStructuredText, so a rendered changelog uses a single markup
+
+use crate::config::ChangelogFormat;
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+// Compiled once: conversion runs for every entry of every updated package
+static MD_HEADING_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^(\s*)#{1,6}\s+(.+?)[\s#]*$").unwrap());
+static MD_LINK_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\[([^\]]+)\]\(([^)\s]+)\)").unwrap());
+static MD_STRONG_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\*\*([^*]+)\*\*").unwrap());
+static RST_REFERENCE_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"`([^`<]+?)\s*<([^>`]+)>`__?").unwrap());
+static RST_ISSUE_ROLE_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r":(?:issue|pr|pull|gh):`#?(\d+)`").unwrap());
+static RST_ROLE_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r":[\w.:+-]+:`([^`]+)`").unwrap());
+static RST_DIRECTIVE_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^(\s*)\.\. (?:code-block|code|sourcecode)::\s*(\S*)").unwrap());
+
+/// Rewrite the markup of an entry written in `source` (unknown: either) for
+/// `format`. Only the common inline and block constructs are handled;
+/// anything else is left as written
+pub fn convert(content: &str, source: Option<ChangelogFormat>, format: ChangelogFormat) -> String {
+    match (source, format) {
+        (Some(source), format) if source == format => content.to_string(),
+        // Plain text has no markup to convert
+        (Some(ChangelogFormat::Text), _) => content.to_string(),
+        (Some(ChangelogFormat::Markdown), ChangelogFormat::Text) => markdown_to_text(content),
+        (_, ChangelogFormat::Markdown) => rst_to_markdown(content),
+        (_, ChangelogFormat::Rst) => markdown_to_rst(content),
+        (_, ChangelogFormat::Text) => markdown_to_text(&rst_to_markdown(content)),
+    }
+}
+
+/// Headings, links, inline code and fenced code blocks
+fn markdown_to_rst(content: &str) -> String {
+    let mut out = Vec::new();
+    // Indentation of the open code fence
+    let mut fence: Option<String> = None;
+    for line in content.lines() {
+        let trimmed = line.trim_start();
+        let indent = &line[..line.len() - trimmed.len()];
+
+        if trimmed.starts_with("```") {
+            if fence.take().is_some() {
+                out.push(String::new());
+                continue;
+            }
+            let language = trimmed.trim_start_matches('`').trim();
+            out.push(if language.is_empty() {
+                format!("{}::", indent)
+            } else {
+                format!("{}.. code-block:: {}", indent, language)
+            });
+            out.push(String::new());
+            fence = Some(indent.to_string());
+            continue;
+        }
+        if let Some(fence_indent) = &fence {
+            let code = line.strip_prefix(fence_indent.as_str()).unwrap_or(trimmed);
+            out.push(format!("{}    {}", fence_indent, code));
+            continue;
+        }
+
+        let line = MD_HEADING_RE.replace(line, "$1**$2**");
+        let line = MD_LINK_RE.replace_all(&line, "`$1 <$2>`__");
+        out.push(replace_literals(&line, 1, "``"));
+    }
+
+    out.join("\n")
+}
+
+/// Section titles, references, roles, inline literals and code-block directives
+fn rst_to_markdown(content: &str) -> String {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut out = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i];
+
+        if let Some(caps) = RST_DIRECTIVE_RE.captures(line) {
+            let indent = caps[1].len();
+            out.push(format!("{}```{}", &caps[1], &caps[2]));
+            i += 1;
+            let mut block = Vec::new();
+            while i < lines.len()
+                && (lines[i].trim().is_empty()
+                    || lines[i].len() - lines[i].trim_start().len() > indent)
+            {
+                block.push(lines[i]);
+                i += 1;
+            }
+            let mut trailing_blanks = 0;
+            while block.last().is_some_and(|l| l.trim().is_empty()) {
+                block.pop();
+                trailing_blanks += 1;
+            }
+            let code_indent = block
+                .iter()
+                .filter(|l| !l.trim().is_empty())
+                .map(|l| l.len() - l.trim_start().len())
+                .min()
+                .unwrap_or(0);
+            for code in block.iter().skip_while(|l| l.trim().is_empty()) {
+                out.push(format!(
+                    "{}{}",
+                    &caps[1],
+                    code.get(code_indent..).unwrap_or("")
+                ));
+            }
+            out.push(format!("{}```", &caps[1]));
+            if trailing_blanks > 0 {
+                out.push(String::new());
+            }
+            continue;
+        }
+
+        if is_underline(line, lines.get(i.wrapping_sub(1)).copied()) {
+            // The title was written on the previous line
+            i += 1;
+            continue;
+        }
+        if lines
+            .get(i + 1)
+            .is_some_and(|next| is_underline(next, Some(line)))
+        {
+            out.push(format!("**{}**", line.trim()));
+            i += 1;
+            continue;
+        }
+
+        let line = RST_ISSUE_ROLE_RE.replace_all(line, "#$1");
+        let line = RST_ROLE_RE.replace_all(&line, "`$1`");
+        let line = RST_REFERENCE_RE.replace_all(&line, "[$1]($2)");
+        out.push(replace_literals(&line, 2, "`"));
+        i += 1;
+    }
+
+    out.join("\n")
+}
+
+/// Markdown markup removed, links kept as `text (url)`
+fn markdown_to_text(content: &str) -> String {
+    content
+        .lines()
+        .filter(|line| !line.trim_start().starts_with("```"))
+        .map(|line| {
+            let line = MD_HEADING_RE.replace(line, "$1$2");
+            let line = MD_STRONG_RE.replace_all(&line, "$1");
+            let line = MD_LINK_RE.replace_all(&line, "$1 ($2)");
+            replace_literals(&line, 1, "")
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// An RST section underline: one punctuation character repeated at least as
+/// long as the title above it
+fn is_underline(line: &str, title: Option<&str>) -> bool {
+    let line = line.trim_end();
+    let Some(first) = line.chars().next() else {
+        return false;
+    };
+    let Some(title) = title.map(str::trim).filter(|t| !t.is_empty()) else {
+        return false;
+    };
+    "=-~^\"'`#*+".contains(first)
+        && line.len() >= 3
+        && line.chars().all(|c| c == first)
+        && line.chars().count() >= title.chars().count()
+        // A bullet list item is not a title
+        && !title.starts_with("- ")
+        && !title.starts_with("* ")
+}
+
+/// Replace the delimiters of inline literals written with `len` backticks by
+/// `to`, leaving RST roles (`:class:`x``) and references (`` `x`_ ``) alone
+fn replace_literals(line: &str, len: usize, to: &str) -> String {
+    let chars: Vec<char> = line.chars().collect();
+
+    // Runs of backticks as (start, length)
+    let mut runs = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        if chars[i] == '`' {
+            let start = i;
+            while i < chars.len() && chars[i] == '`' {
+                i += 1;
+            }
+            runs.push((start, i - start));
+        } else {
+            i += 1;
+        }
+    }
+
+    let mut out = String::new();
+    let mut copied = 0;
+    let mut r = 0;
+    while r + 1 < runs.len() {
+        let (open, open_len) = runs[r];
+        let (close, close_len) = runs[r + 1];
+        if open_len != len || close_len != len {
+            r += 1;
+            continue;
+        }
+        let is_role = open > 0 && chars[open - 1] == ':';
+        let is_reference = chars.get(close + close_len) == Some(&'_');
+        if !is_role && !is_reference {
+            out.extend(&chars[copied..open]);
+            out.push_str(to);
+            out.extend(&chars[open + open_len..close]);
+            out.push_str(to);
+            copied = close + close_len;
+        }
+        r += 2;
+    }
+    out.extend(&chars[copied..]);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_convert_entry_markup() {
+        let markdown = "### Fixed\n\n- Use `plone.api` in [the docs](https://docs.plone.org) **now**\n\n```python\nimport plone\n```";
+        assert_eq!(
+            convert(markdown, Some(ChangelogFormat::Markdown), ChangelogFormat::Rst),
+            "**Fixed**\n\n- Use ``plone.api`` in `the docs <https://docs.plone.org>`__ **now**\n\n.. code-block:: python\n\n    import plone\n"
+        );
+
+        let rst = "Bug fixes:\n\n- Fix ``getSite`` with :class:`plone.Foo` (:issue:`123`), see `docs <https://docs.plone.org>`_\n\nNew\n---\n\n.. code-block:: shell\n\n    bldr update\n\nDone";
+        assert_eq!(
+            convert(rst, Some(ChangelogFormat::Rst), ChangelogFormat::Markdown),
+            "Bug fixes:\n\n- Fix `getSite` with `plone.Foo` (#123), see [docs](https://docs.plone.org)\n\n**New**\n\n```shell\nbldr update\n```\n\nDone"
+        );
+
+        assert_eq!(
+            convert(
+                "- Fix `a` and ``b`` in [docs](https://x.org) **now**",
+                None,
+                ChangelogFormat::Text
+            ),
+            "- Fix a and b in docs (https://x.org) now"
+        );
+        // Already in the target markup
+        let native = "- Fix ``getSite`` (`docs <https://x.org>`__)";
+        assert_eq!(convert(native, None, ChangelogFormat::Rst), native);
+        assert_eq!(
+            convert(native, Some(ChangelogFormat::Rst), ChangelogFormat::Rst),
+            native
+        );
+        // Declared plain text has no markup to rewrite
+        let text = "- Keep `a` and ``b`` as written";
+        assert_eq!(
+            convert(text, Some(ChangelogFormat::Text), ChangelogFormat::Markdown),
+            text
+        );
+        // Declared Markdown is not read as RST on its way to plain text
+        assert_eq!(
+            convert(
+                "- See :issue:`1` in [docs](https://x.org)",
+                Some(ChangelogFormat::Markdown),
+                ChangelogFormat::Text
+            ),
+            "- See :issue:`1` in docs (https://x.org)"
+        );
+    }
+}
@@ -1,19 +1,31 @@
+use crate::config::{HttpConfig, PackageConfig, PyPiConfig};
 use crate::error::{ReleaserError, Result};
-use crate::version::python::{parse_python_version, parse_version_constraint};
+use crate::http::{self, RetryPolicy};
+use crate::version::python::{parse_version_constraint, version_key, ParsedReleaseCache};
+use crate::warnings::{self, WarningKind};
+use chrono::{DateTime, NaiveDateTime, Utc};
 use serde::Deserialize;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
-use tokio::time::sleep;
+use tokio::sync::{OnceCell, Semaphore};
+use tokio::task::JoinSet;
 
 const USER_AGENT: &str = concat!("bldr/", env!("CARGO_PKG_VERSION"));
 const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
 const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
-const MAX_RETRIES: usize = 3;
-const RETRY_BACKOFF: Duration = Duration::from_millis(300);
+const SIMPLE_JSON_ACCEPT: &str = "application/vnd.pypi.simple.v1+json";
 
 #[derive(Debug, Deserialize)]
 pub struct PyPiPackageInfo {
     pub info: PackageInfo,
+    /// Not included in per-version responses
+    #[serde(default)]
     pub releases: std::collections::HashMap<String, Vec<ReleaseInfo>>,
+    /// Files of the requested release (per-version responses only)
+    #[serde(default)]
+    pub urls: Vec<ReleaseInfo>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -23,6 +35,16 @@ pub struct PackageInfo {
     pub summary: Option<String>,
     pub home_page: Option<String>,
     pub project_urls: Option<std::collections::HashMap<String, String>>,
+    #[serde(default)]
+    pub license: Option<String>,
+    /// SPDX expression from core metadata 2.4 (PEP 639)
+    #[serde(default)]
+    pub license_expression: Option<String>,
+    #[serde(default)]
+    pub classifiers: Vec<String>,
+    /// PEP 508 requirement strings; PyPI sends `null` when there are none
+    #[serde(default)]
+    pub requires_dist: Option<Vec<String>>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -31,125 +53,364 @@ pub struct ReleaseInfo {
     pub url: String,
     pub upload_time: String,
     pub yanked: bool,
+    /// Hash algorithm → hex digest
+    #[serde(default)]
+    pub digests: std::collections::HashMap<String, String>,
 }
 
 #[derive(Debug, Clone)]
 pub struct VersionInfo {
-    pub package_name: String,
     pub version: String,
-    pub is_prerelease: bool,
+    /// When the first file of this version was uploaded
+    pub uploaded: Option<DateTime<Utc>>,
+}
+
+/// A dependency declared in `requires_dist`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Requirement {
+    pub name: String,
+    /// Version specifier such as `>=2.0,<3`; empty when unconstrained
+    pub specifier: String,
+}
+
+/// Parse a PEP 508 requirement, skipping ones that only apply to an extra
+pub fn parse_requirement(line: &str) -> Option<Requirement> {
+    let (spec, marker) = match line.split_once(';') {
+        Some((spec, marker)) => (spec, Some(marker)),
+        None => (line, None),
+    };
+
+    if marker.is_some_and(|m| m.contains("extra")) {
+        return None;
+    }
+
+    let spec = spec.trim();
+    let name_end = spec
+        .find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')))
+        .unwrap_or(spec.len());
+    let name = &spec[..name_end];
+    if name.is_empty() {
+        return None;
+    }
+
+    let mut rest = spec[name_end..].trim();
+    if rest.starts_with('[') {
+        rest = rest.split_once(']').map(|(_, r)| r.trim()).unwrap_or("");
+    }
+    let specifier = rest
+        .trim_start_matches('(')
+        .trim_end_matches(')')
+        .replace(' ', "");
+
+    Some(Requirement {
+        name: name.to_string(),
+        specifier,
+    })
+}
+
+/// Default number of concurrent PyPI requests, based on available CPUs
+pub fn default_concurrency() -> usize {
+    std::thread::available_parallelism()
+        .map(|count| (count.get() * 4).clamp(4, 32))
+        .unwrap_or(8)
+}
+
+/// Whether a release was first uploaded at least `min_age_days` ago.
+/// Releases without a parseable upload time are never held back.
+fn is_old_enough(files: &[ReleaseInfo], min_age_days: u32, now: DateTime<Utc>) -> bool {
+    if min_age_days == 0 {
+        return true;
+    }
+
+    match first_upload(files) {
+        Some(uploaded) => now - uploaded >= chrono::Duration::days(i64::from(min_age_days)),
+        None => true,
+    }
+}
+
+/// Earliest upload time among the files of a release
+fn first_upload(files: &[ReleaseInfo]) -> Option<DateTime<Utc>> {
+    files
+        .iter()
+        .filter_map(|f| parse_upload_time(&f.upload_time))
+        .min()
+}
+
+/// Parse PyPI upload times, which are either RFC 3339 (Simple API) or naive UTC (JSON API)
+fn parse_upload_time(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|t| t.with_timezone(&Utc))
+        .or_else(|_| {
+            NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%.f").map(|t| t.and_utc())
+        })
+        .ok()
+}
+
+/// PEP 691 JSON response from a Simple API project page
+#[derive(Debug, Deserialize)]
+struct SimpleProject {
+    name: String,
+    #[serde(default)]
+    versions: Vec<String>,
+    files: Vec<SimpleFile>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SimpleFile {
+    filename: String,
+    url: String,
+    #[serde(rename = "upload-time", default)]
+    upload_time: Option<String>,
+    #[serde(default)]
+    yanked: serde_json::Value,
+    #[serde(default)]
+    hashes: std::collections::HashMap<String, String>,
+}
+
+impl SimpleFile {
+    /// PEP 691 allows `yanked` to be a boolean or a reason string
+    fn is_yanked(&self) -> bool {
+        match &self.yanked {
+            serde_json::Value::Bool(yanked) => *yanked,
+            serde_json::Value::String(_) => true,
+            _ => false,
+        }
+    }
+}
+
+/// A project's JSON document, initialized once; `None` when the index has no such project
+type StoredDocument = Arc<OnceCell<Option<Arc<serde_json::Value>>>>;
+
+/// Project JSON documents (`/pypi/<name>/json`) shared by every client in a run,
+/// so the update and changelog phases download each package only once
+#[derive(Clone, Default)]
+pub struct PackageMetadataStore {
+    /// Keyed by normalized project name
+    entries: Arc<Mutex<HashMap<String, StoredDocument>>>,
+}
+
+impl PackageMetadataStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return the stored document, running `fetch` only for the first caller per package
+    pub async fn get_or_fetch<F, Fut>(
+        &self,
+        package_name: &str,
+        fetch: F,
+    ) -> Result<Option<Arc<serde_json::Value>>>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<Option<serde_json::Value>>>,
+    {
+        let cell = self
+            .entries
+            .lock()
+            .expect("metadata store lock poisoned")
+            .entry(normalize_project_name(package_name))
+            .or_default()
+            .clone();
+
+        cell.get_or_try_init(|| async { fetch().await.map(|doc| doc.map(Arc::new)) })
+            .await
+            .cloned()
+    }
 }
 
 #[derive(Clone)]
 pub struct PyPiClient {
     client: reqwest::Client,
     base_url: String,
+    simple_url: String,
+    min_release_age_days: u32,
+    retry: RetryPolicy,
+    store: Option<PackageMetadataStore>,
+    /// Release lists parsed once per package, shared by clones of the client
+    parsed: ParsedReleaseCache,
 }
 
 impl PyPiClient {
-    pub fn new() -> Result<Self> {
-        let client = reqwest::Client::builder()
+    pub fn with_config(config: &PyPiConfig, http: &HttpConfig) -> Result<Self> {
+        let builder = reqwest::Client::builder()
             .user_agent(USER_AGENT)
             .connect_timeout(CONNECT_TIMEOUT)
-            .timeout(REQUEST_TIMEOUT)
-            .build()?;
+            .timeout(REQUEST_TIMEOUT);
+        let client = http::configure_client(builder, http)?.build()?;
 
         Ok(Self {
             client,
-            base_url: "https://pypi.org/pypi".to_string(),
+            base_url: config.index_url.trim_end_matches('/').to_string(),
+            simple_url: config.simple_url.trim_end_matches('/').to_string(),
+            min_release_age_days: config.min_release_age_days,
+            retry: RetryPolicy::from_config(http),
+            store: None,
+            parsed: ParsedReleaseCache::default(),
         })
     }
 
+    /// Share project metadata with other phases of the run
+    pub fn with_store(mut self, store: &PackageMetadataStore) -> Self {
+        self.store = Some(store.clone());
+        self
+    }
+
     async fn get_with_retry(&self, url: &str) -> Result<reqwest::Response> {
-        let mut last_error: Option<ReleaserError> = None;
-
-        for attempt in 0..MAX_RETRIES {
-            match self.client.get(url).send().await {
-                Ok(response) => {
-                    if response.status().is_server_error() {
-                        last_error = Some(ReleaserError::PyPiError(format!(
-                            "HTTP {} for {}",
-                            response.status(),
-                            url
-                        )));
-                    } else {
-                        return Ok(response);
-                    }
-                }
-                Err(err) => {
-                    last_error = Some(ReleaserError::HttpError(err));
-                }
-            }
+        self.retry.send(self.client.get(url)).await
+    }
 
-            if attempt + 1 < MAX_RETRIES {
-                let delay = RETRY_BACKOFF * 2u32.pow(attempt as u32);
-                sleep(delay).await;
+    /// Fetch package information from PyPI
+    pub async fn get_package_info(&self, package_name: &str) -> Result<PyPiPackageInfo> {
+        let document = match self.store {
+            Some(ref store) => {
+                store
+                    .get_or_fetch(package_name, || self.fetch_project_json(package_name))
+                    .await?
             }
-        }
+            None => self.fetch_project_json(package_name).await?.map(Arc::new),
+        };
 
-        Err(last_error.unwrap_or_else(|| {
-            ReleaserError::PyPiError("Failed to contact PyPI after retries".to_string())
-        }))
+        match document {
+            Some(document) => PyPiPackageInfo::deserialize(document.as_ref())
+                .map_err(|e| ReleaserError::PyPiError(format!("Failed to parse response: {}", e))),
+            // Some mirrors only serve the Simple API
+            None => self.get_simple_package_info(package_name).await,
+        }
     }
 
-    /// Fetch package information from PyPI
-    pub async fn get_package_info(&self, package_name: &str) -> Result<PyPiPackageInfo> {
+    /// Raw JSON API document of a project; `None` when the index doesn't know it
+    async fn fetch_project_json(&self, package_name: &str) -> Result<Option<serde_json::Value>> {
         let url = format!("{}/{}/json", self.base_url, package_name);
 
         let response = self.get_with_retry(&url).await?;
 
         if response.status() == reqwest::StatusCode::NOT_FOUND {
-            return Err(ReleaserError::PackageNotFound(package_name.to_string()));
+            return Ok(None);
         }
 
         if !response.status().is_success() {
-            return Err(ReleaserError::PyPiError(format!(
-                "HTTP {} for package {}",
-                response.status(),
-                package_name
+            return Err(ReleaserError::HttpStatus {
+                url: url.to_string(),
+                status: response.status().as_u16(),
+            });
+        }
+
+        response
+            .json::<serde_json::Value>()
+            .await
+            .map(Some)
+            .map_err(|e| ReleaserError::PyPiError(format!("Failed to parse response: {}", e)))
+    }
+
+    /// Fetch metadata for one specific release
+    pub async fn get_release_info(
+        &self,
+        package_name: &str,
+        version: &str,
+    ) -> Result<PyPiPackageInfo> {
+        let url = format!("{}/{}/{}/json", self.base_url, package_name, version);
+
+        let response = self.get_with_retry(&url).await?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Err(ReleaserError::PackageNotFound(format!(
+                "{}=={}",
+                package_name, version
             )));
         }
 
+        if !response.status().is_success() {
+            return Err(ReleaserError::HttpStatus {
+                url: url.to_string(),
+                status: response.status().as_u16(),
+            });
+        }
+
         response
             .json::<PyPiPackageInfo>()
             .await
             .map_err(|e| ReleaserError::PyPiError(format!("Failed to parse response: {}", e)))
     }
 
-    /// Get the latest version of a package
-    pub async fn get_latest_version(
+    /// List the downloadable files of one release
+    pub async fn release_files(
         &self,
         package_name: &str,
-        allow_prerelease: bool,
-    ) -> Result<VersionInfo> {
-        let info = self.get_package_info(package_name).await?;
+        version: &str,
+    ) -> Result<Vec<ReleaseInfo>> {
+        let info = self.get_release_info(package_name, version).await;
+        if let Ok(info) = info {
+            if !info.urls.is_empty() {
+                return Ok(info.urls);
+            }
+        }
 
-        // Get all non-yanked versions
-        let mut versions: Vec<(semver::Version, String)> = info
-            .releases
-            .iter()
-            .filter(|(_, releases)| !releases.is_empty() && !releases.iter().all(|r| r.yanked))
-            .filter_map(|(version_str, _)| {
-                // Try to parse as semver, handle non-standard versions
-                parse_python_version(version_str).map(|v| (v, version_str.clone()))
-            })
-            .collect();
+        // Mirrors without per-version endpoints: use the full listing instead
+        let mut info = self.get_package_info(package_name).await?;
+        info.releases
+            .remove(version)
+            .ok_or_else(|| ReleaserError::PackageNotFound(format!("{}=={}", package_name, version)))
+    }
+
+    /// Download a release file
+    pub async fn download(&self, url: &str) -> Result<Vec<u8>> {
+        let response = self.get_with_retry(url).await?;
+
+        if !response.status().is_success() {
+            return Err(ReleaserError::HttpStatus {
+                url: url.to_string(),
+                status: response.status().as_u16(),
+            });
+        }
+
+        Ok(response.bytes().await?.to_vec())
+    }
+
+    /// Fetch package files from the PEP 691 Simple JSON API
+    async fn get_simple_package_info(&self, package_name: &str) -> Result<PyPiPackageInfo> {
+        let url = format!(
+            "{}/{}/",
+            self.simple_url,
+            normalize_project_name(package_name)
+        );
 
-        if !allow_prerelease {
-            versions.retain(|(v, _)| v.pre.is_empty());
+        let request = self
+            .client
+            .get(&url)
+            .header(reqwest::header::ACCEPT, SIMPLE_JSON_ACCEPT);
+        let response = self.retry.send(request).await?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Err(ReleaserError::PackageNotFound(package_name.to_string()));
         }
 
-        versions.sort_by(|a, b| b.0.cmp(&a.0));
+        if !response.status().is_success() {
+            return Err(ReleaserError::HttpStatus {
+                url: url.to_string(),
+                status: response.status().as_u16(),
+            });
+        }
 
-        let (parsed_version, version_str) = versions.into_iter().next().ok_or_else(|| {
-            ReleaserError::PyPiError(format!("No valid versions found for {}", package_name))
+        let project = response.json::<SimpleProject>().await.map_err(|e| {
+            ReleaserError::PyPiError(format!("Failed to parse simple index response: {}", e))
         })?;
 
-        Ok(VersionInfo {
-            package_name: info.info.name,
-            version: version_str,
-            is_prerelease: !parsed_version.pre.is_empty(),
-        })
+        Ok(package_info_from_simple(project))
+    }
+
+    /// Get the latest version of a package
+    pub async fn get_latest_version(
+        &self,
+        package_name: &str,
+        allow_prerelease: bool,
+        min_age_days: u32,
+    ) -> Result<VersionInfo> {
+        let info = self.get_package_info(package_name).await?;
+
+        self.newest_release(&info, allow_prerelease, min_age_days, |_| true)
+            .ok_or_else(|| {
+                ReleaserError::PyPiError(format!("No valid versions found for {}", package_name))
+            })
     }
 
     /// Get versions matching a constraint
@@ -158,42 +419,451 @@ impl PyPiClient {
         package_name: &str,
         constraint: &str,
         allow_prerelease: bool,
+        min_age_days: u32,
     ) -> Result<VersionInfo> {
         let info = self.get_package_info(package_name).await?;
         let (req, exclusions) = parse_version_constraint(constraint)?;
 
-        let mut versions: Vec<(semver::Version, String)> = info
-            .releases
-            .iter()
-            .filter(|(_, releases)| !releases.is_empty() && !releases.iter().all(|r| r.yanked))
-            .filter_map(|(version_str, _)| {
-                parse_python_version(version_str).map(|v| (v, version_str.clone()))
-            })
-            .filter(|(v, _)| req.matches(v))
-            .filter(|(v, _)| {
-                exclusions
+        self.newest_release(&info, allow_prerelease, min_age_days, |v| {
+            req.matches(v)
+                && exclusions
                     .iter()
                     .all(|(start, end)| !(v >= start && v < end))
+        })
+        .ok_or_else(|| {
+            ReleaserError::PyPiError(format!(
+                "No versions matching '{}' for {}",
+                constraint, package_name
+            ))
+        })
+    }
+
+    /// Newest non-yanked release past the cooldown that `accept` allows.
+    /// Releases are parsed once per package and walked newest first (on
+    /// epoch, then version), so the first hit is the answer.
+    fn newest_release(
+        &self,
+        info: &PyPiPackageInfo,
+        allow_prerelease: bool,
+        min_age_days: u32,
+        accept: impl Fn(&semver::Version) -> bool,
+    ) -> Option<VersionInfo> {
+        let parsed = self.parsed.get_or_parse(
+            &normalize_project_name(&info.info.name),
+            info.releases.keys(),
+        );
+        let now = Utc::now();
+
+        let newest = parsed
+            .newest_first()
+            .filter(|(version, _)| allow_prerelease || version.pre.is_empty())
+            .filter(|(version, _)| accept(version))
+            .find_map(|(_, version_str)| {
+                let files = info.releases.get(version_str)?;
+                let available = !files.is_empty() && !files.iter().all(|r| r.yanked);
+                (available && is_old_enough(files, min_age_days, now)).then(|| VersionInfo {
+                    version: version_str.to_string(),
+                    uploaded: first_upload(files),
+                })
+            });
+        newest
+    }
+
+    /// Resolve the version to use for a tracked package, honoring its constraint.
+    /// `None` when every candidate is still inside the cooldown: the package then
+    /// keeps its current pin, with a warning.
+    pub async fn latest_for(&self, package: &PackageConfig) -> Result<Option<VersionInfo>> {
+        let min_age_days = package
+            .min_release_age_days
+            .unwrap_or(self.min_release_age_days);
+
+        match self.resolve(package, min_age_days).await {
+            Err(ReleaserError::PyPiError(_))
+                if min_age_days > 0 && self.resolve(package, 0).await.is_ok() =>
+            {
+                warnings::warn(
+                    WarningKind::PyPi,
+                    Some(&package.name),
+                    format!(
+                        "no release is older than {} day(s) yet; keeping the current pin",
+                        min_age_days
+                    ),
+                );
+                Ok(None)
+            }
+            resolved => resolved.map(Some),
+        }
+    }
+
+    async fn resolve(&self, package: &PackageConfig, min_age_days: u32) -> Result<VersionInfo> {
+        match &package.version_constraint {
+            Some(constraint) => {
+                self.get_matching_version(
+                    &package.name,
+                    constraint,
+                    package.allow_prerelease,
+                    min_age_days,
+                )
+                .await
+            }
+            None => {
+                self.get_latest_version(&package.name, package.allow_prerelease, min_age_days)
+                    .await
+            }
+        }
+    }
+
+    /// Resolve versions for many packages with at most `concurrency` requests in flight.
+    /// Results are returned in the same order as `packages`; `on_checked` is called as
+    /// each package completes.
+    pub async fn check_many<F>(
+        &self,
+        packages: &[PackageConfig],
+        concurrency: usize,
+        on_checked: F,
+    ) -> Result<Vec<Option<VersionInfo>>>
+    where
+        F: Fn(&PackageConfig) + Send + Sync + 'static,
+    {
+        self.map_packages(
+            packages,
+            concurrency,
+            |client, package| async move { client.latest_for(&package).await },
+            on_checked,
+        )
+        .await
+    }
+
+    /// Run `fetch` for every package with at most `concurrency` requests in flight.
+    /// Results are returned in the same order as `packages`.
+    pub async fn map_packages<P, T, F, Fut, C>(
+        &self,
+        packages: &[P],
+        concurrency: usize,
+        fetch: F,
+        on_checked: C,
+    ) -> Result<Vec<T>>
+    where
+        P: Clone + Send + 'static,
+        T: Send + 'static,
+        F: Fn(PyPiClient, P) -> Fut,
+        Fut: Future<Output = Result<T>> + Send + 'static,
+        C: Fn(&P) + Send + Sync + 'static,
+    {
+        if packages.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let concurrency = concurrency.clamp(1, packages.len());
+        let semaphore = Arc::new(Semaphore::new(concurrency));
+        let on_checked = Arc::new(on_checked);
+        let mut join_set = JoinSet::new();
+
+        for (index, package) in packages.iter().cloned().enumerate() {
+            let on_checked = on_checked.clone();
+            let permit = semaphore.clone().acquire_owned().await.map_err(|_| {
+                ReleaserError::PyPiError("Failed to acquire PyPI concurrency permit".to_string())
+            })?;
+            let request = fetch(self.clone(), package.clone());
+
+            join_set.spawn(async move {
+                let _permit = permit;
+                let result = request.await?;
+                on_checked(&package);
+                Ok::<(usize, T), ReleaserError>((index, result))
+            });
+        }
+
+        let mut results: Vec<Option<T>> = (0..packages.len()).map(|_| None).collect();
+
+        while let Some(joined) = join_set.join_next().await {
+            match joined {
+                Ok(Ok((index, result))) => {
+                    results[index] = Some(result);
+                }
+                Ok(Err(err)) => return Err(err),
+                Err(err) => {
+                    return Err(ReleaserError::PyPiError(format!(
+                        "Failed to join PyPI request task: {}",
+                        err
+                    )))
+                }
+            }
+        }
+
+        results
+            .into_iter()
+            .enumerate()
+            .map(|(index, result)| {
+                result.ok_or_else(|| {
+                    ReleaserError::PyPiError(format!("Missing PyPI result for index {}", index))
+                })
             })
-            .collect();
+            .collect()
+    }
+}
 
-        if !allow_prerelease {
-            versions.retain(|(v, _)| v.pre.is_empty());
+/// PEP 503 project name normalization
+pub fn normalize_project_name(name: &str) -> String {
+    let mut normalized = String::with_capacity(name.len());
+    let mut last_was_separator = false;
+
+    for c in name.chars() {
+        if matches!(c, '-' | '_' | '.') {
+            if !last_was_separator {
+                normalized.push('-');
+            }
+            last_was_separator = true;
+        } else {
+            normalized.push(c.to_ascii_lowercase());
+            last_was_separator = false;
         }
+    }
 
-        versions.sort_by(|a, b| b.0.cmp(&a.0));
+    normalized
+}
 
-        let (parsed_version, version_str) = versions.into_iter().next().ok_or_else(|| {
-            ReleaserError::PyPiError(format!(
-                "No versions matching '{}' for {}",
-                constraint, package_name
-            ))
-        })?;
+/// Extract the version from a wheel or sdist filename of `project`. Legacy
+/// sdists keep the dashes of the project name (`python-dateutil-2.8.2.tar.gz`),
+/// so the name is stripped as a prefix, falling back to a suffix that is one
+/// of the listed `versions`
+fn version_from_filename(filename: &str, project: &str, versions: &[String]) -> Option<String> {
+    if let Some(stem) = filename.strip_suffix(".whl") {
+        return stem.split('-').nth(1).map(|v| v.to_string());
+    }
+
+    let stem = [".tar.gz", ".tar.bz2", ".tgz", ".zip", ".egg"]
+        .iter()
+        .find_map(|ext| filename.strip_suffix(ext))?;
+    let splits = || {
+        stem.match_indices('-')
+            .map(|(i, _)| (&stem[..i], &stem[i + 1..]))
+    };
+    let project = normalize_project_name(project);
+
+    splits()
+        .find(|(name, _)| normalize_project_name(name) == project)
+        .or_else(|| splits().find(|(_, version)| versions.iter().any(|v| v == version)))
+        .map(|(_, version)| version.to_string())
+}
+
+/// Build the JSON API view of a package from a Simple API listing
+fn package_info_from_simple(project: SimpleProject) -> PyPiPackageInfo {
+    let mut releases: std::collections::HashMap<String, Vec<ReleaseInfo>> = project
+        .versions
+        .iter()
+        .map(|version| (version.clone(), Vec::new()))
+        .collect();
+
+    for file in project.files {
+        let Some(version) = version_from_filename(&file.filename, &project.name, &project.versions)
+        else {
+            continue;
+        };
+
+        releases.entry(version).or_default().push(ReleaseInfo {
+            yanked: file.is_yanked(),
+            filename: file.filename,
+            url: file.url,
+            upload_time: file.upload_time.unwrap_or_default(),
+            digests: file.hashes,
+        });
+    }
+
+    let latest = releases
+        .iter()
+        .filter(|(_, files)| files.iter().any(|f| !f.yanked))
+        .filter_map(|(version, _)| version_key(version).map(|key| (key, version)))
+        .filter(|(key, _)| !key.is_prerelease())
+        .max_by(|a, b| a.0.cmp(&b.0))
+        .map(|(_, version)| version.clone())
+        .unwrap_or_default();
+
+    PyPiPackageInfo {
+        info: PackageInfo {
+            name: project.name,
+            version: latest,
+            summary: None,
+            home_page: None,
+            project_urls: None,
+            license: None,
+            license_expression: None,
+            classifiers: Vec::new(),
+            requires_dist: None,
+        },
+        releases,
+        urls: Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_metadata_store_fetches_each_package_once() {
+        let store = PackageMetadataStore::new();
+        let calls = std::sync::atomic::AtomicUsize::new(0);
+        let fetch = || async {
+            calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Ok(Some(json!({"info": {"name": "Plone.API"}})))
+        };
+
+        let first = store.get_or_fetch("Plone.API", fetch).await.unwrap();
+        let second = store.get_or_fetch("plone-api", fetch).await.unwrap();
+
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 1);
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn test_normalize_project_name() {
+        assert_eq!(normalize_project_name("Plone.API"), "plone-api");
+        assert_eq!(normalize_project_name("zope__interface"), "zope-interface");
+    }
+
+    #[test]
+    fn test_parse_requirement() {
+        assert_eq!(
+            parse_requirement("plone.base (>=1.0.3)"),
+            Some(Requirement {
+                name: "plone.base".to_string(),
+                specifier: ">=1.0.3".to_string(),
+            })
+        );
+        assert_eq!(
+            parse_requirement("Products.CMFCore[zsql]>=3.0, <4 ; python_version >= \"3.8\""),
+            Some(Requirement {
+                name: "Products.CMFCore".to_string(),
+                specifier: ">=3.0,<4".to_string(),
+            })
+        );
+        assert_eq!(parse_requirement("setuptools").unwrap().specifier, "");
+        assert!(parse_requirement("pytest; extra == \"test\"").is_none());
+    }
+
+    #[test]
+    fn test_min_release_age_filter() {
+        let now = parse_upload_time("2024-05-10T12:00:00Z").unwrap();
+        let release = |upload_time: &str| ReleaseInfo {
+            filename: "pkg-1.0.tar.gz".to_string(),
+            url: String::new(),
+            upload_time: upload_time.to_string(),
+            yanked: false,
+            digests: Default::default(),
+        };
+
+        let fresh = [release("2024-05-08T09:30:00")];
+        let settled = [release("2024-05-01T00:00:00.123456Z")];
+        assert!(!is_old_enough(&fresh, 3, now));
+        assert!(is_old_enough(&fresh, 0, now));
+        assert!(is_old_enough(&settled, 3, now));
+        assert!(is_old_enough(&[release("")], 3, now));
+    }
+
+    #[tokio::test]
+    async fn test_latest_for_keeps_pin_inside_cooldown() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
 
-        Ok(VersionInfo {
-            package_name: info.info.name,
-            version: version_str,
-            is_prerelease: !parsed_version.pre.is_empty(),
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        // First published today
+        let body = json!({
+            "info": {"name": "fresh.pkg", "version": "1.0"},
+            "releases": {"1.0": [{
+                "filename": "fresh.pkg-1.0.tar.gz",
+                "url": "https://files/a",
+                "upload_time": Utc::now().to_rfc3339(),
+                "yanked": false,
+                "digests": {}
+            }]}
         })
+        .to_string();
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut buffer = vec![0; 4096];
+                let _ = stream.read(&mut buffer).await;
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+        });
+
+        let config = PyPiConfig {
+            index_url: url,
+            min_release_age_days: 7,
+            ..PyPiConfig::default()
+        };
+        let client = PyPiClient::with_config(&config, &HttpConfig::default()).unwrap();
+        let package = |value| serde_json::from_value::<PackageConfig>(value).unwrap();
+
+        let held_back = client
+            .latest_for(&package(json!({"name": "fresh.pkg"})))
+            .await
+            .unwrap();
+        assert!(held_back.is_none());
+
+        let settled = client
+            .latest_for(&package(
+                json!({"name": "fresh.pkg", "min_release_age_days": 0}),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(settled.unwrap().version, "1.0");
+
+        // Nothing would match even without the cooldown
+        assert!(client
+            .latest_for(&package(
+                json!({"name": "fresh.pkg", "version_constraint": ">=2.0"})
+            ))
+            .await
+            .is_err());
+    }
+
+    #[test]
+    fn test_package_info_from_simple() {
+        let project: SimpleProject = serde_json::from_value(json!({
+            "meta": {"api-version": "1.1"},
+            "name": "plone-api",
+            "versions": ["2.0.0", "2.1.0", "2.2.0a1"],
+            "files": [
+                {"filename": "plone.api-2.0.0.tar.gz", "url": "https://files/a", "hashes": {}},
+                {"filename": "plone.api-2.1.0-py3-none-any.whl", "url": "https://files/b", "hashes": {}},
+                {"filename": "plone.api-2.1.0.tar.gz", "url": "https://files/c", "hashes": {}, "yanked": "broken"},
+                {"filename": "plone.api-2.2.0a1.tar.gz", "url": "https://files/d", "hashes": {}}
+            ]
+        }))
+        .unwrap();
+
+        let info = package_info_from_simple(project);
+
+        assert_eq!(info.info.name, "plone-api");
+        assert_eq!(info.info.version, "2.1.0");
+        assert_eq!(info.releases["2.1.0"].len(), 2);
+        assert!(info.releases["2.1.0"].iter().any(|r| r.yanked));
+        assert!(info.releases.contains_key("2.2.0a1"));
+
+        // Legacy sdists keep the dashes of the project name
+        let project: SimpleProject = serde_json::from_value(json!({
+            "name": "python-dateutil",
+            "versions": ["2.8.1", "2.8.2"],
+            "files": [
+                {"filename": "python-dateutil-2.8.2.tar.gz", "url": "https://files/a", "hashes": {}},
+                {"filename": "dateutil-2.8.1.tar.gz", "url": "https://files/b", "hashes": {}}
+            ]
+        }))
+        .unwrap();
+
+        let info = package_info_from_simple(project);
+
+        assert_eq!(info.info.version, "2.8.2");
+        assert_eq!(info.releases["2.8.2"].len(), 1);
+        assert_eq!(info.releases["2.8.1"].len(), 1);
+        assert!(!info.releases.contains_key("dateutil"));
     }
 }
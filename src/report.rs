@@ -0,0 +1,218 @@
+//! Digest of what happened to the pins over a time window (`bldr report`),
+//! rendered as Markdown for team notes
+
+use crate::audit::AuditFinding;
+use crate::buildout::{PinDiff, VersionUpdate};
+use crate::error::{ReleaserError, Result};
+use chrono::{Duration, NaiveDate};
+use serde::Serialize;
+
+/// A release tag created in the window
+#[derive(Debug, Clone, Serialize)]
+pub struct ReportRelease {
+    pub tag: String,
+    pub date: String,
+}
+
+/// A commit that changed a versions file in the window
+#[derive(Debug, Clone, Serialize)]
+pub struct ReportCommit {
+    pub sha: String,
+    pub date: String,
+    pub subject: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Report {
+    pub since: String,
+    pub until: String,
+    pub releases: Vec<ReportRelease>,
+    pub commits: Vec<ReportCommit>,
+    /// Net pin changes over the window
+    pub updated: Vec<VersionUpdate>,
+    /// Net pins added and removed over the window
+    #[serde(flatten)]
+    pub pin_changes: PinDiff,
+    /// `None` when the check was skipped (`--offline`) or failed
+    pub pending: Option<Vec<VersionUpdate>>,
+    /// Advisories published in the window for current pins; `None` when skipped
+    pub advisories: Option<Vec<AuditFinding>>,
+}
+
+/// Start of the window: `7d`, `2w`, `24h` before `today`, or a `YYYY-MM-DD` date
+pub fn parse_since(since: &str, today: NaiveDate) -> Result<NaiveDate> {
+    let since = since.trim();
+    if let Ok(date) = NaiveDate::parse_from_str(since, "%Y-%m-%d") {
+        return Ok(date);
+    }
+
+    let invalid = || {
+        ReleaserError::ConfigError(format!(
+            "invalid --since '{}': use a duration such as 7d, 2w or 48h, or a date (YYYY-MM-DD)",
+            since
+        ))
+    };
+    let (unit_at, unit) = since.char_indices().last().ok_or_else(invalid)?;
+    let amount: i64 = since[..unit_at].parse().map_err(|_| invalid())?;
+    if amount <= 0 {
+        return Err(invalid());
+    }
+    let span = match unit {
+        'd' => Duration::days(amount),
+        'w' => Duration::weeks(amount),
+        'h' => Duration::hours(amount),
+        _ => return Err(invalid()),
+    };
+
+    // Hours are rounded to whole days, as commit and tag dates are
+    Ok(today - Duration::days((span.num_hours() + 23) / 24))
+}
+
+impl Report {
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!("# Buildout report {} – {}\n\n", self.since, self.until);
+
+        out.push_str("## Releases\n\n");
+        if self.releases.is_empty() {
+            out.push_str("_None._\n");
+        }
+        for release in &self.releases {
+            out.push_str(&format!("- **{}** ({})\n", release.tag, release.date));
+        }
+
+        out.push_str("\n## Updates applied\n\n");
+        let pin_changes = &self.pin_changes;
+        if self.updated.is_empty() && pin_changes.added.is_empty() && pin_changes.removed.is_empty()
+        {
+            out.push_str("_None._\n");
+        }
+        for update in &self.updated {
+            out.push_str(&format!(
+                "- {} {} → {}\n",
+                update.package_name, update.old_version, update.new_version
+            ));
+        }
+        for pin in &pin_changes.added {
+            out.push_str(&format!("- Added {} {}\n", pin.name, pin.version));
+        }
+        for pin in &pin_changes.removed {
+            out.push_str(&format!("- Removed {}\n", pin.name));
+        }
+        if !self.commits.is_empty() {
+            out.push_str(&format!(
+                "\n{} commit(s) changed the versions files:\n\n",
+                self.commits.len()
+            ));
+            for commit in &self.commits {
+                out.push_str(&format!(
+                    "- `{}` {} ({})\n",
+                    commit.sha, commit.subject, commit.date
+                ));
+            }
+        }
+
+        out.push_str("\n## Pending updates\n\n");
+        match &self.pending {
+            None => out.push_str("_Not checked._\n"),
+            Some(pending) if pending.is_empty() => {
+                out.push_str("_All tracked packages are up to date._\n")
+            }
+            Some(pending) => {
+                for update in pending {
+                    out.push_str(&format!(
+                        "- {} {} → {}\n",
+                        update.package_name, update.old_version, update.new_version
+                    ));
+                }
+            }
+        }
+
+        out.push_str("\n## New advisories\n\n");
+        match &self.advisories {
+            None => out.push_str("_Not checked._\n"),
+            Some(findings) if findings.is_empty() => out.push_str("_None._\n"),
+            Some(findings) => {
+                for finding in findings {
+                    for vuln in &finding.vulnerabilities {
+                        out.push_str(&format!(
+                            "- **{}** {} {} [{}]",
+                            vuln.id,
+                            finding.package,
+                            finding.version,
+                            vuln.severity.as_deref().unwrap_or("UNKNOWN")
+                        ));
+                        if let Some(summary) = &vuln.summary {
+                            out.push_str(&format!(": {}", summary));
+                        }
+                        if !vuln.fixed_versions.is_empty() {
+                            out.push_str(&format!(
+                                " (fixed in {})",
+                                vuln.fixed_versions.join(", ")
+                            ));
+                        }
+                        out.push('\n');
+                    }
+                }
+            }
+        }
+
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buildout::PinnedPackage;
+
+    #[test]
+    fn test_parse_since() {
+        let today = NaiveDate::from_ymd_opt(2025, 6, 8).unwrap();
+        let date = |s| parse_since(s, today).unwrap().to_string();
+
+        assert_eq!(date("7d"), "2025-06-01");
+        assert_eq!(date("2w"), "2025-05-25");
+        assert_eq!(date("48h"), "2025-06-06");
+        assert_eq!(date("2025-01-31"), "2025-01-31");
+        assert!(parse_since("soon", today).is_err());
+        assert!(parse_since("", today).is_err());
+        assert!(parse_since("7é", today).is_err());
+        assert!(parse_since("é", today).is_err());
+        assert!(parse_since("0d", today).is_err());
+        assert!(parse_since("-7d", today).is_err());
+    }
+
+    #[test]
+    fn test_report_markdown() {
+        let report = Report {
+            since: "2025-06-01".to_string(),
+            until: "2025-06-08".to_string(),
+            releases: vec![ReportRelease {
+                tag: "v1.8.0".to_string(),
+                date: "2025-06-03".to_string(),
+            }],
+            commits: Vec::new(),
+            updated: vec![VersionUpdate {
+                package_name: "plone.api".to_string(),
+                old_version: "2.0.0".to_string(),
+                new_version: "2.1.0".to_string(),
+            }],
+            pin_changes: PinDiff {
+                added: vec![PinnedPackage {
+                    name: "plone.foo".to_string(),
+                    version: "1.0".to_string(),
+                }],
+                removed: Vec::new(),
+            },
+            pending: Some(Vec::new()),
+            advisories: None,
+        };
+
+        let markdown = report.to_markdown();
+        assert!(markdown.starts_with("# Buildout report 2025-06-01 – 2025-06-08\n"));
+        assert!(markdown.contains("## Releases\n\n- **v1.8.0** (2025-06-03)\n"));
+        assert!(markdown.contains("- plone.api 2.0.0 → 2.1.0\n- Added plone.foo 1.0\n"));
+        assert!(markdown.contains("_All tracked packages are up to date._"));
+        assert!(markdown.ends_with("## New advisories\n\n_Not checked._\n"));
+    }
+}
@@ -0,0 +1,198 @@
+use crate::config::{HttpConfig, PyPiConfig};
+use crate::error::{ReleaserError, Result};
+use crate::http::{self, RetryPolicy};
+use crate::pypi::ReleaseInfo;
+use chrono::{Datelike, Duration as Days, NaiveDate};
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
+
+const USER_AGENT: &str = concat!("bldr/", env!("CARGO_PKG_VERSION"));
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Downloads of a package in one Monday-to-Sunday week
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WeeklyDownloads {
+    pub week_start: NaiveDate,
+    pub downloads: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct OverallResponse {
+    data: Vec<DailyDownloads>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DailyDownloads {
+    date: String,
+    downloads: u64,
+}
+
+/// Client for the pypistats.org API (daily download counts per project)
+pub struct StatsClient {
+    client: reqwest::Client,
+    base_url: String,
+    retry: RetryPolicy,
+}
+
+impl StatsClient {
+    pub fn with_config(config: &PyPiConfig, http: &HttpConfig) -> Result<Self> {
+        let builder = reqwest::Client::builder()
+            .user_agent(USER_AGENT)
+            .timeout(REQUEST_TIMEOUT);
+
+        Ok(Self {
+            client: http::configure_client(builder, http)?.build()?,
+            base_url: config.stats_url.trim_end_matches('/').to_string(),
+            retry: RetryPolicy::from_config(http),
+        })
+    }
+
+    /// Complete weeks of downloads (mirrors excluded), oldest first
+    pub async fn weekly_downloads(&self, package: &str) -> Result<Vec<WeeklyDownloads>> {
+        let url = format!(
+            "{}/packages/{}/overall?mirrors=false",
+            self.base_url,
+            package.to_lowercase()
+        );
+        let response = self.retry.send(self.client.get(&url)).await?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Err(ReleaserError::PackageNotFound(package.to_string()));
+        }
+        if !response.status().is_success() {
+            return Err(ReleaserError::PyPiError(format!(
+                "pypistats returned HTTP {} for {}",
+                response.status(),
+                package
+            )));
+        }
+
+        let overall: OverallResponse = response.json().await?;
+        let daily: Vec<(NaiveDate, u64)> = overall
+            .data
+            .into_iter()
+            .filter_map(|d| {
+                NaiveDate::parse_from_str(&d.date, "%Y-%m-%d")
+                    .ok()
+                    .map(|date| (date, d.downloads))
+            })
+            .collect();
+
+        Ok(weekly_totals(&daily))
+    }
+}
+
+/// Sum daily counts per week, dropping weeks without data for all seven days
+pub fn weekly_totals(daily: &[(NaiveDate, u64)]) -> Vec<WeeklyDownloads> {
+    let mut weeks: BTreeMap<NaiveDate, (u64, usize)> = BTreeMap::new();
+    for (date, downloads) in daily {
+        let week_start = *date - Days::days(date.weekday().num_days_from_monday() as i64);
+        let entry = weeks.entry(week_start).or_default();
+        entry.0 += downloads;
+        entry.1 += 1;
+    }
+
+    weeks
+        .into_iter()
+        .filter(|(_, (_, days))| *days >= 7)
+        .map(|(week_start, (downloads, _))| WeeklyDownloads {
+            week_start,
+            downloads,
+        })
+        .collect()
+}
+
+/// First upload date of every release, oldest first
+pub fn release_dates(releases: &HashMap<String, Vec<ReleaseInfo>>) -> Vec<(NaiveDate, String)> {
+    let mut dates: Vec<(NaiveDate, String)> = releases
+        .iter()
+        .filter_map(|(version, files)| {
+            files
+                .iter()
+                .filter_map(|f| f.upload_time.get(..10))
+                .filter_map(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+                .min()
+                .map(|date| (date, version.clone()))
+        })
+        .collect();
+    dates.sort();
+    dates
+}
+
+/// Versions first uploaded during the week starting at `week_start`
+pub fn released_in_week(dates: &[(NaiveDate, String)], week_start: NaiveDate) -> Vec<&str> {
+    let week_end = week_start + Days::days(7);
+    dates
+        .iter()
+        .filter(|(date, _)| *date >= week_start && *date < week_end)
+        .map(|(_, version)| version.as_str())
+        .collect()
+}
+
+/// Percentage change of the last week compared to the one before
+pub fn trend(weeks: &[WeeklyDownloads]) -> Option<f64> {
+    let [.., previous, last] = weeks else {
+        return None;
+    };
+    if previous.downloads == 0 {
+        return None;
+    }
+    Some((last.downloads as f64 - previous.downloads as f64) / previous.downloads as f64 * 100.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_weekly_totals_skip_partial_weeks() {
+        // 2024-01-01 is a Monday; the second week only has three days of data
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let daily: Vec<(NaiveDate, u64)> = (0..10).map(|d| (start + Days::days(d), 10)).collect();
+
+        assert_eq!(
+            weekly_totals(&daily),
+            vec![WeeklyDownloads {
+                week_start: start,
+                downloads: 70
+            }]
+        );
+    }
+
+    #[test]
+    fn test_released_in_week() {
+        let file = |upload_time: &str| ReleaseInfo {
+            filename: String::new(),
+            url: String::new(),
+            upload_time: upload_time.to_string(),
+            yanked: false,
+            digests: Default::default(),
+        };
+        let releases = HashMap::from([
+            ("1.0".to_string(), vec![file("2024-01-02T10:00:00")]),
+            (
+                "1.1".to_string(),
+                vec![file("2024-01-12T10:00:00"), file("2024-01-08T09:00:00")],
+            ),
+        ]);
+
+        let dates = release_dates(&releases);
+        let week = |day| NaiveDate::from_ymd_opt(2024, 1, day).unwrap();
+        assert_eq!(released_in_week(&dates, week(1)), vec!["1.0"]);
+        assert_eq!(released_in_week(&dates, week(8)), vec!["1.1"]);
+        assert!(released_in_week(&dates, week(15)).is_empty());
+    }
+
+    #[test]
+    fn test_trend() {
+        let week = |day, downloads| WeeklyDownloads {
+            week_start: NaiveDate::from_ymd_opt(2024, 1, day).unwrap(),
+            downloads,
+        };
+
+        assert_eq!(trend(&[week(1, 100), week(8, 125)]), Some(25.0));
+        assert_eq!(trend(&[week(8, 125)]), None);
+        assert_eq!(trend(&[week(1, 0), week(8, 125)]), None);
+    }
+}
@@ -0,0 +1,12 @@
+//! Helpers shared by the unit tests
+
+use tempfile::TempDir;
+
+/// Empty directory for a test, named after `name`. It is removed with its
+/// content when the guard is dropped, including when an assertion fails.
+pub fn temp_dir(name: &str) -> TempDir {
+    tempfile::Builder::new()
+        .prefix(&format!("bldr-{}-", name))
+        .tempdir()
+        .expect("create a temporary directory")
+}
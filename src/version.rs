@@ -1,261 +1,11 @@
 use crate::config::{MetadataFileConfig, VersionBumpType, VersionConfig};
 use crate::error::{ReleaserError, Result};
+use crate::warnings::{self, WarningKind};
 use regex::Regex;
 use std::cmp::Ordering;
 use std::path::Path;
 
-pub mod python {
-    use crate::error::{ReleaserError, Result};
-    use regex::Regex;
-    use semver::{BuildMetadata, Prerelease};
-
-    /// Parse a Python version string into semver
-    pub fn parse_python_version(version: &str) -> Option<semver::Version> {
-        // Handle common Python version formats
-        // PEP 440: X.Y.Z, X.Y.ZaN, X.Y.ZbN, X.Y.ZrcN, X.Y.Z.postN, X.Y.Z.devN
-
-        let version = version.trim().trim_start_matches('v').replace('_', ".");
-
-        // Try direct semver parse first
-        if let Ok(v) = semver::Version::parse(&version) {
-            return Some(v);
-        }
-
-        let (core, local_suffix) = match version.split_once('+') {
-            Some((core, local)) => (core, Some(local)),
-            None => (version.as_str(), None),
-        };
-
-        // Convert Python-style pre-releases to semver
-        let re = Regex::new(concat!(
-            r"^(?P<major>\d+)(?:\.(?P<minor>\d+))?(?:\.(?P<patch>\d+))?",
-            r"(?P<rest>(?:\.\d+)*)",
-            // Pre-release
-            r"(?:(?P<pre_sep>[-_.]?)(?P<pre_label>a|b|rc|alpha|beta|c|pre|preview)(?P<pre_num>\d+)?)?",
-            // Post release
-            r"(?:(?P<post_sep>[-_.]?)(?P<post_label>post|rev|r)(?P<post_num>\d+)?)?",
-            // Dev release
-            r"(?:(?P<dev_sep>[-_.]?)(?P<dev_label>dev)(?P<dev_num>\d+)?)?$"
-        )).ok()?;
-
-        if let Some(caps) = re.captures(core) {
-            let major: u64 = caps.name("major")?.as_str().parse().ok()?;
-            let minor: u64 = caps
-                .name("minor")
-                .map(|m| m.as_str().parse().ok())
-                .flatten()
-                .unwrap_or(0);
-            let patch: u64 = caps
-                .name("patch")
-                .map(|m| m.as_str().parse().ok())
-                .flatten()
-                .unwrap_or(0);
-
-            let mut pre_parts: Vec<String> = Vec::new();
-            if let Some(pre_label) = caps.name("pre_label") {
-                let label = match pre_label.as_str() {
-                    "a" | "alpha" => "alpha",
-                    "b" | "beta" => "beta",
-                    "rc" | "c" | "pre" | "preview" => "rc",
-                    _ => return None,
-                };
-                pre_parts.push(label.to_string());
-
-                if let Some(pre_num) = caps.name("pre_num") {
-                    pre_parts.push(pre_num.as_str().to_string());
-                }
-            }
-
-            if let Some(dev_label) = caps.name("dev_label") {
-                pre_parts.push(dev_label.as_str().to_string());
-                if let Some(dev_num) = caps.name("dev_num") {
-                    pre_parts.push(dev_num.as_str().to_string());
-                }
-            }
-
-            let pre = if pre_parts.is_empty() {
-                Prerelease::EMPTY
-            } else {
-                Prerelease::new(&pre_parts.join(".")).ok()?
-            };
-
-            let mut build_parts: Vec<String> = caps
-                .name("rest")
-                .map(|m| {
-                    m.as_str()
-                        .trim_start_matches('.')
-                        .split('.')
-                        .filter(|s| !s.is_empty())
-                        .map(|s| s.replace('_', "-"))
-                        .collect::<Vec<_>>()
-                })
-                .unwrap_or_default();
-            if caps.name("post_label").is_some() {
-                let post_num = caps.name("post_num").map(|m| m.as_str()).unwrap_or("0");
-                build_parts.push(format!("post{}", post_num));
-            }
-
-            if let Some(local) = local_suffix {
-                if !local.is_empty() {
-                    build_parts.push(local.replace('_', "-"));
-                }
-            }
-
-            let build = if build_parts.is_empty() {
-                BuildMetadata::EMPTY
-            } else {
-                BuildMetadata::new(&build_parts.join(".")).ok()?
-            };
-
-            return Some(semver::Version {
-                major,
-                minor,
-                patch,
-                pre,
-                build,
-            });
-        }
-
-        None
-    }
-
-    /// Parse a Python version constraint to semver requirement
-    pub fn parse_version_constraint(
-        constraint: &str,
-    ) -> Result<(semver::VersionReq, Vec<(semver::Version, semver::Version)>)> {
-        // Convert Python-style constraints to semver
-        // ~=X.Y -> >=X.Y.0, <X+1.0.0 (approximately)
-        // ==X.Y.Z -> =X.Y.Z
-        // >=X.Y.Z -> >=X.Y.Z
-        // etc.
-
-        let constraint = constraint.trim();
-
-        if constraint.is_empty() {
-            return Err(ReleaserError::VersionError(
-                "Empty version constraint".to_string(),
-            ));
-        }
-
-        // Handle ~= (compatible release)
-        if constraint.contains("||") {
-            return Err(ReleaserError::VersionError(
-                "OR (||) constraints are not supported".to_string(),
-            ));
-        }
-
-        if constraint.starts_with("~=") {
-            let version = constraint[2..].trim();
-            let parsed = parse_python_version(version)
-                .ok_or_else(|| ReleaserError::VersionError(version.to_string()))?;
-
-            let release_len = Regex::new(r"^(\d+(?:\.\d+)*)")
-                .ok()
-                .and_then(|re| re.captures(version))
-                .map(|caps| caps[1].split('.').count())
-                .unwrap_or(0);
-
-            let upper_bound = match release_len {
-                0 | 1 | 2 => format!("{}.0.0", parsed.major + 1),
-                _ => format!("{}.{}.0", parsed.major, parsed.minor + 1),
-            };
-
-            let req = semver::VersionReq::parse(&format!(">={}, <{}", parsed, upper_bound))
-                .map_err(|e| ReleaserError::VersionError(e.to_string()))?;
-
-            return Ok((req, Vec::new()));
-        }
-
-        let mut exclusions = Vec::new();
-
-        let parts: Result<Vec<String>> = constraint
-            .split(',')
-            .map(|raw| {
-                let (expr, mut excluded) = normalize_constraint_part(raw)?;
-                exclusions.append(&mut excluded);
-                Ok(expr)
-            })
-            .collect();
-
-        let normalized = parts?.join(", ");
-
-        let req = semver::VersionReq::parse(&normalized)
-            .map_err(|e| ReleaserError::VersionError(format!("{}: {}", normalized, e)))?;
-
-        Ok((req, exclusions))
-    }
-
-    pub fn normalize_constraint_part(
-        part: &str,
-    ) -> Result<(String, Vec<(semver::Version, semver::Version)>)> {
-        let part = part.trim();
-
-        if part.is_empty() {
-            return Err(ReleaserError::VersionError(
-                "Empty constraint segment".to_string(),
-            ));
-        }
-
-        // Wildcard equality (==1.2.*)
-        let wildcard_re = Regex::new(r"^(==|!=)\s*(\d+)(?:\.(\d+))?\.\*$")
-            .map_err(|e| ReleaserError::VersionError(e.to_string()))?;
-
-        if let Some(caps) = wildcard_re.captures(part) {
-            let op = caps.get(1).map(|m| m.as_str()).unwrap_or("==");
-            let major: u64 = caps[2]
-                .parse()
-                .map_err(|_| ReleaserError::VersionError(part.to_string()))?;
-            let minor: Option<u64> = caps.get(3).map(|m| m.as_str().parse().ok()).flatten();
-
-            let normalized_op = if op == "==" { "=" } else { op };
-
-            let (lower, upper) = if let Some(minor) = minor {
-                (
-                    semver::Version::new(major, minor, 0),
-                    semver::Version::new(major, minor + 1, 0),
-                )
-            } else {
-                (
-                    semver::Version::new(major, 0, 0),
-                    semver::Version::new(major + 1, 0, 0),
-                )
-            };
-
-            let expr = match normalized_op {
-                "=" => format!(">={}, <{}", lower, upper),
-                "!=" => "*".to_string(),
-                _ => part.to_string(),
-            };
-
-            let mut exclusions = Vec::new();
-            if normalized_op == "!=" {
-                exclusions.push((lower, upper));
-            }
-
-            return Ok((expr, exclusions));
-        }
-
-        let re = Regex::new(r"^(<=|>=|==|===|!=|<|>|=)?\s*(.+)$")
-            .map_err(|e| ReleaserError::VersionError(e.to_string()))?;
-
-        if let Some(caps) = re.captures(part) {
-            let op = caps.get(1).map(|m| m.as_str()).unwrap_or("=");
-            let version_str = caps.get(2).map(|m| m.as_str()).unwrap_or("");
-
-            let parsed = parse_python_version(version_str)
-                .ok_or_else(|| ReleaserError::VersionError(part.to_string()))?;
-
-            let normalized_op = match op {
-                "==" | "=" | "===" => "=",
-                other => other,
-            };
-
-            return Ok((format!("{}{}", normalized_op, parsed), Vec::new()));
-        }
-
-        Err(ReleaserError::VersionError(part.to_string()))
-    }
-}
+pub use rust_buildout_releaser::version::python;
 
 /// Semantic version representation backed by the semver crate
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -285,44 +35,55 @@ impl Version {
 
     /// Bump the version according to the bump type
     pub fn bump(&self, bump_type: VersionBumpType) -> Self {
+        self.bump_at(bump_type, chrono::Local::now().naive_local())
+    }
+
+    /// Bump the version, stamping `Build` bumps with `now`. A pre-release
+    /// becomes its own release when that is the next version of the level
+    /// (`1.3.0-rc.1` → `1.3.0` for `Minor` and `Patch`)
+    pub fn bump_at(&self, bump_type: VersionBumpType, now: chrono::NaiveDateTime) -> Self {
         let mut bumped = self.inner.clone();
+        bumped.pre = semver::Prerelease::EMPTY;
+        bumped.build = semver::BuildMetadata::EMPTY;
+        let pre = !self.inner.pre.is_empty();
 
         match bump_type {
             VersionBumpType::Major => {
-                bumped.major += 1;
-                bumped.minor = 0;
-                bumped.patch = 0;
+                if !(pre && bumped.minor == 0 && bumped.patch == 0) {
+                    bumped.major += 1;
+                    bumped.minor = 0;
+                    bumped.patch = 0;
+                }
             }
             VersionBumpType::Minor => {
-                bumped.minor += 1;
-                bumped.patch = 0;
+                if !(pre && bumped.patch == 0) {
+                    bumped.minor += 1;
+                    bumped.patch = 0;
+                }
             }
             VersionBumpType::Patch => {
-                bumped.patch += 1;
+                if !pre {
+                    bumped.patch += 1;
+                }
+            }
+            VersionBumpType::Prerelease => {
+                if self.inner.pre.is_empty() {
+                    bumped.patch += 1;
+                }
+                bumped.pre = next_prerelease(&self.inner.pre);
+            }
+            VersionBumpType::Build => {
+                let stamp = now.format("%Y%m%d%H%M").to_string();
+                bumped.pre = self.inner.pre.clone();
+                bumped.build = semver::BuildMetadata::new(&stamp)
+                    .expect("a timestamp is valid build metadata");
             }
+            VersionBumpType::None => return self.clone(),
         }
 
-        bumped.pre = semver::Prerelease::EMPTY;
-        bumped.build = semver::BuildMetadata::EMPTY;
-
         Self { inner: bumped }
     }
 
-    /// Get the major component
-    pub fn major(&self) -> u64 {
-        self.inner.major
-    }
-
-    /// Get the minor component
-    pub fn minor(&self) -> u64 {
-        self.inner.minor
-    }
-
-    /// Get the patch component
-    pub fn patch(&self) -> u64 {
-        self.inner.patch
-    }
-
     /// Get prerelease identifier if present
     pub fn prerelease(&self) -> Option<&str> {
         if self.inner.pre.is_empty() {
@@ -331,15 +92,23 @@ impl Version {
             Some(self.inner.pre.as_str())
         }
     }
+}
 
-    /// Get build metadata if present
-    pub fn build_metadata(&self) -> Option<&str> {
-        if self.inner.build.is_empty() {
-            None
-        } else {
-            Some(self.inner.build.as_str())
+/// `rc.1` for a final release, else `pre` with its last number incremented
+/// (`beta.1` → `beta.2`; `beta` → `beta.1`)
+fn next_prerelease(pre: &semver::Prerelease) -> semver::Prerelease {
+    let next = if pre.is_empty() {
+        "rc.1".to_string()
+    } else {
+        let mut parts: Vec<String> = pre.as_str().split('.').map(str::to_string).collect();
+        match parts.last().and_then(|last| last.parse::<u64>().ok()) {
+            Some(number) => *parts.last_mut().unwrap() = (number + 1).to_string(),
+            None => parts.push("1".to_string()),
         }
-    }
+        parts.join(".")
+    };
+    semver::Prerelease::new(&next)
+        .expect("incrementing the last number of a pre-release keeps it valid")
 }
 
 impl std::fmt::Display for Version {
@@ -360,94 +129,6 @@ impl Ord for Version {
     }
 }
 
-#[cfg(test)]
-mod python_tests {
-    use super::python::{
-        normalize_constraint_part, parse_python_version, parse_version_constraint,
-    };
-
-    #[test]
-    fn parses_additional_python_versions() {
-        let v = parse_python_version("1.2").expect("should parse minor-only");
-        assert_eq!(v.to_string(), "1.2.0");
-
-        let v = parse_python_version("2.0rc1").expect("should parse rc prerelease");
-        assert_eq!(v.to_string(), "2.0.0-rc.1");
-
-        let v = parse_python_version("3.4.post2").expect("should parse post release");
-        assert_eq!(v.to_string(), "3.4.0+post2");
-
-        let v = parse_python_version("4.5.dev7").expect("should parse dev prerelease");
-        assert_eq!(v.to_string(), "4.5.0-dev.7");
-
-        let v = parse_python_version("1.0+local.tag").expect("should parse local metadata");
-        assert_eq!(v.to_string(), "1.0.0+local.tag");
-
-        let v = parse_python_version("4.2.3.1").expect("should parse four-segment release");
-        assert_eq!(v.to_string(), "4.2.3+1");
-
-        let v =
-            parse_python_version("4.2.3.1rc1").expect("should parse four-segment rc prerelease");
-        assert_eq!(v.to_string(), "4.2.3-rc.1+1");
-
-        let v =
-            parse_python_version("4.2.3.28b3").expect("should parse four-segment beta prerelease");
-        assert_eq!(v.to_string(), "4.2.3-beta.3+28");
-
-        let v =
-            parse_python_version("4.2.4.8a2").expect("should parse four-segment alpha prerelease");
-        assert_eq!(v.to_string(), "4.2.4-alpha.2+8");
-
-        let v = parse_python_version("2.5").expect("should parse two-segment release");
-        assert_eq!(v.to_string(), "2.5.0");
-
-        let v = parse_python_version("7").expect("should parse single-segment release");
-        assert_eq!(v.to_string(), "7.0.0");
-    }
-
-    #[test]
-    fn parses_wildcard_constraints() {
-        let (req, exclusions) =
-            parse_version_constraint("==3.8.*").expect("should parse wildcard equality");
-        let matches = req.matches(&semver::Version::parse("3.8.5").unwrap());
-        assert!(matches, "should accept version within wildcard range");
-        assert!(exclusions.is_empty());
-
-        let (req, exclusions) =
-            parse_version_constraint("!=2.*").expect("should parse wildcard inequality");
-        assert!(req.matches(&semver::Version::parse("1.9.9").unwrap()));
-        assert_eq!(exclusions.len(), 1);
-        let (lower, upper) = &exclusions[0];
-        assert_eq!(lower, &semver::Version::new(2, 0, 0));
-        assert_eq!(upper, &semver::Version::new(3, 0, 0));
-    }
-
-    #[test]
-    fn parses_partial_comparators() {
-        let (req, exclusions) =
-            parse_version_constraint(">=3.8").expect("should parse partial comparator");
-        assert!(req.matches(&semver::Version::parse("3.8.1").unwrap()));
-        assert!(exclusions.is_empty());
-
-        let (req, exclusions) =
-            parse_version_constraint("~=3.8").expect("should parse compatible release");
-        assert!(req.matches(&semver::Version::parse("3.8.9").unwrap()));
-        assert!(!req.matches(&semver::Version::parse("4.0.0").unwrap()));
-        assert!(exclusions.is_empty());
-    }
-
-    #[test]
-    fn normalizes_constraint_parts() {
-        let (normalized, exclusions) = normalize_constraint_part("==1.2").unwrap();
-        assert_eq!(normalized, "=1.2.0");
-        assert!(exclusions.is_empty());
-
-        let (normalized, exclusions) = normalize_constraint_part("< 1").unwrap();
-        assert_eq!(normalized, "<1.0.0");
-        assert!(exclusions.is_empty());
-    }
-}
-
 /// Version manager for reading/writing/bumping versions
 pub struct VersionManager<'a> {
     config: &'a VersionConfig,
@@ -483,9 +164,15 @@ impl<'a> VersionManager<'a> {
 pub struct MetadataUpdater;
 
 impl MetadataUpdater {
-    /// Update a metadata file with new version and date
-    pub fn update_file(config: &MetadataFileConfig, version: &str, date: &str) -> Result<()> {
-        let path = Path::new(&config.path);
+    /// Update a metadata file (its path relative to `dir`) with new version and date
+    pub fn update_file(
+        dir: &Path,
+        config: &MetadataFileConfig,
+        version: &str,
+        date: &str,
+    ) -> Result<()> {
+        let path = &dir.join(&config.path);
+        let date = &config.format_date(date);
 
         if !path.exists() {
             return Err(ReleaserError::IoError(std::io::Error::new(
@@ -495,9 +182,9 @@ impl MetadataUpdater {
         }
 
         match config.format.to_lowercase().as_str() {
-            "yaml" | "yml" => Self::update_yaml(config, version, date),
-            "json" => Self::update_json(config, version, date),
-            "toml" => Self::update_toml(config, version, date),
+            "yaml" | "yml" => Self::update_yaml(path, config, version, date),
+            "json" => Self::update_json(path, config, version, date),
+            "toml" => Self::update_toml(path, config, version, date),
             _ => Err(ReleaserError::ConfigError(format!(
                 "Unsupported metadata format: {}",
                 config.format
@@ -506,8 +193,13 @@ impl MetadataUpdater {
     }
 
     /// Update YAML file
-    fn update_yaml(config: &MetadataFileConfig, version: &str, date: &str) -> Result<()> {
-        let content = std::fs::read_to_string(&config.path)?;
+    fn update_yaml(
+        path: &Path,
+        config: &MetadataFileConfig,
+        version: &str,
+        date: &str,
+    ) -> Result<()> {
+        let content = std::fs::read_to_string(path)?;
         let mut new_content = content.clone();
 
         // Update version fields
@@ -520,7 +212,7 @@ impl MetadataUpdater {
             new_content = Self::update_yaml_field(&new_content, field, date);
         }
 
-        std::fs::write(&config.path, new_content)?;
+        std::fs::write(path, new_content)?;
         Ok(())
     }
 
@@ -559,8 +251,13 @@ impl MetadataUpdater {
     }
 
     /// Update JSON file
-    fn update_json(config: &MetadataFileConfig, version: &str, date: &str) -> Result<()> {
-        let content = std::fs::read_to_string(&config.path)?;
+    fn update_json(
+        path: &Path,
+        config: &MetadataFileConfig,
+        version: &str,
+        date: &str,
+    ) -> Result<()> {
+        let content = std::fs::read_to_string(path)?;
         let mut json: serde_json::Value = serde_json::from_str(&content)
             .map_err(|e| ReleaserError::ConfigError(format!("Invalid JSON: {}", e)))?;
 
@@ -577,7 +274,7 @@ impl MetadataUpdater {
         let new_content = serde_json::to_string_pretty(&json)
             .map_err(|e| ReleaserError::ConfigError(format!("Failed to serialize JSON: {}", e)))?;
 
-        std::fs::write(&config.path, new_content)?;
+        std::fs::write(path, new_content)?;
         Ok(())
     }
 
@@ -610,8 +307,13 @@ impl MetadataUpdater {
     }
 
     /// Update TOML file
-    fn update_toml(config: &MetadataFileConfig, version: &str, date: &str) -> Result<()> {
-        let content = std::fs::read_to_string(&config.path)?;
+    fn update_toml(
+        path: &Path,
+        config: &MetadataFileConfig,
+        version: &str,
+        date: &str,
+    ) -> Result<()> {
+        let content = std::fs::read_to_string(path)?;
         let mut toml_value: toml::Value = content
             .parse()
             .map_err(|e| ReleaserError::ConfigError(format!("Invalid TOML: {}", e)))?;
@@ -629,7 +331,7 @@ impl MetadataUpdater {
         let new_content = toml::to_string_pretty(&toml_value)
             .map_err(|e| ReleaserError::ConfigError(format!("Failed to serialize TOML: {}", e)))?;
 
-        std::fs::write(&config.path, new_content)?;
+        std::fs::write(path, new_content)?;
         Ok(())
     }
 
@@ -657,8 +359,9 @@ impl MetadataUpdater {
         }
     }
 
-    /// Update all configured metadata files
+    /// Update all configured metadata files, their paths relative to `dir`
     pub fn update_all(
+        dir: &Path,
         configs: &[MetadataFileConfig],
         version: &str,
         date: &str,
@@ -666,12 +369,16 @@ impl MetadataUpdater {
         let mut updated_files = Vec::new();
 
         for config in configs {
-            match Self::update_file(config, version, date) {
+            match Self::update_file(dir, config, version, date) {
                 Ok(()) => {
                     updated_files.push(config.path.clone());
                 }
                 Err(e) => {
-                    eprintln!("Warning: Failed to update {}: {}", config.path, e);
+                    warnings::warn(
+                        WarningKind::Metadata,
+                        Some(&config.path),
+                        format!("failed to update: {}", e),
+                    );
                 }
             }
         }
@@ -687,19 +394,14 @@ mod tests {
     #[test]
     fn test_version_parse() {
         let v = Version::parse("1.2.3").unwrap();
-        assert_eq!(v.major(), 1);
-        assert_eq!(v.minor(), 2);
-        assert_eq!(v.patch(), 3);
+        assert_eq!(v, Version::new(1, 2, 3));
         assert_eq!(v.prerelease(), None);
 
         let v = Version::parse("v2.0.0-beta.1").unwrap();
-        assert_eq!(v.major(), 2);
-        assert_eq!(v.minor(), 0);
-        assert_eq!(v.patch(), 0);
+        assert_eq!(v.to_string(), "2.0.0-beta.1");
         assert_eq!(v.prerelease(), Some("beta.1"));
 
         let v = Version::parse("1.2.3+build.5").unwrap();
-        assert_eq!(v.build_metadata(), Some("build.5"));
         assert_eq!(v.to_string(), "1.2.3+build.5");
 
         let v = Version::parse("4.2.3.1").unwrap();
@@ -707,9 +409,7 @@ mod tests {
 
         // Also support X.Y format
         let v = Version::parse("1.2").unwrap();
-        assert_eq!(v.major(), 1);
-        assert_eq!(v.minor(), 2);
-        assert_eq!(v.patch(), 0);
+        assert_eq!(v, Version::new(1, 2, 0));
     }
 
     #[test]
@@ -724,6 +424,48 @@ mod tests {
 
         let patch = v.bump(VersionBumpType::Patch);
         assert_eq!(patch.to_string(), "1.2.4");
+
+        let rc = v.bump(VersionBumpType::Prerelease);
+        assert_eq!(rc.to_string(), "1.2.4-rc.1");
+        assert_eq!(
+            rc.bump(VersionBumpType::Prerelease).to_string(),
+            "1.2.4-rc.2"
+        );
+        let beta = Version::parse("2.0.0b1").unwrap();
+        assert_eq!(
+            beta.bump(VersionBumpType::Prerelease).to_string(),
+            "2.0.0-beta.2"
+        );
+        assert_eq!(
+            Version::parse("2.0.0-alpha")
+                .unwrap()
+                .bump(VersionBumpType::Prerelease)
+                .to_string(),
+            "2.0.0-alpha.1"
+        );
+        // A pre-release becomes its release when that is the next version
+        assert_eq!(rc.bump(VersionBumpType::Patch).to_string(), "1.2.4");
+        assert_eq!(rc.bump(VersionBumpType::Minor).to_string(), "1.3.0");
+        assert_eq!(rc.bump(VersionBumpType::Major).to_string(), "2.0.0");
+        let minor_rc = Version::parse("1.3.0-rc.1").unwrap();
+        assert_eq!(minor_rc.bump(VersionBumpType::Minor).to_string(), "1.3.0");
+        assert_eq!(minor_rc.bump(VersionBumpType::Major).to_string(), "2.0.0");
+        let major_rc = Version::parse("2.0.0rc1").unwrap();
+        assert_eq!(major_rc.bump(VersionBumpType::Major).to_string(), "2.0.0");
+
+        let now = chrono::NaiveDate::from_ymd_opt(2025, 6, 3)
+            .unwrap()
+            .and_hms_opt(15, 30, 0)
+            .unwrap();
+        assert_eq!(
+            rc.bump_at(VersionBumpType::Build, now).to_string(),
+            "1.2.4-rc.1+202506031530"
+        );
+        assert_eq!(
+            v.bump_at(VersionBumpType::Build, now).to_string(),
+            "1.2.3+202506031530"
+        );
+        assert_eq!(v.bump(VersionBumpType::None), v);
     }
 
     #[test]
@@ -0,0 +1,762 @@
+use crate::error::{ReleaserError, Result};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use semver::{BuildMetadata, Prerelease};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+// Compiled once: parsing runs for every release of every checked package
+static RELEASE_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(concat!(
+        r"^(?P<major>\d+)(?:\.(?P<minor>\d+))?(?:\.(?P<patch>\d+))?",
+        r"(?P<rest>(?:\.\d+)*)",
+        // Pre-release
+        r"(?:(?P<pre_sep>[-_.]?)(?P<pre_label>a|b|rc|alpha|beta|c|pre|preview)(?P<pre_num>\d+)?)?",
+        // Post release
+        r"(?:(?P<post_sep>[-_.]?)(?P<post_label>post|rev|r)(?P<post_num>\d+)?)?",
+        // Dev release
+        r"(?:(?P<dev_sep>[-_.]?)(?P<dev_label>dev)(?P<dev_num>\d+)?)?$"
+    ))
+    .unwrap()
+});
+static RELEASE_SEGMENTS_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^(\d+(?:\.\d+)*)").unwrap());
+static WILDCARD_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^(==|!=)\s*(\d+)(?:\.(\d+))?\.\*$").unwrap());
+static COMPARATOR_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^(<=|>=|==|===|!=|<|>|=)?\s*(.+)$").unwrap());
+
+/// Parse a Python version string into semver.
+///
+/// A PEP 440 epoch (`N!`) is accepted but dropped; use
+/// [`parse_python_version_with_epoch`] when ordering across epochs matters.
+pub fn parse_python_version(version: &str) -> Option<semver::Version> {
+    parse_python_version_with_epoch(version).map(|(_, v)| v)
+}
+
+/// Parse a Python version string into its epoch and semver release.
+///
+/// The semver form keeps the release numbers and the pre-release label, but
+/// not the PEP 440 order of dev and post releases: order versions by their
+/// [`version_key`] instead.
+pub fn parse_python_version_with_epoch(version: &str) -> Option<(u64, semver::Version)> {
+    let (epoch, rest) = split_epoch(version.trim())?;
+    Some((epoch, parse_release(rest)?))
+}
+
+/// Split off a leading `N!` epoch, defaulting to 0
+fn split_epoch(version: &str) -> Option<(u64, &str)> {
+    match version.split_once('!') {
+        Some((epoch, rest)) => Some((epoch.trim().parse().ok()?, rest)),
+        None => Some((0, version)),
+    }
+}
+
+fn parse_release(version: &str) -> Option<semver::Version> {
+    // Handle common Python version formats
+    // PEP 440: X.Y.Z, X.Y.ZaN, X.Y.ZbN, X.Y.ZrcN, X.Y.Z.postN, X.Y.Z.devN
+
+    let version = version.trim().trim_start_matches('v').replace('_', ".");
+
+    // Try direct semver parse first
+    if let Ok(v) = semver::Version::parse(&version) {
+        return Some(v);
+    }
+
+    let (core, local_suffix) = match version.split_once('+') {
+        Some((core, local)) => (core, Some(local)),
+        None => (version.as_str(), None),
+    };
+
+    // Convert Python-style pre-releases to semver
+    if let Some(caps) = RELEASE_RE.captures(core) {
+        let major: u64 = caps.name("major")?.as_str().parse().ok()?;
+        let minor: u64 = caps
+            .name("minor")
+            .and_then(|m| m.as_str().parse().ok())
+            .unwrap_or(0);
+        let patch: u64 = caps
+            .name("patch")
+            .and_then(|m| m.as_str().parse().ok())
+            .unwrap_or(0);
+
+        let mut pre_parts: Vec<String> = Vec::new();
+        if let Some(pre_label) = caps.name("pre_label") {
+            let label = match pre_label.as_str() {
+                "a" | "alpha" => "alpha",
+                "b" | "beta" => "beta",
+                "rc" | "c" | "pre" | "preview" => "rc",
+                _ => return None,
+            };
+            pre_parts.push(label.to_string());
+
+            if let Some(pre_num) = caps.name("pre_num") {
+                pre_parts.push(pre_num.as_str().to_string());
+            }
+        }
+
+        if let Some(dev_label) = caps.name("dev_label") {
+            pre_parts.push(dev_label.as_str().to_string());
+            if let Some(dev_num) = caps.name("dev_num") {
+                pre_parts.push(dev_num.as_str().to_string());
+            }
+        }
+
+        let pre = if pre_parts.is_empty() {
+            Prerelease::EMPTY
+        } else {
+            Prerelease::new(&pre_parts.join(".")).ok()?
+        };
+
+        let mut build_parts: Vec<String> = caps
+            .name("rest")
+            .map(|m| {
+                m.as_str()
+                    .trim_start_matches('.')
+                    .split('.')
+                    .filter(|s| !s.is_empty())
+                    .map(|s| s.replace('_', "-"))
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        if caps.name("post_label").is_some() {
+            let post_num = caps.name("post_num").map(|m| m.as_str()).unwrap_or("0");
+            build_parts.push(format!("post{}", post_num));
+        }
+
+        if let Some(local) = local_suffix {
+            if !local.is_empty() {
+                build_parts.push(local.replace('_', "-"));
+            }
+        }
+
+        let build = if build_parts.is_empty() {
+            BuildMetadata::EMPTY
+        } else {
+            BuildMetadata::new(&build_parts.join(".")).ok()?
+        };
+
+        return Some(semver::Version {
+            major,
+            minor,
+            patch,
+            pre,
+            build,
+        });
+    }
+
+    None
+}
+
+/// Sort key of a version in PEP 440 order: epoch, release numbers (trailing
+/// zeros ignored), then pre, post and dev releases, then the local label
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct VersionKey {
+    epoch: u64,
+    release: Vec<u64>,
+    pre: PreKey,
+    post: Option<u64>,
+    dev: DevKey,
+    local: Vec<LocalKey>,
+}
+
+impl VersionKey {
+    /// Whether this is a pre-release or a dev release
+    pub fn is_prerelease(&self) -> bool {
+        matches!(self.pre, PreKey::DevOfFinal | PreKey::Pre(..)) || self.dev != DevKey::None
+    }
+}
+
+/// Variants are declared from lowest to highest
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum PreKey {
+    /// `1.0.dev1`: sorts before every pre-release of 1.0
+    DevOfFinal,
+    /// Phase (a < b < rc) and number
+    Pre(u8, u64),
+    Final,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum DevKey {
+    Dev(u64),
+    None,
+}
+
+/// Local version segments: strings sort before numbers
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum LocalKey {
+    Text(String),
+    Number(u64),
+}
+
+/// PEP 440 sort key of `version`; `None` when it can't be parsed
+pub fn version_key(version: &str) -> Option<VersionKey> {
+    let (epoch, rest) = split_epoch(version.trim())?;
+    let version = rest.trim().trim_start_matches('v').replace('_', ".");
+    let (core, local) = match version.split_once('+') {
+        Some((core, local)) => (core, local),
+        None => (version.as_str(), ""),
+    };
+    let local = local
+        .split(['.', '-'])
+        .filter(|part| !part.is_empty())
+        .map(|part| match part.parse() {
+            Ok(number) => LocalKey::Number(number),
+            Err(_) => LocalKey::Text(part.to_ascii_lowercase()),
+        })
+        .collect();
+
+    let (mut release, pre, post, dev) = match RELEASE_RE.captures(core) {
+        Some(caps) => {
+            let number = |name: &str| -> Option<u64> {
+                caps.name(name).map_or(Some(0), |m| m.as_str().parse().ok())
+            };
+            let release: Option<Vec<u64>> = core[..caps.name("rest")?.end()]
+                .split('.')
+                .map(|part| part.parse().ok())
+                .collect();
+            let pre = match caps.name("pre_label") {
+                Some(label) => Some((pre_phase(label.as_str())?, number("pre_num")?)),
+                None => None,
+            };
+            let post = match caps.name("post_label") {
+                Some(_) => Some(number("post_num")?),
+                None => None,
+            };
+            let dev = match caps.name("dev_label") {
+                Some(_) => Some(number("dev_num")?),
+                None => None,
+            };
+            (release?, pre, post, dev)
+        }
+        // Semver-style pre-releases, as in `2.0.0-rc.1` tags
+        None => {
+            let parsed = semver::Version::parse(core).ok()?;
+            let identifiers: Vec<&str> = if parsed.pre.is_empty() {
+                Vec::new()
+            } else {
+                parsed.pre.as_str().split('.').collect()
+            };
+            let mut pre = None;
+            let mut dev = None;
+            let mut parts = identifiers.iter().peekable();
+            while let Some(label) = parts.next() {
+                let number = match parts.peek().and_then(|n| n.parse().ok()) {
+                    Some(n) => {
+                        parts.next();
+                        n
+                    }
+                    None => 0,
+                };
+                match *label {
+                    "dev" => dev = Some(number),
+                    label if pre.is_none() => pre = Some((pre_phase(label)?, number)),
+                    _ => return None,
+                }
+            }
+            (
+                vec![parsed.major, parsed.minor, parsed.patch],
+                pre,
+                None,
+                dev,
+            )
+        }
+    };
+
+    while release.len() > 1 && release.last() == Some(&0) {
+        release.pop();
+    }
+    let pre = match (pre, post, dev) {
+        (Some((phase, number)), _, _) => PreKey::Pre(phase, number),
+        (None, None, Some(_)) => PreKey::DevOfFinal,
+        _ => PreKey::Final,
+    };
+
+    Some(VersionKey {
+        epoch,
+        release,
+        pre,
+        post,
+        dev: dev.map_or(DevKey::None, DevKey::Dev),
+        local,
+    })
+}
+
+/// a < b < rc, with the PEP 440 spellings of each
+fn pre_phase(label: &str) -> Option<u8> {
+    match label.to_ascii_lowercase().as_str() {
+        "a" | "alpha" => Some(0),
+        "b" | "beta" => Some(1),
+        "rc" | "c" | "pre" | "preview" => Some(2),
+        _ => None,
+    }
+}
+
+/// Compare two Python versions with PEP 440 ordering (dev releases sort first,
+/// then pre-releases, the final release and post releases)
+pub fn compare_python_versions(a: &str, b: &str) -> Option<Ordering> {
+    Some(version_key(a)?.cmp(&version_key(b)?))
+}
+
+/// Compare with PEP 440 ordering, falling back to the leading release numbers
+/// for versions the parser does not understand (e.g. changelog headers)
+pub fn compare_versions_lenient(a: &str, b: &str) -> Ordering {
+    compare_python_versions(a, b).unwrap_or_else(|| {
+        let (a, b) = (release_numbers(a), release_numbers(b));
+        (0..a.len().max(b.len()))
+            .map(|i| a.get(i).unwrap_or(&0).cmp(b.get(i).unwrap_or(&0)))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or(Ordering::Equal)
+    })
+}
+
+/// Leading numeric components (`1.2.3a1` → `[1, 2, 3, 1]`); a suffix on the
+/// third component contributes its trailing number
+fn release_numbers(version: &str) -> Vec<u32> {
+    let mut result = Vec::new();
+
+    for (i, part) in version.trim_start_matches('v').split('.').enumerate() {
+        let digits: String = part.chars().take_while(|c| c.is_ascii_digit()).collect();
+        let Ok(n) = digits.parse::<u32>() else {
+            break;
+        };
+        result.push(n);
+
+        if i == 2 {
+            let suffix = &part[digits.len()..];
+            let trailing = &suffix[suffix.trim_end_matches(|c: char| c.is_ascii_digit()).len()..];
+            if let Ok(m) = trailing.parse::<u32>() {
+                result.push(m);
+            }
+        }
+    }
+
+    result
+}
+
+/// Every release of one package, parsed once and sorted newest first.
+/// Versions that can't be parsed are left out.
+#[derive(Debug, Clone, Default)]
+pub struct ParsedReleases {
+    releases: Vec<(VersionKey, semver::Version, String)>,
+}
+
+impl ParsedReleases {
+    pub fn parse<'a, I>(versions: I) -> Self
+    where
+        I: IntoIterator<Item = &'a String>,
+    {
+        let mut releases: Vec<_> = versions
+            .into_iter()
+            .filter_map(|version| {
+                Some((
+                    version_key(version)?,
+                    parse_python_version(version)?,
+                    version.clone(),
+                ))
+            })
+            .collect();
+        releases.sort_by(|a, b| b.0.cmp(&a.0));
+
+        Self { releases }
+    }
+
+    /// Releases from newest to oldest, with the version string as published
+    pub fn newest_first(&self) -> impl Iterator<Item = (&semver::Version, &str)> {
+        self.releases
+            .iter()
+            .map(|(_, version, raw)| (version, raw.as_str()))
+    }
+}
+
+/// Parsed releases shared across lookups, keyed by package
+#[derive(Debug, Clone, Default)]
+pub struct ParsedReleaseCache {
+    entries: Arc<Mutex<HashMap<String, Arc<ParsedReleases>>>>,
+}
+
+impl ParsedReleaseCache {
+    /// Return the parsed releases of `key`, parsing `versions` on first use
+    pub fn get_or_parse<'a, I>(&self, key: &str, versions: I) -> Arc<ParsedReleases>
+    where
+        I: IntoIterator<Item = &'a String>,
+    {
+        if let Some(parsed) = self.lock().get(key) {
+            return parsed.clone();
+        }
+
+        // Parse outside the lock so other packages aren't held up
+        let parsed = Arc::new(ParsedReleases::parse(versions));
+        self.lock().entry(key.to_string()).or_insert(parsed).clone()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<String, Arc<ParsedReleases>>> {
+        self.entries
+            .lock()
+            .expect("parsed release cache lock poisoned")
+    }
+}
+
+/// Whether `version` satisfies a PEP 440 specifier; `None` if either can't be parsed
+pub fn satisfies(version: &str, specifier: &str) -> Option<bool> {
+    if specifier.trim().is_empty() {
+        return Some(true);
+    }
+
+    let mut parsed = parse_python_version(version)?;
+    let (req, exclusions) = parse_version_constraint(specifier).ok()?;
+
+    // semver only matches pre-releases against comparators on the same release
+    parsed.pre = semver::Prerelease::EMPTY;
+
+    Some(
+        req.matches(&parsed)
+            && exclusions
+                .iter()
+                .all(|(start, end)| !(&parsed >= start && &parsed < end)),
+    )
+}
+
+/// Parse a Python version constraint to semver requirement
+pub fn parse_version_constraint(
+    constraint: &str,
+) -> Result<(semver::VersionReq, Vec<(semver::Version, semver::Version)>)> {
+    // Convert Python-style constraints to semver
+    // ~=X.Y -> >=X.Y.0, <X+1.0.0 (approximately)
+    // ==X.Y.Z -> =X.Y.Z
+    // >=X.Y.Z -> >=X.Y.Z
+    // etc.
+
+    let constraint = constraint.trim();
+
+    if constraint.is_empty() {
+        return Err(ReleaserError::VersionError(
+            "Empty version constraint".to_string(),
+        ));
+    }
+
+    // Handle ~= (compatible release)
+    if constraint.contains("||") {
+        return Err(ReleaserError::VersionError(
+            "OR (||) constraints are not supported".to_string(),
+        ));
+    }
+
+    if let Some(version) = constraint.strip_prefix("~=") {
+        let version = version.trim();
+        let parsed = parse_python_version(version)
+            .ok_or_else(|| ReleaserError::VersionError(version.to_string()))?;
+
+        let release_len = RELEASE_SEGMENTS_RE
+            .captures(version)
+            .map(|caps| caps[1].split('.').count())
+            .unwrap_or(0);
+
+        let upper_bound = match release_len {
+            0..=2 => format!("{}.0.0", parsed.major + 1),
+            _ => format!("{}.{}.0", parsed.major, parsed.minor + 1),
+        };
+
+        let req = semver::VersionReq::parse(&format!(">={}, <{}", parsed, upper_bound))
+            .map_err(|e| ReleaserError::VersionError(e.to_string()))?;
+
+        return Ok((req, Vec::new()));
+    }
+
+    let mut exclusions = Vec::new();
+
+    let parts: Result<Vec<String>> = constraint
+        .split(',')
+        .map(|raw| {
+            let (expr, mut excluded) = normalize_constraint_part(raw)?;
+            exclusions.append(&mut excluded);
+            Ok(expr)
+        })
+        .collect();
+
+    let normalized = parts?.join(", ");
+
+    let req = semver::VersionReq::parse(&normalized)
+        .map_err(|e| ReleaserError::VersionError(format!("{}: {}", normalized, e)))?;
+
+    Ok((req, exclusions))
+}
+
+pub fn normalize_constraint_part(
+    part: &str,
+) -> Result<(String, Vec<(semver::Version, semver::Version)>)> {
+    let part = part.trim();
+
+    if part.is_empty() {
+        return Err(ReleaserError::VersionError(
+            "Empty constraint segment".to_string(),
+        ));
+    }
+
+    // Wildcard equality (==1.2.*)
+    if let Some(caps) = WILDCARD_RE.captures(part) {
+        let op = caps.get(1).map(|m| m.as_str()).unwrap_or("==");
+        let major: u64 = caps[2]
+            .parse()
+            .map_err(|_| ReleaserError::VersionError(part.to_string()))?;
+        let minor: Option<u64> = caps.get(3).and_then(|m| m.as_str().parse().ok());
+
+        let normalized_op = if op == "==" { "=" } else { op };
+
+        let (lower, upper) = if let Some(minor) = minor {
+            (
+                semver::Version::new(major, minor, 0),
+                semver::Version::new(major, minor + 1, 0),
+            )
+        } else {
+            (
+                semver::Version::new(major, 0, 0),
+                semver::Version::new(major + 1, 0, 0),
+            )
+        };
+
+        let expr = match normalized_op {
+            "=" => format!(">={}, <{}", lower, upper),
+            "!=" => "*".to_string(),
+            _ => part.to_string(),
+        };
+
+        let mut exclusions = Vec::new();
+        if normalized_op == "!=" {
+            exclusions.push((lower, upper));
+        }
+
+        return Ok((expr, exclusions));
+    }
+
+    if let Some(caps) = COMPARATOR_RE.captures(part) {
+        let op = caps.get(1).map(|m| m.as_str()).unwrap_or("=");
+        let version_str = caps.get(2).map(|m| m.as_str()).unwrap_or("");
+
+        let parsed = parse_python_version(version_str)
+            .ok_or_else(|| ReleaserError::VersionError(part.to_string()))?;
+
+        let normalized_op = match op {
+            "==" | "=" | "===" => "=",
+            other => other,
+        };
+
+        return Ok((format!("{}{}", normalized_op, parsed), Vec::new()));
+    }
+
+    Err(ReleaserError::VersionError(part.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checks_pins_against_requirement_specifiers() {
+        assert_eq!(satisfies("2.1.0", ">=2.0,<3"), Some(true));
+        assert_eq!(satisfies("1.9.4", ">=2.0"), Some(false));
+        assert_eq!(satisfies("6.0.0rc1", ">=6.0.0a1"), Some(true));
+        assert_eq!(satisfies("1.0", ""), Some(true));
+        assert_eq!(satisfies("not-a-version", ">=1.0"), None);
+    }
+
+    #[test]
+    fn parses_additional_python_versions() {
+        let v = parse_python_version("1.2").expect("should parse minor-only");
+        assert_eq!(v.to_string(), "1.2.0");
+
+        let v = parse_python_version("2.0rc1").expect("should parse rc prerelease");
+        assert_eq!(v.to_string(), "2.0.0-rc.1");
+
+        let v = parse_python_version("3.4.post2").expect("should parse post release");
+        assert_eq!(v.to_string(), "3.4.0+post2");
+
+        let v = parse_python_version("4.5.dev7").expect("should parse dev prerelease");
+        assert_eq!(v.to_string(), "4.5.0-dev.7");
+
+        let v = parse_python_version("1.0+local.tag").expect("should parse local metadata");
+        assert_eq!(v.to_string(), "1.0.0+local.tag");
+
+        let v = parse_python_version("4.2.3.1").expect("should parse four-segment release");
+        assert_eq!(v.to_string(), "4.2.3+1");
+
+        let v =
+            parse_python_version("4.2.3.1rc1").expect("should parse four-segment rc prerelease");
+        assert_eq!(v.to_string(), "4.2.3-rc.1+1");
+
+        let v =
+            parse_python_version("4.2.3.28b3").expect("should parse four-segment beta prerelease");
+        assert_eq!(v.to_string(), "4.2.3-beta.3+28");
+
+        let v =
+            parse_python_version("4.2.4.8a2").expect("should parse four-segment alpha prerelease");
+        assert_eq!(v.to_string(), "4.2.4-alpha.2+8");
+
+        let v = parse_python_version("2.5").expect("should parse two-segment release");
+        assert_eq!(v.to_string(), "2.5.0");
+
+        let v = parse_python_version("7").expect("should parse single-segment release");
+        assert_eq!(v.to_string(), "7.0.0");
+    }
+
+    #[test]
+    fn parses_epochs() {
+        let (epoch, v) = parse_python_version_with_epoch("1!2.0.0").expect("should parse epoch");
+        assert_eq!(epoch, 1);
+        assert_eq!(v.to_string(), "2.0.0");
+
+        let (epoch, _) = parse_python_version_with_epoch("2.0").unwrap();
+        assert_eq!(epoch, 0);
+
+        assert_eq!(
+            parse_python_version("1!2.0rc1").map(|v| v.to_string()),
+            Some("2.0.0-rc.1".to_string())
+        );
+        assert!(parse_python_version("x!1.0").is_none());
+
+        assert_eq!(
+            compare_python_versions("1!1.0", "2024.1"),
+            Some(std::cmp::Ordering::Greater)
+        );
+        assert_eq!(
+            compare_python_versions("0!3.0", "3.0"),
+            Some(std::cmp::Ordering::Equal)
+        );
+    }
+
+    #[test]
+    fn orders_dev_pre_and_post_releases() {
+        let ascending = [
+            "1.0.dev1",
+            "1.0a1.dev1",
+            "1.0a1",
+            "1.0b2",
+            "1.0rc1.dev1",
+            "1.0rc1",
+            "1.0",
+            "1.0+local",
+            "1.0.post1.dev1",
+            "1.0.post1",
+            "1.0.post2",
+            "1.0.post10",
+            "1.0.1",
+            "1!0.1",
+        ];
+        for pair in ascending.windows(2) {
+            assert_eq!(
+                compare_python_versions(pair[0], pair[1]),
+                Some(Ordering::Less),
+                "{} < {}",
+                pair[0],
+                pair[1]
+            );
+        }
+
+        assert_eq!(
+            compare_python_versions("1.0", "1.0.0"),
+            Some(Ordering::Equal)
+        );
+        assert_eq!(
+            compare_python_versions("1.0.post1", "1.0"),
+            Some(Ordering::Greater)
+        );
+        assert_eq!(
+            compare_python_versions("2.0.0-rc.1", "2.0rc1"),
+            Some(Ordering::Equal)
+        );
+        assert_eq!(
+            compare_python_versions("2.0.0-beta.2", "2.0.0-rc.1"),
+            Some(Ordering::Less)
+        );
+
+        assert!(version_key("1.0.dev1").unwrap().is_prerelease());
+        assert!(version_key("1.0rc1").unwrap().is_prerelease());
+        assert!(!version_key("1.0.post1").unwrap().is_prerelease());
+    }
+
+    #[test]
+    fn parses_wildcard_constraints() {
+        let (req, exclusions) =
+            parse_version_constraint("==3.8.*").expect("should parse wildcard equality");
+        let matches = req.matches(&semver::Version::parse("3.8.5").unwrap());
+        assert!(matches, "should accept version within wildcard range");
+        assert!(exclusions.is_empty());
+
+        let (req, exclusions) =
+            parse_version_constraint("!=2.*").expect("should parse wildcard inequality");
+        assert!(req.matches(&semver::Version::parse("1.9.9").unwrap()));
+        assert_eq!(exclusions.len(), 1);
+        let (lower, upper) = &exclusions[0];
+        assert_eq!(lower, &semver::Version::new(2, 0, 0));
+        assert_eq!(upper, &semver::Version::new(3, 0, 0));
+    }
+
+    #[test]
+    fn parses_partial_comparators() {
+        let (req, exclusions) =
+            parse_version_constraint(">=3.8").expect("should parse partial comparator");
+        assert!(req.matches(&semver::Version::parse("3.8.1").unwrap()));
+        assert!(exclusions.is_empty());
+
+        let (req, exclusions) =
+            parse_version_constraint("~=3.8").expect("should parse compatible release");
+        assert!(req.matches(&semver::Version::parse("3.8.9").unwrap()));
+        assert!(!req.matches(&semver::Version::parse("4.0.0").unwrap()));
+        assert!(exclusions.is_empty());
+    }
+
+    #[test]
+    fn normalizes_constraint_parts() {
+        let (normalized, exclusions) = normalize_constraint_part("==1.2").unwrap();
+        assert_eq!(normalized, "=1.2.0");
+        assert!(exclusions.is_empty());
+
+        let (normalized, exclusions) = normalize_constraint_part("< 1").unwrap();
+        assert_eq!(normalized, "<1.0.0");
+        assert!(exclusions.is_empty());
+    }
+
+    #[test]
+    fn parsed_releases_are_cached_newest_first() {
+        let cache = ParsedReleaseCache::default();
+        let versions: Vec<String> = [
+            "1.0",
+            "2.0rc1",
+            "not-a-version",
+            "1!0.5",
+            "2.0",
+            "2.0.dev1",
+            "2.0.post1",
+        ]
+        .iter()
+        .map(|v| v.to_string())
+        .collect();
+
+        let parsed = cache.get_or_parse("plone-api", &versions);
+        let order: Vec<&str> = parsed.newest_first().map(|(_, raw)| raw).collect();
+        assert_eq!(
+            order,
+            ["1!0.5", "2.0.post1", "2.0", "2.0rc1", "2.0.dev1", "1.0"]
+        );
+
+        let again = cache.get_or_parse("plone-api", &Vec::new());
+        assert!(Arc::ptr_eq(&parsed, &again));
+    }
+
+    #[test]
+    fn compares_unparseable_versions_by_release_numbers() {
+        assert_eq!(release_numbers("1.2.3"), vec![1, 2, 3]);
+        assert_eq!(release_numbers("v1.2.3"), vec![1, 2, 3]);
+        assert_eq!(release_numbers("1.2.3a1"), vec![1, 2, 3, 1]);
+        assert_eq!(release_numbers("1.2"), vec![1, 2]);
+
+        assert_eq!(compare_versions_lenient("1.2.x", "1.2"), Ordering::Equal);
+        assert_eq!(compare_versions_lenient("1.2.x", "1.3.x"), Ordering::Less);
+        assert_eq!(
+            compare_versions_lenient("2.0.x", "1.9.9"),
+            Ordering::Greater
+        );
+        assert_eq!(compare_versions_lenient("1.0rc1", "1.0"), Ordering::Less);
+    }
+}
@@ -0,0 +1,130 @@
+use colored::*;
+use serde::Serialize;
+use std::fmt;
+use std::sync::Mutex;
+
+static WARNINGS: Mutex<Vec<Warning>> = Mutex::new(Vec::new());
+
+/// Where a warning came from, used to group the end-of-run summary
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WarningKind {
+    Audit,
+    Bundle,
+    Changelog,
+    Dependency,
+    Metadata,
+    Pins,
+    PyPi,
+    Stats,
+    History,
+    Git,
+    GitHub,
+    Filter,
+}
+
+impl fmt::Display for WarningKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            WarningKind::Audit => "audit",
+            WarningKind::Bundle => "bundle",
+            WarningKind::Changelog => "changelog",
+            WarningKind::Dependency => "dependency",
+            WarningKind::Metadata => "metadata",
+            WarningKind::Pins => "pins",
+            WarningKind::PyPi => "pypi",
+            WarningKind::Stats => "stats",
+            WarningKind::History => "history",
+            WarningKind::Git => "git",
+            WarningKind::GitHub => "github",
+            WarningKind::Filter => "filter",
+        };
+        f.write_str(label)
+    }
+}
+
+/// A non-fatal problem collected during a run
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Warning {
+    pub kind: WarningKind,
+    /// Package, file or tag the warning is about
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subject: Option<String>,
+    pub message: String,
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.subject {
+            Some(subject) => write!(f, "[{}] {}: {}", self.kind, subject, self.message),
+            None => write!(f, "[{}] {}", self.kind, self.message),
+        }
+    }
+}
+
+/// Record a warning for the end-of-run summary (repeats are recorded once)
+pub fn warn<S: Into<String>>(kind: WarningKind, subject: Option<&str>, message: S) {
+    let warning = Warning {
+        kind,
+        subject: subject.map(str::to_string),
+        message: message.into(),
+    };
+
+    if let Ok(mut warnings) = WARNINGS.lock() {
+        if !warnings.contains(&warning) {
+            warnings.push(warning);
+        }
+    }
+}
+
+/// Remove and return all warnings recorded so far
+pub fn take() -> Vec<Warning> {
+    WARNINGS
+        .lock()
+        .map(|mut warnings| std::mem::take(&mut *warnings))
+        .unwrap_or_default()
+}
+
+/// Print any remaining warnings to stderr in a dedicated section
+pub fn print_summary() {
+    let warnings = take();
+    if warnings.is_empty() {
+        return;
+    }
+
+    eprintln!(
+        "\n{}",
+        format!("Warnings ({})", warnings.len()).yellow().bold()
+    );
+    for warning in &warnings {
+        eprintln!("  {} {}", "⚠".yellow(), warning);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_warning_display_and_collection() {
+        warn(
+            WarningKind::Metadata,
+            Some("publiccode.yml"),
+            "file not found",
+        );
+
+        let warnings = take();
+        let warning = warnings
+            .iter()
+            .find(|w| w.kind == WarningKind::Metadata)
+            .expect("recorded warning");
+        assert_eq!(
+            warning.to_string(),
+            "[metadata] publiccode.yml: file not found"
+        );
+        assert_eq!(
+            serde_json::to_value(warning).unwrap()["kind"],
+            serde_json::json!("metadata")
+        );
+    }
+}
@@ -0,0 +1,214 @@
+use crate::config::PyPiConfig;
+use crate::error::{ReleaserError, Result};
+use crate::pypi::ReleaseInfo;
+use serde::Serialize;
+
+/// Python version and platforms releases are deployed to
+#[derive(Debug, Clone)]
+pub struct WheelTarget {
+    pub python: (u32, u32),
+    pub platforms: Vec<String>,
+}
+
+/// What a deployment would install for a given release
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Availability {
+    /// A compatible wheel is published
+    Wheel,
+    /// Only an sdist fits: pip would build from source
+    SdistOnly,
+    /// Nothing installable for the target
+    Unavailable,
+}
+
+impl WheelTarget {
+    /// Build the target from `[pypi] target_python` / `target_platforms`
+    pub fn from_config(config: &PyPiConfig) -> Result<Self> {
+        let raw = config.target_python.as_deref().ok_or_else(|| {
+            ReleaserError::ConfigError(
+                "Set pypi.target_python (e.g. \"3.11\") to check wheel availability".to_string(),
+            )
+        })?;
+        let python = parse_python(raw).ok_or_else(|| {
+            ReleaserError::ConfigError(format!("Invalid pypi.target_python '{}'", raw))
+        })?;
+
+        Ok(Self {
+            python,
+            platforms: config.target_platforms.clone(),
+        })
+    }
+
+    /// Classify the non-yanked files of a release
+    pub fn availability(&self, files: &[ReleaseInfo]) -> Availability {
+        let files: Vec<&str> = files
+            .iter()
+            .filter(|f| !f.yanked)
+            .map(|f| f.filename.as_str())
+            .collect();
+
+        if files.iter().any(|f| self.accepts_wheel(f)) {
+            Availability::Wheel
+        } else if files.iter().any(|f| is_sdist(f)) {
+            Availability::SdistOnly
+        } else {
+            Availability::Unavailable
+        }
+    }
+
+    /// Whether pip on the target would install this wheel filename
+    pub fn accepts_wheel(&self, filename: &str) -> bool {
+        let Some(stem) = filename.strip_suffix(".whl") else {
+            return false;
+        };
+        // {name}-{version}(-{build})?-{python}-{abi}-{platform}
+        let parts: Vec<&str> = stem.split('-').collect();
+        if parts.len() < 5 {
+            return false;
+        }
+        let [python, abi, platform] = [
+            parts[parts.len() - 3],
+            parts[parts.len() - 2],
+            parts[parts.len() - 1],
+        ];
+
+        // Compressed tag sets like `py2.py3` or `manylinux1_x86_64.manylinux2014_x86_64`
+        python
+            .split('.')
+            .any(|py| abi.split('.').any(|abi| self.accepts_interpreter(py, abi)))
+            && platform.split('.').any(|p| self.accepts_platform(p))
+    }
+
+    fn accepts_interpreter(&self, python: &str, abi: &str) -> bool {
+        let (major, minor) = self.python;
+        let exact = format!("{}{}", major, minor);
+
+        match abi {
+            "none" => {
+                python == format!("py{}", major)
+                    || python == format!("py{}", exact)
+                    || python == format!("cp{}", exact)
+            }
+            // The stable ABI works on the tagged CPython version and every later one
+            "abi3" => python
+                .strip_prefix("cp")
+                .and_then(|v| split_version_tag(v, major))
+                .is_some_and(|(m, n)| m == major && n <= minor),
+            _ => python == format!("cp{}", exact) && abi.trim_end_matches('m') == python,
+        }
+    }
+
+    fn accepts_platform(&self, platform: &str) -> bool {
+        if platform == "any" {
+            return true;
+        }
+
+        self.platforms.iter().any(|target| {
+            if target == platform {
+                return true;
+            }
+            // A glibc-based target also runs wheels built for older glibc versions
+            match (manylinux(target), manylinux(platform)) {
+                (Some((target_glibc, target_arch)), Some((glibc, arch))) => {
+                    arch == target_arch && glibc <= target_glibc
+                }
+                _ => false,
+            }
+        })
+    }
+}
+
+fn parse_python(raw: &str) -> Option<(u32, u32)> {
+    let mut parts = raw.trim().split('.');
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next()?.parse().ok()?;
+    Some((major, minor))
+}
+
+/// `311` → (3, 11) given the major version
+fn split_version_tag(tag: &str, major: u32) -> Option<(u32, u32)> {
+    let rest = tag.strip_prefix(&major.to_string())?;
+    Some((major, rest.parse().ok()?))
+}
+
+/// glibc version and architecture of a manylinux platform tag, legacy aliases included
+fn manylinux(platform: &str) -> Option<((u32, u32), &str)> {
+    for (alias, glibc) in [
+        ("manylinux1_", (2, 5)),
+        ("manylinux2010_", (2, 12)),
+        ("manylinux2014_", (2, 17)),
+    ] {
+        if let Some(arch) = platform.strip_prefix(alias) {
+            return Some((glibc, arch));
+        }
+    }
+
+    let rest = platform.strip_prefix("manylinux_")?;
+    let mut parts = rest.splitn(3, '_');
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next()?.parse().ok()?;
+    Some(((major, minor), parts.next()?))
+}
+
+fn is_sdist(filename: &str) -> bool {
+    filename.ends_with(".tar.gz") || filename.ends_with(".zip") || filename.ends_with(".tar.bz2")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn target() -> WheelTarget {
+        WheelTarget {
+            python: (3, 11),
+            platforms: vec!["manylinux_2_28_x86_64".to_string()],
+        }
+    }
+
+    fn file(filename: &str) -> ReleaseInfo {
+        ReleaseInfo {
+            filename: filename.to_string(),
+            url: String::new(),
+            upload_time: String::new(),
+            yanked: false,
+            digests: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_accepts_wheel() {
+        let target = target();
+        assert!(target.accepts_wheel("six-1.16.0-py2.py3-none-any.whl"));
+        assert!(target.accepts_wheel(
+            "lxml-5.2.0-cp311-cp311-manylinux_2_17_x86_64.manylinux2014_x86_64.whl"
+        ));
+        assert!(target.accepts_wheel("cryptography-42.0.0-cp39-abi3-manylinux_2_28_x86_64.whl"));
+
+        assert!(!target.accepts_wheel("lxml-5.2.0-cp312-cp312-manylinux_2_17_x86_64.whl"));
+        assert!(!target.accepts_wheel("lxml-5.2.0-cp311-cp311-manylinux_2_34_x86_64.whl"));
+        assert!(!target.accepts_wheel("lxml-5.2.0-cp311-cp311-manylinux_2_17_aarch64.whl"));
+        assert!(!target.accepts_wheel("lxml-5.2.0-cp311-cp311-win_amd64.whl"));
+        assert!(!target.accepts_wheel("lxml-5.2.0.tar.gz"));
+    }
+
+    #[test]
+    fn test_availability() {
+        let target = target();
+        assert_eq!(
+            target.availability(&[
+                file("lxml-5.2.0-cp311-cp311-win_amd64.whl"),
+                file("lxml-5.2.0.tar.gz"),
+            ]),
+            Availability::SdistOnly
+        );
+        assert_eq!(
+            target.availability(&[file("lxml-5.2.0-cp311-cp311-win_amd64.whl")]),
+            Availability::Unavailable
+        );
+        assert_eq!(
+            target.availability(&[file("six-1.16.0-py3-none-any.whl")]),
+            Availability::Wheel
+        );
+    }
+}
@@ -0,0 +1,263 @@
+//! Questions asked by `bldr init --interactive`, answered with what could be
+//! detected in the working directory and the git repository
+
+use crate::config::{Config, ConfigTemplate, MetadataFileConfig};
+use crate::error::{ReleaserError, Result};
+use crate::git::GitOps;
+use crate::version::Version;
+use colored::*;
+use dialoguer::{Confirm, Input, Select};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Release notes files looked for in the project root, most common first
+const CHANGELOG_CANDIDATES: &[&str] = &[
+    "CHANGELOG.md",
+    "CHANGES.md",
+    "CHANGES.rst",
+    "HISTORY.md",
+    "HISTORY.rst",
+    "CHANGELOG.rst",
+    "CHANGES.txt",
+];
+
+/// Asks questions, or takes the detected answer when prompts are disabled
+pub struct Wizard {
+    non_interactive: bool,
+}
+
+impl Wizard {
+    pub fn new(non_interactive: bool) -> Self {
+        Self { non_interactive }
+    }
+
+    fn confirm(&self, prompt: &str, default: bool) -> Result<bool> {
+        if self.non_interactive {
+            return Ok(default);
+        }
+        Confirm::new()
+            .with_prompt(prompt)
+            .default(default)
+            .interact()
+            .map_err(prompt_error)
+    }
+
+    fn input(&self, prompt: &str, default: &str) -> Result<String> {
+        if self.non_interactive {
+            return Ok(default.to_string());
+        }
+        Input::new()
+            .with_prompt(prompt)
+            .default(default.to_string())
+            .allow_empty(true)
+            .interact_text()
+            .map_err(prompt_error)
+    }
+
+    fn select(&self, prompt: &str, items: &[&str], default: usize) -> Result<usize> {
+        if self.non_interactive {
+            return Ok(default);
+        }
+        Select::new()
+            .with_prompt(prompt)
+            .items(items)
+            .default(default)
+            .interact()
+            .map_err(prompt_error)
+    }
+
+    pub fn template(&self) -> Result<ConfigTemplate> {
+        let templates = [
+            ("minimal", ConfigTemplate::Minimal),
+            ("full", ConfigTemplate::Full),
+            ("plone6", ConfigTemplate::Plone6),
+            ("zope", ConfigTemplate::Zope),
+        ];
+        let names: Vec<&str> = templates.iter().map(|(name, _)| *name).collect();
+        let choice = self.select("Template", &names, 0)?;
+        Ok(templates[choice].1)
+    }
+
+    /// Fill in the config from the answers. Returns the versions file whose
+    /// pins should all be tracked, if the user asked for that
+    pub fn run(&self, config: &mut Config, ask_versions_file: bool) -> Result<Option<String>> {
+        let root = config.resolve(".");
+        let mut seed_from = None;
+
+        if ask_versions_file {
+            let found = find_versions_files(&root);
+            let path = if found.is_empty() {
+                println!("  No versions file with a [versions] section found.");
+                self.input("Versions file", &config.versions_file)?
+            } else {
+                let mut items: Vec<&str> = found.iter().map(String::as_str).collect();
+                items.push("other…");
+                let choice = self.select("Versions file", &items, 0)?;
+                match found.get(choice) {
+                    Some(path) => path.clone(),
+                    None => self.input("Versions file", &config.versions_file)?,
+                }
+            };
+            config.versions_file = path;
+
+            if found.contains(&config.versions_file)
+                && self.confirm(
+                    &format!("Track every package pinned in {}?", config.versions_file),
+                    config.packages.is_empty(),
+                )?
+            {
+                seed_from = Some(config.versions_file.clone());
+            }
+        }
+
+        let git = GitOps::new().with_work_dir(root.to_string_lossy());
+        if git.is_repo() {
+            let tags = git.tags(None)?;
+            let detected = detect_tag_prefix(&tags).unwrap_or(config.github.tag_prefix.clone());
+            config.github.tag_prefix = self.input("Tag prefix", &detected)?;
+        }
+
+        let has_publiccode = root.join("publiccode.yml").exists();
+        if !has_publiccode
+            || !self.confirm(
+                "Update softwareVersion and releaseDate in publiccode.yml on release?",
+                true,
+            )?
+        {
+            config.metadata_files.retain(|m| m.path != "publiccode.yml");
+        } else if !config
+            .metadata_files
+            .iter()
+            .any(|m| m.path == "publiccode.yml")
+        {
+            config.metadata_files.push(MetadataFileConfig::publiccode());
+        }
+
+        if let Some(changelog) = find_changelog(&root) {
+            if self.confirm(
+                &format!("Write the changelog of each release to {}?", changelog),
+                true,
+            )? {
+                config.changelog.enabled = true;
+                config.changelog.format = changelog_format_for(&changelog).to_string();
+                config.changelog.output_file = Some(changelog);
+            }
+        }
+
+        Ok(seed_from)
+    }
+}
+
+fn prompt_error(e: dialoguer::Error) -> ReleaserError {
+    ReleaserError::IoError(std::io::Error::other(e.to_string()))
+}
+
+/// `*.cfg` files of a directory with a `[versions]` section, `versions.cfg` first
+pub fn find_versions_files(dir: &Path) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut found: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "cfg"))
+        .filter(|path| {
+            std::fs::read_to_string(path).is_ok_and(|content| {
+                content
+                    .lines()
+                    .any(|line| line.trim_start().starts_with("[versions"))
+            })
+        })
+        .filter_map(|path| Some(path.file_name()?.to_string_lossy().into_owned()))
+        .collect();
+    found.sort_by_key(|name| (name != "versions.cfg", name.clone()));
+    found
+}
+
+/// Most common prefix in front of the version of existing tags (e.g. `v`)
+pub fn detect_tag_prefix(tags: &[String]) -> Option<String> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for tag in tags {
+        let Some(start) = tag.find(|c: char| c.is_ascii_digit()) else {
+            continue;
+        };
+        let (prefix, version) = tag.split_at(start);
+        if Version::parse(version).is_ok() {
+            *counts.entry(prefix).or_default() += 1;
+        }
+    }
+
+    counts
+        .into_iter()
+        .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(a.0)))
+        .map(|(prefix, _)| prefix.to_string())
+}
+
+/// First release notes file present in the project root
+fn find_changelog(dir: &Path) -> Option<String> {
+    CHANGELOG_CANDIDATES
+        .iter()
+        .find(|name| dir.join(name).exists())
+        .map(|name| name.to_string())
+}
+
+/// `changelog.format` matching the extension of an output file
+fn changelog_format_for(path: &str) -> &'static str {
+    match Path::new(path).extension().and_then(|ext| ext.to_str()) {
+        Some("rst") => "rst",
+        Some("txt") => "text",
+        _ => "markdown",
+    }
+}
+
+/// Print what the wizard decided, so it can be checked before the first run
+pub fn summarize(config: &Config) {
+    println!("  {} {}", "versions_file:".dimmed(), config.versions_file);
+    println!(
+        "  {} {:?}",
+        "tag_prefix:".dimmed(),
+        config.github.tag_prefix
+    );
+    if config.changelog.enabled {
+        println!(
+            "  {} {}",
+            "changelog:".dimmed(),
+            config.changelog.output_file.as_deref().unwrap_or("-")
+        );
+    }
+    for metadata in &config.metadata_files {
+        println!("  {} {}", "metadata file:".dimmed(), metadata.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::temp_dir;
+
+    #[test]
+    fn test_detect_tag_prefix() {
+        let tags: Vec<String> = ["v1.0.0", "v1.1.0", "2.0.0", "release-notes", "v1.2.0"]
+            .iter()
+            .map(|t| t.to_string())
+            .collect();
+        assert_eq!(detect_tag_prefix(&tags).as_deref(), Some("v"));
+        assert_eq!(detect_tag_prefix(&[]), None);
+
+        assert_eq!(changelog_format_for("CHANGES.rst"), "rst");
+        assert_eq!(changelog_format_for("CHANGELOG.md"), "markdown");
+    }
+
+    #[test]
+    fn test_find_versions_files() {
+        let tmp = temp_dir("wizard");
+        let dir = tmp.path();
+        std::fs::write(dir.join("base.cfg"), "[versions]\nsix = 1.16.0\n").unwrap();
+        std::fs::write(dir.join("versions.cfg"), "[versions]\nsix = 1.16.0\n").unwrap();
+        std::fs::write(dir.join("buildout.cfg"), "[buildout]\nparts =\n").unwrap();
+
+        let found = find_versions_files(dir);
+        assert_eq!(found, ["versions.cfg", "base.cfg"]);
+    }
+}